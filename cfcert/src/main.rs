@@ -1,103 +1,143 @@
 use std::env;
 use std::error::Error;
+use std::fs;
 
-use cloudflare::endpoints::{dns, zone};
-use cloudflare::framework::{
-    async_api::{ApiClient, Client},
-    auth::Credentials,
-    Environment, HttpApiClientConfig,
-};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use cio_api::dns_providers::{dns_provider_from_env, get_zone_for_domain, DnsRecord};
+use cio_api::dns_zones::{apply_zone, get_dns_zone_config_from_repo, plan_zone, DnsRecordChange, DnsZoneConfig};
+use cio_api::utils::authenticate_github_jwt;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-    let args: Vec<String> = env::args().collect();
+    let zone_args = || {
+        vec![
+            Arg::with_name("zone").help("The zone to reconcile, for example oxide.computer").required(true),
+            Arg::with_name("file")
+                .long("file")
+                .short("f")
+                .takes_value(true)
+                .help("Read the zone config from this file, instead of /dns/{zone}.toml in the configs repo"),
+            Arg::with_name("prune")
+                .long("prune")
+                .help("Also delete the A, AAAA, CNAME and TXT records that are not in the zone config"),
+        ]
+    };
 
-    let domain = &args[1];
-    let ip = &args[2];
+    let matches = App::new("cfcert")
+        .about("Manage DNS records with the provider set in $DNS_PROVIDER (cloudflare, rfc2136 or mock)")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List the A, AAAA, CNAME and TXT records in a zone")
+                .arg(Arg::with_name("zone").help("The zone to list, for example oxide.computer").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("upsert")
+                .about("Create or replace the records of a type for a name")
+                .arg(Arg::with_name("name").help("The fully qualified name of the record").required(true))
+                .arg(Arg::with_name("type").help("The type of the record").possible_values(&["A", "AAAA", "CNAME", "TXT"]).required(true))
+                .arg(Arg::with_name("value").help("The values of the records").required(true).multiple(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("delete")
+                .about("Delete the records of a type for a name")
+                .arg(Arg::with_name("name").help("The fully qualified name of the record").required(true))
+                .arg(Arg::with_name("type").help("The type of the record").possible_values(&["A", "AAAA", "CNAME", "TXT"]).required(true)),
+        )
+        .subcommand(SubCommand::with_name("diff").about("Show the changes needed to make a zone match its config").args(&zone_args()))
+        .subcommand(SubCommand::with_name("apply").about("Make a zone match its config").args(&zone_args()))
+        .get_matches();
 
-    println!("Setting up Cloudflare A record from {} -> {}", ip, domain);
-    // Create the Cloudflare client.
-    let cf_creds = Credentials::UserAuthKey {
-        email: env::var("CLOUDFLARE_EMAIL").unwrap(),
-        key: env::var("CLOUDFLARE_TOKEN").unwrap(),
-    };
-    let api_client = Client::new(cf_creds, HttpApiClientConfig::default(), Environment::Production).unwrap();
-
-    // We need the root of the domain not a subdomain.
-    let domain_parts: Vec<&str> = domain.split('.').collect();
-    let root_domain = format!("{}.{}", domain_parts[domain_parts.len() - 2], domain_parts[domain_parts.len() - 1]);
-
-    // Get the zone ID for the domain.
-    let zones = api_client
-        .request(&zone::ListZones {
-            params: zone::ListZonesParams {
-                name: Some(root_domain.to_string()),
-                ..Default::default()
-            },
-        })
-        .await
-        .unwrap()
-        .result;
-
-    // Our zone identifier should be the first record's ID.
-    if zones.is_empty() {
-        println!("we found no zones!");
-        return Ok(());
-    }
-    let zone_identifier = &zones[0].id;
-
-    // Check if we already have a TXT record and we need to update it.
-    let dns_records = api_client
-        .request(&dns::ListDnsRecords {
-            zone_identifier: &zone_identifier,
-            params: dns::ListDnsRecordsParams {
-                name: Some(domain.to_string()),
-                ..Default::default()
-            },
-        })
-        .await
-        .unwrap()
-        .result;
-
-    // If we have a dns record already, update it. If not, create it.
-    if dns_records.is_empty() {
-        // Create the DNS record.
-        let dns_record = api_client
-            .request(&dns::CreateDnsRecord {
-                zone_identifier: &zone_identifier,
-                params: dns::CreateDnsRecordParams {
-                    name: &domain,
-                    content: dns::DnsContent::A { content: ip.parse().unwrap() },
-                    // This is the min.
-                    ttl: Some(120),
-                    proxied: None,
-                    priority: None,
-                },
-            })
-            .await
-            .unwrap()
-            .result;
-
-        println!("Created DNS record: {:?}", dns_record);
-    } else {
-        // Update the DNS record.
-        let dns_record = api_client
-            .request(&dns::UpdateDnsRecord {
-                zone_identifier: &zone_identifier,
-                identifier: &dns_records[0].id,
-                params: dns::UpdateDnsRecordParams {
-                    name: &domain,
-                    content: dns::DnsContent::A { content: ip.parse().unwrap() },
-                    ttl: None,
-                    proxied: None,
-                },
-            })
-            .await
-            .unwrap()
-            .result;
-
-        println!("Updated DNS record: {:?}", dns_record);
+    // Create the DNS provider, this is Cloudflare unless DNS_PROVIDER says otherwise.
+    let dns_provider = dns_provider_from_env()?;
+
+    match matches.subcommand() {
+        ("list", Some(m)) => {
+            let zone = get_zone_for_domain(m.value_of("zone").unwrap())?;
+            for (name, record) in dns_provider.list_records(&zone).await? {
+                println!("{} {}", name, record);
+            }
+        }
+        ("upsert", Some(m)) => {
+            let name = m.value_of("name").unwrap();
+            let record_type = m.value_of("type").unwrap();
+            let mut records: Vec<DnsRecord> = Default::default();
+            for value in m.values_of("value").unwrap() {
+                records.push(DnsRecord::new(record_type, value)?);
+            }
+            if record_type == "CNAME" && records.len() > 1 {
+                return Err(format!("`{}` can only have one CNAME", name).into());
+            }
+
+            println!("Setting {} {} -> {}", record_type, name, records.iter().map(|r| r.value()).collect::<Vec<String>>().join(", "));
+            dns_provider.ensure_records(name, &records).await?;
+        }
+        ("delete", Some(m)) => {
+            let name = m.value_of("name").unwrap();
+            let record_type = m.value_of("type").unwrap();
+
+            // Any value will do, we delete all the records of the type.
+            let record = match record_type {
+                "A" => DnsRecord::A("0.0.0.0".parse()?),
+                "AAAA" => DnsRecord::AAAA("::".parse()?),
+                "CNAME" => DnsRecord::CNAME(Default::default()),
+                _ => DnsRecord::TXT(Default::default()),
+            };
+
+            println!("Deleting {} {}", record_type, name);
+            dns_provider.delete_record(name, &record).await?;
+        }
+        ("diff", Some(m)) => {
+            let config = get_zone_config(m).await?;
+            let changes = plan_zone(dns_provider.as_ref(), &config, m.is_present("prune")).await?;
+            print_changes(&config.zone, &changes, false);
+        }
+        ("apply", Some(m)) => {
+            let config = get_zone_config(m).await?;
+            let changes = apply_zone(dns_provider.as_ref(), &config, m.is_present("prune")).await?;
+            print_changes(&config.zone, &changes, true);
+        }
+        _ => unreachable!(),
     }
 
     Ok(())
 }
+
+/// Get the zone config from the file passed on the command line, or from the configs repo.
+async fn get_zone_config(m: &ArgMatches<'_>) -> Result<DnsZoneConfig, String> {
+    let zone = get_zone_for_domain(m.value_of("zone").unwrap())?;
+
+    if let Some(file) = m.value_of("file") {
+        let contents = fs::read_to_string(file).map_err(|e| format!("reading `{}` failed: {}", file, e))?;
+        return DnsZoneConfig::parse(&contents, &zone);
+    }
+
+    let missing: Vec<&str> = ["GH_APP_ID", "GH_INSTALLATION_ID", "GH_PRIVATE_KEY"]
+        .iter()
+        .filter(|v| env::var(v).unwrap_or_default().is_empty())
+        .copied()
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("${} must be set to read the zone config from the configs repo, or pass --file", missing.join(", $")));
+    }
+
+    let github = authenticate_github_jwt();
+    get_dns_zone_config_from_repo(&github, &zone).await
+}
+
+fn print_changes(zone: &str, changes: &[DnsRecordChange], applied: bool) {
+    if changes.is_empty() {
+        println!("{} is up to date", zone);
+        return;
+    }
+
+    for change in changes {
+        println!("{}", change);
+    }
+    if applied {
+        println!("applied {} changes to {}", changes.len(), zone);
+    } else {
+        println!("{} changes needed for {}, run `cfcert apply` to make them", changes.len(), zone);
+    }
+}
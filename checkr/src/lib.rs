@@ -149,6 +149,28 @@ impl Checkr {
         Ok(candidates)
     }
 
+    /// Get a candidate by their email, without listing every candidate.
+    pub async fn get_candidate_by_email(&self, email: &str) -> Result<Option<Candidate>, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, "candidates", (), Some(vec![("email", email.to_string())]));
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CandidatesResponse = resp.json().await.unwrap();
+
+        // The filter is not case sensitive, so make sure we have the right one.
+        Ok(r.candidates.into_iter().find(|c| c.email.eq_ignore_ascii_case(email)))
+    }
+
     /// Get a candidate.
     pub async fn get_candidate(&self, id: &str) -> Result<Candidate, APIError> {
         // Build the request.
@@ -230,7 +252,9 @@ impl Checkr {
     }
 
     /// Create a new invitation.
-    pub async fn create_invitation(&self, candidate_id: &str, package: &str) -> Result<Invitation, APIError> {
+    /// The work locations are where the candidate will work, Checkr uses them to
+    /// decide which screenings are allowed and for international packages.
+    pub async fn create_invitation(&self, candidate_id: &str, package: &str, work_locations: Vec<WorkLocation>) -> Result<Invitation, APIError> {
         // Build the request.
         let request = self.request(
             Method::POST,
@@ -238,6 +262,7 @@ impl Checkr {
             InvitationRequest {
                 package: package.to_string(),
                 candidate_id: candidate_id.to_string(),
+                work_locations,
             },
             None,
         );
@@ -426,6 +451,41 @@ pub struct Report {
     pub estimated_completion_time: String,
 }
 
+impl Report {
+    /// Returns the names of the screenings that are part of the report.
+    pub fn screenings(&self) -> Vec<String> {
+        let mut screenings: Vec<String> = Default::default();
+
+        let mut add = |name: &str, present: bool| {
+            if present {
+                screenings.push(name.to_string());
+            }
+        };
+
+        add("ssn_trace", !self.ssn_trace_id.is_empty());
+        add("sex_offender_search", !self.sex_offender_search_id.is_empty());
+        add("global_watchlist_search", !self.global_watchlist_search_id.is_empty());
+        add("national_criminal_search", !self.national_criminal_search_id.is_empty());
+        add("federal_criminal_search", !self.federal_criminal_search_id.is_empty());
+        add("county_criminal_search", !self.county_criminal_search_ids.is_empty());
+        add("state_criminal_search", !self.state_criminal_searches.is_empty());
+        add("arrest_search", !self.arrest_search_id.is_empty());
+        add("facis_search", !self.facis_search_id.is_empty());
+        add("motor_vehicle_report", !self.motor_vehicle_report_id.is_empty());
+        add("drug_screening", !self.drug_screening_id.is_empty());
+        add("personal_reference_verification", !self.personal_reference_verification_ids.is_empty());
+        add("professional_reference_verification", !self.professional_reference_verification_ids.is_empty());
+        add("professional_license_verification", !self.professional_license_verification_ids.is_empty());
+
+        screenings
+    }
+
+    /// Returns if the report includes a criminal screening.
+    pub fn has_criminal_screening(&self) -> bool {
+        self.screenings().iter().any(|s| s.contains("criminal") || s == "sex_offender_search" || s == "global_watchlist_search")
+    }
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct DrugScreening {
     #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "deserialize_null_string::deserialize")]
@@ -468,6 +528,19 @@ pub struct InvitationRequest {
     pub package: String,
     #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "deserialize_null_string::deserialize")]
     pub candidate_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub work_locations: Vec<WorkLocation>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct WorkLocation {
+    /// The ISO 3166 alpha-2 code of the country.
+    #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "deserialize_null_string::deserialize")]
+    pub country: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "deserialize_null_string::deserialize")]
+    pub state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "deserialize_null_string::deserialize")]
+    pub city: String,
 }
 
 #[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
@@ -0,0 +1 @@
+DROP TABLE rfd_state_history
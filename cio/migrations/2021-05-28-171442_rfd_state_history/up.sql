@@ -0,0 +1,13 @@
+CREATE TABLE rfd_state_history (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    rfd_name VARCHAR NOT NULL,
+    from_state VARCHAR NOT NULL,
+    to_state VARCHAR NOT NULL,
+    valid_transition BOOLEAN NOT NULL DEFAULT 't',
+    branch VARCHAR NOT NULL,
+    sha VARCHAR NOT NULL,
+    changed_by VARCHAR NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+)
@@ -0,0 +1,2 @@
+DROP INDEX rfds_search_idx;
+DROP FUNCTION rfd_search_vector
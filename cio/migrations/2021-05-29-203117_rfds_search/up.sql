@@ -0,0 +1,8 @@
+CREATE FUNCTION rfd_search_vector(title VARCHAR, authors VARCHAR, state VARCHAR, content TEXT) RETURNS tsvector AS $$
+    SELECT setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(authors, '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(state, '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(content, '')), 'C')
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE INDEX rfds_search_idx ON rfds USING GIN (rfd_search_vector(title, authors, state, content))
@@ -0,0 +1,10 @@
+CREATE TABLE rfd_references (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    reference_type VARCHAR NOT NULL,
+    target_rfd_number INTEGER NOT NULL DEFAULT 0,
+    target VARCHAR NOT NULL,
+    link VARCHAR NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (rfd_number, link)
+)
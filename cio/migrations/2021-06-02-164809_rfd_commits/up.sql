@@ -0,0 +1,13 @@
+CREATE TABLE rfd_commits (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    sha VARCHAR NOT NULL,
+    branch VARCHAR NOT NULL,
+    message VARCHAR NOT NULL,
+    author VARCHAR NOT NULL,
+    committed_at TIMESTAMPTZ NOT NULL,
+    diff TEXT NOT NULL,
+    link VARCHAR NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (rfd_number, sha)
+)
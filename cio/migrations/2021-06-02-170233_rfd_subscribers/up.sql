@@ -0,0 +1,7 @@
+CREATE TABLE rfd_subscribers (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    subscription VARCHAR NOT NULL DEFAULT 'all',
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+)
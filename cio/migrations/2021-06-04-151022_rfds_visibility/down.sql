@@ -0,0 +1,2 @@
+ALTER TABLE rfds DROP COLUMN visibility_groups;
+ALTER TABLE rfds DROP COLUMN visibility;
@@ -0,0 +1,2 @@
+ALTER TABLE rfds ADD COLUMN visibility VARCHAR NOT NULL DEFAULT 'internal';
+ALTER TABLE rfds ADD COLUMN visibility_groups TEXT [] NOT NULL DEFAULT '{}';
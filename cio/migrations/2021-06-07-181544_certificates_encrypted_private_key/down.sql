@@ -0,0 +1,2 @@
+ALTER TABLE certificates DROP COLUMN fingerprint;
+ALTER TABLE certificates RENAME COLUMN encrypted_private_key TO private_key;
@@ -0,0 +1,3 @@
+ALTER TABLE certificates RENAME COLUMN private_key TO encrypted_private_key;
+-- The existing keys are in plaintext, the next sync encrypts them.
+ALTER TABLE certificates ADD COLUMN fingerprint VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,3 @@
+ALTER TABLE certificates DROP COLUMN renewal_window_days;
+ALTER TABLE certificates DROP COLUMN staging;
+ALTER TABLE certificates DROP COLUMN sans;
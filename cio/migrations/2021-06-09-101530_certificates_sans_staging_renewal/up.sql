@@ -0,0 +1,3 @@
+ALTER TABLE certificates ADD COLUMN sans TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE certificates ADD COLUMN staging BOOLEAN NOT NULL DEFAULT 'f';
+ALTER TABLE certificates ADD COLUMN renewal_window_days INTEGER NOT NULL DEFAULT 7;
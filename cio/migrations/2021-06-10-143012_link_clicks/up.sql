@@ -0,0 +1,13 @@
+CREATE TABLE link_clicks (
+    id SERIAL PRIMARY KEY,
+    time TIMESTAMPTZ NOT NULL,
+    subdomain VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    short_link VARCHAR NOT NULL,
+    target VARCHAR NOT NULL,
+    referrer VARCHAR NOT NULL,
+    user_email VARCHAR NOT NULL,
+    airtable_record_id VARCHAR NOT NULL
+);
+
+CREATE INDEX link_clicks_time ON link_clicks (time);
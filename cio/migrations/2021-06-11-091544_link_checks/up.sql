@@ -0,0 +1,16 @@
+CREATE TABLE link_checks (
+    id SERIAL PRIMARY KEY,
+    short_link VARCHAR NOT NULL UNIQUE,
+    subdomain VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    target VARCHAR NOT NULL,
+    status_code INTEGER NOT NULL,
+    error VARCHAR NOT NULL,
+    redirect_chain TEXT [] NOT NULL,
+    healthy BOOLEAN NOT NULL DEFAULT 'f',
+    last_checked TIMESTAMPTZ NOT NULL,
+    last_success TIMESTAMPTZ,
+    failing_since TIMESTAMPTZ,
+    github_issue VARCHAR NOT NULL,
+    airtable_record_id VARCHAR NOT NULL
+)
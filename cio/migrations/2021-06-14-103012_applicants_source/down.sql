@@ -0,0 +1 @@
+ALTER TABLE applicants DROP COLUMN source;
@@ -0,0 +1 @@
+ALTER TABLE applicants ADD COLUMN source VARCHAR NOT NULL DEFAULT 'sheets';
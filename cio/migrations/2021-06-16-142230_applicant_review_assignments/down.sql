@@ -0,0 +1,4 @@
+ALTER TABLE applicant_reviewers DROP COLUMN out_of_office_until;
+ALTER TABLE applicant_reviewers DROP COLUMN overdue_assignments;
+ALTER TABLE applicant_reviewers DROP COLUMN open_assignments;
+DROP TABLE applicant_review_assignments
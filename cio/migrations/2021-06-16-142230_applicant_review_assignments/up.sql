@@ -0,0 +1,19 @@
+CREATE TABLE applicant_review_assignments (
+    id SERIAL PRIMARY KEY,
+    reviewer VARCHAR NOT NULL,
+    applicant_email VARCHAR NOT NULL,
+    applicant_name VARCHAR NOT NULL,
+    role VARCHAR NOT NULL,
+    sheet_id VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    assigned_time TIMESTAMPTZ NOT NULL,
+    due_time TIMESTAMPTZ NOT NULL,
+    completed_time TIMESTAMPTZ,
+    reminders_sent INTEGER NOT NULL DEFAULT 0,
+    last_reminder_time TIMESTAMPTZ,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (reviewer, applicant_email, sheet_id)
+);
+ALTER TABLE applicant_reviewers ADD COLUMN open_assignments INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN overdue_assignments INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN out_of_office_until TIMESTAMPTZ;
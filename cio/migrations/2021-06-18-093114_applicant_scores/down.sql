@@ -0,0 +1,7 @@
+ALTER TABLE applicants ADD COLUMN scoring_form_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN scoring_form_url VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN scoring_form_responses_url VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants DROP COLUMN scoring_disagreement;
+ALTER TABLE applicants DROP COLUMN scoring_variance;
+ALTER TABLE applicants DROP COLUMN scoring_mean;
+DROP TABLE applicant_scores;
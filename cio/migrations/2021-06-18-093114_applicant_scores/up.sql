@@ -0,0 +1,25 @@
+CREATE TABLE applicant_scores (
+    id SERIAL PRIMARY KEY,
+    reviewer VARCHAR NOT NULL,
+    applicant_email VARCHAR NOT NULL,
+    applicant_name VARCHAR NOT NULL DEFAULT '',
+    role VARCHAR NOT NULL DEFAULT '',
+    sheet_id VARCHAR NOT NULL DEFAULT '',
+    recommendation VARCHAR NOT NULL,
+    score INTEGER NOT NULL,
+    technical_depth INTEGER NOT NULL,
+    writing INTEGER NOT NULL,
+    values_reflected TEXT [] NOT NULL DEFAULT '{}',
+    values_violated TEXT [] NOT NULL DEFAULT '{}',
+    concerns TEXT [] NOT NULL DEFAULT '{}',
+    notes TEXT NOT NULL DEFAULT '',
+    submitted_time TIMESTAMPTZ,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (reviewer, applicant_email, sheet_id)
+);
+ALTER TABLE applicants ADD COLUMN scoring_mean REAL NOT NULL DEFAULT 0;
+ALTER TABLE applicants ADD COLUMN scoring_variance REAL NOT NULL DEFAULT 0;
+ALTER TABLE applicants ADD COLUMN scoring_disagreement BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE applicants DROP COLUMN scoring_form_id;
+ALTER TABLE applicants DROP COLUMN scoring_form_url;
+ALTER TABLE applicants DROP COLUMN scoring_form_responses_url;
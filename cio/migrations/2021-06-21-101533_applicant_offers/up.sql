@@ -0,0 +1,2 @@
+ALTER TABLE applicants ADD COLUMN offer_employment_type VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN offer_salary VARCHAR NOT NULL DEFAULT '';
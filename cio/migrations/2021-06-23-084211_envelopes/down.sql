@@ -0,0 +1,2 @@
+DROP TABLE envelope_events;
+DROP TABLE envelopes;
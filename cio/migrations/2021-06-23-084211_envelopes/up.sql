@@ -0,0 +1,32 @@
+CREATE TABLE envelopes (
+    id SERIAL PRIMARY KEY,
+    envelope_id VARCHAR NOT NULL UNIQUE,
+    applicant_email VARCHAR NOT NULL DEFAULT '',
+    email_subject VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL,
+    created_time TIMESTAMPTZ,
+    sent_time TIMESTAMPTZ,
+    delivered_time TIMESTAMPTZ,
+    completed_time TIMESTAMPTZ,
+    declined_time TIMESTAMPTZ,
+    declined_reason VARCHAR NOT NULL DEFAULT '',
+    voided_time TIMESTAMPTZ,
+    voided_reason VARCHAR NOT NULL DEFAULT '',
+    status_changed_time TIMESTAMPTZ,
+    reminders_sent INTEGER NOT NULL DEFAULT 0,
+    last_reminder_sent_time TIMESTAMPTZ,
+    archived_documents TEXT [] NOT NULL DEFAULT '{}',
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+CREATE TABLE envelope_events (
+    id SERIAL PRIMARY KEY,
+    envelope_id VARCHAR NOT NULL,
+    recipient_email VARCHAR NOT NULL DEFAULT '',
+    recipient_name VARCHAR NOT NULL DEFAULT '',
+    recipient_role VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL,
+    reason VARCHAR NOT NULL DEFAULT '',
+    time TIMESTAMPTZ NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (envelope_id, recipient_email, status)
+);
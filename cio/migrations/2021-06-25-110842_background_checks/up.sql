@@ -0,0 +1,20 @@
+CREATE TABLE background_checks (
+    id SERIAL PRIMARY KEY,
+    report_id VARCHAR NOT NULL UNIQUE,
+    candidate_id VARCHAR NOT NULL DEFAULT '',
+    applicant_email VARCHAR NOT NULL DEFAULT '',
+    package VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT '',
+    result VARCHAR NOT NULL DEFAULT '',
+    adjudication VARCHAR NOT NULL DEFAULT '',
+    screenings TEXT [] NOT NULL DEFAULT '{}',
+    created_time TIMESTAMPTZ,
+    estimated_completion_time TIMESTAMPTZ,
+    completed_time TIMESTAMPTZ,
+    adverse_action_requested BOOLEAN NOT NULL DEFAULT false,
+    adverse_action_reasons TEXT NOT NULL DEFAULT '',
+    adverse_action_status VARCHAR NOT NULL DEFAULT '',
+    pre_adverse_notice_sent_time TIMESTAMPTZ,
+    post_adverse_notice_sent_time TIMESTAMPTZ,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
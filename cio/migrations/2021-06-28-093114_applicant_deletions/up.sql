@@ -0,0 +1,16 @@
+CREATE TABLE applicant_deletions (
+    id SERIAL PRIMARY KEY,
+    applicant_email_hash VARCHAR NOT NULL,
+    sheet_id VARCHAR NOT NULL DEFAULT '',
+    role VARCHAR NOT NULL DEFAULT '',
+    applicant_status VARCHAR NOT NULL DEFAULT '',
+    submitted_time TIMESTAMPTZ,
+    action VARCHAR NOT NULL,
+    reason VARCHAR NOT NULL DEFAULT '',
+    requested_by VARCHAR NOT NULL DEFAULT '',
+    removed TEXT [] NOT NULL DEFAULT '{}',
+    errors TEXT [] NOT NULL DEFAULT '{}',
+    completed_time TIMESTAMPTZ NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (applicant_email_hash, sheet_id, action)
+);
@@ -0,0 +1,19 @@
+CREATE TABLE applicant_interview_schedules (
+    id SERIAL PRIMARY KEY,
+    token VARCHAR NOT NULL UNIQUE,
+    applicant_email VARCHAR NOT NULL,
+    applicant_name VARCHAR NOT NULL,
+    sheet_id VARCHAR NOT NULL DEFAULT '',
+    role VARCHAR NOT NULL,
+    panel VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    conference VARCHAR NOT NULL,
+    created_time TIMESTAMPTZ NOT NULL,
+    expires_time TIMESTAMPTZ NOT NULL,
+    scheduled_start_time TIMESTAMPTZ,
+    scheduled_end_time TIMESTAMPTZ,
+    interviewers TEXT [] NOT NULL DEFAULT '{}',
+    google_event_ids TEXT [] NOT NULL DEFAULT '{}',
+    meeting_links TEXT [] NOT NULL DEFAULT '{}',
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
@@ -0,0 +1,19 @@
+CREATE TABLE interview_packets (
+    id SERIAL PRIMARY KEY,
+    interviewer VARCHAR NOT NULL,
+    applicant_email VARCHAR NOT NULL,
+    applicant_name VARCHAR NOT NULL,
+    role VARCHAR NOT NULL,
+    sheet_id VARCHAR NOT NULL DEFAULT '',
+    interview_start_time TIMESTAMPTZ NOT NULL,
+    interview_end_time TIMESTAMPTZ NOT NULL,
+    content_hash VARCHAR NOT NULL DEFAULT '',
+    google_drive_id VARCHAR NOT NULL DEFAULT '',
+    link VARCHAR NOT NULL DEFAULT '',
+    permission_id VARCHAR NOT NULL DEFAULT '',
+    built_time TIMESTAMPTZ,
+    shared_time TIMESTAMPTZ,
+    revoked_time TIMESTAMPTZ,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (interviewer, applicant_email, sheet_id)
+);
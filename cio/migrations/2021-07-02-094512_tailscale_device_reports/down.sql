@@ -0,0 +1 @@
+DROP TABLE tailscale_device_reports
@@ -0,0 +1,10 @@
+CREATE TABLE tailscale_device_reports (
+    id SERIAL PRIMARY KEY,
+    device_id VARCHAR NOT NULL UNIQUE,
+    device_name VARCHAR NOT NULL,
+    device_user VARCHAR NOT NULL,
+    policy VARCHAR NOT NULL,
+    reason VARCHAR NOT NULL,
+    reported_at TIMESTAMPTZ NOT NULL,
+    airtable_record_id VARCHAR NOT NULL
+)
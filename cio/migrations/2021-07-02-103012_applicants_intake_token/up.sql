@@ -0,0 +1 @@
+ALTER TABLE applicants ADD COLUMN intake_token_hash VARCHAR NOT NULL DEFAULT '';
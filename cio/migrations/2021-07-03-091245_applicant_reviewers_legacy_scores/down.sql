@@ -0,0 +1,6 @@
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_not_applicable;
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_no;
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_pass;
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_yes;
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_emphatic_yes;
+ALTER TABLE applicant_reviewers DROP COLUMN legacy_evaluations;
@@ -0,0 +1,7 @@
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_evaluations INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_emphatic_yes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_yes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_pass INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_no INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicant_reviewers ADD COLUMN legacy_not_applicable INTEGER NOT NULL DEFAULT 0;
+UPDATE applicant_reviewers SET legacy_evaluations = evaluations, legacy_emphatic_yes = emphatic_yes, legacy_yes = yes, legacy_pass = pass, legacy_no = no, legacy_not_applicable = not_applicable;
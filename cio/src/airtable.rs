@@ -5,6 +5,7 @@ pub static AIRTABLE_CUSTOMER_INTERACTIONS_TABLE: &str = "Interactions";
 pub static AIRTABLE_AUTH_USERS_TABLE: &str = "Auth Users";
 pub static AIRTABLE_AUTH_USER_LOGINS_TABLE: &str = "Auth User Logins";
 pub static AIRTABLE_PAGE_VIEWS_TABLE: &str = "Page Views";
+pub static AIRTABLE_LINK_CLICKS_TABLE: &str = "Link Clicks";
 
 pub static AIRTABLE_BASE_ID_DIRECTORY: &str = "appzV7RV5yJH6VFbL";
 pub static AIRTABLE_EMPLOYEES_TABLE: &str = "Employees";
@@ -12,6 +13,8 @@ pub static AIRTABLE_GROUPS_TABLE: &str = "Groups";
 pub static AIRTABLE_BUILDINGS_TABLE: &str = "Buildings";
 pub static AIRTABLE_CONFERENCE_ROOMS_TABLE: &str = "Conference Rooms";
 pub static AIRTABLE_LINKS_TABLE: &str = "Links";
+pub static AIRTABLE_LINK_CHECKS_TABLE: &str = "Link Checks";
+pub static AIRTABLE_TAILSCALE_DEVICE_REPORTS_TABLE: &str = "Tailscale Device Reports";
 
 pub static AIRTABLE_BASE_ID_MISC: &str = "appfWJK9kBNwnavG2";
 pub static AIRTABLE_CERTIFICATES_TABLE: &str = "Certificates";
@@ -22,11 +25,23 @@ pub static AIRTABLE_RECORDED_MEETINGS_TABLE: &str = "Recorded Meetings";
 
 pub static AIRTABLE_BASE_ID_RACK_ROADMAP: &str = "appvAEzcMvB2QNboC";
 pub static AIRTABLE_RFD_TABLE: &str = "RFDs";
+pub static AIRTABLE_RFD_STATE_HISTORY_TABLE: &str = "RFD State History";
+pub static AIRTABLE_RFD_REFERENCES_TABLE: &str = "RFD References";
+pub static AIRTABLE_RFD_COMMITS_TABLE: &str = "RFD Commits";
+pub static AIRTABLE_RFD_SUBSCRIBERS_TABLE: &str = "RFD Subscribers";
 
 pub static AIRTABLE_BASE_ID_RECURITING_APPLICATIONS: &str = "appIw5FNBqWTXFTeV";
 pub static AIRTABLE_APPLICATIONS_TABLE: &str = "Applicants";
 pub static AIRTABLE_INTERVIEWS_TABLE: &str = "Interviews";
+pub static AIRTABLE_INTERVIEW_SCHEDULES_TABLE: &str = "Interview Schedules";
+pub static AIRTABLE_INTERVIEW_PACKETS_TABLE: &str = "Interview Packets";
 pub static AIRTABLE_REVIEWER_LEADERBOARD_TABLE: &str = "Reviewer Leaderboard";
+pub static AIRTABLE_REVIEW_ASSIGNMENTS_TABLE: &str = "Review Assignments";
+pub static AIRTABLE_SCORES_TABLE: &str = "Scores";
+pub static AIRTABLE_ENVELOPES_TABLE: &str = "Envelopes";
+pub static AIRTABLE_ENVELOPE_EVENTS_TABLE: &str = "Envelope Events";
+pub static AIRTABLE_BACKGROUND_CHECKS_TABLE: &str = "Background Checks";
+pub static AIRTABLE_APPLICANT_DELETIONS_TABLE: &str = "Applicant Deletions";
 
 pub static AIRTABLE_DISCUSSION_TOPICS_TABLE: &str = "Discussion topics";
 pub static AIRTABLE_MEETING_SCHEDULE_TABLE: &str = "Meeting schedule";
@@ -1,14 +1,17 @@
 #![allow(clippy::from_over_into)]
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use diesel::sql_types::{BigInt, Timestamptz, Varchar};
+use diesel::RunQueryDsl;
 use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::airtable::{AIRTABLE_BASE_ID_CUSTOMER_LEADS, AIRTABLE_PAGE_VIEWS_TABLE};
+use crate::airtable::{AIRTABLE_BASE_ID_CUSTOMER_LEADS, AIRTABLE_LINK_CLICKS_TABLE, AIRTABLE_PAGE_VIEWS_TABLE};
 use crate::auth_logins::AuthUsers;
 use crate::core::UpdateAirtableRecord;
-use crate::schema::page_views;
+use crate::db::Database;
+use crate::schema::{link_clicks, page_views};
 
 #[db {
     new_struct_name = "PageView",
@@ -60,6 +63,100 @@ impl NewPageView {
     }
 }
 
+/// A click on one of our short URLs, recorded by the short URL redirect server.
+#[db {
+    new_struct_name = "LinkClick",
+    airtable_base_id = "AIRTABLE_BASE_ID_CUSTOMER_LEADS",
+    airtable_table = "AIRTABLE_LINK_CLICKS_TABLE",
+    match_on = {
+        "time" = "DateTime<Utc>",
+        "short_link" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "link_clicks"]
+pub struct NewLinkClick {
+    pub time: DateTime<Utc>,
+    pub subdomain: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// short_link is the canonical short URL that was clicked, ie. `https://42.rfd.oxide.computer`.
+    pub short_link: String,
+    /// target is where we redirected to.
+    pub target: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub referrer: String,
+    /// user_email is the email of the user that clicked, if they were authenticated.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_email: String,
+}
+
+impl NewLinkClick {
+    /// Save the click in the database, returning any error instead of panicking, so a
+    /// problem with the database never gets in the way of a redirect.
+    pub fn record(&self, db: &Database) -> Result<(), String> {
+        let conn = db.try_conn()?;
+        diesel::insert_into(link_clicks::table)
+            .values(self)
+            .execute(&conn)
+            .map_err(|e| format!("recording the click on {} failed: {}", self.short_link, e))?;
+
+        Ok(())
+    }
+}
+
+/// Implement updating the Airtable record for a LinkClick.
+#[async_trait]
+impl UpdateAirtableRecord<LinkClick> for LinkClick {
+    async fn update_airtable_record(&mut self, _record: LinkClick) {}
+}
+
+/// The default number of links we return in the top links report.
+pub static TOP_LINKS_LIMIT: i64 = 25;
+
+/// A short URL and how many times it was clicked.
+#[derive(Debug, Clone, QueryableByName, JsonSchema, Deserialize, Serialize)]
+pub struct TopLink {
+    #[sql_type = "Varchar"]
+    pub short_link: String,
+    #[sql_type = "Varchar"]
+    pub subdomain: String,
+    #[sql_type = "Varchar"]
+    pub name: String,
+    /// target is where the short URL redirected to most recently.
+    #[sql_type = "Varchar"]
+    pub target: String,
+    #[sql_type = "BigInt"]
+    pub clicks: i64,
+    /// users is the number of distinct authenticated users that clicked the link.
+    #[sql_type = "BigInt"]
+    pub users: i64,
+    #[sql_type = "Timestamptz"]
+    pub last_clicked: DateTime<Utc>,
+}
+
+/// Get the most clicked short URLs since a time, optionally only for one subdomain,
+/// ie. `rfd` or `corp`.
+pub fn get_top_links(db: &Database, since: DateTime<Utc>, subdomain: &str, limit: i64) -> Vec<TopLink> {
+    diesel::sql_query(
+        r#"SELECT short_link, subdomain, name,
+            (ARRAY_AGG(target ORDER BY time DESC))[1] AS target,
+            COUNT(*) AS clicks,
+            COUNT(DISTINCT NULLIF(user_email, '')) AS users,
+            MAX(time) AS last_clicked
+        FROM link_clicks
+        WHERE time >= $1 AND ($2 = '' OR subdomain = $2)
+        GROUP BY short_link, subdomain, name
+        ORDER BY clicks DESC, short_link ASC
+        LIMIT $3"#,
+    )
+    .bind::<Timestamptz, _>(since)
+    .bind::<Varchar, _>(subdomain.trim().to_lowercase())
+    .bind::<BigInt, _>(limit)
+    .load::<TopLink>(&db.conn())
+    .unwrap_or_else(|e| panic!("getting the top links since {} failed: {}", since, e))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::analytics::PageViews;
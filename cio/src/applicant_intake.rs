@@ -0,0 +1,629 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use google_drive::GoogleDrive;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::applicants::{get_sheets_map, Applicant, NewApplicant, APPLICANT_SOURCE_API};
+use crate::db::Database;
+use crate::schema::applicants;
+use crate::slack::{get_hiring_channel_post_url, post_to_channel};
+use crate::utils::{get_or_create_automated_documents_folder, DOMAIN};
+
+/// The folder in the "Automated Documents" shared drive where we keep the files
+/// uploaded with applications.
+pub static APPLICANT_UPLOADS_FOLDER: &str = "applicant_uploads";
+
+/// The largest file we accept for a resume or materials, after decoding.
+/// Both of them base64 encoded have to fit in the request body webhooky accepts.
+pub static APPLICANT_UPLOAD_MAX_BYTES: usize = 10 * 1024 * 1024;
+
+/// The most new applications we take through the intake API in an hour, across
+/// everyone. Every new application emails the applicant, posts to Slack and emails
+/// the whole company, so a flood of them has to stop somewhere.
+pub static APPLICATIONS_MAX_NEW_PER_HOUR: i64 = 30;
+
+/// The questions we ask applicants in their candidate materials.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplicationQuestion {
+    WorkSamples,
+    WritingSamples,
+    AnalysisSamples,
+    PresentationSamples,
+    ExploratorySamples,
+    TechnicallyChallenging,
+    ProudOf,
+    Happiest,
+    Unhappiest,
+    ValueReflected,
+    ValueViolated,
+    ValuesInTension,
+    WhyOxide,
+}
+
+impl ApplicationQuestion {
+    /// The question as we ask it in the candidate materials.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            ApplicationQuestion::WorkSamples => "Work sample(s)",
+            ApplicationQuestion::WritingSamples => "Writing sample(s)",
+            ApplicationQuestion::AnalysisSamples => "Analysis sample(s)",
+            ApplicationQuestion::PresentationSamples => "Presentation sample(s)",
+            ApplicationQuestion::ExploratorySamples => "Exploratory sample(s)",
+            ApplicationQuestion::TechnicallyChallenging => "What work have you found most technically challenging in your career and why?",
+            ApplicationQuestion::ProudOf => "What work have you done that you were particularly proud of and why?",
+            ApplicationQuestion::Happiest => "When have you been happiest in your professional career and why?",
+            ApplicationQuestion::Unhappiest => "When have you been unhappiest in your professional career and why?",
+            ApplicationQuestion::ValueReflected => "For one of Oxide's values, describe an example of how it was reflected in a particular body of your work.",
+            ApplicationQuestion::ValueViolated => "For one of Oxide's values, describe an example of how it was violated in your organization or work.",
+            ApplicationQuestion::ValuesInTension => "For a pair of Oxide's values, describe a time in which the two values came into tension for you or your work, and how you resolved it.",
+            ApplicationQuestion::WhyOxide => "Why do you want to work for Oxide?",
+        }
+    }
+
+    /// Set the answer on the matching applicant field.
+    pub fn set_answer(&self, applicant: &mut NewApplicant, answer: &str) {
+        let field = match self {
+            ApplicationQuestion::WorkSamples => &mut applicant.work_samples,
+            ApplicationQuestion::WritingSamples => &mut applicant.writing_samples,
+            ApplicationQuestion::AnalysisSamples => &mut applicant.analysis_samples,
+            ApplicationQuestion::PresentationSamples => &mut applicant.presentation_samples,
+            ApplicationQuestion::ExploratorySamples => &mut applicant.exploratory_samples,
+            ApplicationQuestion::TechnicallyChallenging => &mut applicant.question_technically_challenging,
+            ApplicationQuestion::ProudOf => &mut applicant.question_proud_of,
+            ApplicationQuestion::Happiest => &mut applicant.question_happiest,
+            ApplicationQuestion::Unhappiest => &mut applicant.question_unhappiest,
+            ApplicationQuestion::ValueReflected => &mut applicant.question_value_reflected,
+            ApplicationQuestion::ValueViolated => &mut applicant.question_value_violated,
+            ApplicationQuestion::ValuesInTension => &mut applicant.question_values_in_tension,
+            ApplicationQuestion::WhyOxide => &mut applicant.question_why_oxide,
+        };
+        *field = answer.trim().to_string();
+    }
+}
+
+impl fmt::Display for ApplicationQuestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_value(self).unwrap().as_str().unwrap())
+    }
+}
+
+/// A question as it is asked for a specific role.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RoleQuestion {
+    pub question: ApplicationQuestion,
+    pub prompt: String,
+    /// Required questions must be answered in the submission, unless the
+    /// applicant uploads their candidate materials.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// The questions we ask for a role.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationRole {
+    pub name: String,
+    pub questions: Vec<RoleQuestion>,
+}
+
+impl ApplicationRole {
+    /// Get the question for the role, if we ask it.
+    pub fn get_question(&self, question: ApplicationQuestion) -> Option<&RoleQuestion> {
+        self.questions.iter().find(|q| q.question == question)
+    }
+}
+
+/// Get the questions for every role we are hiring for.
+/// The roles are the same ones we have Google Form response sheets for.
+pub fn get_application_roles() -> Vec<ApplicationRole> {
+    let mut roles: Vec<ApplicationRole> = Default::default();
+    for name in get_sheets_map().keys() {
+        // The samples we ask for differ by role.
+        let samples = match *name {
+            "Engineering" => vec![
+                (ApplicationQuestion::WorkSamples, true),
+                (ApplicationQuestion::WritingSamples, true),
+                (ApplicationQuestion::AnalysisSamples, true),
+                (ApplicationQuestion::PresentationSamples, false),
+            ],
+            "Product Engineering and Design" => vec![
+                (ApplicationQuestion::WorkSamples, true),
+                (ApplicationQuestion::WritingSamples, true),
+                (ApplicationQuestion::AnalysisSamples, false),
+                (ApplicationQuestion::PresentationSamples, false),
+            ],
+            "Technical Program Management" => vec![
+                (ApplicationQuestion::WritingSamples, true),
+                (ApplicationQuestion::ExploratorySamples, true),
+                (ApplicationQuestion::PresentationSamples, false),
+            ],
+            _ => vec![(ApplicationQuestion::WritingSamples, true), (ApplicationQuestion::PresentationSamples, false)],
+        };
+
+        let mut questions: Vec<RoleQuestion> = Default::default();
+        for (question, required) in samples.into_iter().chain(
+            vec![
+                ApplicationQuestion::TechnicallyChallenging,
+                ApplicationQuestion::ProudOf,
+                ApplicationQuestion::Happiest,
+                ApplicationQuestion::Unhappiest,
+                ApplicationQuestion::ValueReflected,
+                ApplicationQuestion::ValueViolated,
+                ApplicationQuestion::ValuesInTension,
+                ApplicationQuestion::WhyOxide,
+            ]
+            .into_iter()
+            .map(|q| (q, true)),
+        ) {
+            questions.push(RoleQuestion {
+                question,
+                prompt: question.prompt().to_string(),
+                required,
+            });
+        }
+
+        roles.push(ApplicationRole { name: name.to_string(), questions });
+    }
+
+    roles
+}
+
+/// Get the questions for a role by its name.
+pub fn get_application_role(name: &str) -> Option<ApplicationRole> {
+    get_application_roles().into_iter().find(|r| r.name == name)
+}
+
+/// A file uploaded with an application.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationUpload {
+    /// The file name, we use the extension to figure out the type of the file.
+    pub name: String,
+    /// The contents of the file, base64 encoded.
+    pub contents: String,
+}
+
+impl ApplicationUpload {
+    /// The file extension, lowercased.
+    /// We keep the `.tar.gz` double extension together.
+    pub fn extension(&self) -> String {
+        let name = self.name.trim().to_lowercase();
+        if name.ends_with(".tar.gz") {
+            return "tar.gz".to_string();
+        }
+
+        match name.rsplit_once('.') {
+            Some((_, ext)) => ext.to_string(),
+            None => "".to_string(),
+        }
+    }
+
+    /// Get the mime type for the file, if it is a type we accept.
+    /// Archives are only accepted for candidate materials.
+    pub fn mime_type(&self, allow_archives: bool) -> Option<&'static str> {
+        match self.extension().as_str() {
+            "pdf" => Some("application/pdf"),
+            "doc" => Some("application/msword"),
+            "docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            "odt" => Some("application/vnd.oasis.opendocument.text"),
+            "txt" => Some("text/plain"),
+            "md" => Some("text/markdown"),
+            "zip" if allow_archives => Some("application/zip"),
+            "tar" if allow_archives => Some("application/x-tar"),
+            "tar.gz" | "tgz" if allow_archives => Some("application/gzip"),
+            _ => None,
+        }
+    }
+
+    /// Decode and check the file, returning the mime type and the bytes.
+    pub fn decode(&self, kind: &str, allow_archives: bool) -> Result<(&'static str, Vec<u8>), String> {
+        let mime_type = match self.mime_type(allow_archives) {
+            Some(m) => m,
+            None => return Err(format!("{} `{}` is not a file type we accept", kind, self.name)),
+        };
+
+        let bytes = base64::decode(self.contents.trim()).map_err(|e| format!("{} `{}` is not valid base64: {}", kind, self.name, e))?;
+        if bytes.is_empty() {
+            return Err(format!("{} `{}` is empty", kind, self.name));
+        }
+        if bytes.len() > APPLICANT_UPLOAD_MAX_BYTES {
+            return Err(format!("{} `{}` is larger than {} MB", kind, self.name, APPLICANT_UPLOAD_MAX_BYTES / 1024 / 1024));
+        }
+
+        Ok((mime_type, bytes))
+    }
+}
+
+/// An application submitted through the intake API.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationSubmission {
+    /// The role, one of the names returned by `get_application_roles`.
+    pub role: String,
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub github: String,
+    #[serde(default)]
+    pub linkedin: String,
+    #[serde(default)]
+    pub portfolio: String,
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub interested_in: Vec<String>,
+    #[serde(default)]
+    pub value_reflected: String,
+    #[serde(default)]
+    pub value_violated: String,
+    #[serde(default)]
+    pub values_in_tension: Vec<String>,
+    /// The answers to the questions for the role.
+    #[serde(default)]
+    pub answers: BTreeMap<ApplicationQuestion, String>,
+    pub resume: ApplicationUpload,
+    /// The candidate materials, if the applicant did not answer the
+    /// questions inline.
+    #[serde(default)]
+    pub materials: Option<ApplicationUpload>,
+    /// The token we emailed the applicant when they first submitted the application,
+    /// required to update it.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl ApplicationSubmission {
+    /// Get the email the application is stored under. Emails are matched without
+    /// case, so `Jane@example.com` and `jane@example.com` are the same application.
+    pub fn normalized_email(&self) -> String {
+        self.email.trim().to_lowercase()
+    }
+
+    /// Check the submission, returning all the problems with it at once.
+    pub fn validate(&self) -> Result<ApplicationRole, String> {
+        let mut problems: Vec<String> = Default::default();
+
+        if self.name.trim().is_empty() {
+            problems.push("name is required".to_string());
+        }
+        let email = self.email.trim();
+        if email.is_empty() || email.contains(char::is_whitespace) || !email.contains('@') {
+            problems.push(format!("email `{}` is not a valid email address", self.email));
+        }
+        if self.values_in_tension.len() > 2 {
+            problems.push("values_in_tension can have at most two values".to_string());
+        }
+
+        if let Err(e) = self.resume.decode("resume", false) {
+            problems.push(e);
+        }
+        if let Some(materials) = &self.materials {
+            if let Err(e) = materials.decode("materials", true) {
+                problems.push(e);
+            }
+        }
+
+        let role = get_application_role(&self.role);
+        match &role {
+            Some(role) => {
+                for question in self.answers.keys() {
+                    if role.get_question(*question).is_none() {
+                        problems.push(format!("`{}` is not a question for the {} role", question, role.name));
+                    }
+                }
+
+                // Without candidate materials, every required question needs an answer.
+                if self.materials.is_none() {
+                    for q in role.questions.iter().filter(|q| q.required) {
+                        if self.answers.get(&q.question).map(|a| a.trim().is_empty()).unwrap_or(true) {
+                            problems.push(format!("`{}` is required without candidate materials", q.question));
+                        }
+                    }
+                }
+            }
+            None => problems.push(format!("`{}` is not a role we are hiring for", self.role)),
+        }
+
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+
+        Ok(role.unwrap())
+    }
+}
+
+/// What we send back after an application was submitted.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationReceipt {
+    pub email: String,
+    pub role: String,
+    pub status: String,
+    pub submitted_time: chrono::DateTime<Utc>,
+    /// If we already had an application for this email and role, in which case
+    /// the new submission replaced it.
+    #[serde(default)]
+    pub resubmitted: bool,
+    pub resume: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub materials: String,
+}
+
+/// Hash the token for updating an application, so we do not store the token itself.
+pub fn hash_application_token(token: &str) -> String {
+    openssl::sha::sha256(token.trim().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns if the token is the one for the application with the token hash.
+/// Applications without a token hash were not submitted through the intake API, so
+/// they can not be updated through it.
+pub fn is_application_token_valid(token_hash: &str, token: &str) -> bool {
+    if token_hash.is_empty() || token.trim().is_empty() {
+        return false;
+    }
+
+    openssl::memcmp::eq(token_hash.as_bytes(), hash_application_token(token).as_bytes())
+}
+
+/// Check the submission is not over our limits for new applications.
+/// Updates to an application are not limited, they need the token we emailed
+/// the applicant and do not send any notifications.
+pub fn check_application_limits(db: &Database, submission: &ApplicationSubmission) -> Result<(), String> {
+    if let Some(sheet_id) = get_sheets_map().get(submission.role.as_str()) {
+        if Applicant::get_from_db(db, submission.normalized_email(), sheet_id.to_string()).is_some() {
+            return Ok(());
+        }
+    }
+
+    let recent = applicants::dsl::applicants
+        .filter(applicants::dsl::source.eq(APPLICANT_SOURCE_API.to_string()))
+        .filter(applicants::dsl::submitted_time.gt(Utc::now() - Duration::hours(1)))
+        .count()
+        .get_result::<i64>(&db.conn())
+        .map_err(|e| format!("counting the recent applications failed: {}", e))?;
+    if recent >= APPLICATIONS_MAX_NEW_PER_HOUR {
+        return Err(format!("we took {} applications in the last hour, try again later", recent));
+    }
+
+    Ok(())
+}
+
+/// Upload a file from an application to the uploads folder, returning the url.
+/// The name only depends on the applicant and the role, so resubmitting replaces
+/// the file rather than adding another one.
+async fn upload_application_file(
+    drive_client: &GoogleDrive,
+    drive_id: &str,
+    parent_id: &str,
+    applicant: &NewApplicant,
+    kind: &str,
+    upload: &ApplicationUpload,
+    allow_archives: bool,
+) -> Result<String, String> {
+    let (mime_type, bytes) = upload.decode(kind, allow_archives)?;
+    let name = format!("{} - {} - {}.{}", applicant.email, applicant.role, kind, upload.extension());
+
+    let file = drive_client
+        .create_or_update_file(drive_id, parent_id, &name, mime_type, &bytes)
+        .await
+        .map_err(|e| format!("uploading {} for {} failed: {}", kind, applicant.email, e))?;
+
+    Ok(format!("https://drive.google.com/open?id={}", file.id))
+}
+
+/// Submit an application.
+/// Applications are matched on email and role, so submitting the same
+/// application again updates it instead of creating a duplicate, and we only
+/// send the emails and Slack message the first time. Updating an application
+/// needs the token we emailed the applicant with the first submission, so only
+/// they can change it. Callers should check `check_application_limits` first.
+pub async fn submit_application(db: &Database, drive_client: &GoogleDrive, submission: &ApplicationSubmission) -> Result<ApplicationReceipt, String> {
+    let role = submission.validate()?;
+    let sheet_id = get_sheets_map().get(role.name.as_str()).unwrap().to_string();
+    let email = submission.normalized_email();
+
+    let existing = Applicant::get_from_db(db, email.to_string(), sheet_id.to_string());
+    let resubmitted = existing.is_some();
+    let mut token = String::new();
+    let mut applicant = match existing {
+        Some(a) => {
+            if !is_application_token_valid(&a.intake_token_hash, &submission.token) {
+                return Err(format!(
+                    "an application for `{}` for the {} role already exists, to update it submit it with the token we emailed you, or email careers@{}",
+                    email, role.name, DOMAIN
+                ));
+            }
+
+            NewApplicant::from(a)
+        }
+        None => {
+            token = rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+
+            let mut a = NewApplicant::new(&role.name, &sheet_id, &email, Utc::now(), APPLICANT_SOURCE_API);
+            a.intake_token_hash = hash_application_token(&token);
+            a
+        }
+    };
+
+    let (github, gitlab) = NewApplicant::parse_github_gitlab(&submission.github);
+    applicant.name = submission.name.trim().to_string();
+    applicant.phone = submission.phone.trim().to_string();
+    applicant.location = submission.location.trim().to_string();
+    applicant.github = github;
+    applicant.gitlab = gitlab;
+    applicant.linkedin = submission.linkedin.trim().to_lowercase();
+    applicant.portfolio = submission.portfolio.trim().to_string();
+    applicant.website = submission.website.trim().to_lowercase();
+    applicant.interested_in = submission.interested_in.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+    applicant.value_reflected = submission.value_reflected.trim().to_lowercase();
+    applicant.value_violated = submission.value_violated.trim().to_lowercase();
+    applicant.values_in_tension = submission.values_in_tension.iter().map(|s| s.trim().to_lowercase()).collect();
+    applicant.values_in_tension.sort();
+    applicant.cleanup_phone();
+
+    // Upload the files to the shared drive.
+    let (drive_id, parent_id) = get_or_create_automated_documents_folder(drive_client, APPLICANT_UPLOADS_FOLDER).await?;
+
+    applicant.resume = upload_application_file(drive_client, &drive_id, &parent_id, &applicant, "resume", &submission.resume, false).await?;
+    applicant.materials = match &submission.materials {
+        Some(materials) => upload_application_file(drive_client, &drive_id, &parent_id, &applicant, "materials", materials, true).await?,
+        None => "".to_string(),
+    };
+
+    // The latest submission replaces the answers of any earlier one.
+    // Parse what we can out of the files first, then let the inline answers win.
+    for question in &role.questions {
+        question.question.set_answer(&mut applicant, "");
+    }
+    applicant.resume_contents = Default::default();
+    applicant.materials_contents = Default::default();
+    applicant.parse_materials(drive_client).await;
+    for (question, answer) in &submission.answers {
+        if !answer.trim().is_empty() {
+            question.set_answer(&mut applicant, answer);
+        }
+    }
+
+    if !applicant.sent_email_received {
+        println!("[applicant] {} is new, sending notifications", applicant.email);
+
+        applicant.send_email_recieved_application_to_applicant(&token).await;
+
+        // Post to Slack.
+        post_to_channel(get_hiring_channel_post_url(), applicant.as_slack_msg()).await;
+
+        // Send a company-wide email.
+        applicant.send_email_internally().await;
+
+        applicant.sent_email_received = true;
+    }
+
+    let a = applicant.upsert(db).await;
+    println!("[applicant] {} submitted an application for {} through the intake api (resubmitted: {})", a.email, a.role, resubmitted);
+
+    Ok(ApplicationReceipt {
+        email: a.email,
+        role: a.role,
+        status: a.status,
+        submitted_time: a.submitted_time,
+        resubmitted,
+        resume: a.resume,
+        materials: a.materials,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::applicant_intake::{get_application_role, get_application_roles, hash_application_token, is_application_token_valid, ApplicationQuestion, ApplicationSubmission, ApplicationUpload};
+    use crate::applicants::get_sheets_map;
+
+    fn upload(name: &str, contents: &[u8]) -> ApplicationUpload {
+        ApplicationUpload {
+            name: name.to_string(),
+            contents: base64::encode(contents),
+        }
+    }
+
+    fn submission() -> ApplicationSubmission {
+        let role = get_application_role("Engineering").unwrap();
+        let mut answers: BTreeMap<ApplicationQuestion, String> = Default::default();
+        for q in role.questions {
+            answers.insert(q.question, format!("my answer to {}", q.prompt));
+        }
+
+        ApplicationSubmission {
+            role: "Engineering".to_string(),
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+            resume: upload("Resume.PDF", b"%PDF-1.4"),
+            answers,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_application_roles() {
+        let roles = get_application_roles();
+        assert_eq!(roles.len(), get_sheets_map().len());
+        for role in roles {
+            assert!(role.get_question(ApplicationQuestion::WhyOxide).unwrap().required);
+        }
+
+        let tpm = get_application_role("Technical Program Management").unwrap();
+        assert!(tpm.get_question(ApplicationQuestion::ExploratorySamples).is_some());
+        assert!(tpm.get_question(ApplicationQuestion::WorkSamples).is_none());
+        assert!(get_application_role("Astronaut").is_none());
+    }
+
+    #[test]
+    fn test_application_upload() {
+        assert_eq!(upload("materials.tar.gz", b"x").extension(), "tar.gz");
+        assert_eq!(upload("resume", b"x").extension(), "");
+        assert_eq!(upload("materials.zip", b"x").mime_type(true), Some("application/zip"));
+        assert_eq!(upload("resume.zip", b"x").mime_type(false), None);
+        assert!(upload("resume.pdf", b"").decode("resume", false).is_err());
+        assert!(ApplicationUpload {
+            name: "resume.pdf".to_string(),
+            contents: "not base64!".to_string(),
+        }
+        .decode("resume", false)
+        .is_err());
+    }
+
+    #[test]
+    fn test_application_token() {
+        let hash = hash_application_token("s3cr3t");
+        assert_ne!(hash, "s3cr3t");
+        assert!(is_application_token_valid(&hash, "s3cr3t"));
+        assert!(is_application_token_valid(&hash, " s3cr3t\n"));
+        assert!(!is_application_token_valid(&hash, "guess"));
+        assert!(!is_application_token_valid(&hash, ""));
+
+        // Applications from the sheets have no token, so nothing updates them.
+        assert!(!is_application_token_valid("", ""));
+        assert!(!is_application_token_valid("", "s3cr3t"));
+    }
+
+    #[test]
+    fn test_validate_submission() {
+        let s = submission();
+        assert_eq!(s.validate().unwrap().name, "Engineering");
+
+        // The email is stored the same way however it was typed.
+        let mut s = submission();
+        s.email = " Jane@Example.com\n".to_string();
+        assert!(s.validate().is_ok());
+        assert_eq!(s.normalized_email(), "jane@example.com");
+
+        // Questions that are not part of the role are rejected.
+        let mut s = submission();
+        s.answers.insert(ApplicationQuestion::ExploratorySamples, "hi".to_string());
+        assert!(s.validate().unwrap_err().contains("`exploratory_samples` is not a question"));
+
+        // Required questions can be left out when we have materials.
+        let mut s = submission();
+        s.answers.remove(&ApplicationQuestion::WhyOxide);
+        assert!(s.validate().unwrap_err().contains("`why_oxide` is required"));
+        s.materials = Some(upload("materials.zip", b"PK"));
+        assert!(s.validate().is_ok());
+
+        // All the problems are returned together.
+        let mut s = submission();
+        s.role = "Astronaut".to_string();
+        s.email = "not an email".to_string();
+        s.resume = upload("resume.exe", b"MZ");
+        let err = s.validate().unwrap_err();
+        assert!(err.contains("`Astronaut` is not a role"));
+        assert!(err.contains("not a valid email address"));
+        assert!(err.contains("`resume.exe` is not a file type we accept"));
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use docusign::{DocuSign, EmailNotification, Envelope, Tab, Tabs, Template, TemplateRole};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::applicants::{Applicant, NewApplicant};
+use crate::configs::User;
+use crate::db::Database;
+use crate::slack::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use crate::utils::get_most_specific;
+
+/// The employment type we use for an applicant that does not have one set.
+pub static DEFAULT_EMPLOYMENT_TYPE: &str = "full-time";
+
+/// The applicant fields we know how to fill into the tabs of an offer letter.
+pub static OFFER_TAB_FIELDS: &[&str] = &["name", "email", "role", "location", "country_code", "start_date", "salary", "employment_type"];
+
+fn default_employment_types() -> Vec<String> {
+    vec![DEFAULT_EMPLOYMENT_TYPE.to_string()]
+}
+
+/// The tabs we fill in for a signer if the configs do not list any, from the
+/// label of the tab in the template to the applicant field.
+/// Tabs the template does not have for the signer are ignored by DocuSign.
+pub fn default_offer_tabs() -> BTreeMap<String, String> {
+    let mut tabs: BTreeMap<String, String> = BTreeMap::new();
+    tabs.insert("Employee Name".to_string(), "name".to_string());
+    tabs.insert("Employee Email".to_string(), "email".to_string());
+    tabs.insert("Role".to_string(), "role".to_string());
+    tabs.insert("Location".to_string(), "location".to_string());
+    tabs.insert("Country".to_string(), "country_code".to_string());
+    tabs.insert("Start Date".to_string(), "start_date".to_string());
+    tabs.insert("Salary".to_string(), "salary".to_string());
+    tabs.insert("Employment Type".to_string(), "employment_type".to_string());
+
+    tabs
+}
+
+/// An offer letter template in DocuSign, from the configs repo.
+/// The key in the configs is the name of the offer, ie. "us-full-time".
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OfferTemplateConfig {
+    /// The name of the template in DocuSign.
+    pub template: String,
+    /// The country codes the template is for, ie. "us".
+    /// If this is empty, the template is used for any country that does not
+    /// have a template of its own, for example international offers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub countries: Vec<String>,
+    /// The types of employment the template is for, ie. "full-time" or "contractor".
+    #[serde(default = "default_employment_types", alias = "employment-types")]
+    pub employment_types: Vec<String>,
+    /// The subject of the envelope.
+    #[serde(default, alias = "email-subject", skip_serializing_if = "String::is_empty")]
+    pub email_subject: String,
+    /// The people who sign the offer, in the order of the roles in the template.
+    /// Exactly one of them is the applicant.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signers: Vec<OfferSignerConfig>,
+}
+
+/// A signer of an offer letter.
+/// The email subject and body can use the placeholders `{applicant_name}`,
+/// `{applicant_email}`, `{role}` and `{signer_name}`.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OfferSignerConfig {
+    /// The name of the role in the DocuSign template, ie. "CEO" or "Applicant".
+    pub role: String,
+    /// The username of the signer, this is empty for the applicant.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub username: String,
+    #[serde(alias = "routing-order")]
+    pub routing_order: i32,
+    #[serde(default, alias = "email-subject", skip_serializing_if = "String::is_empty")]
+    pub email_subject: String,
+    #[serde(default, alias = "email-body", skip_serializing_if = "String::is_empty")]
+    pub email_body: String,
+    /// The tabs we fill in for the signer, from the label of the tab in the
+    /// template to the applicant field, ie. "Start Date" = "start_date".
+    /// Leaving this out fills in the `default_offer_tabs`, set it to `{}` to
+    /// fill in nothing.
+    #[serde(default = "default_offer_tabs")]
+    pub tabs: BTreeMap<String, String>,
+}
+
+impl Default for OfferSignerConfig {
+    fn default() -> Self {
+        OfferSignerConfig {
+            role: Default::default(),
+            username: Default::default(),
+            routing_order: Default::default(),
+            email_subject: Default::default(),
+            email_body: Default::default(),
+            tabs: default_offer_tabs(),
+        }
+    }
+}
+
+impl OfferSignerConfig {
+    pub fn is_applicant(&self) -> bool {
+        self.username.is_empty()
+    }
+}
+
+impl OfferTemplateConfig {
+    /// Returns if the template is for the country and type of employment.
+    pub fn matches(&self, country_code: &str, employment_type: &str) -> bool {
+        if !self.employment_types.iter().any(|t| t.eq_ignore_ascii_case(employment_type)) {
+            return false;
+        }
+
+        self.countries.is_empty() || self.countries.iter().any(|c| c.eq_ignore_ascii_case(country_code))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems: Vec<String> = Default::default();
+
+        if self.template.trim().is_empty() {
+            problems.push("template is required".to_string());
+        }
+
+        let applicants = self.signers.iter().filter(|s| s.is_applicant()).count();
+        if applicants != 1 {
+            problems.push(format!("there must be exactly one signer without a username for the applicant, found {}", applicants));
+        }
+
+        let mut roles: BTreeSet<&str> = Default::default();
+        for signer in &self.signers {
+            if signer.routing_order < 1 {
+                problems.push(format!("signer {} must have a routing order of at least 1", signer.role));
+            }
+            if !roles.insert(&signer.role) {
+                problems.push(format!("signer {} is listed more than once", signer.role));
+            }
+
+            for (label, field) in &signer.tabs {
+                if !OFFER_TAB_FIELDS.contains(&field.as_str()) {
+                    problems.push(format!(
+                        "tab {} for signer {} uses unknown applicant field {}, must be one of: {}",
+                        label,
+                        signer.role,
+                        field,
+                        OFFER_TAB_FIELDS.join(", ")
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// Get the offer template for an applicant's country and type of employment.
+/// A template for the applicant's country is preferred over one for any country.
+/// If we do not know the applicant's country there is no template, since we
+/// should not guess which offer letter someone gets.
+pub fn get_offer_template<'a>(offers: &'a BTreeMap<String, OfferTemplateConfig>, country_code: &str, employment_type: &str) -> Option<(&'a String, &'a OfferTemplateConfig)> {
+    if country_code.trim().is_empty() {
+        return None;
+    }
+    let employment_type = if employment_type.trim().is_empty() { DEFAULT_EMPLOYMENT_TYPE } else { employment_type.trim() };
+
+    // Prefer a template for the country over one for any country.
+    get_most_specific(offers.iter().filter(|(_, offer)| offer.matches(country_code.trim(), employment_type)), |(_, offer)| {
+        !offer.countries.is_empty()
+    })
+}
+
+/// Fill in the placeholders in the email copy for a signer.
+fn fill_offer_placeholders(text: &str, applicant: &NewApplicant, signer_name: &str) -> String {
+    text.replace("{applicant_name}", &applicant.name)
+        .replace("{applicant_email}", &applicant.email)
+        .replace("{role}", &applicant.role)
+        .replace("{signer_name}", signer_name)
+}
+
+impl NewApplicant {
+    /// Returns the value for a field of the applicant we fill into an offer letter.
+    pub fn offer_tab_value(&self, field: &str) -> Result<String, String> {
+        let value = match field {
+            "name" => self.name.to_string(),
+            "email" => self.email.to_string(),
+            "role" => self.role.to_string(),
+            "location" => self.location.to_string(),
+            "country_code" => self.country_code.to_uppercase(),
+            // This is the same format we parse the start date in when the offer is signed.
+            "start_date" => self.start_date.map(|d| d.format("%m/%d/%Y").to_string()).unwrap_or_default(),
+            "salary" => self.offer_salary.to_string(),
+            "employment_type" => {
+                if self.offer_employment_type.is_empty() {
+                    DEFAULT_EMPLOYMENT_TYPE.to_string()
+                } else {
+                    self.offer_employment_type.to_string()
+                }
+            }
+            _ => return Err(format!("unknown applicant field for offer tab: {}", field)),
+        };
+
+        Ok(value.trim().to_string())
+    }
+
+    /// Returns the DocuSign tabs for a signer of the applicant's offer letter.
+    /// Fields we do not have a value for are left out so the signer can fill them in.
+    pub fn as_offer_tabs(&self, tabs: &BTreeMap<String, String>) -> Result<Tabs, String> {
+        let mut text_tabs: Vec<Tab> = Default::default();
+
+        for (label, field) in tabs {
+            let value = self.offer_tab_value(field)?;
+            if value.is_empty() {
+                continue;
+            }
+
+            text_tabs.push(Tab {
+                tab_label: label.to_string(),
+                value,
+                locked: Default::default(),
+            });
+        }
+
+        Ok(Tabs { text_tabs })
+    }
+
+    /// Returns the template roles for the applicant's offer letter.
+    pub fn as_offer_template_roles(&self, db: &Database, offer: &OfferTemplateConfig) -> Result<Vec<TemplateRole>, String> {
+        let mut roles: Vec<TemplateRole> = Default::default();
+
+        for signer in &offer.signers {
+            let (name, email) = if signer.is_applicant() {
+                (self.name.to_string(), self.email.to_string())
+            } else {
+                match User::get_from_db(db, signer.username.to_string()) {
+                    Some(user) => (user.full_name(), user.email()),
+                    None => return Err(format!("signer {} for role {} is not a user", signer.username, signer.role)),
+                }
+            };
+
+            roles.push(TemplateRole {
+                name: name.to_string(),
+                role_name: signer.role.to_string(),
+                email,
+                signer_name: name.to_string(),
+                routing_order: signer.routing_order.to_string(),
+                email_notification: EmailNotification {
+                    email_subject: fill_offer_placeholders(&signer.email_subject, self, &name),
+                    email_body: fill_offer_placeholders(&signer.email_body, self, &name),
+                    language: Default::default(),
+                },
+                tabs: self.as_offer_tabs(&signer.tabs)?,
+            });
+        }
+
+        roles.sort_by_key(|r| r.routing_order.parse::<i32>().unwrap_or_default());
+
+        Ok(roles)
+    }
+}
+
+/// Create and send the offer letter envelope for an applicant, using the
+/// template for their country and type of employment.
+pub async fn send_offer_envelope(db: &Database, ds: &DocuSign, offers: &BTreeMap<String, OfferTemplateConfig>, templates: &[Template], applicant: &Applicant) -> Result<Envelope, String> {
+    if offers.is_empty() {
+        return Err("the configs repo has no offer templates, add them to `offer_templates`".to_string());
+    }
+    let (name, offer) = match get_offer_template(offers, &applicant.country_code, &applicant.offer_employment_type) {
+        Some(o) => o,
+        None => {
+            return Err(format!(
+                "no offer template for country `{}` and employment type `{}`",
+                applicant.country_code, applicant.offer_employment_type
+            ))
+        }
+    };
+    offer.validate().map_err(|e| format!("offer template {} is invalid: {}", name, e))?;
+
+    let template = match templates.iter().find(|t| t.name == offer.template) {
+        Some(t) => t,
+        None => return Err(format!("offer template {} uses DocuSign template `{}` which does not exist", name, offer.template)),
+    };
+
+    let new_applicant = NewApplicant::from(applicant.clone());
+
+    let mut new_envelope: Envelope = Default::default();
+    // Sent the status to `sent` so it sends.
+    // To save it as a draft set the status as `created`.
+    new_envelope.status = "sent".to_string();
+    new_envelope.email_subject = fill_offer_placeholders(&offer.email_subject, &new_applicant, &applicant.name);
+    new_envelope.template_id = template.template_id.to_string();
+    new_envelope.template_roles = new_applicant.as_offer_template_roles(db, offer)?;
+
+    println!("[docusign] sending offer {} with template `{}` to applicant {}", name, offer.template, applicant.email);
+    ds.create_envelope(new_envelope).await.map_err(|e| format!("creating envelope failed: {}", e))
+}
+
+/// Returns a message for the hiring channel about an offer we could not send.
+pub fn offer_failed_slack_msg(applicant: &Applicant, error: &str) -> Value {
+    json!(FormattedMessage {
+        channel: Default::default(),
+        attachments: Default::default(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        ":rotating_light: the offer letter for *{}* <mailto:{}|{}> ({}) could not be sent, someone needs to fix it or send it by hand",
+                        applicant.name, applicant.email, applicant.email, applicant.role
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Context,
+                elements: vec![MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!("```{}```", error),
+                }],
+                text: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            }
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{NaiveDate, Utc};
+
+    use crate::applicant_offers::{default_offer_tabs, get_offer_template, OfferSignerConfig, OfferTemplateConfig, OFFER_TAB_FIELDS};
+    use crate::applicants::NewApplicant;
+
+    fn offer(template: &str, countries: &[&str], employment_types: &[&str]) -> OfferTemplateConfig {
+        OfferTemplateConfig {
+            template: template.to_string(),
+            countries: countries.iter().map(|c| c.to_string()).collect(),
+            employment_types: employment_types.iter().map(|t| t.to_string()).collect(),
+            email_subject: Default::default(),
+            signers: vec![
+                OfferSignerConfig {
+                    role: "CEO".to_string(),
+                    username: "ceo".to_string(),
+                    routing_order: 1,
+                    ..Default::default()
+                },
+                OfferSignerConfig {
+                    role: "Applicant".to_string(),
+                    routing_order: 2,
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_get_offer_template() {
+        let mut offers: BTreeMap<String, OfferTemplateConfig> = BTreeMap::new();
+        offers.insert("us-full-time".to_string(), offer("Employee Offer Letter (US)", &["us"], &["full-time"]));
+        offers.insert("us-contractor".to_string(), offer("Contractor Agreement (US)", &["us"], &["contractor"]));
+        offers.insert("international".to_string(), offer("Contractor Agreement (International)", &[], &["full-time", "contractor"]));
+
+        assert_eq!(get_offer_template(&offers, "US", "").unwrap().0, "us-full-time");
+        assert_eq!(get_offer_template(&offers, "us", "contractor").unwrap().0, "us-contractor");
+        assert_eq!(get_offer_template(&offers, "gb", "full-time").unwrap().0, "international");
+        assert!(get_offer_template(&offers, "", "contractor").is_none());
+        assert!(get_offer_template(&offers, " ", "full-time").is_none());
+        assert!(get_offer_template(&offers, "us", "intern").is_none());
+    }
+
+    #[test]
+    fn test_default_offer_tabs() {
+        // Every field we know how to fill in has a tab by default.
+        let tabs = default_offer_tabs();
+        for field in OFFER_TAB_FIELDS {
+            assert!(tabs.values().any(|f| f == *field), "no default tab for {}", field);
+        }
+
+        let signer: OfferSignerConfig = toml::from_str("role = \"CEO\"\nusername = \"ceo\"\nrouting_order = 1").unwrap();
+        assert_eq!(signer.tabs, tabs);
+        let signer: OfferSignerConfig = toml::from_str("role = \"Applicant\"\nrouting_order = 2\ntabs = {}").unwrap();
+        assert!(signer.tabs.is_empty());
+    }
+
+    #[test]
+    fn test_validate_offer_template() {
+        assert!(offer("Employee Offer Letter (US)", &["us"], &["full-time"]).validate().is_ok());
+
+        let mut invalid = offer("", &["us"], &["full-time"]);
+        invalid.signers[1].username = "someone".to_string();
+        invalid.signers[0].tabs.insert("Salary".to_string(), "compensation".to_string());
+        let err = invalid.validate().unwrap_err();
+        assert!(err.contains("template is required"));
+        assert!(err.contains("exactly one signer"));
+        assert!(err.contains("unknown applicant field compensation"));
+    }
+
+    #[test]
+    fn test_offer_tabs() {
+        let mut applicant = NewApplicant::new("Engineering", "sheet", "jane@example.com", Utc::now(), "api");
+        applicant.name = "Jane Doe".to_string();
+        applicant.start_date = Some(NaiveDate::from_ymd(2021, 7, 6));
+        applicant.offer_salary = "$175,000".to_string();
+
+        let mut tabs: BTreeMap<String, String> = BTreeMap::new();
+        tabs.insert("Employee Name".to_string(), "name".to_string());
+        tabs.insert("Start Date".to_string(), "start_date".to_string());
+        tabs.insert("Salary".to_string(), "salary".to_string());
+        tabs.insert("Location".to_string(), "location".to_string());
+
+        let result = applicant.as_offer_tabs(&tabs).unwrap();
+        let values: Vec<(String, String)> = result.text_tabs.into_iter().map(|t| (t.tab_label, t.value)).collect();
+        assert_eq!(
+            values,
+            vec![
+                ("Employee Name".to_string(), "Jane Doe".to_string()),
+                ("Salary".to_string(), "$175,000".to_string()),
+                ("Start Date".to_string(), "07/06/2021".to_string()),
+            ]
+        );
+
+        assert_eq!(applicant.offer_tab_value("employment_type").unwrap(), "full-time");
+        assert!(applicant.offer_tab_value("compensation").is_err());
+    }
+}
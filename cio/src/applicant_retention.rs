@@ -0,0 +1,715 @@
+#![allow(clippy::from_over_into)]
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use google_drive::GoogleDrive;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sheets::Sheets;
+
+use crate::airtable::{AIRTABLE_APPLICANT_DELETIONS_TABLE, AIRTABLE_APPLICATIONS_TABLE, AIRTABLE_BASE_ID_RECURITING_APPLICATIONS};
+use crate::applicant_reviews::ApplicantReviewAssignment;
+use crate::applicant_scores::ApplicantScore;
+use crate::applicant_status::Status;
+use crate::applicants::{get_drive_file_id, Applicant, ApplicantSheetColumns, Applicants, APPLICANT_SOURCE_API};
+use crate::background_checks::BackgroundCheck;
+use crate::configs::get_configs_from_repo;
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::envelopes::{Envelope, EnvelopeEvent};
+use crate::interview_packets::InterviewPacket;
+use crate::interviews::ApplicantInterview;
+use crate::schema::{applicant_deletions, applicant_interviews, applicant_review_assignments, applicant_scores, applicants, background_checks, envelope_events, envelopes, interview_packets};
+use crate::utils::{authenticate_github_jwt, get_column_letters, get_gsuite_token, get_most_specific};
+
+/// What we replace the applicant's data with in the application sheets.
+pub static REDACTED: &str = "[redacted]";
+
+/// The reason on the audit record when the applicant asked us to delete their data.
+pub static ERASURE_REQUEST_REASON: &str = "erasure request";
+
+/// What we do with an applicant's data when a retention policy applies to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum RetentionAction {
+    /// Remove the resume, materials, answers, interview packet and contact
+    /// details, but keep the applicant's name, role, status and scores for our
+    /// hiring numbers.
+    Redact,
+    /// Remove the applicant from everywhere we keep them.
+    Delete,
+}
+
+impl FromStr for RetentionAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "redact" => Ok(RetentionAction::Redact),
+            "delete" => Ok(RetentionAction::Delete),
+            _ => Err(format!("invalid retention action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for RetentionAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RetentionAction::Redact => "redact",
+            RetentionAction::Delete => "delete",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A retention policy for applicant data, from the configs repo.
+/// The key in the configs is the name of the policy, ie. "declined-after-a-year".
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantRetentionPolicyConfig {
+    /// The applicant statuses the policy is for, ie. "Declined".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+    /// How many days after the applicant applied the policy applies.
+    #[serde(default)]
+    pub days: i64,
+    /// One of the `RetentionAction`s.
+    #[serde(default)]
+    pub action: String,
+}
+
+impl ApplicantRetentionPolicyConfig {
+    /// Validate the policy, returning the action it takes.
+    pub fn validate(&self) -> Result<RetentionAction, String> {
+        let mut errors: Vec<String> = Default::default();
+
+        if self.statuses.is_empty() {
+            errors.push("the policy must be for at least one applicant status".to_string());
+        }
+        if self.days < 1 {
+            errors.push(format!("days must be at least 1, got {}", self.days));
+        }
+
+        let action = RetentionAction::from_str(&self.action);
+        if let Err(e) = &action {
+            errors.push(e.to_string());
+        }
+        if action == Ok(RetentionAction::Delete) && self.statuses.iter().any(|s| is_employee_status(s)) {
+            errors.push("the records of applicants we hired are employee records, they cannot be deleted by a retention policy".to_string());
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        Ok(action.unwrap())
+    }
+
+    /// Returns if the policy applies to an applicant with the status, who applied
+    /// at the submitted time.
+    pub fn applies_to(&self, status: &str, submitted_time: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let status = Status::from_str(status);
+        self.statuses.iter().any(|s| Status::from_str(s) == status) && submitted_time + Duration::days(self.days) <= now
+    }
+}
+
+/// Returns if the status is one of an applicant we hired.
+fn is_employee_status(status: &str) -> bool {
+    matches!(Status::from_str(status), Ok(Status::Hired) | Ok(Status::Onboarding) | Ok(Status::Contractor))
+}
+
+/// Get the retention policy that applies to the applicant, by name, and the action
+/// it takes. If more than one policy applies, deleting wins over redacting.
+pub fn get_retention_policy(policies: &BTreeMap<String, ApplicantRetentionPolicyConfig>, applicant: &Applicant, now: DateTime<Utc>) -> Option<(String, RetentionAction)> {
+    get_most_specific(
+        policies
+            .iter()
+            .filter(|(_, p)| p.applies_to(&applicant.status, applicant.submitted_time, now))
+            .filter_map(|(name, p)| p.validate().ok().map(|action| (name.to_string(), action))),
+        |(_, action)| *action,
+    )
+}
+
+/// Hash the applicant's email, so the audit record does not hold on to the
+/// data it is about.
+pub fn hash_applicant_email(email: &str) -> String {
+    openssl::sha::sha256(email.trim().to_lowercase().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The audit record of the data we redacted or deleted for an applicant.
+#[db {
+    new_struct_name = "ApplicantDeletion",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_APPLICANT_DELETIONS_TABLE",
+    match_on = {
+        "applicant_email_hash" = "String",
+        "sheet_id" = "String",
+        "action" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "applicant_deletions"]
+pub struct NewApplicantDeletion {
+    /// See `hash_applicant_email`.
+    pub applicant_email_hash: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sheet_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub role: String,
+    /// The status of the applicant when we removed their data.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_time: Option<DateTime<Utc>>,
+    /// One of the `RetentionAction`s.
+    pub action: String,
+    /// The name of the retention policy, or `ERASURE_REQUEST_REASON`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub requested_by: String,
+    /// Where we removed the applicant's data from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// What we failed to remove, these need to be cleaned up by hand.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    pub completed_time: DateTime<Utc>,
+}
+
+/// Implement updating the Airtable record for an ApplicantDeletion.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantDeletion> for ApplicantDeletion {
+    async fn update_airtable_record(&mut self, _record: ApplicantDeletion) {}
+}
+
+/// Returns if we deleted an applicant with the email, so we do not bring them
+/// back from somewhere else, like the interviews calendar.
+pub fn is_applicant_deleted(db: &Database, email: &str) -> bool {
+    applicant_deletions::dsl::applicant_deletions
+        .filter(applicant_deletions::dsl::applicant_email_hash.eq(hash_applicant_email(email)))
+        .filter(applicant_deletions::dsl::action.eq(RetentionAction::Delete.to_string()))
+        .first::<ApplicantDeletion>(&db.conn())
+        .is_ok()
+}
+
+/// All the data we hold about an applicant, for when they ask us for it.
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantDataExport {
+    pub email: String,
+    /// The applicant has one of these for every role they applied for.
+    pub applications: Vec<Applicant>,
+    pub scores: Vec<ApplicantScore>,
+    pub review_assignments: Vec<ApplicantReviewAssignment>,
+    pub interviews: Vec<ApplicantInterview>,
+    pub interview_packets: Vec<InterviewPacket>,
+    pub background_checks: Vec<BackgroundCheck>,
+    pub envelopes: Vec<Envelope>,
+    pub envelope_events: Vec<EnvelopeEvent>,
+    /// The data we already redacted or deleted.
+    pub deletions: Vec<ApplicantDeletion>,
+}
+
+sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+/// Export all the data we hold about an applicant.
+pub fn export_applicant_data(db: &Database, email: &str) -> ApplicantDataExport {
+    // Emails are matched without case, some of them come from forms as they were typed.
+    let email = email.trim().to_lowercase();
+
+    let envelopes = envelopes::dsl::envelopes
+        .filter(lower(envelopes::dsl::applicant_email).eq(email.to_string()))
+        .order_by(envelopes::dsl::id)
+        .load::<Envelope>(&db.conn())
+        .unwrap();
+    let envelope_ids: Vec<String> = envelopes.iter().map(|e| e.envelope_id.to_string()).collect();
+
+    ApplicantDataExport {
+        applications: applicants::dsl::applicants
+            .filter(lower(applicants::dsl::email).eq(email.to_string()))
+            .order_by(applicants::dsl::id)
+            .load::<Applicant>(&db.conn())
+            .unwrap(),
+        scores: applicant_scores::dsl::applicant_scores
+            .filter(lower(applicant_scores::dsl::applicant_email).eq(email.to_string()))
+            .order_by(applicant_scores::dsl::id)
+            .load::<ApplicantScore>(&db.conn())
+            .unwrap(),
+        review_assignments: applicant_review_assignments::dsl::applicant_review_assignments
+            .filter(lower(applicant_review_assignments::dsl::applicant_email).eq(email.to_string()))
+            .order_by(applicant_review_assignments::dsl::id)
+            .load::<ApplicantReviewAssignment>(&db.conn())
+            .unwrap(),
+        interviews: applicant_interviews::dsl::applicant_interviews
+            .filter(lower(applicant_interviews::dsl::email).eq(email.to_string()))
+            .order_by(applicant_interviews::dsl::start_time)
+            .load::<ApplicantInterview>(&db.conn())
+            .unwrap(),
+        interview_packets: interview_packets::dsl::interview_packets
+            .filter(lower(interview_packets::dsl::applicant_email).eq(email.to_string()))
+            .order_by(interview_packets::dsl::id)
+            .load::<InterviewPacket>(&db.conn())
+            .unwrap(),
+        background_checks: background_checks::dsl::background_checks
+            .filter(lower(background_checks::dsl::applicant_email).eq(email.to_string()))
+            .order_by(background_checks::dsl::id)
+            .load::<BackgroundCheck>(&db.conn())
+            .unwrap(),
+        envelope_events: envelope_events::dsl::envelope_events
+            .filter(envelope_events::dsl::envelope_id.eq_any(envelope_ids))
+            .order_by(envelope_events::dsl::time)
+            .load::<EnvelopeEvent>(&db.conn())
+            .unwrap(),
+        envelopes,
+        deletions: applicant_deletions::dsl::applicant_deletions
+            .filter(applicant_deletions::dsl::applicant_email_hash.eq(hash_applicant_email(&email)))
+            .order_by(applicant_deletions::dsl::completed_time)
+            .load::<ApplicantDeletion>(&db.conn())
+            .unwrap(),
+        email,
+    }
+}
+
+/// Remove the files left in the temporary directory from reading a file from
+/// Google Drive. Files are now read in a sandbox that is removed afterwards,
+/// see `crate::materials`, but older versions left them behind, named by the
+/// id of the file. We only remove those, the temporary directory is shared
+/// with everything else running on the host.
+fn remove_temp_files(id: &str) {
+    if id.is_empty() {
+        return;
+    }
+
+    let entries = match fs::read_dir(env::temp_dir()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("[retention] reading the temporary directory failed: {}", e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name != id && !name.starts_with(&format!("{}.", id)) {
+            continue;
+        }
+
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            println!("[retention] removing temporary file {} failed: {}", path.display(), e);
+        }
+    }
+}
+
+/// Delete a file in Google Drive by its URL, and anything left from reading it.
+async fn delete_drive_file(drive_client: &GoogleDrive, what: &str, url: &str, removed: &mut Vec<String>, errors: &mut Vec<String>) {
+    if url.trim().is_empty() {
+        return;
+    }
+
+    let id = match get_drive_file_id(url) {
+        Some(id) => id,
+        None => {
+            errors.push(format!("the {} is not in Google Drive: {}", what, url));
+            return;
+        }
+    };
+
+    remove_temp_files(&id);
+
+    match drive_client.delete_file_by_id(&id).await {
+        Ok(_) => removed.push(format!("google drive: {}", what)),
+        Err(e) => errors.push(format!("deleting the {} from Google Drive failed: {}", what, e)),
+    }
+}
+
+/// Replace the applicant's row in the application sheet with `REDACTED`, so
+/// the next import from the sheet does not bring their data back.
+/// Returns if the row was found.
+async fn redact_applicant_sheet_row(sheets_client: &Sheets, applicant: &Applicant) -> Result<bool, String> {
+    let values = sheets_client
+        .get_values(&applicant.sheet_id, "Form Responses 1!A1:ZZ1000".to_string())
+        .await
+        .map_err(|e| format!("getting the values in sheet {} failed: {}", applicant.sheet_id, e))?
+        .values
+        .unwrap_or_default();
+    if values.is_empty() || values[0].is_empty() {
+        return Ok(false);
+    }
+
+    let columns = ApplicantSheetColumns::parse(&values);
+    let row_index = match values.iter().skip(1).position(|row| row.get(columns.email).map(|e| e.trim() == applicant.email).unwrap_or(false)) {
+        // Skip the header row, and the sheet rows start at 1.
+        Some(i) => i + 2,
+        None => return Ok(false),
+    };
+
+    // Redact the whole row in one request, so we do not run into the rate limits.
+    let columns_count = values[0].len();
+    let rng = format!("Form Responses 1!A{}:{}{}", row_index, get_column_letters(columns_count - 1), row_index);
+    sheets_client
+        .update_row_values(&applicant.sheet_id, &rng, vec![REDACTED.to_string(); columns_count])
+        .await
+        .map_err(|e| format!("redacting row {} in sheet {} failed: {}", row_index, applicant.sheet_id, e))?;
+
+    Ok(true)
+}
+
+/// Redact or delete an applicant's data everywhere we keep it, and save the
+/// audit record of what we did.
+///
+/// For a deletion this is the applicant in the database and Airtable, their resume,
+/// materials and interview packets in Google Drive, their row in the application sheet,
+/// and their scores, review assignments, interviews, background checks and envelopes.
+/// The background check reports are kept by Checkr and the envelopes by DocuSign,
+/// we only remove our references to them.
+pub async fn remove_applicant_data(
+    db: &Database,
+    drive_client: &GoogleDrive,
+    sheets_client: &Sheets,
+    applicant: &Applicant,
+    action: RetentionAction,
+    reason: &str,
+    requested_by: &str,
+) -> ApplicantDeletion {
+    let mut removed: Vec<String> = Default::default();
+    let mut errors: Vec<String> = Default::default();
+
+    delete_drive_file(drive_client, "resume", &applicant.resume, &mut removed, &mut errors).await;
+    delete_drive_file(drive_client, "materials", &applicant.materials, &mut removed, &mut errors).await;
+    delete_drive_file(drive_client, "interview packet", &applicant.interview_packet, &mut removed, &mut errors).await;
+
+    // The packets for the interviewers have the applicant's materials and scores in them,
+    // the packet for the applicant was removed above.
+    let packets = interview_packets::dsl::interview_packets
+        .filter(interview_packets::dsl::applicant_email.eq(applicant.email.to_string()))
+        .filter(interview_packets::dsl::sheet_id.eq(applicant.sheet_id.to_string()))
+        .load::<InterviewPacket>(&db.conn())
+        .unwrap();
+    let count = packets.len();
+    for packet in packets {
+        if packet.link != applicant.interview_packet {
+            delete_drive_file(drive_client, "interview packet", &packet.link, &mut removed, &mut errors).await;
+        }
+        packet.delete(db).await;
+    }
+    if count > 0 {
+        removed.push(format!("interview packets: {}", count));
+    }
+
+    // Applications submitted through the intake API are not in a sheet.
+    if applicant.source != APPLICANT_SOURCE_API {
+        match redact_applicant_sheet_row(sheets_client, applicant).await {
+            Ok(true) => removed.push("google sheets: application row".to_string()),
+            Ok(false) => (),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    let scores = applicant_scores::dsl::applicant_scores
+        .filter(applicant_scores::dsl::applicant_email.eq(applicant.email.to_string()))
+        .filter(applicant_scores::dsl::sheet_id.eq(applicant.sheet_id.to_string()))
+        .load::<ApplicantScore>(&db.conn())
+        .unwrap();
+
+    match action {
+        RetentionAction::Redact => {
+            let mut redacted = applicant.clone();
+            redacted.phone = "".to_string();
+            redacted.location = "".to_string();
+            redacted.geocode_cache = "".to_string();
+            redacted.github = "".to_string();
+            redacted.gitlab = "".to_string();
+            redacted.linkedin = "".to_string();
+            redacted.portfolio = "".to_string();
+            redacted.website = "".to_string();
+            redacted.resume = "".to_string();
+            redacted.materials = "".to_string();
+            redacted.interview_packet = "".to_string();
+            redacted.resume_contents = "".to_string();
+            redacted.materials_contents = "".to_string();
+            redacted.work_samples = "".to_string();
+            redacted.writing_samples = "".to_string();
+            redacted.analysis_samples = "".to_string();
+            redacted.presentation_samples = "".to_string();
+            redacted.exploratory_samples = "".to_string();
+            redacted.question_technically_challenging = "".to_string();
+            redacted.question_proud_of = "".to_string();
+            redacted.question_happiest = "".to_string();
+            redacted.question_unhappiest = "".to_string();
+            redacted.question_value_reflected = "".to_string();
+            redacted.question_value_violated = "".to_string();
+            redacted.question_values_in_tension = "".to_string();
+            redacted.question_why_oxide = "".to_string();
+            redacted.update(db).await;
+            removed.push("applicants: contact details, resume, materials and answers".to_string());
+
+            let mut notes = 0;
+            for mut score in scores {
+                if score.notes.is_empty() {
+                    continue;
+                }
+                score.notes = "".to_string();
+                score.update(db).await;
+                notes += 1;
+            }
+            if notes > 0 {
+                removed.push(format!("scores: notes from {} reviewers", notes));
+            }
+        }
+        RetentionAction::Delete => {
+            let count = scores.len();
+            for score in scores {
+                score.delete(db).await;
+            }
+            if count > 0 {
+                removed.push(format!("scores: {}", count));
+            }
+
+            let assignments = applicant_review_assignments::dsl::applicant_review_assignments
+                .filter(applicant_review_assignments::dsl::applicant_email.eq(applicant.email.to_string()))
+                .filter(applicant_review_assignments::dsl::sheet_id.eq(applicant.sheet_id.to_string()))
+                .load::<ApplicantReviewAssignment>(&db.conn())
+                .unwrap();
+            let count = assignments.len();
+            for assignment in assignments {
+                assignment.delete(db).await;
+            }
+            if count > 0 {
+                removed.push(format!("review assignments: {}", count));
+            }
+
+            // The interviews, background checks and envelopes are not for a
+            // single role, keep them if the applicant applied for another.
+            let other_applications = applicants::dsl::applicants
+                .filter(applicants::dsl::email.eq(applicant.email.to_string()))
+                .filter(applicants::dsl::sheet_id.ne(applicant.sheet_id.to_string()))
+                .load::<Applicant>(&db.conn())
+                .unwrap();
+            if other_applications.is_empty() {
+                let interviews = applicant_interviews::dsl::applicant_interviews
+                    .filter(applicant_interviews::dsl::email.eq(applicant.email.to_string()))
+                    .load::<ApplicantInterview>(&db.conn())
+                    .unwrap();
+                let count = interviews.len();
+                for interview in interviews {
+                    interview.delete(db).await;
+                }
+                if count > 0 {
+                    removed.push(format!("interviews: {}", count));
+                }
+
+                let checks = background_checks::dsl::background_checks
+                    .filter(background_checks::dsl::applicant_email.eq(applicant.email.to_string()))
+                    .load::<BackgroundCheck>(&db.conn())
+                    .unwrap();
+                let count = checks.len();
+                for check in checks {
+                    check.delete(db).await;
+                }
+                if count > 0 {
+                    removed.push(format!("background checks: {} Checkr candidate and report references", count));
+                }
+
+                let envelopes = envelopes::dsl::envelopes
+                    .filter(envelopes::dsl::applicant_email.eq(applicant.email.to_string()))
+                    .load::<Envelope>(&db.conn())
+                    .unwrap();
+                let count = envelopes.len();
+                for envelope in envelopes {
+                    for document in &envelope.archived_documents {
+                        delete_drive_file(drive_client, "signed document", document, &mut removed, &mut errors).await;
+                    }
+
+                    let events = envelope_events::dsl::envelope_events
+                        .filter(envelope_events::dsl::envelope_id.eq(envelope.envelope_id.to_string()))
+                        .load::<EnvelopeEvent>(&db.conn())
+                        .unwrap();
+                    for event in events {
+                        event.delete(db).await;
+                    }
+
+                    envelope.delete(db).await;
+                }
+                if count > 0 {
+                    removed.push(format!("envelopes: {} DocuSign envelope references and their events", count));
+                }
+            }
+
+            applicant.delete(db).await;
+            removed.push("applicants: database and Airtable".to_string());
+        }
+    }
+
+    let deletion = NewApplicantDeletion {
+        applicant_email_hash: hash_applicant_email(&applicant.email),
+        sheet_id: applicant.sheet_id.to_string(),
+        role: applicant.role.to_string(),
+        applicant_status: applicant.status.to_string(),
+        submitted_time: Some(applicant.submitted_time),
+        action: action.to_string(),
+        reason: reason.to_string(),
+        requested_by: requested_by.to_string(),
+        removed,
+        errors,
+        completed_time: Utc::now(),
+    }
+    .upsert(db)
+    .await;
+
+    println!(
+        "[retention] {} applicant {} for {} ({}), {} errors",
+        if action == RetentionAction::Delete { "deleted" } else { "redacted" },
+        deletion.applicant_email_hash,
+        deletion.role,
+        reason,
+        deletion.errors.len()
+    );
+
+    deletion
+}
+
+/// An applicant's request to delete their data, from their row in the applicants
+/// table in Airtable. The Airtable script sets who asked for it on the row before
+/// it calls the webhook, so we read it back from Airtable rather than trust what
+/// is sent to us.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ApplicantErasureRequest {
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub sheet_id: String,
+    /// The person in Airtable who asked for the applicant to be deleted.
+    #[serde(default, deserialize_with = "airtable_api::user_format_as_string::deserialize")]
+    pub erasure_requested_by: String,
+}
+
+/// Get the request to delete an applicant's data from their row in Airtable.
+pub async fn get_applicant_erasure_request(record_id: &str) -> Result<ApplicantErasureRequest, String> {
+    let airtable = airtable_api::Airtable::new(airtable_api::api_key_from_env(), AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, "");
+    let record: airtable_api::Record<ApplicantErasureRequest> = airtable
+        .get_record(AIRTABLE_APPLICATIONS_TABLE, record_id)
+        .await
+        .map_err(|e| format!("getting Airtable record {} failed: {}", record_id, e))?;
+
+    let request = record.fields;
+    if request.email.is_empty() {
+        return Err(format!("Airtable record {} is not an applicant", record_id));
+    }
+    if request.erasure_requested_by.is_empty() {
+        return Err(format!("nobody asked for the applicant in Airtable record {} to be deleted", record_id));
+    }
+
+    Ok(request)
+}
+
+/// Delete an applicant's data everywhere we keep it, because they asked us to.
+pub async fn erase_applicant(db: &Database, applicant: &Applicant, requested_by: &str) -> Result<ApplicantDeletion, String> {
+    if is_employee_status(&applicant.status) {
+        return Err(format!(
+            "the applicant has the status `{}`, their records are employee records and need to be removed by hand",
+            applicant.status
+        ));
+    }
+
+    // Get the GSuite token.
+    let token = get_gsuite_token("").await;
+    let drive_client = GoogleDrive::new(token.clone());
+    let sheets_client = Sheets::new(token);
+
+    Ok(remove_applicant_data(db, &drive_client, &sheets_client, applicant, RetentionAction::Delete, ERASURE_REQUEST_REASON, requested_by).await)
+}
+
+/// Apply the retention policies from the configs repo to the applicants.
+pub async fn refresh_applicant_retention(db: &Database) {
+    let github = authenticate_github_jwt();
+    let policies = get_configs_from_repo(&github).await.applicant_retention_policies;
+    if policies.is_empty() {
+        return;
+    }
+    for (name, policy) in &policies {
+        if let Err(e) = policy.validate() {
+            println!("[retention] skipping retention policy {}: {}", name, e);
+        }
+    }
+
+    // Get the GSuite token.
+    let token = get_gsuite_token("").await;
+    let drive_client = GoogleDrive::new(token.clone());
+    let sheets_client = Sheets::new(token);
+
+    let now = Utc::now();
+    for applicant in Applicants::get_from_db(db) {
+        let (name, action) = match get_retention_policy(&policies, &applicant, now) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        // We only need to redact an applicant once.
+        if ApplicantDeletion::get_from_db(db, hash_applicant_email(&applicant.email), applicant.sheet_id.to_string(), action.to_string()).is_some() {
+            continue;
+        }
+
+        remove_applicant_data(db, &drive_client, &sheets_client, &applicant, action, &name, "retention policy").await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::applicant_retention::{hash_applicant_email, refresh_applicant_retention, ApplicantRetentionPolicyConfig, RetentionAction};
+    use crate::db::Database;
+
+    fn policy(statuses: &[&str], days: i64, action: &str) -> ApplicantRetentionPolicyConfig {
+        ApplicantRetentionPolicyConfig {
+            statuses: statuses.iter().map(|s| s.to_string()).collect(),
+            days,
+            action: action.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_retention_policy() {
+        assert_eq!(policy(&["Declined"], 365, "delete").validate(), Ok(RetentionAction::Delete));
+        assert_eq!(policy(&["Deferred"], 90, "Redact").validate(), Ok(RetentionAction::Redact));
+        assert_eq!(policy(&["Hired"], 365, "redact").validate(), Ok(RetentionAction::Redact));
+
+        assert!(policy(&[], 365, "delete").validate().is_err());
+        assert!(policy(&["Declined"], 0, "delete").validate().is_err());
+        assert!(policy(&["Declined"], 365, "archive").validate().is_err());
+        assert!(policy(&["Declined", "Onboarding"], 365, "delete").validate().is_err());
+    }
+
+    #[test]
+    fn test_retention_policy_applies_to() {
+        let now = Utc.ymd(2021, 6, 28).and_hms(12, 0, 0);
+        let p = policy(&["Declined", "Deferred"], 90, "redact");
+
+        assert!(p.applies_to("Declined", now - Duration::days(90), now));
+        assert!(p.applies_to("deferred", now - Duration::days(400), now));
+        assert!(!p.applies_to("Declined", now - Duration::days(89), now));
+        assert!(!p.applies_to("Interviewing", now - Duration::days(400), now));
+    }
+
+    #[test]
+    fn test_hash_applicant_email() {
+        assert_eq!(hash_applicant_email("Jane@Example.com "), hash_applicant_email("jane@example.com"));
+        assert_ne!(hash_applicant_email("jane@example.com"), hash_applicant_email("john@example.com"));
+        assert_eq!(hash_applicant_email("jane@example.com").len(), 64);
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_applicants_retention() {
+        let db = Database::new();
+        refresh_applicant_retention(&db).await;
+    }
+}
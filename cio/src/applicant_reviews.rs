@@ -0,0 +1,687 @@
+#![allow(clippy::from_over_into)]
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use gsuite_api::{CalendarEvent, GSuite};
+use hubcaps::Github;
+use macros::db;
+use schemars::JsonSchema;
+use sendgrid_api::SendGrid;
+use serde::{Deserialize, Serialize};
+
+use crate::airtable::{AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_REVIEW_ASSIGNMENTS_TABLE, AIRTABLE_SCORES_TABLE};
+use crate::applicant_status::Status;
+use crate::applicants::{ApplicantReviewer, Applicants, NewApplicantReviewer};
+use crate::configs::{get_configs_from_repo, User, Users};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::applicant_review_assignments;
+use crate::utils::{get_gsuite_token, DOMAIN, GSUITE_DOMAIN};
+
+fn default_reviewers_per_applicant() -> usize {
+    5
+}
+
+fn default_review_due_days() -> i64 {
+    7
+}
+
+fn default_reminder_interval_days() -> i64 {
+    2
+}
+
+/// How we assign reviewers for a role, from the configs repo.
+/// The key in the configs is the name of the role, ie. "Engineering".
+/// Roles without a config do not get reviewers assigned automatically.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantReviewersConfig {
+    /// The usernames of the people who review applicants for the role.
+    /// If this is empty, every full-time employee reviews applicants for the role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
+    /// The usernames of the people who never review applicants for the role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded: Vec<String>,
+    #[serde(default = "default_reviewers_per_applicant")]
+    pub reviewers_per_applicant: usize,
+    /// How many days reviewers have to score an applicant.
+    #[serde(default = "default_review_due_days")]
+    pub review_due_days: i64,
+    /// How many days we wait between reminders for an overdue review.
+    #[serde(default = "default_reminder_interval_days")]
+    pub reminder_interval_days: i64,
+    /// Conflicts of interest, by reviewer username. The values are the email
+    /// addresses, or `@domain`s, of the applicants they should not review.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub conflicts: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for ApplicantReviewersConfig {
+    fn default() -> Self {
+        ApplicantReviewersConfig {
+            reviewers: Default::default(),
+            excluded: Default::default(),
+            reviewers_per_applicant: default_reviewers_per_applicant(),
+            review_due_days: default_review_due_days(),
+            reminder_interval_days: default_reminder_interval_days(),
+            conflicts: Default::default(),
+        }
+    }
+}
+
+impl ApplicantReviewersConfig {
+    /// Returns if the user reviews applicants for the role.
+    pub fn is_reviewer(&self, user: &User) -> bool {
+        if user.is_system_account() || user.typev != "full-time" {
+            return false;
+        }
+        if self.excluded.contains(&user.username) {
+            return false;
+        }
+
+        self.reviewers.is_empty() || self.reviewers.contains(&user.username)
+    }
+
+    /// Returns if the reviewer has a conflict of interest with the applicant.
+    /// Besides the conflicts in the config, nobody reviews their own application.
+    pub fn is_conflicted(&self, reviewer: &ReviewerCandidate, applicant_email: &str) -> bool {
+        let applicant_email = applicant_email.trim().to_lowercase();
+        if applicant_email == reviewer.email.to_lowercase() || applicant_email == reviewer.recovery_email.to_lowercase() {
+            return true;
+        }
+
+        match self.conflicts.get(&reviewer.username) {
+            Some(conflicts) => conflicts
+                .iter()
+                .map(|c| c.trim().to_lowercase())
+                .any(|c| if c.starts_with('@') { applicant_email.ends_with(&c) } else { applicant_email == c }),
+            None => false,
+        }
+    }
+}
+
+/// The states a review assignment can be in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum ReviewAssignmentStatus {
+    /// The reviewer still needs to score the applicant.
+    Assigned,
+    /// The reviewer scored the applicant.
+    Completed,
+    /// The reviewer was out of office, so someone else was assigned instead.
+    Reassigned,
+    /// The applicant moved past triage before the reviewer scored them.
+    Cancelled,
+}
+
+impl FromStr for ReviewAssignmentStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status.to_lowercase().trim() {
+            "assigned" => Ok(ReviewAssignmentStatus::Assigned),
+            "completed" => Ok(ReviewAssignmentStatus::Completed),
+            "reassigned" => Ok(ReviewAssignmentStatus::Reassigned),
+            "cancelled" => Ok(ReviewAssignmentStatus::Cancelled),
+            s => Err(format!("`{}` is not a valid review assignment status", s)),
+        }
+    }
+}
+
+impl fmt::Display for ReviewAssignmentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ReviewAssignmentStatus::Assigned => "assigned",
+            ReviewAssignmentStatus::Completed => "completed",
+            ReviewAssignmentStatus::Reassigned => "reassigned",
+            ReviewAssignmentStatus::Cancelled => "cancelled",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// A reviewer assigned to score an applicant.
+#[db {
+    new_struct_name = "ApplicantReviewAssignment",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_REVIEW_ASSIGNMENTS_TABLE",
+    match_on = {
+        "reviewer" = "String",
+        "applicant_email" = "String",
+        "sheet_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "applicant_review_assignments"]
+pub struct NewApplicantReviewAssignment {
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        serialize_with = "airtable_api::user_format_as_string::serialize",
+        deserialize_with = "airtable_api::user_format_as_string::deserialize"
+    )]
+    pub reviewer: String,
+    pub applicant_email: String,
+    pub applicant_name: String,
+    pub role: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sheet_id: String,
+    pub status: String,
+    pub assigned_time: DateTime<Utc>,
+    pub due_time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reminders_sent: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reminder_time: Option<DateTime<Utc>>,
+}
+
+/// Implement updating the Airtable record for an ApplicantReviewAssignment.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantReviewAssignment> for ApplicantReviewAssignment {
+    async fn update_airtable_record(&mut self, _record: ApplicantReviewAssignment) {}
+}
+
+impl ApplicantReviewAssignment {
+    /// Returns if the reviewer still needs to score the applicant.
+    pub fn is_open(&self) -> bool {
+        self.status == ReviewAssignmentStatus::Assigned.to_string()
+    }
+
+    /// Returns if the review is open and past its due time.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.is_open() && self.due_time < now
+    }
+
+    /// Returns if we should remind the reviewer about the overdue review.
+    pub fn needs_reminder(&self, now: DateTime<Utc>, reminder_interval_days: i64) -> bool {
+        if !self.is_overdue(now) {
+            return false;
+        }
+
+        match self.last_reminder_time {
+            Some(last) => now - last >= Duration::days(reminder_interval_days),
+            None => true,
+        }
+    }
+}
+
+/// Someone who could be assigned to review an applicant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewerCandidate {
+    pub username: String,
+    pub email: String,
+    pub recovery_email: String,
+    /// The number of applicants they still need to score.
+    pub open_assignments: usize,
+    /// The number of applicants they have scored, from the leaderboard.
+    pub evaluations: i32,
+    /// The start and end of the out of office events on their calendar.
+    pub out_of_office: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl ReviewerCandidate {
+    /// Returns if the reviewer is around for at least half of the time between `from` and `to`.
+    pub fn is_available(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        let mut away = Duration::zero();
+        for (start, end) in &self.out_of_office {
+            let start = if *start > from { *start } else { from };
+            let end = if *end < to { *end } else { to };
+            if end > start {
+                away = away + (end - start);
+            }
+        }
+
+        away * 2 < to - from
+    }
+
+    /// Returns the end of the out of office event that they are in right now, if any.
+    pub fn out_of_office_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.out_of_office.iter().filter(|(start, end)| *start <= now && *end > now).map(|(_, end)| *end).max()
+    }
+}
+
+/// Choose reviewers for an applicant, from the candidates who review for the role, are
+/// around until the review is due, and have no conflict of interest. The reviewers with
+/// the fewest open assignments go first, then the ones with the fewest evaluations, so the
+/// load evens out over time. Candidates in `skip` are never chosen.
+pub fn choose_reviewers(config: &ApplicantReviewersConfig, applicant_email: &str, candidates: &[ReviewerCandidate], skip: &BTreeSet<String>, count: usize, now: DateTime<Utc>) -> Vec<String> {
+    let due = now + Duration::days(config.review_due_days);
+
+    let mut eligible: Vec<&ReviewerCandidate> = candidates
+        .iter()
+        .filter(|c| !skip.contains(&c.email))
+        .filter(|c| !config.is_conflicted(c, applicant_email))
+        .filter(|c| c.is_available(now, due))
+        .collect();
+    eligible.sort_by(|a, b| a.open_assignments.cmp(&b.open_assignments).then(a.evaluations.cmp(&b.evaluations)).then(a.email.cmp(&b.email)));
+
+    eligible.into_iter().take(count).map(|c| c.email.to_string()).collect()
+}
+
+/// Get the start and end of the out of office events on the reviewer's calendar.
+async fn get_out_of_office(gsuite: &GSuite, email: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let events: Vec<CalendarEvent> = match gsuite.list_out_of_office_events(email, from, to).await {
+        Ok(events) => events,
+        Err(e) => {
+            // Do not hold up the assignments if we cannot read someone's calendar.
+            println!("[applicant_reviews] getting the out of office events for {} failed: {}", email, e);
+            return vec![];
+        }
+    };
+
+    let mut out_of_office: Vec<(DateTime<Utc>, DateTime<Utc>)> = Default::default();
+    for event in events {
+        // All day events only have a date.
+        let start = event
+            .start
+            .date_time
+            .or_else(|| event.start.date.map(|d| Utc.from_utc_datetime(&d.and_time(NaiveTime::from_hms(0, 0, 0)))));
+        let end = event.end.date_time.or_else(|| event.end.date.map(|d| Utc.from_utc_datetime(&d.and_time(NaiveTime::from_hms(0, 0, 0)))));
+        if let (Some(start), Some(end)) = (start, end) {
+            out_of_office.push((start, end));
+        }
+    }
+
+    out_of_office
+}
+
+/// Let the reviewer know they have a new applicant to score.
+async fn send_email_review_assigned(assignment: &NewApplicantReviewAssignment) {
+    // Initialize the SendGrid client.
+    let sendgrid_client = SendGrid::new_from_env();
+
+    // Send the message.
+    sendgrid_client
+        .send_mail(
+            format!("Please review {} for {}", assignment.applicant_name, assignment.role),
+            format!(
+                "You have been assigned to review the application from {} for {}.
+
+Please score them by {} by adding a row to the \"{}\" table in the applicants Airtable:
+https://airtable-applicants.corp.oxide.computer
+
+If you cannot review them, let recruiting know and they will find someone else.
+
+Thanks!",
+                assignment.applicant_name,
+                assignment.role,
+                assignment.due_time.format("%A, %B %-d"),
+                AIRTABLE_SCORES_TABLE
+            ),
+            vec![assignment.reviewer.to_string()],
+            vec![],
+            vec![],
+            format!("careers@{}", DOMAIN),
+        )
+        .await;
+}
+
+/// Remind the reviewer that their review is overdue.
+async fn send_email_review_overdue(assignment: &ApplicantReviewAssignment) {
+    // Initialize the SendGrid client.
+    let sendgrid_client = SendGrid::new_from_env();
+
+    // Send the message.
+    sendgrid_client
+        .send_mail(
+            format!("Reminder: your review of {} is overdue", assignment.applicant_name),
+            format!(
+                "Your review of the application from {} for {} was due {}.
+
+Please score them by adding a row to the \"{}\" table in the applicants Airtable:
+https://airtable-applicants.corp.oxide.computer
+
+If you cannot review them, let recruiting know and they will find someone else.
+
+Thanks!",
+                assignment.applicant_name,
+                assignment.role,
+                assignment.due_time.format("%A, %B %-d"),
+                AIRTABLE_SCORES_TABLE
+            ),
+            vec![assignment.reviewer.to_string()],
+            vec![],
+            vec![],
+            format!("careers@{}", DOMAIN),
+        )
+        .await;
+}
+
+/// Get the scorers for an applicant: the reviewers someone added by hand, who have no
+/// assignment for the applicant and did not score them yet, and the reviewers with an
+/// open assignment.
+pub fn get_applicant_scorers(scorers: &[String], scorers_completed: &[String], assigned: &BTreeSet<String>, open: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = scorers.iter().filter(|s| !assigned.contains(*s) && !scorers_completed.contains(s)).cloned().collect();
+    merged.extend(open.iter().cloned());
+    merged.sort();
+    merged.dedup();
+    merged
+}
+
+/// Assign reviewers to the applicants that need to be triaged, keep the assignments in
+/// sync with the scores we have, and remind reviewers of overdue reviews.
+pub async fn refresh_applicant_review_assignments(db: &Database, github: &Github) {
+    let configs = get_configs_from_repo(github).await.applicant_reviewers;
+    if configs.is_empty() {
+        println!("[applicant_reviews] no roles have reviewers configured, skipping assignments");
+        return;
+    }
+
+    let now = Utc::now();
+    let users: Vec<User> = Users::get_from_db(db).into_iter().collect();
+    let applicants = Applicants::get_from_db(db);
+
+    let mut assignments: Vec<ApplicantReviewAssignment> = ApplicantReviewAssignments::get_from_db(db).into_iter().collect();
+
+    // Close out the assignments that were scored, or where the applicant moved on.
+    for assignment in assignments.iter_mut().filter(|a| a.is_open()) {
+        let applicant = applicants.iter().find(|a| a.email == assignment.applicant_email && a.sheet_id == assignment.sheet_id);
+        let status = match applicant {
+            Some(a) if a.scorers_completed.contains(&assignment.reviewer) => ReviewAssignmentStatus::Completed,
+            Some(a) if Status::from_str(&a.status) == Ok(Status::NeedsToBeTriaged) => continue,
+            _ => ReviewAssignmentStatus::Cancelled,
+        };
+
+        assignment.status = status.to_string();
+        if status == ReviewAssignmentStatus::Completed {
+            assignment.completed_time = Some(now);
+        }
+        *assignment = assignment.update(db).await;
+    }
+
+    // Get everyone who reviews for any role, with their load and their time off.
+    let gsuite_customer = env::var("GADMIN_ACCOUNT_ID").unwrap();
+    let token = get_gsuite_token("").await;
+    let gsuite = GSuite::new(&gsuite_customer, GSUITE_DOMAIN, token);
+    let longest_due_days = configs.values().map(|c| c.review_due_days).max().unwrap_or_else(default_review_due_days);
+
+    let mut candidates: BTreeMap<String, ReviewerCandidate> = Default::default();
+    for user in &users {
+        if !configs.values().any(|c| c.is_reviewer(user)) {
+            continue;
+        }
+
+        let email = user.email();
+        candidates.insert(
+            user.username.to_string(),
+            ReviewerCandidate {
+                username: user.username.to_string(),
+                email: email.to_string(),
+                recovery_email: user.recovery_email.to_string(),
+                open_assignments: assignments.iter().filter(|a| a.is_open() && a.reviewer == email).count(),
+                evaluations: ApplicantReviewer::get_from_db(db, email.to_string()).map(|r| r.evaluations).unwrap_or_default(),
+                out_of_office: get_out_of_office(&gsuite, &email, now, now + Duration::days(longest_due_days)).await,
+            },
+        );
+    }
+
+    for mut applicant in applicants {
+        if Status::from_str(&applicant.status) != Ok(Status::NeedsToBeTriaged) {
+            continue;
+        }
+        let config = match configs.get(&applicant.role) {
+            Some(c) => c,
+            None => continue,
+        };
+        let due = now + Duration::days(config.review_due_days);
+
+        // Hand off the open reviews of anyone who will be out for most of the time
+        // they have left to do them.
+        for assignment in assignments
+            .iter_mut()
+            .filter(|a| a.is_open() && a.applicant_email == applicant.email && a.sheet_id == applicant.sheet_id)
+        {
+            if let Some(candidate) = candidates.values_mut().find(|c| c.email == assignment.reviewer) {
+                if assignment.due_time > now && !candidate.is_available(now, assignment.due_time) {
+                    println!(
+                        "[applicant_reviews] {} is out of office, reassigning their review of {}",
+                        assignment.reviewer, assignment.applicant_email
+                    );
+                    assignment.status = ReviewAssignmentStatus::Reassigned.to_string();
+                    *assignment = assignment.update(db).await;
+                    candidate.open_assignments = candidate.open_assignments.saturating_sub(1);
+                }
+            }
+        }
+
+        // Anyone who was ever assigned to the applicant, already scored them, or was
+        // added as a scorer by hand is not assigned again.
+        let mut assigned: BTreeSet<String> = assignments
+            .iter()
+            .filter(|a| a.applicant_email == applicant.email && a.sheet_id == applicant.sheet_id)
+            .map(|a| a.reviewer.to_string())
+            .collect();
+        let manual = get_applicant_scorers(&applicant.scorers, &applicant.scorers_completed, &assigned, &[]);
+        let skip: BTreeSet<String> = applicant.scorers_completed.iter().chain(manual.iter()).chain(assigned.iter()).cloned().collect();
+        let mut active = applicant.scorers_completed.len() + manual.len();
+        active += assignments
+            .iter()
+            .filter(|a| a.is_open() && a.applicant_email == applicant.email && a.sheet_id == applicant.sheet_id)
+            .count();
+
+        if active < config.reviewers_per_applicant {
+            let role_candidates: Vec<ReviewerCandidate> = users.iter().filter(|u| config.is_reviewer(u)).filter_map(|u| candidates.get(&u.username)).cloned().collect();
+            let chosen = choose_reviewers(config, &applicant.email, &role_candidates, &skip, config.reviewers_per_applicant - active, now);
+            if chosen.len() < config.reviewers_per_applicant - active {
+                println!(
+                    "[applicant_reviews] only found {} of the {} reviewers needed for {} ({})",
+                    chosen.len(),
+                    config.reviewers_per_applicant - active,
+                    applicant.email,
+                    applicant.role
+                );
+            }
+
+            for reviewer in chosen {
+                let new_assignment = NewApplicantReviewAssignment {
+                    reviewer: reviewer.to_string(),
+                    applicant_email: applicant.email.to_string(),
+                    applicant_name: applicant.name.to_string(),
+                    role: applicant.role.to_string(),
+                    sheet_id: applicant.sheet_id.to_string(),
+                    status: ReviewAssignmentStatus::Assigned.to_string(),
+                    assigned_time: now,
+                    due_time: due,
+                    completed_time: None,
+                    reminders_sent: 0,
+                    last_reminder_time: None,
+                };
+                // Save the assignment before we let the reviewer know, so a failure
+                // never leaves them with a review we do not know about.
+                let assignment = new_assignment.upsert(db).await;
+                send_email_review_assigned(&new_assignment).await;
+                println!("[applicant_reviews] assigned {} to review {} ({})", reviewer, applicant.email, applicant.role);
+
+                assigned.insert(reviewer.to_string());
+                assignments.push(assignment);
+                if let Some(candidate) = candidates.values_mut().find(|c| c.email == reviewer) {
+                    candidate.open_assignments += 1;
+                }
+            }
+        }
+
+        // The scorers on the applicant are the reviewers who still need to score them,
+        // including the ones added by hand.
+        let open: Vec<String> = assignments
+            .iter()
+            .filter(|a| a.is_open() && a.applicant_email == applicant.email && a.sheet_id == applicant.sheet_id)
+            .map(|a| a.reviewer.to_string())
+            .collect();
+        let scorers = get_applicant_scorers(&applicant.scorers, &applicant.scorers_completed, &assigned, &open);
+        if scorers != applicant.scorers {
+            applicant.scorers = scorers;
+            applicant.update(db).await;
+        }
+    }
+
+    // Remind reviewers of the reviews that are overdue.
+    for assignment in assignments.iter_mut() {
+        let config = configs.get(&assignment.role).cloned().unwrap_or_default();
+        if !assignment.needs_reminder(now, config.reminder_interval_days) {
+            continue;
+        }
+
+        send_email_review_overdue(assignment).await;
+        println!("[applicant_reviews] reminded {} about their overdue review of {}", assignment.reviewer, assignment.applicant_email);
+
+        assignment.reminders_sent += 1;
+        assignment.last_reminder_time = Some(now);
+        *assignment = assignment.update(db).await;
+    }
+
+    // Keep the reviewer load on the leaderboard.
+    for candidate in candidates.values() {
+        let user = users.iter().find(|u| u.username == candidate.username).unwrap();
+        let mut reviewer: NewApplicantReviewer = match ApplicantReviewer::get_from_db(db, candidate.email.to_string()) {
+            Some(r) => r.into(),
+            None => NewApplicantReviewer {
+                name: user.full_name(),
+                email: candidate.email.to_string(),
+                evaluations: 0,
+                emphatic_yes: 0,
+                yes: 0,
+                pass: 0,
+                no: 0,
+                not_applicable: 0,
+                open_assignments: 0,
+                overdue_assignments: 0,
+                out_of_office_until: None,
+                legacy_evaluations: 0,
+                legacy_emphatic_yes: 0,
+                legacy_yes: 0,
+                legacy_pass: 0,
+                legacy_no: 0,
+                legacy_not_applicable: 0,
+            },
+        };
+        reviewer.open_assignments = candidate.open_assignments as i32;
+        reviewer.overdue_assignments = assignments.iter().filter(|a| a.reviewer == candidate.email && a.is_overdue(now)).count() as i32;
+        reviewer.out_of_office_until = candidate.out_of_office_until(now);
+        reviewer.upsert(db).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::applicant_reviews::{choose_reviewers, get_applicant_scorers, refresh_applicant_review_assignments, ApplicantReviewersConfig, ReviewerCandidate};
+    use crate::db::Database;
+    use crate::utils::authenticate_github_jwt;
+
+    fn candidate(username: &str, open_assignments: usize, evaluations: i32) -> ReviewerCandidate {
+        ReviewerCandidate {
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            recovery_email: format!("{}@gmail.com", username),
+            open_assignments,
+            evaluations,
+            out_of_office: vec![],
+        }
+    }
+
+    #[test]
+    fn test_choose_reviewers_balances_load() {
+        let now = Utc.ymd(2021, 6, 14).and_hms(9, 0, 0);
+        let config = ApplicantReviewersConfig::default();
+        let candidates = vec![candidate("alice", 3, 0), candidate("bob", 0, 10), candidate("carol", 0, 2), candidate("dave", 1, 0)];
+
+        assert_eq!(
+            choose_reviewers(&config, "applicant@example.org", &candidates, &BTreeSet::new(), 3, now),
+            vec!["carol@example.com", "bob@example.com", "dave@example.com"]
+        );
+
+        // People who already reviewed the applicant are skipped.
+        let skip: BTreeSet<String> = vec!["carol@example.com".to_string()].into_iter().collect();
+        assert_eq!(choose_reviewers(&config, "applicant@example.org", &candidates, &skip, 1, now), vec!["bob@example.com"]);
+    }
+
+    #[test]
+    fn test_choose_reviewers_conflicts() {
+        let now = Utc.ymd(2021, 6, 14).and_hms(9, 0, 0);
+        let mut conflicts: BTreeMap<String, Vec<String>> = Default::default();
+        conflicts.insert("bob".to_string(), vec!["@Former-Employer.com".to_string()]);
+        conflicts.insert("carol".to_string(), vec!["friend@example.org".to_string()]);
+        let config = ApplicantReviewersConfig { conflicts, ..Default::default() };
+        let candidates = vec![candidate("alice", 0, 0), candidate("bob", 0, 0), candidate("carol", 0, 0)];
+
+        assert_eq!(
+            choose_reviewers(&config, "someone@former-employer.com", &candidates, &BTreeSet::new(), 5, now),
+            vec!["alice@example.com", "carol@example.com"]
+        );
+        assert_eq!(
+            choose_reviewers(&config, "Friend@example.org", &candidates, &BTreeSet::new(), 5, now),
+            vec!["alice@example.com", "bob@example.com"]
+        );
+        // Nobody reviews their own application.
+        assert_eq!(
+            choose_reviewers(&config, "alice@gmail.com", &candidates, &BTreeSet::new(), 5, now),
+            vec!["bob@example.com", "carol@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_choose_reviewers_out_of_office() {
+        let now = Utc.ymd(2021, 6, 14).and_hms(9, 0, 0);
+        let config = ApplicantReviewersConfig::default();
+
+        // Alice is out for most of the week, Bob only for a day.
+        let mut alice = candidate("alice", 0, 0);
+        alice.out_of_office.push((now - Duration::days(1), now + Duration::days(5)));
+        let mut bob = candidate("bob", 0, 0);
+        bob.out_of_office.push((now + Duration::days(2), now + Duration::days(3)));
+
+        assert!(!alice.is_available(now, now + Duration::days(7)));
+        assert!(bob.is_available(now, now + Duration::days(7)));
+        assert_eq!(alice.out_of_office_until(now), Some(now + Duration::days(5)));
+        assert_eq!(bob.out_of_office_until(now), None);
+
+        assert_eq!(choose_reviewers(&config, "applicant@example.org", &[alice, bob], &BTreeSet::new(), 2, now), vec!["bob@example.com"]);
+    }
+
+    #[test]
+    fn test_get_applicant_scorers() {
+        let strings = |s: &[&str]| -> Vec<String> { s.iter().map(|s| s.to_string()).collect() };
+
+        // Alice was added by hand, Bob's assignment was reassigned and Carol already
+        // scored the applicant, so only Alice stays next to the open assignments.
+        let scorers = strings(&["alice@example.com", "bob@example.com", "carol@example.com"]);
+        let completed = strings(&["carol@example.com"]);
+        let assigned: BTreeSet<String> = strings(&["bob@example.com", "dave@example.com", "erin@example.com"]).into_iter().collect();
+        let open = strings(&["erin@example.com", "dave@example.com"]);
+
+        assert_eq!(
+            get_applicant_scorers(&scorers, &completed, &assigned, &open),
+            strings(&["alice@example.com", "dave@example.com", "erin@example.com"])
+        );
+        assert_eq!(get_applicant_scorers(&scorers, &completed, &assigned, &[]), strings(&["alice@example.com"]));
+    }
+
+    #[test]
+    fn test_applicant_reviewers_config_excluded() {
+        // Nobody is excluded unless the config says so.
+        let config: ApplicantReviewersConfig = toml::from_str("reviewers_per_applicant = 3").unwrap();
+        assert!(config.excluded.is_empty());
+        assert_eq!(config, ApplicantReviewersConfig::default());
+
+        let config: ApplicantReviewersConfig = toml::from_str(r#"excluded = ["jess"]"#).unwrap();
+        assert_eq!(config.excluded, vec!["jess".to_string()]);
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cron_applicant_review_assignments() {
+        let github = authenticate_github_jwt();
+        let db = Database::new();
+
+        refresh_applicant_review_assignments(&db, &github).await;
+    }
+}
@@ -0,0 +1,633 @@
+#![allow(clippy::from_over_into)]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::airtable::{AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_SCORES_TABLE};
+use crate::applicant_reviews::{ApplicantReviewAssignment, ReviewAssignmentStatus};
+use crate::applicant_status::Status;
+use crate::applicants::{get_sheets_map, Applicant, Applicants};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::{applicant_scores, applicants};
+
+/// The lowest score for a dimension of the rubric.
+pub static RUBRIC_MIN: i32 = 1;
+/// The highest score for a dimension of the rubric.
+pub static RUBRIC_MAX: i32 = 5;
+/// The variance of the overall scores at which we say the reviewers disagree.
+/// On a one to five scale, a 5 and a 2 is a variance of 2.25.
+pub static DISAGREEMENT_VARIANCE: f32 = 1.5;
+
+/// What the reviewer thinks we should do with the applicant.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum ScoringRecommendation {
+    EmphaticYes,
+    Yes,
+    Pass,
+    No,
+    /// The reviewer cannot judge the applicant, ie. the role is outside their expertise.
+    NotApplicable,
+}
+
+impl FromStr for ScoringRecommendation {
+    type Err = String;
+
+    fn from_str(recommendation: &str) -> Result<Self, Self::Err> {
+        match recommendation.to_lowercase().replace('_', " ").trim() {
+            "emphatic yes" | "enthusiastic yes" => Ok(ScoringRecommendation::EmphaticYes),
+            "yes" => Ok(ScoringRecommendation::Yes),
+            "pass" => Ok(ScoringRecommendation::Pass),
+            "no" => Ok(ScoringRecommendation::No),
+            "not applicable" | "n/a" => Ok(ScoringRecommendation::NotApplicable),
+            s => Err(format!("`{}` is not a valid recommendation", s)),
+        }
+    }
+}
+
+impl fmt::Display for ScoringRecommendation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ScoringRecommendation::EmphaticYes => "Emphatic yes",
+            ScoringRecommendation::Yes => "Yes",
+            ScoringRecommendation::Pass => "Pass",
+            ScoringRecommendation::No => "No",
+            ScoringRecommendation::NotApplicable => "Not applicable",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// The concerns a reviewer can raise about an applicant.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum ScoringConcern {
+    InsufficientExperience,
+    InapplicableExperience,
+    JobFunctionYetNeeded,
+    UnderwhelmingMaterials,
+}
+
+impl FromStr for ScoringConcern {
+    type Err = String;
+
+    fn from_str(concern: &str) -> Result<Self, Self::Err> {
+        match concern.to_lowercase().replace('_', " ").trim() {
+            "insufficient experience" => Ok(ScoringConcern::InsufficientExperience),
+            "inapplicable experience" => Ok(ScoringConcern::InapplicableExperience),
+            "job function yet needed" | "job function not yet needed" => Ok(ScoringConcern::JobFunctionYetNeeded),
+            "underwhelming materials" => Ok(ScoringConcern::UnderwhelmingMaterials),
+            s => Err(format!("`{}` is not a valid concern", s)),
+        }
+    }
+}
+
+impl fmt::Display for ScoringConcern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ScoringConcern::InsufficientExperience => "Insufficient experience",
+            ScoringConcern::InapplicableExperience => "Inapplicable experience",
+            ScoringConcern::JobFunctionYetNeeded => "Job function not yet needed",
+            ScoringConcern::UnderwhelmingMaterials => "Underwhelming materials",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// A reviewer's score for an applicant.
+/// Reviewers add these as rows in the Airtable, an Airtable script sends them to
+/// webhooky, and webhooky saves them with `submit_applicant_score`.
+#[db {
+    new_struct_name = "ApplicantScore",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_SCORES_TABLE",
+    match_on = {
+        "reviewer" = "String",
+        "applicant_email" = "String",
+        "sheet_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "applicant_scores"]
+pub struct NewApplicantScore {
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        serialize_with = "airtable_api::user_format_as_string::serialize",
+        deserialize_with = "airtable_api::user_format_as_string::deserialize"
+    )]
+    pub reviewer: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_name: String,
+    /// The role the applicant applied for. This only needs to be set by the
+    /// reviewer if the applicant applied for more than one role.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub role: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sheet_id: String,
+    /// One of the `ScoringRecommendation`s.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub recommendation: String,
+    /// The overall score, from `RUBRIC_MIN` to `RUBRIC_MAX`.
+    /// The scores are ignored when the recommendation is "Not applicable".
+    #[serde(default)]
+    pub score: i32,
+    /// The depth of the applicant's technical work, from `RUBRIC_MIN` to `RUBRIC_MAX`.
+    #[serde(default)]
+    pub technical_depth: i32,
+    /// The quality of the applicant's writing, from `RUBRIC_MIN` to `RUBRIC_MAX`.
+    #[serde(default)]
+    pub writing: i32,
+    /// The values the reviewer saw reflected in the applicant's materials.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values_reflected: Vec<String>,
+    /// The values the reviewer saw violated in the applicant's materials.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values_violated: Vec<String>,
+    /// Any of the `ScoringConcern`s.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub concerns: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_time: Option<DateTime<Utc>>,
+}
+
+/// Implement updating the Airtable record for an ApplicantScore.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantScore> for ApplicantScore {
+    async fn update_airtable_record(&mut self, _record: ApplicantScore) {}
+}
+
+impl NewApplicantScore {
+    /// Clean up the score so that it can be compared with the other scores
+    /// for the applicant.
+    pub fn normalize(&mut self) {
+        self.reviewer = self.reviewer.trim().to_lowercase();
+        self.applicant_email = self.applicant_email.trim().to_lowercase();
+        self.role = self.role.trim().to_string();
+        self.notes = self.notes.trim().to_string();
+
+        if let Ok(recommendation) = ScoringRecommendation::from_str(&self.recommendation) {
+            self.recommendation = recommendation.to_string();
+        }
+
+        let mut concerns: Vec<String> = self
+            .concerns
+            .iter()
+            .map(|c| match ScoringConcern::from_str(c) {
+                Ok(concern) => concern.to_string(),
+                Err(_) => c.trim().to_string(),
+            })
+            .collect();
+        concerns.sort();
+        concerns.dedup();
+        self.concerns = concerns;
+
+        self.values_reflected = normalize_values(&self.values_reflected);
+        self.values_violated = normalize_values(&self.values_violated);
+    }
+
+    /// Check the score, returning all the problems with it at once.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems: Vec<String> = Default::default();
+
+        if self.reviewer.is_empty() {
+            problems.push("the reviewer is required".to_string());
+        }
+        if self.applicant_email.is_empty() {
+            problems.push("the applicant email is required".to_string());
+        }
+        let recommendation = ScoringRecommendation::from_str(&self.recommendation);
+        if let Err(e) = &recommendation {
+            problems.push(e.to_string());
+        }
+        // A reviewer who cannot judge the applicant does not score them.
+        let scored = recommendation != Ok(ScoringRecommendation::NotApplicable);
+        for (dimension, value) in &[("score", self.score), ("technical depth", self.technical_depth), ("writing", self.writing)] {
+            if scored && (*value < RUBRIC_MIN || *value > RUBRIC_MAX) {
+                problems.push(format!("the {} must be from {} to {}, not {}", dimension, RUBRIC_MIN, RUBRIC_MAX, value));
+            }
+        }
+        for concern in &self.concerns {
+            if let Err(e) = ScoringConcern::from_str(concern) {
+                problems.push(e);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// Lowercase, sort and dedup the values, dropping any that are "n/a".
+fn normalize_values(values: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = values.iter().map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty() && v != "n/a").collect();
+    normalized.sort();
+    normalized.dedup();
+
+    normalized
+}
+
+/// The spread of the scores for one dimension of the rubric.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ScoreSummary {
+    pub count: i32,
+    pub mean: f32,
+    /// The population variance, so a single score has no variance.
+    pub variance: f32,
+    pub min: i32,
+    pub max: i32,
+}
+
+impl ScoreSummary {
+    pub fn new(values: &[i32]) -> Self {
+        if values.is_empty() {
+            return Default::default();
+        }
+
+        let count = values.len() as f32;
+        let mean = values.iter().sum::<i32>() as f32 / count;
+        let variance = values.iter().map(|v| (*v as f32 - mean).powi(2)).sum::<f32>() / count;
+
+        ScoreSummary {
+            count: values.len() as i32,
+            mean,
+            variance,
+            min: *values.iter().min().unwrap(),
+            max: *values.iter().max().unwrap(),
+        }
+    }
+}
+
+/// All the scores for an applicant, aggregated.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantScorecard {
+    pub applicant_email: String,
+    pub applicant_name: String,
+    pub role: String,
+    pub sheet_id: String,
+    pub status: String,
+    /// The reviewers who scored the applicant.
+    pub reviewers: Vec<String>,
+    pub evaluations: i32,
+    pub score: ScoreSummary,
+    pub technical_depth: ScoreSummary,
+    pub writing: ScoreSummary,
+    pub emphatic_yes: i32,
+    pub yes: i32,
+    pub pass: i32,
+    pub no: i32,
+    pub not_applicable: i32,
+    /// The number of reviewers who raised each concern.
+    pub concerns: BTreeMap<String, i32>,
+    /// The number of reviewers who saw each value reflected.
+    pub values_reflected: BTreeMap<String, i32>,
+    /// The number of reviewers who saw each value violated.
+    pub values_violated: BTreeMap<String, i32>,
+    /// The notes from each reviewer, by reviewer.
+    pub notes: BTreeMap<String, String>,
+    /// If the overall scores are spread out, or someone said yes while someone
+    /// else said no.
+    pub disagreement: bool,
+}
+
+impl ApplicantScorecard {
+    pub fn new(applicant: &Applicant, scores: &[ApplicantScore]) -> Self {
+        ApplicantScorecard {
+            applicant_email: applicant.email.to_string(),
+            applicant_name: applicant.name.to_string(),
+            role: applicant.role.to_string(),
+            sheet_id: applicant.sheet_id.to_string(),
+            status: applicant.status.to_string(),
+            ..ApplicantScorecard::from_scores(scores)
+        }
+    }
+
+    /// Aggregate the scores, without the information about the applicant.
+    /// Reviews that are "Not applicable" are counted, but their numbers are left
+    /// out of the summaries and the disagreement.
+    pub fn from_scores(scores: &[ApplicantScore]) -> Self {
+        let scored: Vec<&ApplicantScore> = scores
+            .iter()
+            .filter(|s| ScoringRecommendation::from_str(&s.recommendation) != Ok(ScoringRecommendation::NotApplicable))
+            .collect();
+        let mut scorecard = ApplicantScorecard {
+            evaluations: scores.len() as i32,
+            score: ScoreSummary::new(&scored.iter().map(|s| s.score).collect::<Vec<i32>>()),
+            technical_depth: ScoreSummary::new(&scored.iter().map(|s| s.technical_depth).collect::<Vec<i32>>()),
+            writing: ScoreSummary::new(&scored.iter().map(|s| s.writing).collect::<Vec<i32>>()),
+            ..Default::default()
+        };
+
+        for score in scores {
+            scorecard.reviewers.push(score.reviewer.to_string());
+
+            match ScoringRecommendation::from_str(&score.recommendation) {
+                Ok(ScoringRecommendation::EmphaticYes) => scorecard.emphatic_yes += 1,
+                Ok(ScoringRecommendation::Yes) => scorecard.yes += 1,
+                Ok(ScoringRecommendation::Pass) => scorecard.pass += 1,
+                Ok(ScoringRecommendation::No) => scorecard.no += 1,
+                Ok(ScoringRecommendation::NotApplicable) => scorecard.not_applicable += 1,
+                Err(_) => (),
+            }
+
+            for concern in &score.concerns {
+                *scorecard.concerns.entry(concern.to_string()).or_insert(0) += 1;
+            }
+            for value in &score.values_reflected {
+                *scorecard.values_reflected.entry(value.to_string()).or_insert(0) += 1;
+            }
+            for value in &score.values_violated {
+                *scorecard.values_violated.entry(value.to_string()).or_insert(0) += 1;
+            }
+            if !score.notes.is_empty() {
+                scorecard.notes.insert(score.reviewer.to_string(), score.notes.to_string());
+            }
+        }
+        scorecard.reviewers.sort();
+
+        scorecard.disagreement = scorecard.score.count > 1 && (scorecard.score.variance >= DISAGREEMENT_VARIANCE || (scorecard.emphatic_yes + scorecard.yes > 0 && scorecard.no > 0));
+
+        scorecard
+    }
+
+    /// Get the number of reviewers who raised the concern.
+    pub fn concern_count(&self, concern: ScoringConcern) -> i32 {
+        self.concerns.get(&concern.to_string()).cloned().unwrap_or_default()
+    }
+
+    /// Set the scoring fields on the applicant from the scorecard.
+    pub fn apply(&self, applicant: &mut Applicant) {
+        applicant.scorers_completed = self.reviewers.clone();
+        applicant.scoring_evaluations_count = self.evaluations;
+        applicant.scoring_enthusiastic_yes_count = self.emphatic_yes;
+        applicant.scoring_yes_count = self.yes;
+        applicant.scoring_pass_count = self.pass;
+        applicant.scoring_no_count = self.no;
+        applicant.scoring_not_applicable_count = self.not_applicable;
+        applicant.scoring_insufficient_experience_count = self.concern_count(ScoringConcern::InsufficientExperience);
+        applicant.scoring_inapplicable_experience_count = self.concern_count(ScoringConcern::InapplicableExperience);
+        applicant.scoring_job_function_yet_needed_count = self.concern_count(ScoringConcern::JobFunctionYetNeeded);
+        applicant.scoring_underwhelming_materials_count = self.concern_count(ScoringConcern::UnderwhelmingMaterials);
+        applicant.scoring_mean = self.score.mean;
+        applicant.scoring_variance = self.score.variance;
+        applicant.scoring_disagreement = self.disagreement;
+    }
+}
+
+/// Get the scores for an applicant.
+pub fn get_scores_for_applicant(db: &Database, applicant: &Applicant) -> Vec<ApplicantScore> {
+    applicant_scores::dsl::applicant_scores
+        .filter(applicant_scores::dsl::applicant_email.eq(applicant.email.to_string()))
+        .filter(applicant_scores::dsl::sheet_id.eq(applicant.sheet_id.to_string()))
+        .order_by(applicant_scores::dsl::id)
+        .load::<ApplicantScore>(&db.conn())
+        .unwrap()
+}
+
+/// Get the scorecards for every applicant who has been scored.
+pub fn get_applicant_scorecards(db: &Database) -> Vec<ApplicantScorecard> {
+    let mut scores: BTreeMap<(String, String), Vec<ApplicantScore>> = Default::default();
+    for score in ApplicantScores::get_from_db(db) {
+        scores.entry((score.applicant_email.to_string(), score.sheet_id.to_string())).or_insert_with(Vec::new).push(score);
+    }
+
+    let mut scorecards: Vec<ApplicantScorecard> = Default::default();
+    for applicant in Applicants::get_from_db(db) {
+        if let Some(s) = scores.get(&(applicant.email.to_string(), applicant.sheet_id.to_string())) {
+            scorecards.push(ApplicantScorecard::new(&applicant, s));
+        }
+    }
+
+    scorecards
+}
+
+/// Find the applicant a score is for.
+fn get_applicant_for_score(db: &Database, score: &NewApplicantScore) -> Result<Applicant, String> {
+    let mut sheet_id = score.sheet_id.to_string();
+    if sheet_id.is_empty() && !score.role.is_empty() {
+        sheet_id = match get_sheets_map().iter().find(|(role, _)| role.to_lowercase() == score.role.to_lowercase()) {
+            Some((_, id)) => id.to_string(),
+            None => return Err(format!("`{}` is not a role we are hiring for", score.role)),
+        };
+    }
+    if !sheet_id.is_empty() {
+        return Applicant::get_from_db(db, score.applicant_email.to_string(), sheet_id.to_string())
+            .ok_or_else(|| format!("could not find an applicant with email {} for sheet {}", score.applicant_email, sheet_id));
+    }
+
+    let candidates = applicants::dsl::applicants
+        .filter(applicants::dsl::email.eq(score.applicant_email.to_string()))
+        .load::<Applicant>(&db.conn())
+        .unwrap();
+    if candidates.len() < 2 {
+        return candidates.into_iter().next().ok_or_else(|| format!("could not find an applicant with email {}", score.applicant_email));
+    }
+
+    // If they applied for more than one role, go with the one the reviewer was assigned.
+    let assigned: Vec<Applicant> = candidates
+        .into_iter()
+        .filter(|a| {
+            ApplicantReviewAssignment::get_from_db(db, score.reviewer.to_string(), a.email.to_string(), a.sheet_id.to_string())
+                .map(|r| r.is_open())
+                .unwrap_or(false)
+        })
+        .collect();
+    if assigned.len() == 1 {
+        return Ok(assigned[0].clone());
+    }
+
+    Err(format!("{} applied for more than one role, the score needs to say which role it is for", score.applicant_email))
+}
+
+/// Update the scoring fields on the applicant from their scores.
+pub async fn refresh_applicant_scorecard(db: &Database, applicant: &mut Applicant) -> ApplicantScorecard {
+    let scores = get_scores_for_applicant(db, applicant);
+    let mut scorecard = ApplicantScorecard::new(applicant, &scores);
+
+    // Anyone with the status of "Onboarding" or "Hired" gets their scores zeroed out.
+    let status = Status::from_str(&applicant.status);
+    if status == Ok(Status::Onboarding) || status == Ok(Status::Hired) {
+        scorecard = ApplicantScorecard {
+            reviewers: scorecard.reviewers,
+            ..ApplicantScorecard::new(applicant, &[])
+        };
+    } else if scores.is_empty() {
+        // Applicants scored with the Google Forms, before the scores were kept in the
+        // database, keep those counts until someone scores them again.
+        return scorecard;
+    }
+
+    let mut updated = applicant.clone();
+    scorecard.apply(&mut updated);
+    if updated != *applicant {
+        *applicant = updated.update(db).await;
+    }
+
+    scorecard
+}
+
+/// Update the scoring fields on all the applicants from their scores.
+pub async fn refresh_applicant_scorecards(db: &Database) {
+    for mut applicant in Applicants::get_from_db(db) {
+        refresh_applicant_scorecard(db, &mut applicant).await;
+    }
+}
+
+/// Save a score from a reviewer and update the scorecard for the applicant.
+/// If the score came from a row in Airtable, `airtable_record_id` is the id of that row.
+/// A reviewer who scores the same applicant again replaces their old score.
+pub async fn submit_applicant_score(db: &Database, score: &NewApplicantScore, airtable_record_id: &str) -> Result<ApplicantScorecard, String> {
+    let mut score = score.clone();
+    score.normalize();
+    score.validate()?;
+
+    let mut applicant = get_applicant_for_score(db, &score)?;
+    score.applicant_name = applicant.name.to_string();
+    score.role = applicant.role.to_string();
+    score.sheet_id = applicant.sheet_id.to_string();
+    if score.submitted_time.is_none() {
+        score.submitted_time = Some(Utc::now());
+    }
+
+    let existing = ApplicantScore::get_from_db(db, score.reviewer.to_string(), score.applicant_email.to_string(), score.sheet_id.to_string());
+    let mut record = score.upsert_in_db(db);
+    if !airtable_record_id.is_empty() {
+        // Remove the row for the old score, so there is only one per reviewer in Airtable.
+        if let Some(existing) = existing {
+            if existing.airtable_record_id != airtable_record_id {
+                existing.delete_from_airtable().await;
+            }
+        }
+        record.airtable_record_id = airtable_record_id.to_string();
+    }
+    record.update(db).await;
+    println!("[applicant_scores] saved the score from {} for {} ({})", score.reviewer, score.applicant_email, score.role);
+
+    // Close out the reviewer's assignment.
+    if let Some(mut assignment) = ApplicantReviewAssignment::get_from_db(db, score.reviewer.to_string(), score.applicant_email.to_string(), score.sheet_id.to_string()) {
+        if assignment.is_open() {
+            assignment.status = ReviewAssignmentStatus::Completed.to_string();
+            assignment.completed_time = score.submitted_time;
+            assignment.update(db).await;
+        }
+    }
+
+    Ok(refresh_applicant_scorecard(db, &mut applicant).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::applicant_scores::{ApplicantScore, ApplicantScorecard, NewApplicantScore, ScoreSummary, ScoringConcern, ScoringRecommendation};
+
+    fn score(reviewer: &str, recommendation: &str, score: i32) -> ApplicantScore {
+        ApplicantScore {
+            id: 0,
+            reviewer: format!("{}@example.com", reviewer),
+            applicant_email: "applicant@example.org".to_string(),
+            applicant_name: "Applicant".to_string(),
+            role: "Engineering".to_string(),
+            sheet_id: "sheet".to_string(),
+            recommendation: recommendation.to_string(),
+            score,
+            technical_depth: 3,
+            writing: 4,
+            values_reflected: vec![],
+            values_violated: vec![],
+            concerns: vec![],
+            notes: "".to_string(),
+            submitted_time: None,
+            airtable_record_id: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_score_summary() {
+        assert_eq!(ScoreSummary::new(&[]), ScoreSummary::default());
+
+        let summary = ScoreSummary::new(&[5, 2]);
+        assert_eq!(summary.count, 2);
+        assert!((summary.mean - 3.5).abs() < f32::EPSILON);
+        assert!((summary.variance - 2.25).abs() < f32::EPSILON);
+        assert_eq!((summary.min, summary.max), (2, 5));
+    }
+
+    #[test]
+    fn test_normalize_and_validate_score() {
+        let mut s: NewApplicantScore = score("alice", "enthusiastic_yes", 5).into();
+        s.reviewer = " Alice@Example.com ".to_string();
+        s.values_reflected = vec!["Candor ".to_string(), "candor".to_string(), "N/A".to_string()];
+        s.concerns = vec!["job function yet needed".to_string()];
+        s.normalize();
+
+        assert_eq!(s.reviewer, "alice@example.com");
+        assert_eq!(s.recommendation, ScoringRecommendation::EmphaticYes.to_string());
+        assert_eq!(s.values_reflected, vec!["candor"]);
+        assert_eq!(s.concerns, vec![ScoringConcern::JobFunctionYetNeeded.to_string()]);
+        assert!(s.validate().is_ok());
+
+        let mut s: NewApplicantScore = score("bob", "maybe", 6).into();
+        s.writing = 0;
+        s.concerns = vec!["too tall".to_string()];
+        assert_eq!(
+            s.validate().unwrap_err(),
+            "`maybe` is not a valid recommendation; the score must be from 1 to 5, not 6; the writing must be from 1 to 5, not 0; `too tall` is not a valid concern"
+        );
+
+        assert_eq!(ScoringRecommendation::from_str("N/A"), Ok(ScoringRecommendation::NotApplicable));
+
+        // Reviewers who cannot judge the applicant do not have to score them.
+        let mut s: NewApplicantScore = score("carol", "Not applicable", 0).into();
+        s.technical_depth = 0;
+        s.writing = 0;
+        assert!(s.validate().is_ok());
+    }
+
+    #[test]
+    fn test_scorecard() {
+        let mut alice = score("alice", "Yes", 4);
+        alice.concerns = vec![ScoringConcern::InsufficientExperience.to_string()];
+        alice.values_reflected = vec!["candor".to_string(), "rigor".to_string()];
+        alice.notes = "Great writing samples.".to_string();
+        let mut bob = score("bob", "Pass", 3);
+        bob.values_reflected = vec!["rigor".to_string()];
+
+        let scorecard = ApplicantScorecard::from_scores(&[bob, alice]);
+        assert_eq!(scorecard.evaluations, 2);
+        assert_eq!(scorecard.reviewers, vec!["alice@example.com", "bob@example.com"]);
+        assert_eq!((scorecard.yes, scorecard.pass, scorecard.no), (1, 1, 0));
+        assert_eq!(scorecard.concern_count(ScoringConcern::InsufficientExperience), 1);
+        assert_eq!(scorecard.values_reflected.get("rigor"), Some(&2));
+        assert_eq!(scorecard.notes.get("alice@example.com").map(|n| n.as_str()), Some("Great writing samples."));
+        assert!(!scorecard.disagreement);
+
+        // A yes and a no is a disagreement, even if the scores are close.
+        assert!(ApplicantScorecard::from_scores(&[score("alice", "Yes", 3), score("bob", "No", 2)]).disagreement);
+        // So are scores that are far apart.
+        assert!(ApplicantScorecard::from_scores(&[score("alice", "Pass", 5), score("bob", "Pass", 2)]).disagreement);
+        // One reviewer cannot disagree with themselves.
+        assert!(!ApplicantScorecard::from_scores(&[score("alice", "No", 1)]).disagreement);
+
+        // Reviewers who cannot judge the applicant are counted, but not scored.
+        let scorecard = ApplicantScorecard::from_scores(&[score("alice", "Pass", 5), score("bob", "Not applicable", 0)]);
+        assert_eq!((scorecard.evaluations, scorecard.not_applicable), (2, 1));
+        assert_eq!(scorecard.score, ScoreSummary::new(&[5]));
+        assert_eq!(scorecard.writing.count, 1);
+        assert!(!scorecard.disagreement);
+    }
+}
@@ -1,9 +1,5 @@
 #![allow(clippy::from_over_into)]
 use std::collections::{BTreeMap, HashMap};
-use std::env;
-use std::fs;
-use std::io::{copy, stderr, stdout, Write};
-use std::process::Command;
 use std::str::FromStr;
 use std::thread;
 
@@ -15,12 +11,10 @@ use chrono::{DateTime, Duration};
 use chrono_humanize::HumanTime;
 use docusign::DocuSign;
 use google_drive::GoogleDrive;
-use html2text::from_read;
 use hubcaps::comments::CommentOptions;
 use hubcaps::issues::{Issue, IssueListOptions, IssueOptions, State};
 use hubcaps::Github;
 use macros::db;
-use pandoc::OutputKind;
 use regex::Regex;
 use schemars::JsonSchema;
 use sendgrid_api::SendGrid;
@@ -28,17 +22,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sheets::Sheets;
 use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
-use tar::Archive;
-use walkdir::WalkDir;
 
-use crate::airtable::{AIRTABLE_APPLICATIONS_TABLE, AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_REVIEWER_LEADERBOARD_TABLE};
-use crate::configs::{User, Users};
+use crate::airtable::{AIRTABLE_APPLICATIONS_TABLE, AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_REVIEWER_LEADERBOARD_TABLE, AIRTABLE_SCORES_TABLE};
+use crate::applicant_offers::{offer_failed_slack_msg, send_offer_envelope};
+use crate::applicant_retention::REDACTED;
+use crate::applicant_scores::{ApplicantScores, ScoringRecommendation};
+use crate::background_checks::{get_background_check_package, missing_background_check_package_slack_msg};
+use crate::configs::{get_configs_from_repo, User};
 use crate::core::UpdateAirtableRecord;
 use crate::db::Database;
+use crate::envelopes::{archive_envelope_documents, record_envelope, refresh_envelope_reminders, EnvelopeStatus};
+use crate::materials::extract_materials;
 use crate::models::get_value;
 use crate::schema::{applicant_reviewers, applicants, users};
 use crate::slack::{get_hiring_channel_post_url, post_to_channel};
-use crate::utils::{authenticate_github_jwt, check_if_github_issue_exists, get_gsuite_token, github_org, DOMAIN, GSUITE_DOMAIN};
+use crate::utils::{authenticate_github_jwt, check_if_github_issue_exists, get_column_letters, get_gsuite_token, github_org, DOMAIN, GSUITE_DOMAIN};
 
 // The line breaks that get parsed are weird thats why we have the random asterisks here.
 static QUESTION_TECHNICALLY_CHALLENGING: &str = r"W(?s:.*)at work(?s:.*)ave you found mos(?s:.*)challenging(?s:.*)caree(?s:.*)wh(?s:.*)\?";
@@ -51,6 +49,11 @@ static QUESTION_VALUES_IN_TENSION: &str =
     r"F(?s:.*)r a pair of Oxide(?s:.*)s values(?s:.*)describe a time in whic(?s:.*)the tw(?s:.*)values(?s:.*)tensio(?s:.*)for(?s:.*)your(?s:.*)and how yo(?s:.*)resolved it\.";
 static QUESTION_WHY_OXIDE: &str = r"W(?s:.*)y do you want to work for Oxide\?";
 
+/// The source for applicants submitted through the intake API.
+pub static APPLICANT_SOURCE_API: &str = "api";
+/// The source for applicants imported from a Google Form response sheet.
+pub static APPLICANT_SOURCE_SHEETS: &str = "sheets";
+
 /// The data type for a NewApplicant.
 #[db {
     new_struct_name = "Applicant",
@@ -153,14 +156,8 @@ pub struct NewApplicant {
         deserialize_with = "airtable_api::user_format_as_array_of_strings::deserialize"
     )]
     pub scorers_completed: Vec<String>,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub scoring_form_id: String,
-    /// The form for scoring/evaluating applicants.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub scoring_form_url: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub scoring_form_responses_url: String,
-    /// The number of form responses for the applicant.
+    /// The number of scores for the applicant.
+    /// These are aggregated from the applicant scores, see `crate::applicant_scores`.
     #[serde(default)]
     pub scoring_evaluations_count: i32,
     #[serde(default)]
@@ -181,6 +178,15 @@ pub struct NewApplicant {
     pub scoring_job_function_yet_needed_count: i32,
     #[serde(default)]
     pub scoring_underwhelming_materials_count: i32,
+    /// The mean of the overall scores.
+    #[serde(default)]
+    pub scoring_mean: f32,
+    /// The variance of the overall scores.
+    #[serde(default)]
+    pub scoring_variance: f32,
+    /// If the reviewers disagree on the applicant.
+    #[serde(default)]
+    pub scoring_disagreement: bool,
 
     #[serde(default)]
     pub request_background_check: bool,
@@ -191,6 +197,12 @@ pub struct NewApplicant {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_date: Option<NaiveDate>,
+    /// The type of employment we are offering, ie. `full-time` or `contractor`.
+    /// This picks the offer letter template in DocuSign.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub offer_employment_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub offer_salary: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub interested_in: Vec<String>,
 
@@ -203,6 +215,15 @@ pub struct NewApplicant {
     pub docusign_envelope_id: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub docusign_envelope_status: String,
+
+    /// Where the application came from, either the intake API or an imported
+    /// Google Form response sheet.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
+    /// The hash of the token we emailed the applicant when they submitted their
+    /// application through the intake API. They need the token to update it.
+    #[serde(default, skip_serializing)]
+    pub intake_token_hash: String,
 }
 
 impl NewApplicant {
@@ -223,14 +244,9 @@ impl NewApplicant {
         }
 
         NewApplicant {
-            submitted_time: NewApplicant::parse_timestamp(&get_value(values, "Timestamp")),
-            role: get_role_from_sheet_id(sheet_id),
-            sheet_id: sheet_id.to_string(),
             name: get_value(values, "Name"),
-            email: get_value(values, "Email Address"),
             location: get_value(values, "Location (City, State or Region)"),
             phone: get_value(values, "Phone Number"),
-            country_code: Default::default(),
             github,
             gitlab,
             linkedin: get_value(values, "LinkedIn profile URL"),
@@ -238,8 +254,39 @@ impl NewApplicant {
             website: get_value(values, "Website"),
             resume: get_value(values, "Submit your resume (or PDF export of LinkedIn profile)"),
             materials: get_value(values, "Submit your Oxide candidate materials"),
-            status: crate::applicant_status::Status::NeedsToBeTriaged.to_string(),
             raw_status: get_value(values, "Status"),
+            interested_in,
+            ..NewApplicant::new(
+                &get_role_from_sheet_id(sheet_id),
+                sheet_id,
+                &get_value(values, "Email Address"),
+                NewApplicant::parse_timestamp(&get_value(values, "Timestamp")),
+                APPLICANT_SOURCE_SHEETS,
+            )
+        }
+    }
+
+    /// Create a new applicant that still needs to be triaged, with nothing
+    /// but the bookkeeping fields filled in.
+    pub fn new(role: &str, sheet_id: &str, email: &str, submitted_time: DateTime<Utc>, source: &str) -> Self {
+        NewApplicant {
+            name: Default::default(),
+            role: role.to_string(),
+            sheet_id: sheet_id.to_string(),
+            status: crate::applicant_status::Status::NeedsToBeTriaged.to_string(),
+            raw_status: Default::default(),
+            submitted_time,
+            email: email.to_string(),
+            phone: Default::default(),
+            country_code: Default::default(),
+            location: Default::default(),
+            github: Default::default(),
+            gitlab: Default::default(),
+            linkedin: Default::default(),
+            portfolio: Default::default(),
+            website: Default::default(),
+            resume: Default::default(),
+            materials: Default::default(),
             sent_email_received: false,
             sent_email_follow_up: false,
             value_reflected: Default::default(),
@@ -264,9 +311,6 @@ impl NewApplicant {
             interviews: Default::default(),
             scorers: Default::default(),
             scorers_completed: Default::default(),
-            scoring_form_id: Default::default(),
-            scoring_form_url: Default::default(),
-            scoring_form_responses_url: Default::default(),
             scoring_evaluations_count: Default::default(),
             scoring_enthusiastic_yes_count: Default::default(),
             scoring_yes_count: Default::default(),
@@ -277,22 +321,43 @@ impl NewApplicant {
             scoring_inapplicable_experience_count: Default::default(),
             scoring_job_function_yet_needed_count: Default::default(),
             scoring_underwhelming_materials_count: Default::default(),
+            scoring_mean: Default::default(),
+            scoring_variance: Default::default(),
+            scoring_disagreement: Default::default(),
             request_background_check: Default::default(),
             criminal_background_check_status: Default::default(),
             motor_vehicle_background_check_status: Default::default(),
             start_date: None,
-            interested_in,
+            offer_employment_type: Default::default(),
+            offer_salary: Default::default(),
+            interested_in: Default::default(),
             geocode_cache: Default::default(),
             docusign_envelope_id: Default::default(),
             docusign_envelope_status: Default::default(),
+            source: source.to_string(),
+            intake_token_hash: Default::default(),
         }
     }
 
     /// Send an email to the applicant that we recieved their application.
-    pub async fn send_email_recieved_application_to_applicant(&self) {
+    /// The `update_token` lets them update an application submitted through the
+    /// intake API, it is empty for applications from the sheets.
+    pub async fn send_email_recieved_application_to_applicant(&self, update_token: &str) {
         // Initialize the SendGrid client.
         let sendgrid_client = SendGrid::new_from_env();
 
+        let update = if update_token.is_empty() {
+            "".to_string()
+        } else {
+            format!(
+                "
+
+If you need to change anything in your application, submit it again with
+this token: {}",
+                update_token
+            )
+        };
+
         // Send the message.
         sendgrid_client
             .send_mail(
@@ -302,11 +367,11 @@ impl NewApplicant {
 
 Thank you for submitting your application materials! We really appreciate all
 the time and thought everyone puts into their application. We will be in touch
-within the next couple weeks with more information.
+within the next couple weeks with more information.{}
 
 Sincerely,
   The Oxide Team",
-                    self.name
+                    self.name, update
                 ),
                 vec![self.email.to_string()],
                 vec![format!("careers@{}", DOMAIN)],
@@ -562,9 +627,6 @@ The Oxide Team",
 
         let mut scorers: Vec<String> = Default::default();
         let mut scorers_completed: Vec<String> = Default::default();
-        let mut scoring_form_id = "".to_string();
-        let mut scoring_form_url = "".to_string();
-        let mut scoring_form_responses_url = "".to_string();
 
         // Set the defaults.
         let mut scoring_evaluations_count = 0;
@@ -577,11 +639,17 @@ The Oxide Team",
         let mut scoring_inapplicable_experience_count = 0;
         let mut scoring_job_function_yet_needed_count = 0;
         let mut scoring_underwhelming_materials_count = 0;
+        let mut scoring_mean = 0.0;
+        let mut scoring_variance = 0.0;
+        let mut scoring_disagreement = false;
 
         let mut request_background_check = false;
         let mut criminal_background_check_status = "".to_string();
         let mut motor_vehicle_background_check_status = "".to_string();
 
+        let mut offer_employment_type = "".to_string();
+        let mut offer_salary = "".to_string();
+
         let mut docusign_envelope_id = "".to_string();
         let mut docusign_envelope_status = "".to_string();
 
@@ -595,13 +663,19 @@ The Oxide Team",
             .filter(applicants::dsl::sheet_id.eq(sheet_id.to_string()))
             .first::<Applicant>(&db.conn())
         {
+            // The offer is filled out in airtable, so keep what we have if we can't
+            // get the record.
+            offer_employment_type = a.offer_employment_type.to_string();
+            offer_salary = a.offer_salary.to_string();
+
             // Try to get from airtable.
             // This ensures if we had any one offs added in airtable that they stay intact.
             if let Some(record) = a.get_existing_airtable_record().await {
                 scorers = record.fields.scorers;
-                scorers_completed = a.scorers_completed;
                 request_background_check = record.fields.request_background_check;
                 interviews = record.fields.interviews;
+                offer_employment_type = record.fields.offer_employment_type;
+                offer_salary = record.fields.offer_salary;
             }
 
             // If the database has them as "Onboarding" and we have them as "Giving offer",
@@ -654,23 +728,22 @@ The Oxide Team",
                 values_in_tension = a.values_in_tension.clone();
                 values_in_tension.sort();
             }
-            // Set the scorers data so we don't keep adding new scorers.
-            if !a.scorers.is_empty() {
-                scoring_form_id = a.scoring_form_id.to_string();
-                scoring_form_url = a.scoring_form_url.to_string();
-                scoring_form_responses_url = a.scoring_form_responses_url.to_string();
-
-                scoring_evaluations_count = a.scoring_evaluations_count;
-                scoring_enthusiastic_yes_count = a.scoring_enthusiastic_yes_count;
-                scoring_yes_count = a.scoring_yes_count;
-                scoring_pass_count = a.scoring_pass_count;
-                scoring_no_count = a.scoring_no_count;
-                scoring_not_applicable_count = a.scoring_not_applicable_count;
-                scoring_insufficient_experience_count = a.scoring_insufficient_experience_count;
-                scoring_inapplicable_experience_count = a.scoring_inapplicable_experience_count;
-                scoring_job_function_yet_needed_count = a.scoring_job_function_yet_needed_count;
-                scoring_underwhelming_materials_count = a.scoring_underwhelming_materials_count;
-            }
+            // Keep the scorecard, it is aggregated from the applicant scores and
+            // not from the sheet.
+            scorers_completed = a.scorers_completed;
+            scoring_evaluations_count = a.scoring_evaluations_count;
+            scoring_enthusiastic_yes_count = a.scoring_enthusiastic_yes_count;
+            scoring_yes_count = a.scoring_yes_count;
+            scoring_pass_count = a.scoring_pass_count;
+            scoring_no_count = a.scoring_no_count;
+            scoring_not_applicable_count = a.scoring_not_applicable_count;
+            scoring_insufficient_experience_count = a.scoring_insufficient_experience_count;
+            scoring_inapplicable_experience_count = a.scoring_inapplicable_experience_count;
+            scoring_job_function_yet_needed_count = a.scoring_job_function_yet_needed_count;
+            scoring_underwhelming_materials_count = a.scoring_underwhelming_materials_count;
+            scoring_mean = a.scoring_mean;
+            scoring_variance = a.scoring_variance;
+            scoring_disagreement = a.scoring_disagreement;
             if !a.criminal_background_check_status.is_empty() {
                 criminal_background_check_status = a.criminal_background_check_status.to_string();
             }
@@ -739,9 +812,6 @@ The Oxide Team",
             interviews,
             scorers,
             scorers_completed,
-            scoring_form_id,
-            scoring_form_url,
-            scoring_form_responses_url,
             scoring_evaluations_count,
             scoring_enthusiastic_yes_count,
             scoring_yes_count,
@@ -752,14 +822,21 @@ The Oxide Team",
             scoring_inapplicable_experience_count,
             scoring_job_function_yet_needed_count,
             scoring_underwhelming_materials_count,
+            scoring_mean,
+            scoring_variance,
+            scoring_disagreement,
             request_background_check,
             criminal_background_check_status,
             motor_vehicle_background_check_status,
             start_date,
+            offer_employment_type,
+            offer_salary,
             interested_in,
             geocode_cache: Default::default(),
             docusign_envelope_id,
             docusign_envelope_status,
+            source: APPLICANT_SOURCE_SHEETS.to_string(),
+            intake_token_hash: Default::default(),
         }
     }
 
@@ -769,7 +846,8 @@ The Oxide Team",
         DateTime::parse_from_str(&time_str, "%m/%d/%Y %H:%M:%S  %:z").unwrap().with_timezone(&Utc)
     }
 
-    fn parse_github_gitlab(s: &str) -> (String, String) {
+    /// Parse the GitHub and GitLab handles out of what was given as the GitHub profile.
+    pub fn parse_github_gitlab(s: &str) -> (String, String) {
         let mut github = "".to_string();
         let mut gitlab = "".to_string();
         if !s.trim().is_empty() {
@@ -808,11 +886,10 @@ The Oxide Team",
         // Check if we have sent them an email that we received their application.
         if !self.sent_email_received {
             // Send them an email.
-            self.send_email_recieved_application_to_applicant().await;
+            self.send_email_recieved_application_to_applicant("").await;
 
             // Mark the column as true not false.
-            let mut colmn = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars();
-            let rng = format!("{}{}", colmn.nth(sent_email_received_column_index).unwrap().to_string(), row_index);
+            let rng = format!("{}{}", get_column_letters(sent_email_received_column_index), row_index);
 
             sheets_client.update_values(&self.sheet_id, &rng, "TRUE".to_string()).await.unwrap();
 
@@ -822,8 +899,7 @@ The Oxide Team",
         // Send an email follow up if we should.
         if !self.sent_email_follow_up {
             // Get the right cell to eventually change in the google sheet.
-            let mut colmn = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars();
-            let rng = format!("{}{}", colmn.nth(sent_email_follow_up_index).unwrap().to_string(), row_index);
+            let rng = format!("{}{}", get_column_letters(sent_email_follow_up_index), row_index);
 
             let status = crate::applicant_status::Status::from_str(&self.status).unwrap_or_default();
             if status == crate::applicant_status::Status::Declined || status == crate::applicant_status::Status::Deferred {
@@ -862,7 +938,20 @@ The Oxide Team",
             }
         }
 
-        // Cleanup and parse the phone number and country code.
+        self.cleanup_phone();
+
+        // Get the time seven days ago.
+        let duration_from_now = Utc::now().signed_duration_since(self.submitted_time);
+
+        // If the application is as new as the last week then parse all the contents.
+        // This takes a long time so we skip all the others.
+        if duration_from_now < Duration::days(5) && self.status != crate::applicant_status::Status::Declined.to_string() {
+            self.parse_materials(drive_client).await;
+        }
+    }
+
+    /// Cleanup and parse the phone number and country code.
+    pub fn cleanup_phone(&mut self) {
         let mut phone = self.phone.replace(" ", "").replace("-", "").replace("+", "").replace("(", "").replace(")", "");
 
         let location = self.location.to_string();
@@ -941,110 +1030,142 @@ The Oxide Team",
         }
         self.phone = phone;
         self.country_code = country_code;
+    }
 
-        // Get the time seven days ago.
-        let duration_from_now = Utc::now().signed_duration_since(self.submitted_time);
-
-        // If the application is as new as the last week then parse all the contents.
-        // This takes a long time so we skip all the others.
-        if duration_from_now < Duration::days(5) && self.status != crate::applicant_status::Status::Declined.to_string() {
-            // Read the file contents.
+    /// Read the resume and materials from Drive and parse the samples and
+    /// questions out of the materials. Answers we cannot find in the materials
+    /// are left as they are.
+    pub async fn parse_materials(&mut self, drive_client: &GoogleDrive) {
+        // Read the file contents.
+        if !self.resume.is_empty() {
             self.resume_contents = get_file_contents(drive_client, &self.resume).await;
+        }
+        if !self.materials.is_empty() {
             self.materials_contents = get_file_contents(drive_client, &self.materials).await;
+        }
 
-            // Parse the samples and materials.
-            let materials_contents = self.materials_contents.clone();
-            let mut work_samples = parse_question(r"Work sample\(s\)", "Writing samples", &materials_contents);
+        // Parse the samples and materials.
+        let materials_contents = self.materials_contents.clone();
+        let mut work_samples = parse_question(r"Work sample\(s\)", "Writing samples", &materials_contents);
+        if work_samples.is_empty() {
+            work_samples = parse_question(
+                r"If(?s:.*)his work is entirely proprietary(?s:.*)please describe it as fully as y(?s:.*)can, providing necessary context\.",
+                "Writing samples",
+                &materials_contents,
+            );
             if work_samples.is_empty() {
-                work_samples = parse_question(
-                    r"If(?s:.*)his work is entirely proprietary(?s:.*)please describe it as fully as y(?s:.*)can, providing necessary context\.",
-                    "Writing samples",
-                    &materials_contents,
-                );
+                // Try to parse work samples for TPM role.
+                work_samples = parse_question(r"What would you have done differently\?", "Exploratory samples", &materials_contents);
+
                 if work_samples.is_empty() {
-                    // Try to parse work samples for TPM role.
-                    work_samples = parse_question(r"What would you have done differently\?", "Exploratory samples", &materials_contents);
+                    work_samples = parse_question(r"Some questions(?s:.*)o have in mind as you describe them:", "Exploratory samples", &materials_contents);
 
                     if work_samples.is_empty() {
-                        work_samples = parse_question(r"Some questions(?s:.*)o have in mind as you describe them:", "Exploratory samples", &materials_contents);
+                        work_samples = parse_question(r"Work samples", "Exploratory samples", &materials_contents);
 
                         if work_samples.is_empty() {
-                            work_samples = parse_question(r"Work samples", "Exploratory samples", &materials_contents);
-
-                            if work_samples.is_empty() {
-                                work_samples = parse_question(r"design sample\(s\)", "Questionnaire", &materials_contents);
-                            }
+                            work_samples = parse_question(r"design sample\(s\)", "Questionnaire", &materials_contents);
                         }
                     }
                 }
             }
-            self.work_samples = work_samples;
+        }
+        set_if_not_empty(&mut self.work_samples, work_samples);
 
-            let mut writing_samples = parse_question(r"Writing sample\(s\)", "Analysis samples", &materials_contents);
+        let mut writing_samples = parse_question(r"Writing sample\(s\)", "Analysis samples", &materials_contents);
+        if writing_samples.is_empty() {
+            writing_samples = parse_question(
+                r"Please submit at least one writing sample \(and no more tha(?s:.*)three\) that you feel represent(?s:.*)you(?s:.*)providin(?s:.*)links if(?s:.*)necessary\.",
+                "Analysis samples",
+                &materials_contents,
+            );
             if writing_samples.is_empty() {
-                writing_samples = parse_question(
-                    r"Please submit at least one writing sample \(and no more tha(?s:.*)three\) that you feel represent(?s:.*)you(?s:.*)providin(?s:.*)links if(?s:.*)necessary\.",
-                    "Analysis samples",
-                    &materials_contents,
-                );
-                if writing_samples.is_empty() {
-                    writing_samples = parse_question(r"Writing samples", "Analysis samples", &materials_contents);
+                writing_samples = parse_question(r"Writing samples", "Analysis samples", &materials_contents);
 
-                    if writing_samples.is_empty() {
-                        writing_samples = parse_question(r"Writing sample\(s\)", "Code and/or design sample", &materials_contents);
-                    }
+                if writing_samples.is_empty() {
+                    writing_samples = parse_question(r"Writing sample\(s\)", "Code and/or design sample", &materials_contents);
                 }
             }
-            self.writing_samples = writing_samples;
+        }
+        set_if_not_empty(&mut self.writing_samples, writing_samples);
 
-            let mut analysis_samples = parse_question(r"Analysis sample\(s\)$", "Presentation samples", &materials_contents);
+        let mut analysis_samples = parse_question(r"Analysis sample\(s\)$", "Presentation samples", &materials_contents);
+        if analysis_samples.is_empty() {
+            analysis_samples = parse_question(
+                r"please recount a(?s:.*)incident(?s:.*)which you analyzed syste(?s:.*)misbehavior(?s:.*)including as much technical detail as you can recall\.",
+                "Presentation samples",
+                &materials_contents,
+            );
             if analysis_samples.is_empty() {
-                analysis_samples = parse_question(
-                    r"please recount a(?s:.*)incident(?s:.*)which you analyzed syste(?s:.*)misbehavior(?s:.*)including as much technical detail as you can recall\.",
-                    "Presentation samples",
-                    &materials_contents,
-                );
-                if analysis_samples.is_empty() {
-                    analysis_samples = parse_question(r"Analysis samples", "Presentation samples", &materials_contents);
-                }
+                analysis_samples = parse_question(r"Analysis samples", "Presentation samples", &materials_contents);
             }
-            self.analysis_samples = analysis_samples;
+        }
+        set_if_not_empty(&mut self.analysis_samples, analysis_samples);
 
-            let mut presentation_samples = parse_question(r"Presentation sample\(s\)", "Questionnaire", &materials_contents);
+        let mut presentation_samples = parse_question(r"Presentation sample\(s\)", "Questionnaire", &materials_contents);
+        if presentation_samples.is_empty() {
+            presentation_samples = parse_question(
+                r"I(?s:.*)you don’t have a publicl(?s:.*)available presentation(?s:.*)pleas(?s:.*)describe a topic on which you have presented in th(?s:.*)past\.",
+                "Questionnaire",
+                &materials_contents,
+            );
             if presentation_samples.is_empty() {
-                presentation_samples = parse_question(
-                    r"I(?s:.*)you don’t have a publicl(?s:.*)available presentation(?s:.*)pleas(?s:.*)describe a topic on which you have presented in th(?s:.*)past\.",
-                    "Questionnaire",
-                    &materials_contents,
-                );
-                if presentation_samples.is_empty() {
-                    presentation_samples = parse_question(r"Presentation samples", "Questionnaire", &materials_contents);
-                }
+                presentation_samples = parse_question(r"Presentation samples", "Questionnaire", &materials_contents);
             }
-            self.presentation_samples = presentation_samples;
+        }
+        set_if_not_empty(&mut self.presentation_samples, presentation_samples);
 
-            let mut exploratory_samples = parse_question(r"Exploratory sample\(s\)", "Questionnaire", &materials_contents);
+        let mut exploratory_samples = parse_question(r"Exploratory sample\(s\)", "Questionnaire", &materials_contents);
+        if exploratory_samples.is_empty() {
+            exploratory_samples = parse_question(
+                r"What’s an example o(?s:.*)something that you needed to explore, reverse engineer, decipher or otherwise figure out a(?s:.*)part of a program or project and how did you do it\? Please provide as much detail as you ca(?s:.*)recall\.",
+                "Questionnaire",
+                &materials_contents,
+            );
             if exploratory_samples.is_empty() {
-                exploratory_samples = parse_question(
-                    r"What’s an example o(?s:.*)something that you needed to explore, reverse engineer, decipher or otherwise figure out a(?s:.*)part of a program or project and how did you do it\? Please provide as much detail as you ca(?s:.*)recall\.",
-                    "Questionnaire",
-                    &materials_contents,
-                );
-                if exploratory_samples.is_empty() {
-                    exploratory_samples = parse_question(r"Exploratory samples", "Questionnaire", &materials_contents);
-                }
+                exploratory_samples = parse_question(r"Exploratory samples", "Questionnaire", &materials_contents);
             }
-            self.exploratory_samples = exploratory_samples;
+        }
+        set_if_not_empty(&mut self.exploratory_samples, exploratory_samples);
 
-            self.question_technically_challenging = parse_question(QUESTION_TECHNICALLY_CHALLENGING, QUESTION_WORK_PROUD_OF, &materials_contents);
-            self.question_proud_of = parse_question(QUESTION_WORK_PROUD_OF, QUESTION_HAPPIEST_CAREER, &materials_contents);
-            self.question_happiest = parse_question(QUESTION_HAPPIEST_CAREER, QUESTION_UNHAPPIEST_CAREER, &materials_contents);
-            self.question_unhappiest = parse_question(QUESTION_UNHAPPIEST_CAREER, QUESTION_VALUE_REFLECTED, &materials_contents);
-            self.question_value_reflected = parse_question(QUESTION_VALUE_REFLECTED, QUESTION_VALUE_VIOLATED, &materials_contents);
-            self.question_value_violated = parse_question(QUESTION_VALUE_VIOLATED, QUESTION_VALUES_IN_TENSION, &materials_contents);
-            self.question_values_in_tension = parse_question(QUESTION_VALUES_IN_TENSION, QUESTION_WHY_OXIDE, &materials_contents);
-            self.question_why_oxide = parse_question(QUESTION_WHY_OXIDE, "", &materials_contents);
+        set_if_not_empty(
+            &mut self.question_technically_challenging,
+            parse_question(QUESTION_TECHNICALLY_CHALLENGING, QUESTION_WORK_PROUD_OF, &materials_contents),
+        );
+        set_if_not_empty(&mut self.question_proud_of, parse_question(QUESTION_WORK_PROUD_OF, QUESTION_HAPPIEST_CAREER, &materials_contents));
+        set_if_not_empty(&mut self.question_happiest, parse_question(QUESTION_HAPPIEST_CAREER, QUESTION_UNHAPPIEST_CAREER, &materials_contents));
+        set_if_not_empty(&mut self.question_unhappiest, parse_question(QUESTION_UNHAPPIEST_CAREER, QUESTION_VALUE_REFLECTED, &materials_contents));
+        set_if_not_empty(
+            &mut self.question_value_reflected,
+            parse_question(QUESTION_VALUE_REFLECTED, QUESTION_VALUE_VIOLATED, &materials_contents),
+        );
+        set_if_not_empty(
+            &mut self.question_value_violated,
+            parse_question(QUESTION_VALUE_VIOLATED, QUESTION_VALUES_IN_TENSION, &materials_contents),
+        );
+        set_if_not_empty(
+            &mut self.question_values_in_tension,
+            parse_question(QUESTION_VALUES_IN_TENSION, QUESTION_WHY_OXIDE, &materials_contents),
+        );
+        set_if_not_empty(&mut self.question_why_oxide, parse_question(QUESTION_WHY_OXIDE, "", &materials_contents));
+    }
+
+    /// Summarize the scorecard for the applicant, for the status line of a Slack message.
+    /// This is empty if nobody has scored the applicant yet.
+    pub fn scoring_msg(&self) -> String {
+        if self.scoring_evaluations_count == 0 {
+            return "".to_string();
         }
+
+        let mut msg = format!(
+            " | score: *{:.1}* from {} ({} emphatic yes, {} yes, {} pass, {} no)",
+            self.scoring_mean, self.scoring_evaluations_count, self.scoring_enthusiastic_yes_count, self.scoring_yes_count, self.scoring_pass_count, self.scoring_no_count
+        );
+        if self.scoring_disagreement {
+            msg += &format!(" :warning: *reviewers disagree* (variance {:.1})", self.scoring_variance);
+        }
+
+        msg
     }
 
     /// Get the human duration of time since the application was submitted.
@@ -1065,6 +1186,7 @@ The Oxide Team",
         if !self.status.is_empty() {
             status_msg += &format!(" | status: *{}*", self.status);
         }
+        status_msg += &self.scoring_msg();
 
         let mut values_msg = "".to_string();
         if !self.value_reflected.is_empty() {
@@ -1227,38 +1349,48 @@ impl Applicant {
         HumanTime::from(dur)
     }
 
-    /// Send an invite to the applicant to do a background check.
+    /// Send an invite to the applicant to do a background check, with the
+    /// package for their role and country.
     pub async fn send_background_check_invitation(&mut self, db: &Database) {
-        // Initialize the Checker client.
-        let checkr = Checkr::new_from_env();
-
-        // Check if we already sent them an invitation.
-        let candidates = checkr.list_candidates().await.unwrap();
-        for candidate in candidates {
-            if candidate.email == self.email {
-                // Check if we already have sent their invitation.
-                if self.criminal_background_check_status.is_empty() {
-                    // Create an invitation for the candidate.
-                    checkr.create_invitation(&candidate.id, "premium_criminal").await.unwrap();
-
-                    // Update the database.
-                    self.request_background_check = true;
-                    self.criminal_background_check_status = "requested".to_string();
-
-                    self.update(db).await;
+        // Check if we already have sent their invitation.
+        if !self.criminal_background_check_status.is_empty() {
+            return;
+        }
 
-                    println!("[applicant] sent background check invitation to: {}", self.email);
-                }
-                // We can return early they already exist as a candidate.
+        let github = authenticate_github_jwt();
+        let packages = get_configs_from_repo(&github).await.background_check_packages;
+        let package = match get_background_check_package(&packages, &self.role, &self.country_code) {
+            Some(package) => package,
+            None => {
+                println!(
+                    "[applicant] no background check package for role {} in country `{}`, not sending an invitation to: {}",
+                    self.role, self.country_code, self.email
+                );
+                // Let the hiring channel know, someone needs to sort out their background check.
+                post_to_channel(get_hiring_channel_post_url(), missing_background_check_package_slack_msg(self)).await;
                 return;
             }
-        }
+        };
 
-        // Create a new candidate for the applicant in checkr.
-        let candidate = checkr.create_candidate(&self.email).await.unwrap();
+        // Initialize the Checker client.
+        let checkr = Checkr::new_from_env();
+
+        // Check if they are already a candidate, otherwise create a new candidate
+        // for the applicant in checkr.
+        let candidate = match checkr.get_candidate_by_email(&self.email).await.unwrap() {
+            Some(candidate) => candidate,
+            None => checkr.create_candidate(&self.email).await.unwrap(),
+        };
 
         // Create an invitation for the candidate.
-        checkr.create_invitation(&candidate.id, "premium_criminal").await.unwrap();
+        let mut work_locations: Vec<checkr::WorkLocation> = Default::default();
+        if !self.country_code.is_empty() {
+            work_locations.push(checkr::WorkLocation {
+                country: self.country_code.to_uppercase(),
+                ..Default::default()
+            });
+        }
+        checkr.create_invitation(&candidate.id, &package, work_locations).await.unwrap();
 
         // Update the database.
         self.request_background_check = true;
@@ -1266,7 +1398,7 @@ impl Applicant {
 
         self.update(db).await;
 
-        println!("[applicant] sent background check invitation to: {}", self.email);
+        println!("[applicant] sent background check invitation with package {} to: {}", package, self.email);
     }
 
     /// Convert the applicant into JSON for a Slack message.
@@ -1277,6 +1409,7 @@ impl Applicant {
         if !self.status.is_empty() {
             status_msg += &format!(" | status: *{}*", self.status);
         }
+        status_msg += &NewApplicant::from(self.clone()).scoring_msg();
 
         let mut values_msg = "".to_string();
         if !self.value_reflected.is_empty() {
@@ -1432,15 +1565,17 @@ Email: {}",
         msg += &format!(
             "\nResume: {}
 Oxide Candidate Materials: {}
-Scoring form: {}
-Scoring form responses: {}
+
+## Scoring
+
+Score the applicant by adding a row to the \"{}\" table in the applicants Airtable.
 
 ## Reminder
 
 The applicants Airtable is at: https://airtable-applicants.corp.oxide.computer
 
 ",
-            self.resume, self.materials, self.scoring_form_url, self.scoring_form_responses_url,
+            self.resume, self.materials, AIRTABLE_SCORES_TABLE,
         );
 
         msg
@@ -1738,7 +1873,28 @@ impl ApplicantSheetColumns {
     }
 }
 
-/// Get the contexts of a file in Google Drive by it's URL as a text string.
+/// Set the field to the value, unless the value is empty.
+fn set_if_not_empty(field: &mut String, value: String) {
+    if !value.is_empty() {
+        *field = value;
+    }
+}
+
+/// Get the ID of a file in Google Drive from its URL.
+pub fn get_drive_file_id(url: &str) -> Option<String> {
+    let url = url.trim();
+    if !url.starts_with("https://drive.google.com/") {
+        return None;
+    }
+
+    Some(
+        url.replace("https://drive.google.com/open?id=", "")
+            .replace("https://drive.google.com/file/d/", "")
+            .replace("/view", ""),
+    )
+}
+
+/// Get the contents of a file in Google Drive by it's URL as a text string.
 pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> String {
     let id = url
         .replace("https://drive.google.com/open?id=", "")
@@ -1750,183 +1906,22 @@ pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> String
     let mime_type = drive_file.mime_type;
     let name = drive_file.name;
 
-    let mut path = env::temp_dir();
-    let mut output = env::temp_dir();
-
-    let mut result: String = Default::default();
-
-    if mime_type == "application/pdf" {
-        // Get the PDF contents from Drive.
-        let contents = drive_client.download_file_by_id(&id).await.unwrap();
-
-        path.push(format!("{}.pdf", id));
-
-        let mut file = fs::File::create(&path).unwrap();
-        file.write_all(&contents).unwrap();
-
-        result = read_pdf(&name, path.clone());
-    } else if mime_type == "text/html" {
-        let contents = drive_client.download_file_by_id(&id).await.unwrap();
-
-        // Wrap lines at 80 characters.
-        result = from_read(&contents[..], 80);
-    } else if mime_type == "application/vnd.google-apps.document" {
-        result = drive_client.get_file_contents_by_id(&id).await.unwrap();
-    } else if name.ends_with(".tar") {
-        // Get the ip contents from Drive.
-        let contents = drive_client.download_file_by_id(&id).await.unwrap();
-
-        path.push(format!("{}.tar", id));
-
-        let mut file = fs::File::create(&path).unwrap();
-        file.write_all(&contents).unwrap();
-
-        // Unpack the tarball.
-        let mut tar = Archive::new(fs::File::open(&path).unwrap());
-        output.push(id);
-        println!("unpacking tarball: {:?} -> {:?}", path, output);
-        tar.unpack(&output).unwrap();
-
-        // Walk the output directory trying to find our file.
-        for entry in WalkDir::new(&output).min_depth(1) {
-            let entry = entry.unwrap();
-            let path = entry.path();
-            if is_materials(path.file_name().unwrap().to_str().unwrap()) {
-                // Concatenate all the zip files into our result.
-                result += &format!(
-                    "====================== tarball file: {} ======================\n\n",
-                    path.to_str().unwrap().replace(env::temp_dir().as_path().to_str().unwrap(), "")
-                );
-                if path.extension().unwrap() == "pdf" {
-                    result += &read_pdf(&name, path.to_path_buf());
-                } else {
-                    result += &fs::read_to_string(&path).unwrap();
-                }
-                result += "\n\n\n";
-            }
-        }
-    } else if name.ends_with(".zip") {
-        // This is patrick :)
-        // Get the ip contents from Drive.
-        let contents = drive_client.download_file_by_id(&id).await.unwrap();
-
-        path.push(format!("{}.zip", id));
-
-        let mut file = fs::File::create(&path).unwrap();
-        file.write_all(&contents).unwrap();
-        file = fs::File::open(&path).unwrap();
-
-        // Unzip the file.
-        let mut archive = zip::ZipArchive::new(file).unwrap();
-        for i in 0..archive.len() {
-            match archive.by_index(i) {
-                Ok(mut file) => {
-                    output = env::temp_dir();
-                    output.push("zip/");
-                    output.push(file.name());
-
-                    let comment = file.comment();
-                    if !comment.is_empty() {
-                        println!("[applicants] zip file {} comment: {}", i, comment);
-                    }
-
-                    if (&*file.name()).ends_with('/') {
-                        println!("[applicants] zip file {} extracted to \"{}\"", i, output.as_path().display());
-                        fs::create_dir_all(&output).unwrap();
-                    } else {
-                        println!("[applicants] zip file {} extracted to \"{}\" ({} bytes)", i, output.as_path().display(), file.size());
-
-                        if let Some(p) = output.parent() {
-                            if !p.exists() {
-                                fs::create_dir_all(&p).unwrap();
-                            }
-                        }
-                        let mut outfile = fs::File::create(&output).unwrap();
-                        copy(&mut file, &mut outfile).unwrap();
-
-                        let file_name = output.to_str().unwrap();
-                        if !output.is_dir() && is_materials(file_name) {
-                            // Concatenate all the zip files into our result.
-                            result += &format!(
-                                "====================== zip file: {} ======================\n\n",
-                                output.as_path().to_str().unwrap().replace(env::temp_dir().as_path().to_str().unwrap(), "")
-                            );
-                            if output.as_path().extension().unwrap() == "pdf" {
-                                result += &read_pdf(&name, output.clone());
-                            } else {
-                                result += &fs::read_to_string(&output).unwrap();
-                            }
-                            result += "\n\n\n";
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("[applicants] error unwrapping materials name {}: {}", name, e);
-                }
-            }
-        }
+    if mime_type == "application/vnd.google-apps.document" {
+        return drive_client.get_file_contents_by_id(&id).await.unwrap().trim().to_string();
     } else if name.ends_with(".doc") || name.ends_with(".pptx") || name.ends_with(".jpg")
     // TODO: handle these formats
     {
-        println!("[applicants] unsupported doc format -- mime type: {}, name: {}, path: {}", mime_type, name, path.to_str().unwrap());
-    } else {
-        let contents = drive_client.download_file_by_id(&id).await.unwrap();
-        path.push(name.to_string());
-
-        let mut file = fs::File::create(&path).unwrap();
-        file.write_all(&contents).unwrap();
-
-        output.push(format!("{}.txt", id));
-
-        let mut pandoc = pandoc::new();
-        pandoc.add_input(&path);
-        pandoc.set_output(OutputKind::File(output.clone()));
-        match pandoc.execute() {
-            Ok(_) => (),
-            Err(e) => {
-                println!("pandoc failed: {}", e);
-                return "".to_string();
-            }
-        }
-        result = fs::read_to_string(output.clone()).unwrap();
+        println!("[applicants] unsupported doc format -- mime type: {}, name: {}", mime_type, name);
+        return "".to_string();
     }
 
-    // Delete the temporary file, if it exists.
-    for p in vec![path, output] {
-        if p.exists() && !p.is_dir() {
-            fs::remove_file(p).unwrap();
-        }
+    let contents = drive_client.download_file_by_id(&id).await.unwrap();
+    let extraction = extract_materials(&name, &mime_type, &contents);
+    for error in extraction.errors() {
+        println!("[applicants] extracting text from {} failed: {}", id, error);
     }
 
-    result.trim().to_string()
-}
-
-fn read_pdf(name: &str, path: std::path::PathBuf) -> String {
-    let mut output = env::temp_dir();
-    output.push("tempfile.txt");
-
-    // Extract the text from the PDF
-    let cmd_output = Command::new("pdftotext").args(&["-enc", "UTF-8", path.to_str().unwrap(), output.to_str().unwrap()]).output().unwrap();
-
-    let result = match fs::read_to_string(output.clone()) {
-        Ok(r) => r,
-        Err(e) => {
-            println!("[applicants] running pdf2text failed: {} | name: {}, path: {}", e, name, path.as_path().display());
-            stdout().write_all(&cmd_output.stdout).unwrap();
-            stderr().write_all(&cmd_output.stderr).unwrap();
-
-            "".to_string()
-        }
-    };
-
-    // Delete the temporary file, if it exists.
-    for p in vec![path, output] {
-        if p.exists() && !p.is_dir() {
-            fs::remove_file(p).unwrap();
-        }
-    }
-
-    result
+    extraction.text()
 }
 
 pub fn get_tracking_sheets() -> Vec<&'static str> {
@@ -1978,7 +1973,7 @@ pub async fn refresh_db_applicants(db: &Database) {
     // depending on the application status.
     for (sheet_name, sheet_id) in get_sheets_map() {
         // Get the values in the sheet.
-        let sheet_values = sheets_client.get_values(&sheet_id, "Form Responses 1!A1:Z1000".to_string()).await.unwrap();
+        let sheet_values = sheets_client.get_values(&sheet_id, "Form Responses 1!A1:ZZ1000".to_string()).await.unwrap();
         let values = sheet_values.values.unwrap();
 
         if values.is_empty() {
@@ -2000,6 +1995,19 @@ pub async fn refresh_db_applicants(db: &Database) {
                 break;
             }
 
+            // Skip the rows we redacted, see `crate::applicant_retention`.
+            if row[columns.email].trim() == REDACTED {
+                continue;
+            }
+
+            // The intake API is the source of truth for applications submitted through it,
+            // so do not let a stale sheet row overwrite them.
+            if let Some(existing) = Applicant::get_from_db(db, row[columns.email].trim().to_string(), sheet_id.to_string()) {
+                if existing.source == APPLICANT_SOURCE_API {
+                    continue;
+                }
+            }
+
             // Parse the applicant out of the row information.
             let mut applicant = NewApplicant::parse_from_row_with_columns(sheet_name, sheet_id, &columns, &row).await;
             applicant
@@ -2021,373 +2029,6 @@ pub async fn refresh_db_applicants(db: &Database) {
     }
 }
 
-/// The data type for a Google Sheet applicant form columns, we use this when
-/// parsing the Google Sheets for applicant forms where we leave our voting.
-#[derive(Debug, Default, Deserialize, Serialize)]
-pub struct ApplicantFormSheetColumns {
-    pub name: usize,
-    pub email: usize,
-    pub form_id: usize,
-    pub form_url: usize,
-    pub form_responses_url: usize,
-    pub scorers_completed: usize,
-}
-
-impl ApplicantFormSheetColumns {
-    fn new() -> Self {
-        ApplicantFormSheetColumns {
-            name: 0,
-            email: 1,
-            form_id: 2,
-            form_url: 3,
-            form_responses_url: 4,
-            scorers_completed: 5,
-        }
-    }
-}
-
-pub fn get_reviewer_pool(db: &Database) -> Vec<String> {
-    let users = Users::get_from_db(db);
-
-    let mut reviewers: Vec<String> = Default::default();
-    for user in users {
-        if user.typev == "full-time" && user.username != "robert.keith" && user.username != "robert" && user.username != "keith" && user.username != "thomas" && user.username != "arjen" {
-            reviewers.push(user.email());
-        }
-    }
-    reviewers
-}
-
-pub async fn update_applications_with_scoring_forms(db: &Database) {
-    // Get the GSuite token.
-    let token = get_gsuite_token("").await;
-
-    // Initialize the GSuite sheets client.
-    let sheets_client = Sheets::new(token.clone());
-    for sheet_id in get_tracking_sheets() {
-        // Get the values in the sheet.
-        let sheet_values = sheets_client.get_values(&sheet_id, "Applicants to review!A1:G1000".to_string()).await.unwrap();
-        let values = sheet_values.values.unwrap();
-
-        if values.is_empty() {
-            panic!("unable to retrieve any data values from Google sheet for applicant forms {}", sheet_id);
-        }
-
-        // Parse the sheet columns.
-        let columns = ApplicantFormSheetColumns::new();
-
-        /*let mut reviewer_pool = get_reviewer_pool(db);
-
-        // We'll assign reviewers randomly but attempt to produce roughly even loads
-        // across reviewers. To do this, we shuffle the list of reviewers, and then
-        // create a cycling iterator that produces that shuffled sequence forever.
-        // Whenever we need a group of 5 interviewers, we'll just take the next 5.
-        let mut rng = rand::thread_rng();
-        reviewer_pool.shuffle(&mut rng);
-        reviewer_pool = reviewer_pool.iter().cloned().cycle();*/
-
-        // Iterate over the rows.
-        for (_, row) in values.iter().enumerate() {
-            if row[columns.email].is_empty() {
-                // Break our loop we are in an empty row.
-                break;
-            }
-
-            let email = row[columns.email].to_string();
-            let form_id = row[columns.form_id].to_string();
-            let form_url = row[columns.form_url].to_string();
-            let form_responses_url = row[columns.form_responses_url].to_string();
-
-            let mut scorers_completed: Vec<String> = vec![];
-            if row.len() > columns.scorers_completed {
-                let scorers_completed_string = row[columns.scorers_completed].to_string();
-                let scorers_completed_str: Vec<&str> = scorers_completed_string.split(',').collect();
-                for s in scorers_completed_str {
-                    match User::get_from_db(db, s.trim_end_matches(GSUITE_DOMAIN).trim_end_matches('@').to_string()) {
-                        Some(user) => {
-                            scorers_completed.push(user.email());
-                        }
-                        None => {
-                            println!("could not find user with email: {}", email);
-                        }
-                    }
-                }
-            }
-
-            // Update each of the applicants.
-            for (_, sheet_id) in get_sheets_map() {
-                if let Ok(mut applicant) = applicants::dsl::applicants
-                    .filter(applicants::dsl::email.eq(email.to_string()))
-                    .filter(applicants::dsl::sheet_id.eq(sheet_id.to_string()))
-                    .first::<Applicant>(&db.conn())
-                {
-                    // Try to get from airtable.
-                    // This ensures if we had any one offs added in airtable that they stay intact.
-                    if let Some(record) = applicant.get_existing_airtable_record().await {
-                        applicant.scorers = record.fields.scorers;
-                        applicant.request_background_check = record.fields.request_background_check;
-                        applicant.interviews = record.fields.interviews;
-                    }
-                    applicant.scorers_completed = scorers_completed.clone();
-
-                    // Remove anyone from the scorers if they have already completed their review.
-                    for _ in 0..5 {
-                        for (index, scorer) in applicant.scorers.clone().iter().enumerate() {
-                            if applicant.scorers_completed.contains(scorer) {
-                                applicant.scorers.remove(index);
-                                // Break the loop since now the indexes are all off.
-                                // The next cron run will catch it.
-                                // TODO: make this better.
-                                break;
-                            }
-                        }
-                    }
-
-                    // Make sure the status is "Needs to be triaged".
-                    let status = crate::applicant_status::Status::from_str(&applicant.status);
-                    if status != Ok(crate::applicant_status::Status::NeedsToBeTriaged) {
-                        // Update the applicant in the database.
-                        applicant.update(db).await;
-
-                        // Continue we don't care.
-                        continue;
-                    }
-
-                    applicant.scoring_form_id = form_id.to_string();
-                    applicant.scoring_form_url = form_url.to_string();
-                    applicant.scoring_form_responses_url = form_responses_url.to_string();
-
-                    // See if we already have scorers assigned.
-                    /*
-                    DO NOT ASSIGN NEW SCORERS RANDOMLY.
-                     if applicant.scorers.is_empty() || (applicant.scorers.len() + applicant.scorers_completed.len()) < 5 {
-                         // Assign scorers and send email.
-                         // Choose next five reviewers.
-                         let mut random_five: Vec<String> = reviewer_pool.by_ref().take(5).collect();
-                         applicant.scorers.append(&mut random_five);
-                         // Remove any duplicates.
-                         applicant.scorers.sort_unstable();
-                         applicant.scorers.dedup();
-                     }
-
-                     // Remove anyone from the scorers if they have already completed their review.
-                     for _ in 0..5 {
-                         for (index, scorer) in applicant.scorers.clone().iter().enumerate() {
-                             if applicant.scorers_completed.contains(scorer) {
-                                 applicant.scorers.remove(index);
-                                 // Break the loop since now the indexes are all off.
-                                 // The next cron run will catch it.
-                                 // TODO: make this better.
-                                 break;
-                             }
-                         }
-                     }*/
-
-                    // Update the applicant in the database.
-                    applicant.update(db).await;
-                }
-            }
-        }
-    }
-}
-
-pub async fn update_applications_with_scoring_results(db: &Database) {
-    // Get the GSuite token.
-    let token = get_gsuite_token("").await;
-
-    // Initialize the GSuite sheets client.
-    let sheets_client = Sheets::new(token.clone());
-    for sheet_id in get_tracking_sheets() {
-        // Get the values in the sheet.
-        let sheet_values = sheets_client.get_values(&sheet_id, "Responses!A1:R1000".to_string()).await.unwrap();
-        let values = sheet_values.values.unwrap();
-
-        if values.is_empty() {
-            panic!("unable to retrieve any data values from Google sheet for applicant form responses {}", sheet_id);
-        }
-
-        // Iterate over the rows.
-        for (row_index, row) in values.iter().enumerate() {
-            if row_index == 0 {
-                // We are on the header row.
-                continue;
-            }
-            if row[0].is_empty() {
-                // Break our loop we are in an empty row.
-                break;
-            }
-
-            let email = row[1].to_string();
-
-            // Parse the scoring results.
-            let scoring_evaluations_count = row[2].parse::<i32>().unwrap_or(0);
-            let scoring_enthusiastic_yes_count = row[3].parse::<i32>().unwrap_or(0);
-            let scoring_yes_count = row[4].parse::<i32>().unwrap_or(0);
-            let scoring_pass_count = row[5].parse::<i32>().unwrap_or(0);
-            let scoring_no_count = row[6].parse::<i32>().unwrap_or(0);
-            let scoring_not_applicable_count = row[7].parse::<i32>().unwrap_or(0);
-            let mut scoring_insufficient_experience_count = 0;
-            let mut scoring_inapplicable_experience_count = 0;
-            let mut scoring_job_function_yet_needed_count = 0;
-            let mut scoring_underwhelming_materials_count = 0;
-            let mut value_reflected = "".to_string();
-            let mut value_violated = "".to_string();
-            let mut values_in_tension: Vec<String> = vec![];
-
-            if row.len() >= 10 {
-                scoring_insufficient_experience_count = row[9].parse::<i32>().unwrap_or(0);
-                scoring_inapplicable_experience_count = row[10].parse::<i32>().unwrap_or(0);
-                scoring_job_function_yet_needed_count = row[11].parse::<i32>().unwrap_or(0);
-                scoring_underwhelming_materials_count = row[12].parse::<i32>().unwrap_or(0);
-
-                // Parse the values.
-                value_reflected = row[14].to_lowercase().to_string();
-                if value_reflected == "n/a" {
-                    value_reflected = "".to_string();
-                }
-                value_violated = row[15].to_lowercase().to_string();
-                if value_violated == "n/a" {
-                    value_violated = "".to_string();
-                }
-                let value_in_tension_1 = row[16].to_lowercase().to_string();
-                if value_in_tension_1 != "n/a" && !value_in_tension_1.trim().is_empty() {
-                    values_in_tension.push(value_in_tension_1);
-                }
-                let value_in_tension_2 = row[17].to_lowercase().to_string();
-                if value_in_tension_2 != "n/a" && !value_in_tension_2.trim().is_empty() {
-                    values_in_tension.push(value_in_tension_2);
-                }
-                values_in_tension.sort();
-            }
-
-            // Update each of the applicants.
-            for (_, sheet_id) in get_sheets_map() {
-                if let Ok(mut applicant) = applicants::dsl::applicants
-                    .filter(applicants::dsl::email.eq(email.to_string()))
-                    .filter(applicants::dsl::sheet_id.eq(sheet_id.to_string()))
-                    .first::<Applicant>(&db.conn())
-                {
-                    if applicant.status == crate::applicant_status::Status::Onboarding.to_string() || applicant.status == crate::applicant_status::Status::Hired.to_string() {
-                        // Zero out the values for the scores.
-                        applicant.scoring_evaluations_count = 0;
-                        applicant.scoring_enthusiastic_yes_count = 0;
-                        applicant.scoring_yes_count = 0;
-                        applicant.scoring_pass_count = 0;
-                        applicant.scoring_no_count = 0;
-                        applicant.scoring_not_applicable_count = 0;
-                        applicant.scoring_insufficient_experience_count = 0;
-                        applicant.scoring_inapplicable_experience_count = 0;
-                        applicant.scoring_job_function_yet_needed_count = 0;
-                        applicant.scoring_underwhelming_materials_count = 0;
-                    } else {
-                        applicant.scoring_evaluations_count = scoring_evaluations_count;
-                        applicant.scoring_enthusiastic_yes_count = scoring_enthusiastic_yes_count;
-                        applicant.scoring_yes_count = scoring_yes_count;
-                        applicant.scoring_pass_count = scoring_pass_count;
-                        applicant.scoring_no_count = scoring_no_count;
-                        applicant.scoring_not_applicable_count = scoring_not_applicable_count;
-                        applicant.scoring_insufficient_experience_count = scoring_insufficient_experience_count;
-                        applicant.scoring_inapplicable_experience_count = scoring_inapplicable_experience_count;
-                        applicant.scoring_job_function_yet_needed_count = scoring_job_function_yet_needed_count;
-                        applicant.scoring_underwhelming_materials_count = scoring_underwhelming_materials_count;
-                    }
-
-                    applicant.value_reflected = value_reflected.to_string();
-                    applicant.value_violated = value_violated.to_string();
-                    applicant.values_in_tension = values_in_tension.clone();
-
-                    // Update the applicant in the database.
-                    applicant.update(db).await;
-                }
-            }
-        }
-    }
-
-    // Ensure anyone with the status of "Onboarding" or "Hired" gets their scores zero-ed out.
-    let applicants = applicants::dsl::applicants
-        .filter(
-            applicants::dsl::status
-                .eq(crate::applicant_status::Status::Onboarding.to_string())
-                .or(applicants::dsl::status.eq(crate::applicant_status::Status::Hired.to_string())),
-        )
-        .load::<Applicant>(&db.conn())
-        .unwrap();
-    for mut applicant in applicants {
-        // Zero out the values for the scores.
-        applicant.scoring_evaluations_count = 0;
-        applicant.scoring_enthusiastic_yes_count = 0;
-        applicant.scoring_yes_count = 0;
-        applicant.scoring_pass_count = 0;
-        applicant.scoring_no_count = 0;
-        applicant.scoring_not_applicable_count = 0;
-        applicant.scoring_insufficient_experience_count = 0;
-        applicant.scoring_inapplicable_experience_count = 0;
-        applicant.scoring_job_function_yet_needed_count = 0;
-        applicant.scoring_underwhelming_materials_count = 0;
-
-        // Update the applicant in the database.
-        applicant.update(db).await;
-    }
-}
-
-fn is_materials(file_name: &str) -> bool {
-    file_name.ends_with("responses.pdf")
-        || (file_name.starts_with("Oxide Candidate Materials") && file_name.ends_with(".pdf"))
-        || (file_name.starts_with("Oxide_Candidate_Materials") && file_name.ends_with(".pdf"))
-        || file_name.ends_with("Oxide Candidate Materials.pdf")
-        || file_name.ends_with("Oxide Candidate Materials.pdf.pdf")
-        || file_name.ends_with("OxideQuestions.pdf")
-        || file_name.ends_with("oxide-computer-candidate-materials.pdf")
-        || file_name.ends_with("Questionnaire.pdf")
-        || file_name.ends_with("Questionairre.pdf")
-        || file_name.ends_with("Operations Manager.pdf")
-}
-
-pub async fn refresh_background_checks(db: &Database) {
-    // Initialize the Checker client.
-    let checkr = Checkr::new_from_env();
-
-    // List the candidates.
-    let candidates = checkr.list_candidates().await.unwrap();
-    for candidate in candidates {
-        // Try to match the candidate based on their email.
-        // Try for all the sheet_ids.
-        for (_, sheet_id) in get_sheets_map() {
-            // Match on their email or their name.
-            // TODO: name is working for now but might want to make it more fuzzy in the future.
-            // This could be problematic if we have two John Smiths join in the same week.
-            if let Ok(mut applicant) = applicants::dsl::applicants
-                .filter(
-                    applicants::dsl::email
-                        .eq(candidate.email.to_string())
-                        .or(applicants::dsl::name.eq(format!("{} {}", candidate.first_name, candidate.last_name))),
-                )
-                .filter(applicants::dsl::sheet_id.eq(sheet_id.to_string()))
-                .filter(applicants::dsl::status.eq(crate::applicant_status::Status::Onboarding.to_string()))
-                .first::<Applicant>(&db.conn())
-            {
-                for report_id in &candidate.report_ids {
-                    // Get the report for the candidate.
-                    let report = checkr.get_report(&report_id).await.unwrap();
-
-                    // Set the status for the report.
-                    if report.package.contains("premium_criminal") {
-                        applicant.criminal_background_check_status = report.status.to_string();
-                    }
-                    if report.package.contains("motor_vehicle") {
-                        applicant.motor_vehicle_background_check_status = report.status.to_string();
-                    }
-
-                    // Update the applicant.
-                    applicant.update(db).await;
-                }
-            } else {
-                println!("[checkr] could not find applicant with email {} in sheet_id {}", candidate.email, sheet_id);
-            }
-        }
-    }
-}
-
 /// The data type for a ApplicantReviewer.
 #[db {
     new_struct_name = "ApplicantReviewer",
@@ -2421,6 +2062,29 @@ pub struct NewApplicantReviewer {
     pub no: i32,
     #[serde(default)]
     pub not_applicable: i32,
+    /// The number of applicants assigned to the reviewer that they have not scored yet.
+    #[serde(default)]
+    pub open_assignments: i32,
+    /// The number of open assignments that are past their due time.
+    #[serde(default)]
+    pub overdue_assignments: i32,
+    /// When the reviewer is back, if their calendar has them out of office.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub out_of_office_until: Option<DateTime<Utc>>,
+    /// The counts from the Google Forms scoring, from before the scores were kept in the
+    /// database. The scores in the database are added on top of these.
+    #[serde(default, skip_serializing)]
+    pub legacy_evaluations: i32,
+    #[serde(default, skip_serializing)]
+    pub legacy_emphatic_yes: i32,
+    #[serde(default, skip_serializing)]
+    pub legacy_yes: i32,
+    #[serde(default, skip_serializing)]
+    pub legacy_pass: i32,
+    #[serde(default, skip_serializing)]
+    pub legacy_no: i32,
+    #[serde(default, skip_serializing)]
+    pub legacy_not_applicable: i32,
 }
 
 /// Implement updating the Airtable record for an ApplicantReviewer.
@@ -2429,80 +2093,96 @@ impl UpdateAirtableRecord<ApplicantReviewer> for ApplicantReviewer {
     async fn update_airtable_record(&mut self, _record: ApplicantReviewer) {}
 }
 
+/// Update the reviewer leaderboard from the applicant scores.
 pub async fn update_applicant_reviewers(db: &Database) {
-    // Get the GSuite token.
-    let token = get_gsuite_token("").await;
-
-    // Initialize the GSuite sheets client.
-    let sheets_client = Sheets::new(token.clone());
-    let sheet_id = "1BOeZTdSNixkJsVHwf3Z0LMVlaXsc_0J8Fsy9BkCa7XM";
-
-    // Get the values in the sheet.
-    let sheet_values = sheets_client.get_values(&sheet_id, "Leaderboard!A1:R1000".to_string()).await.unwrap();
-    let values = sheet_values.values.unwrap();
-
-    if values.is_empty() {
-        panic!("unable to retrieve any data values from Google sheet for reviewer leaderboard {}", sheet_id);
+    let mut leaderboard: BTreeMap<String, NewApplicantReviewer> = Default::default();
+    // Start from the counts from the Google Forms scoring, so reviewers keep their history.
+    // Keep the assignment counts, those are set by the reviewer assignments.
+    for existing in ApplicantReviewers::get_from_db(db) {
+        leaderboard.insert(
+            existing.email.to_string(),
+            NewApplicantReviewer {
+                name: existing.name.to_string(),
+                email: existing.email.to_string(),
+                evaluations: existing.legacy_evaluations,
+                emphatic_yes: existing.legacy_emphatic_yes,
+                yes: existing.legacy_yes,
+                pass: existing.legacy_pass,
+                no: existing.legacy_no,
+                not_applicable: existing.legacy_not_applicable,
+                open_assignments: existing.open_assignments,
+                overdue_assignments: existing.overdue_assignments,
+                out_of_office_until: existing.out_of_office_until,
+                legacy_evaluations: existing.legacy_evaluations,
+                legacy_emphatic_yes: existing.legacy_emphatic_yes,
+                legacy_yes: existing.legacy_yes,
+                legacy_pass: existing.legacy_pass,
+                legacy_no: existing.legacy_no,
+                legacy_not_applicable: existing.legacy_not_applicable,
+            },
+        );
     }
 
-    // Iterate over the rows.
-    for (row_index, row) in values.iter().enumerate() {
-        if row_index == 0 {
-            // We are on the header row.
-            continue;
-        }
-        if row[0].is_empty() {
-            // Break our loop we are in an empty row.
-            break;
-        }
-
-        let email = row[0].to_string();
-
-        // Parse the scoring results.
-        let evaluations = row[1].parse::<i32>().unwrap_or(0);
-        let emphatic_yes = row[2].parse::<i32>().unwrap_or(0);
-        let yes = row[3].parse::<i32>().unwrap_or(0);
-        let pass = row[4].parse::<i32>().unwrap_or(0);
-        let no = row[5].parse::<i32>().unwrap_or(0);
-        let not_applicable = row[6].parse::<i32>().unwrap_or(0);
+    for score in ApplicantScores::get_from_db(db) {
+        if !leaderboard.contains_key(&score.reviewer) {
+            let user = match User::get_from_db(db, score.reviewer.trim_end_matches(GSUITE_DOMAIN).trim_end_matches('@').to_string()) {
+                Some(user) => user,
+                None => {
+                    println!("could not find user with email: {}", score.reviewer);
+                    continue;
+                }
+            };
 
-        match User::get_from_db(db, email.trim_end_matches(GSUITE_DOMAIN).trim_end_matches('@').to_string()) {
-            Some(user) => {
-                let reviewer = NewApplicantReviewer {
+            leaderboard.insert(
+                score.reviewer.to_string(),
+                NewApplicantReviewer {
                     name: user.full_name(),
-                    email,
-                    evaluations,
-                    emphatic_yes,
-                    yes,
-                    pass,
-                    no,
-                    not_applicable,
-                };
-
-                // Upsert the applicant reviewer in the database.
-                reviewer.upsert(db).await;
-            }
-            None => {
-                println!("could not find user with email: {}", email);
-            }
+                    email: score.reviewer.to_string(),
+                    evaluations: 0,
+                    emphatic_yes: 0,
+                    yes: 0,
+                    pass: 0,
+                    no: 0,
+                    not_applicable: 0,
+                    open_assignments: 0,
+                    overdue_assignments: 0,
+                    out_of_office_until: None,
+                    legacy_evaluations: 0,
+                    legacy_emphatic_yes: 0,
+                    legacy_yes: 0,
+                    legacy_pass: 0,
+                    legacy_no: 0,
+                    legacy_not_applicable: 0,
+                },
+            );
+        }
+
+        let reviewer = leaderboard.get_mut(&score.reviewer).unwrap();
+        reviewer.evaluations += 1;
+        match ScoringRecommendation::from_str(&score.recommendation) {
+            Ok(ScoringRecommendation::EmphaticYes) => reviewer.emphatic_yes += 1,
+            Ok(ScoringRecommendation::Yes) => reviewer.yes += 1,
+            Ok(ScoringRecommendation::Pass) => reviewer.pass += 1,
+            Ok(ScoringRecommendation::No) => reviewer.no += 1,
+            Ok(ScoringRecommendation::NotApplicable) => reviewer.not_applicable += 1,
+            Err(e) => println!("[applicants] score {} from {} is invalid: {}", score.id, score.reviewer, e),
         }
     }
+
+    for reviewer in leaderboard.values() {
+        // Upsert the applicant reviewer in the database.
+        reviewer.upsert(db).await;
+    }
 }
 
-pub async fn refresh_docusign_for_applicants(db: &Database) {
+pub async fn refresh_docusign_for_applicants(db: &Database, github: &Github) {
     // Authenticate DocuSign.
     let ds = DocuSign::new_from_env().await;
 
-    // Get the template we need.
-    let mut template_id = "".to_string();
+    // Get the offer letter templates, by country and type of employment.
+    let configs = get_configs_from_repo(github).await;
+    let offers = configs.offer_templates;
     let templates = ds.list_templates().await.unwrap();
-    for template in templates {
-        if template.name == "Employee Offer Letter (US)" {
-            template_id = template.template_id;
-            // We can break our loop.
-            break;
-        }
-    }
 
     // TODO: we could actually query the DB by status, but whatever.
     let applicants = Applicants::get_from_db(db);
@@ -2519,53 +2199,18 @@ pub async fn refresh_docusign_for_applicants(db: &Database) {
         if applicant.docusign_envelope_id.is_empty() && applicant.status == crate::applicant_status::Status::GivingOffer.to_string() {
             println!("[docusign] applicant has status giving offer: {}, generating offer in docusign for them!", applicant.name);
             // We haven't sent their offer yet, so let's do that.
-            // Let's create a new envelope for the user.
-            let mut new_envelope: docusign::Envelope = Default::default();
-
-            // Sent the status to `sent` so it sends.
-            // To save it as a draft set the status as `created`.
-            new_envelope.status = "sent".to_string();
-
-            // Set the email subject.
-            new_envelope.email_subject = "Sign your Oxide Computer Company Offer Letter".to_string();
-
-            // Set the template id to that of our template.
-            new_envelope.template_id = template_id.to_string();
-
-            // Set the recipients of the template.
-            // The first recipient needs to be the CEO (or whoever is going to do the mad lib for
-            // the offer.
-            // The second recipient needs to be the Applicant.
-            new_envelope.template_roles = vec![
-                docusign::TemplateRole {
-                    name: "Steve Tuck".to_string(),
-                    role_name: "CEO".to_string(),
-                    email: format!("steve@{}", GSUITE_DOMAIN),
-                    signer_name: "Steve Tuck".to_string(),
-                    routing_order: "1".to_string(),
-                    // Make Steve's email notification different than the actual applicant.
-                    email_notification: docusign::EmailNotification {
-                        email_subject: format!("Complete the offer letter for {}", applicant.name),
-                        email_body: format!("The status for the applicant, {}, has been changed to `Giving offer`. Therefore, we are sending you an offer letter to complete, as Jess calls, the 'Mad Libs'. GO COMPLETE THE MAD LIBS! After you finish, we will send the offer letter to {} at {} to sign and date! Thanks!", applicant.name, applicant.name, applicant.email),
-                        language: Default::default(),
-                    },
-                },
-                docusign::TemplateRole {
-                    name: applicant.name.to_string(),
-                    role_name: "Applicant".to_string(),
-                    email: applicant.email.to_string(),
-                    signer_name: applicant.name.to_string(),
-                    routing_order: "2".to_string(),
-                    email_notification: docusign::EmailNotification {
-                        email_subject: "Sign your Oxide Computer Company Offer Letter".to_string(),
-                        email_body: "We are very excited to offer you a position at the Oxide Computer Company!".to_string(),
-                        language: Default::default(),
-                    },
-                },
-            ];
-
-            // Let's create the envelope.
-            let envelope = ds.create_envelope(new_envelope.clone()).await.unwrap();
+            // The template decides who signs the offer and in what order. The first signer
+            // is usually the CEO (or whoever is going to do the mad lib for the offer),
+            // with the tabs we know already filled in.
+            let envelope = match send_offer_envelope(db, &ds, &offers, &templates, &applicant).await {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    println!("[docusign] sending offer to applicant {} failed: {}", applicant.email, e);
+                    // Let the hiring channel know, the applicant is waiting on their offer.
+                    post_to_channel(get_hiring_channel_post_url(), offer_failed_slack_msg(&applicant, &e)).await;
+                    continue;
+                }
+            };
 
             // Set the id of the envelope.
             applicant.docusign_envelope_id = envelope.envelope_id.to_string();
@@ -2582,18 +2227,45 @@ pub async fn refresh_docusign_for_applicants(db: &Database) {
             applicant.update_applicant_from_docusign_envelope(db, &ds, envelope).await;
         }
     }
+
+    // Remind anyone we are still waiting on to sign.
+    refresh_envelope_reminders(db, &ds, &configs.envelope_reminders).await;
 }
 
 impl Applicant {
     pub async fn update_applicant_from_docusign_envelope(&mut self, db: &Database, ds: &DocuSign, envelope: docusign::Envelope) {
-        // Set the status in the database and airtable.
-        self.docusign_envelope_status = envelope.status.to_string();
+        // The webhook does not always send us the recipients, and we need them
+        // to keep track of who the envelope is waiting on.
+        let mut envelope = envelope;
+        if envelope.recipients.signers.is_empty() {
+            if let Ok(e) = ds.get_envelope(&envelope.envelope_id).await {
+                envelope = e;
+            }
+        }
 
-        // If the document is completed, let's save it to Google Drive.
-        if envelope.status != "completed" {
-            // We will skip to the end and return early, only updating the status.
-            self.update(db).await;
-            return;
+        // Save every status change of the envelope.
+        let mut record = record_envelope(db, &envelope, &self.email).await;
+
+        // Set the status in the database and airtable.
+        let previous_status = self.docusign_envelope_status.to_string();
+        self.docusign_envelope_status = record.status.to_string();
+
+        match EnvelopeStatus::from_str(&record.status) {
+            Ok(EnvelopeStatus::Completed) => (),
+            Ok(EnvelopeStatus::Declined) | Ok(EnvelopeStatus::Voided) | Ok(EnvelopeStatus::Expired) => {
+                // The offer will never be signed, let the hiring channel know so
+                // someone can follow up or send a new one.
+                if previous_status != record.status {
+                    post_to_channel(get_hiring_channel_post_url(), record.as_slack_msg(&self.name)).await;
+                }
+                self.update(db).await;
+                return;
+            }
+            _ => {
+                // We will skip to the end and return early, only updating the status.
+                self.update(db).await;
+                return;
+            }
         }
 
         // Since the status is completed, let's set their status to "Onboarding".
@@ -2604,38 +2276,20 @@ impl Applicant {
             self.send_background_check_invitation(db).await;
         }
 
-        // Get gsuite token.
-        let token = get_gsuite_token("").await;
-
-        // Initialize the Google Drive client.
-        let drive_client = GoogleDrive::new(token);
-        // Figure out where our directory is.
-        // It should be in the shared drive : "Offer Letters"
-        let shared_drive = drive_client.get_drive_by_name("Offer Letters").await.unwrap();
-        let drive_id = shared_drive.id.to_string();
-
-        for document in envelope.documents {
-            let mut bytes = base64::decode(&document.pdf_bytes).unwrap_or_default();
-            // Check if we already have bytes to the data.
-            if document.pdf_bytes.is_empty() {
-                // Get the document from docusign.
-                // In order to not "over excessively poll the API here, we need to sleep for 15
-                // min before getting each of the documents.
-                // https://developers.docusign.com/docs/esign-rest-api/esign101/rules-and-limits/
-                thread::sleep(std::time::Duration::from_secs(15));
-                bytes = ds.get_document(&envelope.envelope_id, &document.id).await.unwrap().to_vec();
-            }
+        // Archive the signed documents, if we have not already.
+        if record.archived_documents.is_empty() {
+            // Get gsuite token.
+            let token = get_gsuite_token("").await;
 
-            let mut filename = format!("{} - {}.pdf", self.name, document.name);
-            if document.name.contains("Offer Letter") {
-                filename = format!("{} - Offer.pdf", self.name);
-            } else if document.name.contains("Summary") {
-                filename = format!("{} - DocuSign Summary.pdf", self.name);
+            // Initialize the Google Drive client.
+            let drive_client = GoogleDrive::new(token);
+            match archive_envelope_documents(&drive_client, ds, &envelope, &self.name).await {
+                Ok(links) => {
+                    record.archived_documents = links;
+                    record.update(db).await;
+                }
+                Err(e) => println!("[docusign] archiving envelope {} for applicant {} failed: {}", envelope.envelope_id, self.email, e),
             }
-
-            // Create or update the file in the google_drive.
-            drive_client.create_or_update_file(&drive_id, "", &filename, "application/pdf", &bytes).await.unwrap();
-            println!("[docusign] uploaded completed file {} to drive", filename);
         }
 
         // In order to not "over excessively poll the API here, we need to sleep for 15
@@ -2690,12 +2344,12 @@ impl Applicant {
 
 #[cfg(test)]
 mod tests {
-    use crate::applicants::{
-        refresh_background_checks, refresh_db_applicants, refresh_docusign_for_applicants, update_applicant_reviewers, update_applications_with_scoring_forms,
-        update_applications_with_scoring_results, Applicant, Applicants,
-    };
+    use crate::applicant_reviews::refresh_applicant_review_assignments;
+    use crate::applicant_scores::refresh_applicant_scorecards;
+    use crate::applicants::{refresh_db_applicants, refresh_docusign_for_applicants, update_applicant_reviewers, Applicant, Applicants};
     use crate::db::Database;
     use crate::schema::applicants;
+    use crate::utils::authenticate_github_jwt;
 
     use diesel::prelude::*;
     use serde_json::json;
@@ -2724,14 +2378,6 @@ mod tests {
         update_applicant_reviewers(&db).await;
     }
 
-    #[ignore]
-    #[tokio::test(flavor = "multi_thread")]
-    async fn test_applicants_background_checks() {
-        let db = Database::new();
-
-        refresh_background_checks(&db).await;
-    }
-
     #[ignore]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_applicants() {
@@ -2742,19 +2388,20 @@ mod tests {
         Applicants::get_from_db(&db).update_airtable().await;
 
         // Refresh DocuSign for the applicants.
-        refresh_docusign_for_applicants(&db).await;
+        let github = authenticate_github_jwt();
+        refresh_docusign_for_applicants(&db, &github).await;
     }
 
     #[ignore]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_reviewers() {
         let db = Database::new();
-        // These come from the sheet at:
-        // https://docs.google.com/spreadsheets/d/1BOeZTdSNixkJsVHwf3Z0LMVlaXsc_0J8Fsy9BkCa7XM/edit#gid=2017435653
-        update_applications_with_scoring_forms(&db).await;
+        // This must be before the assignments, so that if someone has scored
+        // the applicant then we remove them from the scorers.
+        refresh_applicant_scorecards(&db).await;
+        update_applicant_reviewers(&db).await;
 
-        // This must be after update_applications_with_scoring_forms, so that if someone
-        // has done the application then we remove them from the scorers.
-        update_applications_with_scoring_results(&db).await;
+        // Now that we know who has scored who, assign reviewers and send reminders.
+        refresh_applicant_review_assignments(&db, &authenticate_github_jwt()).await;
     }
 }
@@ -0,0 +1,600 @@
+#![allow(clippy::from_over_into)]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use checkr::Checkr;
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
+use macros::db;
+use schemars::JsonSchema;
+use sendgrid_api::SendGrid;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::airtable::{AIRTABLE_BACKGROUND_CHECKS_TABLE, AIRTABLE_BASE_ID_RECURITING_APPLICATIONS};
+use crate::applicants::Applicant;
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::{applicants, background_checks};
+use crate::slack::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use crate::utils::{get_most_specific, DOMAIN};
+
+/// The package we use when there are no packages in the configs.
+pub static DEFAULT_BACKGROUND_CHECK_PACKAGE: &str = "premium_criminal";
+
+/// How many business days we give the applicant to respond to a pre-adverse
+/// action notice before we send the post-adverse action notice. The FCRA asks
+/// for a "reasonable" amount of time, five business days is the norm.
+pub static ADVERSE_ACTION_WAITING_BUSINESS_DAYS: i64 = 5;
+
+/// Checkr's contact information, which the FCRA requires in the post-adverse
+/// action notice.
+pub static CHECKR_CONTACT: &str = "Checkr, Inc.
+One Montgomery Street, Suite 2400
+San Francisco, CA 94104
+(844) 824-3257
+https://candidate.checkr.com";
+
+/// A Summary of Your Rights Under the Fair Credit Reporting Act, which must be
+/// sent with the pre-adverse action notice.
+pub static FCRA_SUMMARY_OF_RIGHTS_URL: &str = "https://files.consumerfinance.gov/f/documents/bcfp_consumer-rights-summary_2018-09.pdf";
+
+/// Which background check package to use, from the configs repo.
+/// The key in the configs is the name of the package config, ie. "us-engineering".
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct BackgroundCheckPackageConfig {
+    /// The slug of the package in Checkr, ie. "premium_criminal".
+    pub package: String,
+    /// The roles the package is for. If this is empty, the package is for every role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// The country codes the package is for, ie. "us". If this is empty, the
+    /// package is for every country.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub countries: Vec<String>,
+}
+
+impl BackgroundCheckPackageConfig {
+    /// Returns how well the package matches the role and country, or `None` if
+    /// it does not match at all. A match on the country counts for more than a
+    /// match on the role.
+    fn specificity(&self, role: &str, country_code: &str) -> Option<u8> {
+        let mut specificity = 0;
+
+        if !self.roles.is_empty() {
+            if !self.roles.iter().any(|r| r.eq_ignore_ascii_case(role)) {
+                return None;
+            }
+            specificity += 1;
+        }
+
+        if !self.countries.is_empty() {
+            if !self.countries.iter().any(|c| c.eq_ignore_ascii_case(country_code)) {
+                return None;
+            }
+            specificity += 2;
+        }
+
+        Some(specificity)
+    }
+}
+
+/// Get the background check package for a role and country. The most specific
+/// package wins. If there are no packages configured, we use `DEFAULT_BACKGROUND_CHECK_PACKAGE`.
+pub fn get_background_check_package(packages: &BTreeMap<String, BackgroundCheckPackageConfig>, role: &str, country_code: &str) -> Option<String> {
+    if packages.is_empty() {
+        return Some(DEFAULT_BACKGROUND_CHECK_PACKAGE.to_string());
+    }
+
+    get_most_specific(packages.values().filter_map(|p| p.specificity(role.trim(), country_code.trim()).map(|s| (s, p))), |(s, _)| *s).map(|(_, p)| p.package.to_string())
+}
+
+/// Returns a message for the hiring channel about an applicant we could not
+/// send a background check invitation to, since no package matches them.
+pub fn missing_background_check_package_slack_msg(applicant: &Applicant) -> Value {
+    json!(FormattedMessage {
+        channel: Default::default(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    ":warning: there is no background check package for *{}* <mailto:{}|{}> ({}) in country `{}`, add one to the configs or send their invitation by hand",
+                    applicant.name, applicant.email, applicant.email, applicant.role, applicant.country_code
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    })
+}
+
+/// Where we are in the adverse action flow for a background check.
+///
+/// The FCRA requires that before we decide not to hire someone based on their
+/// background check we:
+///
+/// 1. Send them a pre-adverse action notice, with a copy of their report and
+///    "A Summary of Your Rights Under the Fair Credit Reporting Act".
+/// 2. Wait a reasonable amount of time (`ADVERSE_ACTION_WAITING_BUSINESS_DAYS`)
+///    for them to dispute the report or explain it to us.
+/// 3. Send them a post-adverse action notice, with the contact information of
+///    the consumer reporting agency, a statement that the agency did not make
+///    the decision, and their right to a free copy of the report and to dispute it.
+///
+/// The flow is started by checking "Adverse action requested" on the report in the
+/// "Background Checks" table in Airtable, with the reasons filled in. Setting the
+/// status to `cancelled` in Airtable stops it before the post-adverse action notice.
+/// If the applicant disputes the report, the post-adverse action notice waits until
+/// the dispute is resolved.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum AdverseActionStatus {
+    PreAdverseNoticeSent,
+    PostAdverseNoticeSent,
+    Cancelled,
+}
+
+impl FromStr for AdverseActionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pre-adverse notice sent" => Ok(AdverseActionStatus::PreAdverseNoticeSent),
+            "post-adverse notice sent" => Ok(AdverseActionStatus::PostAdverseNoticeSent),
+            "cancelled" => Ok(AdverseActionStatus::Cancelled),
+            _ => Err(format!("invalid adverse action status: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for AdverseActionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            AdverseActionStatus::PreAdverseNoticeSent => "pre-adverse notice sent",
+            AdverseActionStatus::PostAdverseNoticeSent => "post-adverse notice sent",
+            AdverseActionStatus::Cancelled => "cancelled",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The data type for a background check report from Checkr.
+#[db {
+    new_struct_name = "BackgroundCheck",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_BACKGROUND_CHECKS_TABLE",
+    match_on = {
+        "report_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "background_checks"]
+pub struct NewBackgroundCheck {
+    pub report_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub candidate_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub package: String,
+    /// The status of the report in Checkr: `pending`, `complete`, `suspended` or `dispute`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// The result of the report in Checkr: `clear` or `consider`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub result: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub adjudication: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub screenings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_completion_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_time: Option<DateTime<Utc>>,
+    /// Set in Airtable to start the adverse action flow.
+    #[serde(default)]
+    pub adverse_action_requested: bool,
+    /// Why we are taking adverse action, this is sent to the applicant.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub adverse_action_reasons: String,
+    /// One of the `AdverseActionStatus`es.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub adverse_action_status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_adverse_notice_sent_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_adverse_notice_sent_time: Option<DateTime<Utc>>,
+}
+
+/// Implement updating the Airtable record for a BackgroundCheck.
+#[async_trait]
+impl UpdateAirtableRecord<BackgroundCheck> for BackgroundCheck {
+    async fn update_airtable_record(&mut self, _record: BackgroundCheck) {}
+}
+
+/// Add business days to a time, skipping weekends.
+pub fn add_business_days(time: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+    let mut result = time;
+    let mut added = 0;
+    while added < days {
+        result = result + Duration::days(1);
+        if result.weekday() != Weekday::Sat && result.weekday() != Weekday::Sun {
+            added += 1;
+        }
+    }
+
+    result
+}
+
+impl NewBackgroundCheck {
+    /// Parse the background check out of a report from Checkr.
+    pub fn from_report(report: &checkr::Report, applicant_email: &str) -> Self {
+        NewBackgroundCheck {
+            report_id: report.id.to_string(),
+            candidate_id: report.candidate_id.to_string(),
+            applicant_email: applicant_email.to_string(),
+            package: report.package.to_string(),
+            status: report.status.to_string(),
+            result: report.result.to_string(),
+            adjudication: report.adjudication.to_string(),
+            screenings: report.screenings(),
+            created_time: Some(report.created_at),
+            estimated_completion_time: DateTime::parse_from_rfc3339(&report.estimated_completion_time).map(|t| t.with_timezone(&Utc)).ok(),
+            completed_time: report.completed_at,
+            adverse_action_requested: false,
+            adverse_action_reasons: Default::default(),
+            adverse_action_status: Default::default(),
+            pre_adverse_notice_sent_time: None,
+            post_adverse_notice_sent_time: None,
+        }
+    }
+
+    /// Returns when the post-adverse action notice can be sent, if we are
+    /// waiting to send it.
+    pub fn post_adverse_notice_due(&self) -> Option<DateTime<Utc>> {
+        if AdverseActionStatus::from_str(&self.adverse_action_status) != Ok(AdverseActionStatus::PreAdverseNoticeSent) {
+            return None;
+        }
+
+        self.pre_adverse_notice_sent_time.map(|t| add_business_days(t, ADVERSE_ACTION_WAITING_BUSINESS_DAYS))
+    }
+
+    /// Returns if we can start the adverse action flow, or why not.
+    pub fn can_start_adverse_action(&self) -> Result<(), String> {
+        if !self.adverse_action_status.is_empty() {
+            return Err(format!("adverse action is already {}", self.adverse_action_status));
+        }
+        if self.status != "complete" {
+            return Err(format!("the report is {}, not complete", self.status));
+        }
+        if self.result != "consider" {
+            return Err(format!("the result of the report is {}, adverse action is only for reports to consider", self.result));
+        }
+        if self.adverse_action_reasons.trim().is_empty() {
+            return Err("the reasons for the adverse action are required".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl BackgroundCheck {
+    /// Send the pre-adverse action notice to the applicant.
+    async fn send_pre_adverse_notice(&self) {
+        let sendgrid_client = SendGrid::new_from_env();
+        sendgrid_client
+            .send_mail(
+                "Pre-adverse action notice regarding your background check".to_string(),
+                format!(
+                    "We are writing about the background check you authorized as part of your application to the Oxide Computer Company.
+
+Based in whole or in part on information in your background check report, we are considering not moving forward with your employment for the following reasons:
+
+{}
+
+Before we make a final decision, you have the right to review the report and to dispute any information in it that you believe is inaccurate or incomplete. You can view and download a copy of your report from Checkr, the consumer reporting agency that prepared it, at:
+https://candidate.checkr.com
+
+A Summary of Your Rights Under the Fair Credit Reporting Act:
+{}
+
+We will not make a final decision for at least {} business days. If you would like to dispute the report or share anything with us about it, please contact Checkr or reply to this email.
+
+Thank you,
+The Oxide Team",
+                    self.adverse_action_reasons.trim(),
+                    FCRA_SUMMARY_OF_RIGHTS_URL,
+                    ADVERSE_ACTION_WAITING_BUSINESS_DAYS,
+                ),
+                vec![self.applicant_email.to_string()],
+                vec![format!("careers@{}", DOMAIN)],
+                vec![],
+                format!("careers@{}", DOMAIN),
+            )
+            .await;
+    }
+
+    /// Send the post-adverse action notice to the applicant.
+    async fn send_post_adverse_notice(&self) {
+        let sendgrid_client = SendGrid::new_from_env();
+        sendgrid_client
+            .send_mail(
+                "Adverse action notice regarding your background check".to_string(),
+                format!(
+                    "We previously let you know that we were considering not moving forward with your employment based on information in your background check report. We have now made the final decision not to move forward with your employment at the Oxide Computer Company.
+
+The background check report was prepared by the following consumer reporting agency:
+
+{}
+
+Checkr did not make this decision and is unable to tell you why it was made.
+
+You have the right to a free copy of your report from Checkr if you ask for it within 60 days. You also have the right to dispute with Checkr the accuracy or completeness of any information in the report.
+
+A Summary of Your Rights Under the Fair Credit Reporting Act:
+{}
+
+Thank you,
+The Oxide Team",
+                    CHECKR_CONTACT, FCRA_SUMMARY_OF_RIGHTS_URL,
+                ),
+                vec![self.applicant_email.to_string()],
+                vec![format!("careers@{}", DOMAIN)],
+                vec![],
+                format!("careers@{}", DOMAIN),
+            )
+            .await;
+    }
+
+    /// Start the adverse action flow, by sending the pre-adverse action notice.
+    pub async fn start_adverse_action(&mut self, db: &Database) -> Result<(), String> {
+        NewBackgroundCheck::from(self.clone()).can_start_adverse_action()?;
+
+        self.send_pre_adverse_notice().await;
+        self.adverse_action_status = AdverseActionStatus::PreAdverseNoticeSent.to_string();
+        self.pre_adverse_notice_sent_time = Some(Utc::now());
+        self.update(db).await;
+
+        println!("[checkr] sent pre-adverse action notice to {} for report {}", self.applicant_email, self.report_id);
+        Ok(())
+    }
+}
+
+/// Save a report from Checkr, and update the status of the background check
+/// for the applicant.
+pub async fn ingest_background_check_report(db: &Database, report: &checkr::Report, applicant: Option<&mut Applicant>) -> BackgroundCheck {
+    let applicant_email = applicant.as_ref().map(|a| a.email.to_string()).unwrap_or_default();
+    let mut background_check = NewBackgroundCheck::from_report(report, &applicant_email);
+
+    // Keep the adverse action flow, that does not come from Checkr.
+    if let Some(existing) = BackgroundCheck::get_from_db(db, report.id.to_string()) {
+        if background_check.applicant_email.is_empty() {
+            background_check.applicant_email = existing.applicant_email.to_string();
+        }
+        background_check.adverse_action_requested = existing.adverse_action_requested;
+        background_check.adverse_action_reasons = existing.adverse_action_reasons.to_string();
+        background_check.adverse_action_status = existing.adverse_action_status.to_string();
+        background_check.pre_adverse_notice_sent_time = existing.pre_adverse_notice_sent_time;
+        background_check.post_adverse_notice_sent_time = existing.post_adverse_notice_sent_time;
+    }
+
+    if let Some(applicant) = applicant {
+        // Set the status for the report.
+        if report.has_criminal_screening() {
+            applicant.criminal_background_check_status = report.status.to_string();
+        }
+        if !report.motor_vehicle_report_id.is_empty() {
+            applicant.motor_vehicle_background_check_status = report.status.to_string();
+        }
+        applicant.update(db).await;
+    }
+
+    background_check.upsert(db).await
+}
+
+/// Get the applicant we sent the background check to for a Checkr candidate.
+pub fn get_applicant_for_candidate(db: &Database, candidate: &checkr::Candidate) -> Option<Applicant> {
+    applicants::dsl::applicants
+        .filter(
+            applicants::dsl::email
+                .eq(candidate.email.to_string())
+                // TODO: matching on name might be a bad idea here.
+                .or(applicants::dsl::name.eq(format!("{} {}", candidate.first_name, candidate.last_name))),
+        )
+        .filter(applicants::dsl::status.eq(crate::applicant_status::Status::Onboarding.to_string()))
+        .first::<Applicant>(&db.conn())
+        .ok()
+}
+
+/// Update a background check from the changes made to it in Airtable, this
+/// starts or cancels the adverse action flow.
+pub async fn update_background_check_from_airtable(db: &Database, record: BackgroundCheck) -> Result<BackgroundCheck, String> {
+    let mut background_check = match BackgroundCheck::get_from_db(db, record.report_id.to_string()) {
+        Some(b) => b,
+        None => return Err(format!("background check for report {} does not exist", record.report_id)),
+    };
+
+    background_check.adverse_action_requested = record.adverse_action_requested;
+    background_check.adverse_action_reasons = record.adverse_action_reasons.to_string();
+
+    if AdverseActionStatus::from_str(&record.adverse_action_status) == Ok(AdverseActionStatus::Cancelled) {
+        if AdverseActionStatus::from_str(&background_check.adverse_action_status) == Ok(AdverseActionStatus::PostAdverseNoticeSent) {
+            return Err("the post-adverse action notice was already sent, it cannot be cancelled".to_string());
+        }
+        background_check.adverse_action_status = AdverseActionStatus::Cancelled.to_string();
+        println!("[checkr] cancelled adverse action for {} for report {}", background_check.applicant_email, background_check.report_id);
+        return Ok(background_check.update(db).await);
+    }
+
+    if background_check.adverse_action_requested && background_check.adverse_action_status.is_empty() {
+        background_check.start_adverse_action(db).await?;
+        return Ok(background_check);
+    }
+
+    Ok(background_check.update(db).await)
+}
+
+/// Send the post-adverse action notices that are due.
+pub async fn refresh_adverse_actions(db: &Database) {
+    let now = Utc::now();
+
+    for mut background_check in BackgroundChecks::get_from_db(db) {
+        let due = match NewBackgroundCheck::from(background_check.clone()).post_adverse_notice_due() {
+            Some(due) => due,
+            None => continue,
+        };
+        if now < due {
+            continue;
+        }
+
+        // Wait for any dispute to be resolved before making a final decision.
+        if background_check.status == "dispute" {
+            println!(
+                "[checkr] report {} for {} is disputed, waiting to send the post-adverse action notice",
+                background_check.report_id, background_check.applicant_email
+            );
+            continue;
+        }
+
+        background_check.send_post_adverse_notice().await;
+        background_check.adverse_action_status = AdverseActionStatus::PostAdverseNoticeSent.to_string();
+        background_check.post_adverse_notice_sent_time = Some(now);
+        background_check.update(db).await;
+
+        println!(
+            "[checkr] sent post-adverse action notice to {} for report {}",
+            background_check.applicant_email, background_check.report_id
+        );
+    }
+}
+
+/// Refresh the background checks for the applicants who are onboarding.
+pub async fn refresh_background_checks(db: &Database) {
+    // Initialize the Checker client.
+    let checkr = Checkr::new_from_env();
+
+    let applicants = applicants::dsl::applicants
+        .filter(applicants::dsl::status.eq(crate::applicant_status::Status::Onboarding.to_string()))
+        .load::<Applicant>(&db.conn())
+        .unwrap();
+    for mut applicant in applicants {
+        let candidate = match checkr.get_candidate_by_email(&applicant.email).await {
+            Ok(Some(candidate)) => candidate,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("[checkr] getting the candidate for {} failed: {}", applicant.email, e);
+                continue;
+            }
+        };
+
+        for report_id in &candidate.report_ids {
+            // Get the report for the candidate.
+            match checkr.get_report(report_id).await {
+                Ok(report) => {
+                    ingest_background_check_report(db, &report, Some(&mut applicant)).await;
+                }
+                Err(e) => println!("[checkr] getting report {} for {} failed: {}", report_id, applicant.email, e),
+            }
+        }
+    }
+
+    refresh_adverse_actions(db).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{Datelike, TimeZone, Utc, Weekday};
+
+    use crate::background_checks::{
+        add_business_days, get_background_check_package, refresh_background_checks, AdverseActionStatus, BackgroundCheckPackageConfig, NewBackgroundCheck, DEFAULT_BACKGROUND_CHECK_PACKAGE,
+    };
+    use crate::db::Database;
+
+    fn package(package: &str, roles: &[&str], countries: &[&str]) -> BackgroundCheckPackageConfig {
+        BackgroundCheckPackageConfig {
+            package: package.to_string(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            countries: countries.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_get_background_check_package() {
+        let mut packages: BTreeMap<String, BackgroundCheckPackageConfig> = BTreeMap::new();
+        assert_eq!(get_background_check_package(&packages, "Engineering", "us").unwrap(), DEFAULT_BACKGROUND_CHECK_PACKAGE);
+
+        packages.insert("us".to_string(), package("premium_criminal", &[], &["us"]));
+        packages.insert("us-operations".to_string(), package("premium_criminal_mvr", &["Operations"], &["us"]));
+        packages.insert("international".to_string(), package("international_basic", &[], &[]));
+
+        assert_eq!(get_background_check_package(&packages, "Engineering", "US").unwrap(), "premium_criminal");
+        assert_eq!(get_background_check_package(&packages, "Operations", "us").unwrap(), "premium_criminal_mvr");
+        assert_eq!(get_background_check_package(&packages, "Operations", "gb").unwrap(), "international_basic");
+
+        packages.remove("international");
+        assert!(get_background_check_package(&packages, "Engineering", "gb").is_none());
+    }
+
+    #[test]
+    fn test_add_business_days() {
+        // A Friday.
+        let friday = Utc.ymd(2021, 6, 25).and_hms(17, 0, 0);
+        assert_eq!(friday.weekday(), Weekday::Fri);
+        assert_eq!(add_business_days(friday, 1), Utc.ymd(2021, 6, 28).and_hms(17, 0, 0));
+        assert_eq!(add_business_days(friday, 5), Utc.ymd(2021, 7, 2).and_hms(17, 0, 0));
+    }
+
+    #[test]
+    fn test_adverse_action() {
+        let mut report = NewBackgroundCheck {
+            report_id: "report".to_string(),
+            candidate_id: "candidate".to_string(),
+            applicant_email: "jane@example.com".to_string(),
+            package: "premium_criminal".to_string(),
+            status: "complete".to_string(),
+            result: "clear".to_string(),
+            adjudication: Default::default(),
+            screenings: vec!["ssn_trace".to_string()],
+            created_time: None,
+            estimated_completion_time: None,
+            completed_time: None,
+            adverse_action_requested: true,
+            adverse_action_reasons: "".to_string(),
+            adverse_action_status: Default::default(),
+            pre_adverse_notice_sent_time: None,
+            post_adverse_notice_sent_time: None,
+        };
+        assert!(report.can_start_adverse_action().unwrap_err().contains("clear"));
+
+        report.result = "consider".to_string();
+        assert!(report.can_start_adverse_action().unwrap_err().contains("reasons"));
+
+        report.adverse_action_reasons = "Reasons".to_string();
+        assert!(report.can_start_adverse_action().is_ok());
+        assert!(report.post_adverse_notice_due().is_none());
+
+        report.adverse_action_status = AdverseActionStatus::PreAdverseNoticeSent.to_string();
+        report.pre_adverse_notice_sent_time = Some(Utc.ymd(2021, 6, 25).and_hms(17, 0, 0));
+        assert!(report.can_start_adverse_action().is_err());
+        assert_eq!(report.post_adverse_notice_due().unwrap(), Utc.ymd(2021, 7, 2).and_hms(17, 0, 0));
+
+        report.adverse_action_status = AdverseActionStatus::Cancelled.to_string();
+        assert!(report.post_adverse_notice_due().is_none());
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_applicants_background_checks() {
+        let db = Database::new();
+
+        refresh_background_checks(&db).await;
+    }
+}
@@ -1,10 +1,9 @@
 #![allow(clippy::from_over_into)]
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::from_utf8;
-use std::thread;
-use std::time;
 
 use acme_lib::create_p384_key;
 use acme_lib::persist::FilePersist;
@@ -12,49 +11,64 @@ use acme_lib::{Directory, DirectoryUrl};
 use async_trait::async_trait;
 use chrono::NaiveDate;
 use chrono::{DateTime, TimeZone, Utc};
-use cloudflare::endpoints::{dns, zone};
-use cloudflare::framework::{
-    async_api::{ApiClient, Client},
-    auth::Credentials,
-    Environment, HttpApiClientConfig,
-};
 use hubcaps::Github;
 use macros::db;
+use openssl::hash::MessageDigest;
 use openssl::x509::X509;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
 
 use crate::airtable::{AIRTABLE_BASE_ID_MISC, AIRTABLE_CERTIFICATES_TABLE};
 use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::dns_providers::{dns_provider_from_env, DnsProvider, DnsRecord};
 use crate::schema::certificates;
+use crate::secrets::{decrypt_secret, encrypt_secret, is_encrypted_secret};
 use crate::utils::{create_or_update_file_in_github_repo, github_org};
 
-/// Creates a Let's Encrypt SSL certificate for a domain by using a DNS challenge.
-/// The DNS Challenge TXT record is added to Cloudflare automatically.
-pub async fn create_ssl_certificate(domain: &str) -> NewCertificate {
-    let email = env::var("CLOUDFLARE_EMAIL").unwrap();
+/// The file in the configs repo that holds the encrypted private key for a certificate.
+pub static ENCRYPTED_PRIVATE_KEY_FILE: &str = "privkey.pem.age";
 
-    // Create the Cloudflare client.
-    let cf_creds = Credentials::UserAuthKey {
-        email: env::var("CLOUDFLARE_EMAIL").unwrap(),
-        key: env::var("CLOUDFLARE_TOKEN").unwrap(),
-    };
-    let api_client = Client::new(cf_creds, HttpApiClientConfig::default(), Environment::Production).unwrap();
+/// The file the private key used to be saved in, before we encrypted them.
+/// We still write the plaintext key to this file on disk for the server that uses it.
+pub static LEGACY_PRIVATE_KEY_FILE: &str = "privkey.pem";
+
+/// The number of days before a certificate expires that we renew it, if the config does
+/// not set one.
+pub static DEFAULT_RENEWAL_WINDOW_DAYS: i32 = 7;
+
+/// Get the name of the TXT record Let's Encrypt looks for to validate a domain.
+/// Wildcard domains are validated with the record for their base domain.
+pub fn get_acme_challenge_record_name(domain: &str) -> String {
+    format!("_acme-challenge.{}", domain.trim_start_matches("*."))
+}
+
+/// Creates a Let's Encrypt SSL certificate for a domain, and any extra subject alternative
+/// names, in one order by using DNS challenges.
+/// The DNS Challenge TXT records are added with the DNS provider, and removed again once
+/// the challenges have been validated.
+/// If `staging` is set, the certificate is issued by the Let's Encrypt staging environment,
+/// which is not trusted by browsers but has much higher rate limits.
+pub async fn create_ssl_certificate(domain: &str, sans: &[String], staging: bool, dns_provider: &dyn DnsProvider) -> Result<NewCertificate, String> {
+    let email = env::var("CLOUDFLARE_EMAIL").map_err(|e| format!("getting CLOUDFLARE_EMAIL from the environment failed: {}", e))?;
 
     // Save/load keys and certificates to a temporary directory, we will re-save elsewhere.
     let persist = FilePersist::new(env::temp_dir());
 
     // Create a directory entrypoint.
-    // Use DirectoryUrl::LetsEncrypStaging for dev/testing.
-    let dir = Directory::from_url(persist, DirectoryUrl::LetsEncrypt).unwrap();
+    let url = if staging { DirectoryUrl::LetsEncryptStaging } else { DirectoryUrl::LetsEncrypt };
+    let dir = Directory::from_url(persist, url).map_err(|e| format!("creating acme directory failed: {}", e))?;
 
     // Reads the private account key from persistence, or
     // creates a new one before accessing the API to establish
     // that it's there.
-    let acc = dir.account(&email).unwrap();
+    let acc = dir.account(&email).map_err(|e| format!("getting acme account for {} failed: {}", email, e))?;
 
-    // Order a new TLS certificate for a domain.
-    let mut ord_new = acc.new_order(domain, &[]).unwrap();
+    // Order a new TLS certificate for the domain and all its alternative names.
+    let alt_names: Vec<&str> = sans.iter().map(|s| s.as_str()).filter(|s| *s != domain).collect();
+    let mut ord_new = acc.new_order(domain, &alt_names).map_err(|e| format!("creating order for {} failed: {}", domain, e))?;
 
     // If the ownership of the domain(s) have already been
     // authorized in a previous order, you might be able to
@@ -65,107 +79,66 @@ pub async fn create_ssl_certificate(domain: &str) -> NewCertificate {
             break ord_csr;
         }
 
-        // Get the possible authorizations (for a single domain
-        // this will only be one element).
-        let auths = ord_new.authorizations().unwrap();
+        // Get the authorizations, one for each name on the certificate.
+        let auths = ord_new.authorizations().map_err(|e| format!("getting authorizations for {} failed: {}", domain, e))?;
 
-        // Get the proff we need for the TXT record:
+        // Get the proofs we need for the TXT records:
         // _acme-challenge.<domain-to-be-proven>.  TXT  <proof>
-        let challenge = auths[0].dns_challenge();
-
-        // Create a TXT record for _acme-challenge.{domain} with the value of
-        // the proof.
-        // Use the Cloudflare API for this.
-
-        // We need the root of the domain not a subdomain.
-        let domain_parts: Vec<&str> = domain.split('.').collect();
-        let root_domain = format!("{}.{}", domain_parts[domain_parts.len() - 2], domain_parts[domain_parts.len() - 1]);
-
-        // Get the zone ID for the domain.
-        let zones = api_client
-            .request(&zone::ListZones {
-                params: zone::ListZonesParams {
-                    name: Some(root_domain.to_string()),
-                    ..Default::default()
-                },
-            })
-            .await
-            .unwrap()
-            .result;
-
-        // Our zone identifier should be the first record's ID.
-        let zone_identifier = &zones[0].id;
-        let record_name = format!("_acme-challenge.{}", domain.replace("*.", ""));
-
-        // Check if we already have a TXT record and we need to update it.
-        let dns_records = api_client
-            .request(&dns::ListDnsRecords {
-                zone_identifier: &zone_identifier,
-                params: dns::ListDnsRecordsParams {
-                    name: Some(record_name.to_string()),
-                    ..Default::default()
-                },
-            })
-            .await
-            .unwrap()
-            .result;
-
-        // If we have a dns record already, update it. If not, create it.
-        if dns_records.is_empty() {
-            // Create the DNS record.
-            let dns_record = api_client
-                .request(&dns::CreateDnsRecord {
-                    zone_identifier: &zone_identifier,
-                    params: dns::CreateDnsRecordParams {
-                        name: &record_name,
-                        content: dns::DnsContent::TXT { content: challenge.dns_proof() },
-                        ttl: None,
-                        proxied: None,
-                        priority: None,
-                    },
-                })
-                .await
-                .unwrap()
-                .result;
-
-            println!("[certs] created dns record: {:?}", dns_record);
-        } else {
-            // Update the DNS record.
-            let dns_record = api_client
-                .request(&dns::UpdateDnsRecord {
-                    zone_identifier: &zone_identifier,
-                    identifier: &dns_records[0].id,
-                    params: dns::UpdateDnsRecordParams {
-                        name: &record_name,
-                        content: dns::DnsContent::TXT { content: challenge.dns_proof() },
-                        ttl: None,
-                        proxied: None,
-                    },
-                })
-                .await
-                .unwrap()
-                .result;
-
-            println!("[certs] updated dns record: {:?}", dns_record);
+        // A wildcard and its base domain use the same record name, so group the proofs
+        // by name, that record then needs to hold both.
+        let mut challenges = Vec::new();
+        let mut records: BTreeMap<String, Vec<DnsRecord>> = BTreeMap::new();
+        for auth in auths.iter().filter(|a| a.need_challenge()) {
+            let challenge = auth.dns_challenge();
+            let record_name = get_acme_challenge_record_name(auth.domain_name());
+            let record = DnsRecord::TXT(challenge.dns_proof());
+
+            records.entry(record_name.to_string()).or_default().push(record.clone());
+            challenges.push((auth.domain_name().to_string(), record_name, record, challenge));
         }
 
-        // TODO: make this less awful than a sleep.
-        println!("validating the proof...");
-        let dur = time::Duration::from_secs(10);
-        thread::sleep(dur);
+        // Create the TXT records with the values of the proofs.
+        for (record_name, txts) in &records {
+            dns_provider.ensure_records(record_name, txts).await?;
+        }
 
-        // After the TXT record is accessible, the calls
+        // Wait for the TXT records to be on all the authoritative nameservers, before we
+        // ask Let's Encrypt to look for them.
+        println!("[certs] waiting for the proofs to propagate...");
+        for (_, record_name, record, _) in &challenges {
+            if let Err(e) = dns_provider.wait_for_propagation(record_name, record).await {
+                // Let's Encrypt might still see it, so try anyways.
+                println!("[certs] {}", e);
+            }
+        }
+
+        // After the TXT records are accessible, the calls
         // this to tell the ACME API to start checking the
-        // existence of the proof.
+        // existence of the proofs.
         //
         // The order at ACME will change status to either
         // confirm ownership of the domain, or fail due to the
         // not finding the proof. To see the change, we poll
         // the API with 5000 milliseconds wait between.
-        challenge.validate(5000).unwrap();
+        println!("[certs] validating the proofs...");
+        let mut validated = Ok(());
+        for (name, _, _, challenge) in challenges {
+            if let Err(e) = challenge.validate(5000) {
+                validated = Err(format!("validating the proof for {} failed: {}", name, e));
+                break;
+            }
+        }
+
+        // We don't need the TXT records anymore, whether or not it worked.
+        for (record_name, txts) in &records {
+            if let Err(e) = dns_provider.delete_record(record_name, &txts[0]).await {
+                println!("[certs] cleaning up the proof failed: {}", e);
+            }
+        }
+        validated?;
 
         // Update the state against the ACME API.
-        ord_new.refresh().unwrap();
+        ord_new.refresh().map_err(|e| format!("refreshing order for {} failed: {}", domain, e))?;
     };
 
     // Ownership is proven. Create a private key for
@@ -177,19 +150,28 @@ pub async fn create_ssl_certificate(domain: &str) -> NewCertificate {
     // state of "processing" that must be polled until the
     // certificate is either issued or rejected. Again we poll
     // for the status change.
-    let ord_cert = ord_csr.finalize_pkey(pkey_pri, 5000).unwrap();
+    let ord_cert = ord_csr.finalize_pkey(pkey_pri, 5000).map_err(|e| format!("finalizing order for {} failed: {}", domain, e))?;
 
     // Now download the certificate. Also stores the cert in
     // the persistence.
-    let cert = ord_cert.download_and_save_cert().unwrap();
+    let cert = ord_cert.download_and_save_cert().map_err(|e| format!("downloading certificate for {} failed: {}", domain, e))?;
 
-    NewCertificate {
-        private_key: cert.private_key().to_string(),
-        certificate: cert.certificate().to_string(),
+    let mut certificate = NewCertificate {
         domain: domain.to_string(),
+        certificate: cert.certificate().to_string(),
+        encrypted_private_key: Default::default(),
+        fingerprint: Default::default(),
         valid_days_left: cert.valid_days_left() as i32,
         expiration_date: crate::utils::default_date(),
-    }
+        sans: sans.to_vec(),
+        staging,
+        renewal_window_days: DEFAULT_RENEWAL_WINDOW_DAYS,
+    };
+    certificate.set_private_key(cert.private_key())?;
+    certificate.expiration_date = certificate.expiration_date()?.date().naive_utc();
+    certificate.fingerprint = certificate.fingerprint()?;
+
+    Ok(certificate)
 }
 
 /// A data type to hold the values of a let's encrypt certificate for a domain.
@@ -205,55 +187,180 @@ pub async fn create_ssl_certificate(domain: &str) -> NewCertificate {
 #[table_name = "certificates"]
 pub struct NewCertificate {
     pub domain: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
+    /// certificate is the PEM encoded full chain.
+    /// This is not serialized so it stays out of Airtable.
+    #[serde(default, skip_serializing)]
     pub certificate: String,
+    /// encrypted_private_key is the PEM encoded private key, encrypted with the key from
+    /// `crate::secrets`. Use `private_key()` to get the decrypted key.
+    /// This is not serialized so it stays out of Airtable.
+    #[serde(default, skip_serializing)]
+    pub encrypted_private_key: String,
+    /// fingerprint is the SHA-256 fingerprint of the certificate.
     #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub private_key: String,
+    pub fingerprint: String,
     #[serde(default)]
     pub valid_days_left: i32,
     #[serde(default = "crate::utils::default_date", serialize_with = "crate::configs::null_date_format::serialize")]
     pub expiration_date: NaiveDate,
+    /// sans are the extra subject alternative names on the certificate, for example
+    /// `*.oxide.computer` for a certificate for `oxide.computer`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sans: Vec<String>,
+    /// staging is set if the certificate is issued by the Let's Encrypt staging environment.
+    #[serde(default)]
+    pub staging: bool,
+    /// renewal_window_days is how many days before the certificate expires we renew it.
+    #[serde(default = "default_renewal_window_days")]
+    pub renewal_window_days: i32,
+}
+
+fn default_renewal_window_days() -> i32 {
+    DEFAULT_RENEWAL_WINDOW_DAYS
 }
 
 impl NewCertificate {
     /// For a certificate struct, populate the certificate fields for the domain.
-    /// This will create the cert from Let's Encrypt and update Cloudflare TXT records for the
-    /// verification.
-    pub async fn populate(&mut self) {
-        *self = create_ssl_certificate(&self.domain).await;
+    /// This will create the cert from Let's Encrypt and update the TXT records with the DNS
+    /// provider from the environment for the verification.
+    /// The settings from the config are kept as they are.
+    pub async fn populate(&mut self) -> Result<(), String> {
+        let dns_provider = dns_provider_from_env()?;
+        let cert = create_ssl_certificate(&self.domain, &self.sans, self.staging, dns_provider.as_ref()).await?;
+
+        self.certificate = cert.certificate;
+        self.encrypted_private_key = cert.encrypted_private_key;
+        self.fingerprint = cert.fingerprint;
+        self.valid_days_left = cert.valid_days_left;
+        self.expiration_date = cert.expiration_date;
+
+        Ok(())
+    }
+
+    /// Returns if the certificate expires within its renewal window and should be renewed.
+    pub fn needs_renewal(&self) -> bool {
+        self.certificate.is_empty() || self.valid_days_left <= self.renewal_window_days
+    }
+
+    /// Get the Slack message to alert on when renewing the certificate failed.
+    pub fn renewal_failed_slack_msg(&self, error: &str) -> Value {
+        let mut names = vec![self.domain.to_string()];
+        names.extend(self.sans.iter().filter(|s| **s != self.domain).cloned());
+
+        let expiry = if self.certificate.is_empty() {
+            "there is no existing certificate".to_string()
+        } else {
+            format!("the existing certificate expires in {} days on {}", self.valid_days_left, self.expiration_date)
+        };
+
+        json!(FormattedMessage {
+            channel: Default::default(),
+            attachments: Default::default(),
+            blocks: vec![
+                MessageBlock {
+                    block_type: MessageBlockType::Section,
+                    text: Some(MessageBlockText {
+                        text_type: MessageType::Markdown,
+                        text: format!(":rotating_light: renewing the certificate for *{}* failed, {}", names.join(", "), expiry),
+                    }),
+                    elements: Default::default(),
+                    accessory: Default::default(),
+                    block_id: Default::default(),
+                    fields: Default::default(),
+                },
+                MessageBlock {
+                    block_type: MessageBlockType::Context,
+                    elements: vec![MessageBlockText {
+                        text_type: MessageType::Markdown,
+                        text: format!("```{}```", error),
+                    }],
+                    text: Default::default(),
+                    accessory: Default::default(),
+                    block_id: Default::default(),
+                    fields: Default::default(),
+                }
+            ],
+        })
     }
 
     /// For a certificate struct, populate the certificate and private_key fields from
     /// GitHub, then fill in the rest.
-    pub async fn populate_from_github(&mut self, github: &Github) {
+    pub async fn populate_from_github(&mut self, github: &Github) -> Result<(), String> {
         let repo = github.repo(github_org(), "configs");
-        let r = repo.get().await.unwrap();
+        let r = repo.get().await.map_err(|e| format!("getting the configs repo failed: {}", e))?;
 
         if let Ok(cert) = repo.content().file(&self.get_github_path("fullchain.pem"), &r.default_branch).await {
-            self.certificate = from_utf8(&cert.content).unwrap().to_string();
+            self.certificate = from_utf8(&cert.content).map_err(|e| format!("certificate for {} is not valid utf8: {}", self.domain, e))?.to_string();
         }
-        if let Ok(p) = repo.content().file(&self.get_github_path("privkey.pem"), &r.default_branch).await {
-            self.private_key = from_utf8(&p.content).unwrap().to_string();
+        if let Ok(p) = repo.content().file(&self.get_github_path(ENCRYPTED_PRIVATE_KEY_FILE), &r.default_branch).await {
+            self.encrypted_private_key = from_utf8(&p.content)
+                .map_err(|e| format!("encrypted private key for {} is not valid utf8: {}", self.domain, e))?
+                .to_string();
+        } else if let Ok(p) = repo.content().file(&self.get_github_path(LEGACY_PRIVATE_KEY_FILE), &r.default_branch).await {
+            // We have not migrated this key yet, encrypt it.
+            // The plaintext file is deleted the next time we save to GitHub.
+            self.set_private_key(from_utf8(&p.content).map_err(|e| format!("private key for {} is not valid utf8: {}", self.domain, e))?)?;
         }
 
-        let exp_date = self.expiration_date();
-        self.expiration_date = exp_date.date().naive_utc();
-        self.valid_days_left = self.valid_days_left();
+        self.encrypt_plaintext_private_key()?;
+        self.populate_from_certificate()
     }
 
     /// For a certificate struct, populate the certificate and private_key fields from
     /// disk, then fill in the rest.
-    pub fn populate_from_disk(&mut self, dir: &str) {
+    pub fn populate_from_disk(&mut self, dir: &str) -> Result<(), String> {
         let path = self.get_path(dir);
 
         self.certificate = fs::read_to_string(path.join("fullchain.pem")).unwrap_or_default();
-        self.private_key = fs::read_to_string(path.join("privkey.pem")).unwrap_or_default();
+        let private_key = fs::read_to_string(path.join(LEGACY_PRIVATE_KEY_FILE)).unwrap_or_default();
+        if !private_key.is_empty() {
+            self.set_private_key(&private_key)?;
+        }
+
+        self.populate_from_certificate()
+    }
+
+    /// Fill in the expiration date, valid days left and fingerprint from the certificate,
+    /// if we have one.
+    fn populate_from_certificate(&mut self) -> Result<(), String> {
+        if self.certificate.is_empty() {
+            return Ok(());
+        }
+
+        self.expiration_date = self.expiration_date()?.date().naive_utc();
+        self.valid_days_left = self.valid_days_left()?;
+        self.fingerprint = self.fingerprint()?;
+
+        Ok(())
+    }
 
-        if !self.certificate.is_empty() {
-            let exp_date = self.expiration_date();
-            self.expiration_date = exp_date.date().naive_utc();
-            self.valid_days_left = self.valid_days_left();
+    /// Decrypt and return the PEM encoded private key.
+    /// Only call this when you need to hand the key to something that serves the certificate.
+    pub fn private_key(&self) -> Result<String, String> {
+        get_private_key(&self.domain, &self.encrypted_private_key)
+    }
+
+    /// Encrypt and set the PEM encoded private key. Every encryption gives a different
+    /// ciphertext, so the one we have is kept if it is already this key.
+    pub fn set_private_key(&mut self, private_key: &str) -> Result<(), String> {
+        if is_encrypted_secret(&self.encrypted_private_key) && self.private_key().ok().as_deref() == Some(private_key) {
+            return Ok(());
         }
+
+        self.encrypted_private_key = encrypt_secret(private_key)?;
+
+        Ok(())
+    }
+
+    /// Encrypt the private key if it is still in plaintext, from before we encrypted them.
+    pub fn encrypt_plaintext_private_key(&mut self) -> Result<(), String> {
+        if self.encrypted_private_key.is_empty() || is_encrypted_secret(&self.encrypted_private_key) {
+            return Ok(());
+        }
+
+        self.encrypted_private_key = encrypt_secret(&self.encrypted_private_key)?;
+
+        Ok(())
     }
 
     fn get_path(&self, dir: &str) -> PathBuf {
@@ -265,25 +372,93 @@ impl NewCertificate {
     }
 
     /// Saves the fullchain certificate and privkey to /{dir}/{domain}/{privkey.pem,fullchain.pem}
-    pub fn save_to_directory(&self, dir: &str) {
+    pub fn save_to_directory(&self, dir: &str) -> Result<(), String> {
         let path = self.get_path(dir);
+        // The server reading these needs the plaintext key, so this is the one place we decrypt it.
+        let private_key = self.private_key()?;
 
         // Create the directory if it does not exist.
-        fs::create_dir_all(path.clone()).unwrap();
+        fs::create_dir_all(&path).map_err(|e| format!("creating {} failed: {}", path.display(), e))?;
 
         // Write the files.
-        fs::write(path.join("fullchain.pem"), self.certificate.as_bytes()).unwrap();
-        fs::write(path.join("privkey.pem"), self.private_key.as_bytes()).unwrap();
+        fs::write(path.join("fullchain.pem"), self.certificate.as_bytes()).map_err(|e| format!("writing the certificate for {} failed: {}", self.domain, e))?;
+        fs::write(path.join(LEGACY_PRIVATE_KEY_FILE), private_key.as_bytes()).map_err(|e| format!("writing the private key for {} failed: {}", self.domain, e))?;
+
+        Ok(())
     }
 
-    /// Saves the fullchain certificate and privkey to the configs github repo.
+    /// Saves the fullchain certificate and the encrypted privkey to the configs github repo.
     pub async fn save_to_github_repo(&self, github: &Github) {
         let repo = github.repo(github_org(), "configs");
         let r = repo.get().await.unwrap();
 
         // Write the files.
         create_or_update_file_in_github_repo(&repo, &r.default_branch, &self.get_github_path("fullchain.pem"), self.certificate.as_bytes().to_vec()).await;
-        create_or_update_file_in_github_repo(&repo, &r.default_branch, &self.get_github_path("privkey.pem"), self.private_key.as_bytes().to_vec()).await;
+        create_or_update_file_in_github_repo(
+            &repo,
+            &r.default_branch,
+            &self.get_github_path(ENCRYPTED_PRIVATE_KEY_FILE),
+            self.encrypted_private_key.as_bytes().to_vec(),
+        )
+        .await;
+
+        // Delete the plaintext key if we still have one from before we encrypted them.
+        self.delete_plaintext_private_key_from_github_repo(github).await;
+    }
+
+    /// Deletes the plaintext privkey from the configs github repo, if we still have one from
+    /// before we encrypted them. The encrypted privkey is saved first so we never lose the key.
+    pub async fn delete_plaintext_private_key_from_github_repo(&self, github: &Github) {
+        let repo = github.repo(github_org(), "configs");
+        let r = repo.get().await.unwrap();
+
+        let legacy_path = self.get_github_path(LEGACY_PRIVATE_KEY_FILE);
+        let legacy = match repo.content().file(&legacy_path, &r.default_branch).await {
+            Ok(l) => l,
+            // We don't have a plaintext key, we can return early.
+            Err(_) => return,
+        };
+
+        if !is_encrypted_secret(&self.encrypted_private_key) {
+            println!("[certs] not deleting `{}` from the configs repo, we do not have the encrypted key yet", legacy_path);
+            return;
+        }
+        create_or_update_file_in_github_repo(
+            &repo,
+            &r.default_branch,
+            &self.get_github_path(ENCRYPTED_PRIVATE_KEY_FILE),
+            self.encrypted_private_key.as_bytes().to_vec(),
+        )
+        .await;
+
+        repo.content()
+            .delete(
+                &legacy_path,
+                &format!(
+                    "Deleting plaintext private key {} programatically\n\nIt has been replaced by {}.",
+                    legacy_path, ENCRYPTED_PRIVATE_KEY_FILE
+                ),
+                &legacy.sha,
+                &r.default_branch,
+            )
+            .await
+            .unwrap();
+        println!("[certs] deleted plaintext private key `{}` from the configs repo", legacy_path);
+    }
+
+    /// Get the SHA-256 fingerprint of the certificate, in the same format as
+    /// `openssl x509 -noout -fingerprint -sha256`.
+    pub fn fingerprint(&self) -> Result<String, String> {
+        if self.certificate.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let x509 = self.x509()?;
+        let digest = x509
+            .digest(MessageDigest::sha256())
+            .map_err(|e| format!("getting the fingerprint of the certificate for {} failed: {}", self.domain, e))?;
+
+        Ok(digest.iter().map(|b| format!("{:02X}", b)).collect::<Vec<String>>().join(":"))
     }
 
     /// Inspect the certificate to count the number of (whole) valid days left.
@@ -293,32 +468,84 @@ impl NewCertificate {
     /// issued cert, since it counts _whole_ days.
     ///
     /// It is possible to get negative days for an expired certificate.
-    pub fn valid_days_left(&self) -> i32 {
-        let expires = self.expiration_date();
+    pub fn valid_days_left(&self) -> Result<i32, String> {
+        let expires = self.expiration_date()?;
         let dur = expires - Utc::now();
 
-        dur.num_days() as i32
+        Ok(dur.num_days() as i32)
     }
 
     /// Inspect the certificate to get the expiration_date.
-    pub fn expiration_date(&self) -> DateTime<Utc> {
+    pub fn expiration_date(&self) -> Result<DateTime<Utc>, String> {
         if self.certificate.is_empty() {
-            return Utc::now();
+            return Ok(Utc::now());
         }
 
         // load as x509
-        let x509 = X509::from_pem(self.certificate.as_bytes()).expect("from_pem");
+        let x509 = self.x509()?;
 
         // convert asn1 time to Tm
         let not_after = format!("{}", x509.not_after());
         // Display trait produces this format, which is kinda dumb.
         // Apr 19 08:48:46 2019 GMT
-        Utc.datetime_from_str(&not_after, "%h %e %H:%M:%S %Y %Z").expect("strptime")
+        Utc.datetime_from_str(&not_after, "%h %e %H:%M:%S %Y %Z")
+            .map_err(|e| format!("parsing the expiration date `{}` of the certificate for {} failed: {}", not_after, self.domain, e))
+    }
+
+    /// Parse the PEM encoded certificate.
+    fn x509(&self) -> Result<X509, String> {
+        X509::from_pem(self.certificate.as_bytes()).map_err(|e| format!("parsing the certificate for {} failed: {}", self.domain, e))
     }
 }
 
 /// Implement updating the Airtable record for a Certificate.
 #[async_trait]
 impl UpdateAirtableRecord<Certificate> for Certificate {
-    async fn update_airtable_record(&mut self, _record: Certificate) {}
+    async fn update_airtable_record(&mut self, _record: Certificate) {
+        // Only the metadata goes to Airtable, these are never serialized so clear them
+        // here too so we can tell when the record is unchanged.
+        self.certificate = Default::default();
+        self.encrypted_private_key = Default::default();
+    }
+}
+
+impl Certificate {
+    /// Get the number of (whole) days until the certificate expires, as of today.
+    /// This does not need the certificate itself, so it works on the rows from the database.
+    pub fn days_to_expiry(&self) -> i32 {
+        (self.expiration_date - Utc::today().naive_utc()).num_days() as i32
+    }
+
+    /// Decrypt and return the PEM encoded private key.
+    /// Only call this when you need to hand the key to something that serves the certificate.
+    pub fn private_key(&self) -> Result<String, String> {
+        get_private_key(&self.domain, &self.encrypted_private_key)
+    }
+}
+
+/// Decrypt the private key for the domain. A key saved before we encrypted them is
+/// returned as it is, until the next sync encrypts it.
+fn get_private_key(domain: &str, encrypted_private_key: &str) -> Result<String, String> {
+    if encrypted_private_key.is_empty() {
+        return Err(format!("certificate for {} does not have a private key", domain));
+    }
+    if !is_encrypted_secret(encrypted_private_key) {
+        return Ok(encrypted_private_key.to_string());
+    }
+
+    decrypt_secret(encrypted_private_key)
+}
+
+impl Certificates {
+    /// Get all the certificates from the database with their days to expiry refreshed,
+    /// the ones expiring soonest first.
+    pub fn get_by_expiry_from_db(db: &Database) -> Self {
+        let mut certificates = Certificates::get_from_db(db).0;
+        for certificate in certificates.iter_mut() {
+            certificate.valid_days_left = certificate.days_to_expiry();
+        }
+        certificates.sort_by(|a, b| a.valid_days_left.cmp(&b.valid_days_left).then_with(|| a.domain.cmp(&b.domain)));
+
+        Certificates(certificates)
+    }
 }
@@ -17,14 +17,22 @@ use sendgrid_api::SendGrid;
 use serde::{Deserialize, Serialize};
 
 use crate::airtable::{AIRTABLE_BASE_ID_DIRECTORY, AIRTABLE_BUILDINGS_TABLE, AIRTABLE_CONFERENCE_ROOMS_TABLE, AIRTABLE_EMPLOYEES_TABLE, AIRTABLE_GROUPS_TABLE, AIRTABLE_LINKS_TABLE};
+use crate::applicant_offers::OfferTemplateConfig;
+use crate::applicant_retention::ApplicantRetentionPolicyConfig;
+use crate::applicant_reviews::ApplicantReviewersConfig;
 use crate::applicants::Applicant;
+use crate::background_checks::BackgroundCheckPackageConfig;
 use crate::certs::{Certificate, Certificates, NewCertificate};
 use crate::core::UpdateAirtableRecord;
 use crate::db::Database;
+use crate::envelopes::EnvelopeRemindersConfig;
 use crate::gsuite::{update_google_group_settings, update_group_aliases, update_gsuite_building, update_gsuite_calendar_resource};
+use crate::interview_schedules::InterviewPanelConfig;
 use crate::schema::{applicants, buildings, conference_rooms, groups, links, users};
 use crate::shipments::NewOutboundShipment;
-use crate::templates::{generate_terraform_files_for_aws_and_github, generate_terraform_files_for_okta};
+use crate::slack::{get_infra_channel_post_url, post_to_channel};
+use crate::tailscale::{generate_tailscale_acl, TailscaleDevicePolicy};
+use crate::templates::{generate_terraform_files_for_aws_and_github, generate_terraform_files_for_gsuite, generate_terraform_files_for_okta, generate_terraform_files_for_slack};
 use crate::utils::{get_github_user_public_ssh_keys, get_gsuite_token, github_org, DOMAIN, GSUITE_DOMAIN};
 
 /// The data type for our configuration files.
@@ -45,6 +53,33 @@ pub struct Config {
 
     #[serde(default)]
     pub certificates: BTreeMap<String, NewCertificate>,
+
+    #[serde(default, alias = "tailscale-device-policies")]
+    pub tailscale_device_policies: BTreeMap<String, TailscaleDevicePolicy>,
+
+    /// How we assign reviewers to applicants, by role.
+    #[serde(default, alias = "applicant-reviewers")]
+    pub applicant_reviewers: BTreeMap<String, ApplicantReviewersConfig>,
+
+    /// The offer letter templates in DocuSign, by country and type of employment.
+    #[serde(default, alias = "offer-templates")]
+    pub offer_templates: BTreeMap<String, OfferTemplateConfig>,
+
+    /// How we remind people to sign their offer letters.
+    #[serde(default, alias = "envelope-reminders")]
+    pub envelope_reminders: EnvelopeRemindersConfig,
+
+    /// The background check packages in Checkr, by role and country.
+    #[serde(default, alias = "background-check-packages")]
+    pub background_check_packages: BTreeMap<String, BackgroundCheckPackageConfig>,
+
+    /// How long we keep applicant data, by applicant status.
+    #[serde(default, alias = "applicant-retention-policies")]
+    pub applicant_retention_policies: BTreeMap<String, ApplicantRetentionPolicyConfig>,
+
+    /// The interview panels applicants schedule their interviews with, by role.
+    #[serde(default, alias = "interview-panels")]
+    pub interview_panels: BTreeMap<String, InterviewPanelConfig>,
 }
 
 impl Config {
@@ -986,15 +1021,23 @@ pub struct HuddleConfig {
 pub async fn get_configs_from_repo(github: &Github) -> Config {
     let repo = github.repo(github_org(), "configs");
     let r = repo.get().await.unwrap();
+
+    get_configs_from_repo_at_ref(github, &r.default_branch).await
+}
+
+/// Get the configs from the configs repo at a branch or commit, for example the
+/// head of a pull request.
+pub async fn get_configs_from_repo_at_ref(github: &Github, git_ref: &str) -> Config {
+    let repo = github.repo(github_org(), "configs");
     let repo_contents = repo.content();
 
-    let files = repo_contents.iter("/configs/", &r.default_branch).try_collect::<Vec<hubcaps::content::DirectoryItem>>().await.unwrap();
+    let files = repo_contents.iter("/configs/", git_ref).try_collect::<Vec<hubcaps::content::DirectoryItem>>().await.unwrap();
 
     let mut file_contents = String::new();
     for file in files {
         println!("decoding {}", file.name);
         // Get the contents of the file.
-        let contents = repo_contents.file(&format!("/{}", file.path), &r.default_branch).await.unwrap();
+        let contents = repo_contents.file(&format!("/{}", file.path), git_ref).await.unwrap();
 
         let decoded = from_utf8(&contents.content).unwrap().trim().to_string();
 
@@ -1524,18 +1567,45 @@ pub async fn sync_certificates(db: &Database, github: &Github, certificates: BTr
     }
     // Sync certificates.
     for (_, mut certificate) in certificates {
-        certificate.populate_from_github(github).await;
+        // Start from the key we have, so it is not encrypted again if it did not change.
+        if let Some(existing) = certificate_map.get(&certificate.domain) {
+            certificate.encrypted_private_key = existing.encrypted_private_key.to_string();
+        }
+        if let Err(e) = certificate.populate_from_github(github).await {
+            println!("[certs] reading cert {} from the configs repo failed: {}", certificate.domain, e);
 
-        // If the cert is going to expire in less than 7 days, renew it.
+            // Let the infra channel know, we can't tell when it expires or renew it safely.
+            // Keep what we have in the database.
+            post_to_channel(get_infra_channel_post_url(), certificate.renewal_failed_slack_msg(&e)).await;
+            certificate_map.remove(&certificate.domain);
+            continue;
+        }
+
+        // If the cert is going to expire within its renewal window, renew it.
         // Otherwise, return early.
-        if certificate.valid_days_left > 7 {
+        if !certificate.needs_renewal() {
             println!("cert {} is valid for {} more days, skipping", certificate.domain, certificate.valid_days_left);
+
+            // Make sure we are not keeping a plaintext key from before we encrypted them.
+            certificate.delete_plaintext_private_key_from_github_repo(github).await;
         } else {
+            // Keep what we have, so a failed renewal does not clobber a working certificate.
+            let existing = certificate.clone();
+
             // Populate the certificate.
-            certificate.populate().await;
+            match certificate.populate().await {
+                Ok(_) => {
+                    // Save the certificate to disk.
+                    certificate.save_to_github_repo(github).await;
+                }
+                Err(e) => {
+                    println!("[certs] renewing cert {} failed: {}", certificate.domain, e);
+                    certificate = existing;
 
-            // Save the certificate to disk.
-            certificate.save_to_github_repo(github).await;
+                    // Let the infra channel know, before the certificate expires.
+                    post_to_channel(get_infra_channel_post_url(), certificate.renewal_failed_slack_msg(&e)).await;
+                }
+            }
         }
 
         // Update the database and Airtable.
@@ -1581,6 +1651,13 @@ pub async fn refresh_db_configs_and_airtable(github: &Github) {
     generate_terraform_files_for_okta(github, &db).await;
     // Generate the terraform files for teams.
     generate_terraform_files_for_aws_and_github(github, &db).await;
+    // Generate the terraform files for the GSuite groups and resources and the Slack user groups.
+    generate_terraform_files_for_gsuite(github, &db).await;
+    generate_terraform_files_for_slack(github, &db).await;
+    // Generate the Tailscale ACL with the groups, for review.
+    if let Err(e) = generate_tailscale_acl(github, &db).await {
+        println!("[tailscale] {}", e);
+    }
 
     // Sync links.
     sync_links(&db, configs.links, configs.huddles).await;
@@ -1664,7 +1741,7 @@ pub async fn refresh_anniversary_events(db: &Database) {
 
         if user.google_anniversary_event_id.is_empty() {
             // Create the event.
-            let event = gsuite.create_calendar_event(&anniversary_cal_id, &new_event).await.unwrap();
+            let event = gsuite.create_calendar_event(&anniversary_cal_id, &new_event, false).await.unwrap();
             println!("created event for user {} anniversary: {:?}", user.username, event);
 
             user.google_anniversary_event_id = event.id.to_string();
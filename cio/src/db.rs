@@ -29,4 +29,10 @@ impl Database {
     pub fn conn(&self) -> r2d2::PooledConnection<r2d2::ConnectionManager<PgConnection>> {
         self.pool.get().unwrap_or_else(|e| panic!("getting a connection from the pool failed: {}", e))
     }
+
+    /// Returns a connection from the pool, or an error if we could not get one.
+    /// Use this over `conn` where a database problem should not take down the caller.
+    pub fn try_conn(&self) -> Result<r2d2::PooledConnection<r2d2::ConnectionManager<PgConnection>>, String> {
+        self.pool.get().map_err(|e| format!("getting a connection from the pool failed: {}", e))
+    }
 }
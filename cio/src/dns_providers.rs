@@ -0,0 +1,718 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use cloudflare::endpoints::{dns, zone};
+use cloudflare::framework::{
+    async_api::{ApiClient, Client},
+    auth::Credentials,
+    Environment, HttpApiClientConfig,
+};
+use tokio::time::{sleep, Duration, Instant};
+use trust_dns_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+use trust_dns_resolver::proto::xfer::DnsRequestOptions;
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// The TTL we set on the records we create.
+/// This is the minimum Cloudflare allows.
+pub static DNS_RECORD_TTL: u32 = 120;
+
+/// How long we wait for a record to show up on all the authoritative nameservers.
+pub static DNS_PROPAGATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long we wait between checking the authoritative nameservers.
+pub static DNS_PROPAGATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A DNS record we manage.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DnsRecord {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    CNAME(String),
+    TXT(String),
+}
+
+impl DnsRecord {
+    /// Create a record from its type and value, as they would be written in a zone file.
+    pub fn new(record_type: &str, value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        match record_type.trim().to_uppercase().as_str() {
+            "A" => Ok(DnsRecord::A(value.parse().map_err(|e| format!("`{}` is not a valid IPv4 address: {}", value, e))?)),
+            "AAAA" => Ok(DnsRecord::AAAA(value.parse().map_err(|e| format!("`{}` is not a valid IPv6 address: {}", value, e))?)),
+            "CNAME" => Ok(DnsRecord::CNAME(value.trim_end_matches('.').to_lowercase())),
+            "TXT" => Ok(DnsRecord::TXT(value.trim_matches('"').to_string())),
+            t => Err(format!("`{}` is not a record type we manage, try `A`, `AAAA`, `CNAME` or `TXT`", t)),
+        }
+    }
+
+    /// Returns the type of the record, as it would be written in a zone file.
+    pub fn record_type(&self) -> &'static str {
+        match self {
+            DnsRecord::A(_) => "A",
+            DnsRecord::AAAA(_) => "AAAA",
+            DnsRecord::CNAME(_) => "CNAME",
+            DnsRecord::TXT(_) => "TXT",
+        }
+    }
+
+    /// Returns the value of the record, as it would be written in a zone file.
+    pub fn value(&self) -> String {
+        match self {
+            DnsRecord::A(ip) => ip.to_string(),
+            DnsRecord::AAAA(ip) => ip.to_string(),
+            DnsRecord::CNAME(target) => target.to_string(),
+            DnsRecord::TXT(content) => content.to_string(),
+        }
+    }
+
+    fn to_cloudflare(&self) -> dns::DnsContent {
+        match self {
+            DnsRecord::A(ip) => dns::DnsContent::A { content: *ip },
+            DnsRecord::AAAA(ip) => dns::DnsContent::AAAA { content: *ip },
+            DnsRecord::CNAME(target) => dns::DnsContent::CNAME { content: target.to_string() },
+            DnsRecord::TXT(content) => dns::DnsContent::TXT { content: content.to_string() },
+        }
+    }
+
+    fn from_cloudflare(content: &dns::DnsContent) -> Option<Self> {
+        match content {
+            dns::DnsContent::A { content } => Some(DnsRecord::A(*content)),
+            dns::DnsContent::AAAA { content } => Some(DnsRecord::AAAA(*content)),
+            dns::DnsContent::CNAME { content } => Some(DnsRecord::CNAME(content.to_string())),
+            dns::DnsContent::TXT { content } => Some(DnsRecord::TXT(content.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DnsRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.record_type(), self.value())
+    }
+}
+
+/// Get the zone a domain lives in, using the public suffix list.
+/// For example, `rfd.oxide.computer` is in `oxide.computer` and `thing.example.co.uk` is in
+/// `example.co.uk`.
+pub fn get_zone_for_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim().trim_start_matches("*.").trim_end_matches('.').to_lowercase();
+
+    match psl::domain_str(&domain) {
+        Some(zone) => Ok(zone.to_string()),
+        None => Err(format!("could not find the zone for `{}` in the public suffix list", domain)),
+    }
+}
+
+/// A provider we can manage DNS records with.
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Make sure `name` has exactly the given records of their type, creating them and
+    /// replacing or removing any other existing records of that type.
+    /// The records must all be of the same type.
+    async fn ensure_records(&self, name: &str, records: &[DnsRecord]) -> Result<(), String>;
+
+    /// Make sure `name` has a record of the given type with the given value, creating it or
+    /// replacing the existing record of the same type.
+    async fn ensure_record(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        self.ensure_records(name, &[record.clone()]).await
+    }
+
+    /// Delete the records of the same type as `record` for `name`.
+    async fn delete_record(&self, name: &str, record: &DnsRecord) -> Result<(), String>;
+
+    /// List all the A, AAAA, CNAME and TXT records in a zone, by their fully qualified name.
+    /// Records of any other type are left out, since we do not manage them.
+    async fn list_records(&self, zone: &str) -> Result<Vec<(String, DnsRecord)>, String>;
+
+    /// Wait until every authoritative nameserver for the zone returns the record.
+    async fn wait_for_propagation(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        wait_for_propagation_on_authoritative_nameservers(name, record).await
+    }
+}
+
+/// Get the DNS provider from the `DNS_PROVIDER` environment variable.
+/// This is one of `cloudflare` (the default), `rfc2136` or `mock`.
+/// Returns an error if the environment variables the provider needs are not set.
+pub fn dns_provider_from_env() -> Result<Box<dyn DnsProvider>, String> {
+    match env::var("DNS_PROVIDER").unwrap_or_default().to_lowercase().trim() {
+        "" | "cloudflare" => {
+            check_env(&["CLOUDFLARE_EMAIL", "CLOUDFLARE_TOKEN"])?;
+            Ok(Box::new(CloudflareDnsProvider::new_from_env()))
+        }
+        "rfc2136" => {
+            check_env(&["RFC2136_NAMESERVER"])?;
+            Ok(Box::new(Rfc2136DnsProvider::new_from_env()))
+        }
+        "mock" => Ok(Box::new(MockDnsProvider::default())),
+        p => Err(format!("`{}` is not a DNS provider we know about, try `cloudflare`, `rfc2136` or `mock`", p)),
+    }
+}
+
+fn check_env(vars: &[&str]) -> Result<(), String> {
+    let missing: Vec<&str> = vars.iter().filter(|v| env::var(v).unwrap_or_default().is_empty()).copied().collect();
+    if !missing.is_empty() {
+        return Err(format!("${} must be set", missing.join(", $")));
+    }
+
+    Ok(())
+}
+
+/// Poll the authoritative nameservers for the zone of `name` until they all return the record.
+pub async fn wait_for_propagation_on_authoritative_nameservers(name: &str, record: &DnsRecord) -> Result<(), String> {
+    let zone = get_zone_for_domain(name)?;
+
+    // Find the authoritative nameservers for the zone.
+    let system = TokioAsyncResolver::tokio_from_system_conf().map_err(|e| format!("creating dns resolver failed: {}", e))?;
+    let nameservers = system.ns_lookup(zone.as_str()).await.map_err(|e| format!("looking up the nameservers for `{}` failed: {}", zone, e))?;
+
+    // Create a resolver for each nameserver, so that we know the record is on all of them.
+    // We turn off the cache so we see changes as soon as they happen.
+    let mut resolvers: Vec<(String, TokioAsyncResolver)> = Default::default();
+    for ns in nameservers.iter() {
+        let ips: Vec<IpAddr> = match system.lookup_ip(ns.to_string().as_str()).await {
+            Ok(ips) => ips.iter().collect(),
+            Err(e) => {
+                println!("[dns] looking up the ips for nameserver `{}` failed: {}", ns, e);
+                continue;
+            }
+        };
+
+        let config = ResolverConfig::from_parts(None, vec![], NameServerConfigGroup::from_ips_clear(&ips, 53, true));
+        let mut opts = ResolverOpts::default();
+        opts.cache_size = 0;
+        let resolver = TokioAsyncResolver::tokio(config, opts).map_err(|e| format!("creating dns resolver for `{}` failed: {}", ns, e))?;
+        resolvers.push((ns.to_string(), resolver));
+    }
+    if resolvers.is_empty() {
+        return Err(format!("found no authoritative nameservers for `{}`", zone));
+    }
+
+    let start = Instant::now();
+    loop {
+        let mut pending: Vec<String> = Default::default();
+        for (ns, resolver) in &resolvers {
+            if !nameserver_has_record(resolver, name, record).await {
+                pending.push(ns.to_string());
+            }
+        }
+
+        if pending.is_empty() {
+            println!("[dns] `{}` {} has propagated to all {} nameservers", name, record, resolvers.len());
+            return Ok(());
+        }
+
+        if start.elapsed() > DNS_PROPAGATION_TIMEOUT {
+            return Err(format!(
+                "`{}` {} did not propagate to {} after {} seconds",
+                name,
+                record,
+                pending.join(", "),
+                DNS_PROPAGATION_TIMEOUT.as_secs()
+            ));
+        }
+
+        println!("[dns] waiting for `{}` {} to propagate to {}", name, record, pending.join(", "));
+        sleep(DNS_PROPAGATION_INTERVAL).await;
+    }
+}
+
+async fn nameserver_has_record(resolver: &TokioAsyncResolver, name: &str, record: &DnsRecord) -> bool {
+    match record {
+        DnsRecord::A(_) | DnsRecord::AAAA(_) => match resolver.lookup_ip(name).await {
+            Ok(ips) => ips.iter().any(|ip| ip.to_string() == record.value()),
+            Err(_) => false,
+        },
+        DnsRecord::CNAME(target) => match resolver.lookup(name, RecordType::CNAME, DnsRequestOptions::default()).await {
+            Ok(lookup) => lookup.iter().any(|r| match r {
+                RData::CNAME(n) => n.to_string().trim_end_matches('.') == target.trim_end_matches('.'),
+                _ => false,
+            }),
+            Err(_) => false,
+        },
+        DnsRecord::TXT(content) => match resolver.txt_lookup(name).await {
+            Ok(txts) => txts
+                .iter()
+                .any(|txt| txt.txt_data().iter().map(|d| String::from_utf8_lossy(d).to_string()).collect::<String>() == *content),
+            Err(_) => false,
+        },
+    }
+}
+
+/// Manage DNS records in Cloudflare.
+pub struct CloudflareDnsProvider {
+    client: Client,
+}
+
+impl CloudflareDnsProvider {
+    /// Create a Cloudflare provider from the `CLOUDFLARE_EMAIL` and `CLOUDFLARE_TOKEN`
+    /// environment variables.
+    pub fn new_from_env() -> Self {
+        let cf_creds = Credentials::UserAuthKey {
+            email: env::var("CLOUDFLARE_EMAIL").unwrap(),
+            key: env::var("CLOUDFLARE_TOKEN").unwrap(),
+        };
+
+        CloudflareDnsProvider {
+            client: Client::new(cf_creds, HttpApiClientConfig::default(), Environment::Production).unwrap(),
+        }
+    }
+
+    /// Get the Cloudflare zone identifier for the zone `name` is in.
+    async fn get_zone_identifier(&self, name: &str) -> Result<String, String> {
+        let zone = get_zone_for_domain(name)?;
+
+        let zones = self
+            .client
+            .request(&zone::ListZones {
+                params: zone::ListZonesParams {
+                    name: Some(zone.to_string()),
+                    ..Default::default()
+                },
+            })
+            .await
+            .map_err(|e| format!("listing cloudflare zones for `{}` failed: {}", zone, e))?
+            .result;
+
+        // Our zone identifier should be the first record's ID.
+        match zones.get(0) {
+            Some(z) => Ok(z.id.to_string()),
+            None => Err(format!("cloudflare has no zone `{}`", zone)),
+        }
+    }
+
+    /// Get the existing records for `name` that have the same type as `record`.
+    async fn get_existing_records(&self, zone_identifier: &str, name: &str, record: &DnsRecord) -> Result<Vec<dns::DnsRecord>, String> {
+        let records = self
+            .client
+            .request(&dns::ListDnsRecords {
+                zone_identifier,
+                params: dns::ListDnsRecordsParams {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            })
+            .await
+            .map_err(|e| format!("listing cloudflare dns records for `{}` failed: {}", name, e))?
+            .result;
+
+        Ok(records
+            .into_iter()
+            .filter(|r| match DnsRecord::from_cloudflare(&r.content) {
+                Some(existing) => existing.record_type() == record.record_type(),
+                None => false,
+            })
+            .collect())
+    }
+
+    async fn delete_cloudflare_record(&self, zone_identifier: &str, name: &str, record: &dns::DnsRecord) -> Result<(), String> {
+        self.client
+            .request(&dns::DeleteDnsRecord {
+                zone_identifier,
+                identifier: &record.id,
+            })
+            .await
+            .map_err(|e| format!("deleting cloudflare dns record `{}` {:?} failed: {}", name, record.content, e))?;
+
+        println!("[dns] deleted cloudflare dns record `{}` {:?}", name, record.content);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for CloudflareDnsProvider {
+    async fn ensure_records(&self, name: &str, records: &[DnsRecord]) -> Result<(), String> {
+        let first = match records.get(0) {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        let zone_identifier = self.get_zone_identifier(name).await?;
+        let mut existing = self.get_existing_records(&zone_identifier, name, first).await?;
+
+        // Keep the records we already have.
+        let mut missing: Vec<&DnsRecord> = Default::default();
+        for record in records {
+            match existing.iter().position(|e| DnsRecord::from_cloudflare(&e.content).as_ref() == Some(record)) {
+                Some(i) => {
+                    println!("[dns] cloudflare dns record `{}` {} is already up to date", name, record);
+                    existing.remove(i);
+                }
+                None => missing.push(record),
+            }
+        }
+
+        // Update the records we don't need anymore to the ones we are missing and create the rest.
+        for record in missing {
+            match existing.pop() {
+                Some(e) => {
+                    let dns_record = self
+                        .client
+                        .request(&dns::UpdateDnsRecord {
+                            zone_identifier: &zone_identifier,
+                            identifier: &e.id,
+                            params: dns::UpdateDnsRecordParams {
+                                name,
+                                content: record.to_cloudflare(),
+                                ttl: Some(DNS_RECORD_TTL),
+                                proxied: None,
+                            },
+                        })
+                        .await
+                        .map_err(|e| format!("updating cloudflare dns record `{}` {} failed: {}", name, record, e))?
+                        .result;
+
+                    println!("[dns] updated cloudflare dns record: {:?}", dns_record);
+                }
+                None => {
+                    let dns_record = self
+                        .client
+                        .request(&dns::CreateDnsRecord {
+                            zone_identifier: &zone_identifier,
+                            params: dns::CreateDnsRecordParams {
+                                name,
+                                content: record.to_cloudflare(),
+                                ttl: Some(DNS_RECORD_TTL),
+                                proxied: None,
+                                priority: None,
+                            },
+                        })
+                        .await
+                        .map_err(|e| format!("creating cloudflare dns record `{}` {} failed: {}", name, record, e))?
+                        .result;
+
+                    println!("[dns] created cloudflare dns record: {:?}", dns_record);
+                }
+            }
+        }
+
+        // Delete any records that are left over.
+        for e in existing {
+            self.delete_cloudflare_record(&zone_identifier, name, &e).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_record(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        let zone_identifier = self.get_zone_identifier(name).await?;
+
+        for existing in self.get_existing_records(&zone_identifier, name, record).await? {
+            self.delete_cloudflare_record(&zone_identifier, name, &existing).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<(String, DnsRecord)>, String> {
+        let zone_identifier = self.get_zone_identifier(zone).await?;
+
+        // Cloudflare pages the records, so keep going until we get a short page.
+        let per_page = 100;
+        let mut page = 1;
+        let mut records: Vec<(String, DnsRecord)> = Default::default();
+        loop {
+            let result = self
+                .client
+                .request(&dns::ListDnsRecords {
+                    zone_identifier: &zone_identifier,
+                    params: dns::ListDnsRecordsParams {
+                        page: Some(page),
+                        per_page: Some(per_page),
+                        ..Default::default()
+                    },
+                })
+                .await
+                .map_err(|e| format!("listing cloudflare dns records for `{}` failed: {}", zone, e))?
+                .result;
+
+            let count = result.len();
+            for r in result {
+                if let Some(record) = DnsRecord::from_cloudflare(&r.content) {
+                    records.push((r.name.to_lowercase(), record));
+                }
+            }
+
+            if count < per_page as usize {
+                break;
+            }
+            page += 1;
+        }
+
+        records.sort();
+        Ok(records)
+    }
+}
+
+/// Manage DNS records with RFC 2136 dynamic updates, by running `nsupdate`.
+pub struct Rfc2136DnsProvider {
+    /// The nameserver to send updates to.
+    pub nameserver: String,
+    /// The path to the TSIG key file to sign updates with, as passed to `nsupdate -k`.
+    pub key_file: String,
+}
+
+impl Rfc2136DnsProvider {
+    /// Create an RFC 2136 provider from the `RFC2136_NAMESERVER` and `RFC2136_TSIG_KEY_FILE`
+    /// environment variables.
+    pub fn new_from_env() -> Self {
+        Rfc2136DnsProvider {
+            nameserver: env::var("RFC2136_NAMESERVER").unwrap(),
+            key_file: env::var("RFC2136_TSIG_KEY_FILE").unwrap_or_default(),
+        }
+    }
+
+    /// Get the `nsupdate` script for a set of update commands.
+    fn get_update_script(&self, name: &str, updates: &[String]) -> Result<String, String> {
+        let zone = get_zone_for_domain(name)?;
+
+        let mut script = format!("server {}\nzone {}\n", self.nameserver, zone);
+        for update in updates {
+            script += &format!("update {}\n", update);
+        }
+        script += "send\n";
+
+        Ok(script)
+    }
+
+    /// Send a script to `nsupdate`.
+    fn nsupdate(&self, script: &str) -> Result<(), String> {
+        let mut cmd = Command::new("nsupdate");
+        if !self.key_file.is_empty() {
+            cmd.args(&["-k", &self.key_file]);
+        }
+
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("running nsupdate failed: {}", e))?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(script.as_bytes())
+            .map_err(|e| format!("writing to nsupdate failed: {}", e))?;
+
+        let output = child.wait_with_output().map_err(|e| format!("running nsupdate failed: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("nsupdate failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Rfc2136DnsProvider {
+    async fn ensure_records(&self, name: &str, records: &[DnsRecord]) -> Result<(), String> {
+        let first = match records.get(0) {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        // Replace all the records of this type in one update.
+        let mut updates = vec![format!("delete {}. {}", name, first.record_type())];
+        for record in records {
+            let value = match record {
+                DnsRecord::TXT(content) => format!("\"{}\"", content),
+                _ => record.value(),
+            };
+            updates.push(format!("add {}. {} {} {}", name, DNS_RECORD_TTL, record.record_type(), value));
+        }
+
+        let script = self.get_update_script(name, &updates)?;
+        self.nsupdate(&script)?;
+
+        println!("[dns] updated `{}` {} records on {}", name, first.record_type(), self.nameserver);
+        Ok(())
+    }
+
+    async fn delete_record(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        let script = self.get_update_script(name, &[format!("delete {}. {}", name, record.record_type())])?;
+        self.nsupdate(&script)?;
+
+        println!("[dns] deleted `{}` {} on {}", name, record.record_type(), self.nameserver);
+        Ok(())
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<(String, DnsRecord)>, String> {
+        // Transfer the zone with `dig`, signed with the same key we send updates with.
+        let mut cmd = Command::new("dig");
+        if !self.key_file.is_empty() {
+            cmd.args(&["-k", &self.key_file]);
+        }
+        let output = cmd
+            .args(&[&format!("@{}", self.nameserver), zone, "AXFR", "+noall", "+answer"])
+            .output()
+            .map_err(|e| format!("running dig failed: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("transferring zone `{}` failed: {}", zone, String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let mut records = parse_zone_transfer(&String::from_utf8_lossy(&output.stdout));
+        records.sort();
+        records.dedup();
+        Ok(records)
+    }
+}
+
+/// Parse the answer section of a zone transfer, as printed by `dig`, into the records we manage.
+/// Each line looks like `rfd.oxide.computer. 120 IN A 1.2.3.4`.
+fn parse_zone_transfer(output: &str) -> Vec<(String, DnsRecord)> {
+    let mut records: Vec<(String, DnsRecord)> = Default::default();
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        // Split off the name, ttl, class and type, the rest of the line is the value.
+        let mut parts: Vec<&str> = Default::default();
+        let mut rest = line;
+        for _ in 0..4 {
+            let trimmed = rest.trim_start();
+            let end = trimmed.find(char::is_whitespace).unwrap_or_else(|| trimmed.len());
+            parts.push(&trimmed[..end]);
+            rest = &trimmed[end..];
+        }
+        let rest = rest.trim();
+        if rest.is_empty() {
+            continue;
+        }
+
+        // TXT records can be split into more than one quoted string, join them back up.
+        let value = if parts[3] == "TXT" {
+            rest.split('"').skip(1).step_by(2).collect::<String>()
+        } else {
+            rest.to_string()
+        };
+        if let Ok(record) = DnsRecord::new(parts[3], &value) {
+            records.push((parts[0].trim_end_matches('.').to_lowercase(), record));
+        }
+    }
+
+    records
+}
+
+/// A DNS provider that keeps records in memory, for tests.
+#[derive(Debug, Default)]
+pub struct MockDnsProvider {
+    pub records: Mutex<BTreeMap<(String, &'static str), Vec<DnsRecord>>>,
+}
+
+impl MockDnsProvider {
+    /// Get the records of a type for `name`.
+    pub fn get_records(&self, name: &str, record_type: &'static str) -> Vec<DnsRecord> {
+        self.records.lock().unwrap().get(&(name.to_string(), record_type)).cloned().unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl DnsProvider for MockDnsProvider {
+    async fn ensure_records(&self, name: &str, records: &[DnsRecord]) -> Result<(), String> {
+        if let Some(first) = records.get(0) {
+            self.records.lock().unwrap().insert((name.to_string(), first.record_type()), records.to_vec());
+        }
+        Ok(())
+    }
+
+    async fn delete_record(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        self.records.lock().unwrap().remove(&(name.to_string(), record.record_type()));
+        Ok(())
+    }
+
+    async fn list_records(&self, zone: &str) -> Result<Vec<(String, DnsRecord)>, String> {
+        let suffix = format!(".{}", zone);
+
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((name, _), _)| name == zone || name.ends_with(&suffix))
+            .flat_map(|((name, _), records)| records.iter().map(move |r| (name.to_string(), r.clone())))
+            .collect())
+    }
+
+    async fn wait_for_propagation(&self, name: &str, record: &DnsRecord) -> Result<(), String> {
+        if self.get_records(name, record.record_type()).contains(record) {
+            return Ok(());
+        }
+
+        Err(format!("`{}` {} does not exist", name, record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns_providers::{get_zone_for_domain, parse_zone_transfer, DnsProvider, DnsRecord, MockDnsProvider};
+
+    #[test]
+    fn test_get_zone_for_domain() {
+        assert_eq!("oxide.computer", get_zone_for_domain("rfd.oxide.computer").unwrap());
+        assert_eq!("oxide.computer", get_zone_for_domain("*.rfd.oxide.computer").unwrap());
+        assert_eq!("oxide.computer", get_zone_for_domain("oxide.computer.").unwrap());
+        assert_eq!("example.co.uk", get_zone_for_domain("_acme-challenge.thing.example.co.uk").unwrap());
+        assert!(get_zone_for_domain("co.uk").is_err());
+    }
+
+    #[test]
+    fn test_dns_record_new() {
+        assert_eq!(DnsRecord::A("1.2.3.4".parse().unwrap()), DnsRecord::new("a", " 1.2.3.4 ").unwrap());
+        assert_eq!(DnsRecord::CNAME("rfd.oxide.computer".to_string()), DnsRecord::new("CNAME", "RFD.oxide.computer.").unwrap());
+        assert_eq!(DnsRecord::TXT("v=spf1 -all".to_string()), DnsRecord::new("TXT", "\"v=spf1 -all\"").unwrap());
+        assert!(DnsRecord::new("AAAA", "1.2.3.4").is_err());
+        assert!(DnsRecord::new("MX", "10 mail.oxide.computer").is_err());
+    }
+
+    #[test]
+    fn test_parse_zone_transfer() {
+        let output = "; <<>> DiG 9.16.1 <<>> @ns1 oxide.computer AXFR
+oxide.computer.\t3600\tIN\tSOA\tns1.oxide.computer. hostmaster.oxide.computer. 1 3600 600 86400 120
+rfd.oxide.computer.\t120\tIN\tA\t1.2.3.4
+www.oxide.computer.\t120\tIN\tCNAME\toxide.computer.
+oxide.computer.\t120\tIN\tTXT\t\"v=spf1 \" \"-all\"
+oxide.computer.\t120\tIN\tMX\t10 mail.oxide.computer.
+";
+        assert_eq!(
+            vec![
+                ("rfd.oxide.computer".to_string(), DnsRecord::A("1.2.3.4".parse().unwrap())),
+                ("www.oxide.computer".to_string(), DnsRecord::CNAME("oxide.computer".to_string())),
+                ("oxide.computer".to_string(), DnsRecord::TXT("v=spf1 -all".to_string())),
+            ],
+            parse_zone_transfer(output)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_dns_provider() {
+        let provider = MockDnsProvider::default();
+        let name = "_acme-challenge.rfd.oxide.computer";
+        let record = DnsRecord::TXT("proof".to_string());
+
+        assert!(provider.wait_for_propagation(name, &record).await.is_err());
+
+        provider.ensure_record(name, &record).await.unwrap();
+        provider.wait_for_propagation(name, &record).await.unwrap();
+
+        // Ensuring a record replaces the old one.
+        let updated = DnsRecord::TXT("new proof".to_string());
+        provider.ensure_record(name, &updated).await.unwrap();
+        assert!(provider.wait_for_propagation(name, &record).await.is_err());
+        assert_eq!(vec![updated.clone()], provider.get_records(name, "TXT"));
+
+        // A name can have more than one record of a type.
+        provider.ensure_records(name, &[record.clone(), updated.clone()]).await.unwrap();
+        provider.wait_for_propagation(name, &record).await.unwrap();
+        provider.wait_for_propagation(name, &updated).await.unwrap();
+
+        provider.delete_record(name, &updated).await.unwrap();
+        assert!(provider.get_records(name, "TXT").is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::from_utf8;
+
+use hubcaps::Github;
+use serde::{Deserialize, Serialize};
+
+use crate::dns_providers::{DnsProvider, DnsRecord};
+use crate::utils::github_org;
+
+/// The records we want in a zone, as declared in `/dns/{zone}.toml` in the configs repo.
+/// For example:
+///
+/// ```toml
+/// zone = "oxide.computer"
+///
+/// [[records]]
+/// name = "rfd"
+/// type = "A"
+/// value = "1.2.3.4"
+///
+/// [[records]]
+/// name = "@"
+/// type = "TXT"
+/// value = "v=spf1 include:_spf.google.com -all"
+/// ```
+///
+/// This is an alternative to the Terraform files we generate for the short URLs, for zones
+/// that are not in Cloudflare or that we want to manage by hand.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DnsZoneConfig {
+    pub zone: String,
+    #[serde(default)]
+    pub records: Vec<DnsRecordConfig>,
+}
+
+/// A record in a zone config.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DnsRecordConfig {
+    /// name is the name of the record, relative to the zone or fully qualified.
+    /// Use `@` for the zone itself.
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+}
+
+impl DnsRecordConfig {
+    /// Get the fully qualified name of the record.
+    pub fn fqdn(&self, zone: &str) -> String {
+        get_fqdn(&self.name, zone)
+    }
+}
+
+/// Get the fully qualified name for a name that is relative to the zone or already
+/// fully qualified. `@` is the zone itself.
+pub fn get_fqdn(name: &str, zone: &str) -> String {
+    let name = name.trim().trim_end_matches('.').to_lowercase();
+    let zone = zone.trim().trim_end_matches('.').to_lowercase();
+
+    if name.is_empty() || name == "@" || name == zone {
+        zone
+    } else if name.ends_with(&format!(".{}", zone)) {
+        name
+    } else {
+        format!("{}.{}", name, zone)
+    }
+}
+
+impl DnsZoneConfig {
+    /// Parse a zone config from TOML.
+    /// If the file does not set the zone, `zone` is used.
+    pub fn parse(contents: &str, zone: &str) -> Result<Self, String> {
+        let mut config: DnsZoneConfig = toml::from_str(contents).map_err(|e| format!("decoding the dns config for `{}` failed: {}", zone, e))?;
+        if config.zone.is_empty() {
+            config.zone = zone.to_string();
+        }
+        config.zone = config.zone.trim().trim_end_matches('.').to_lowercase();
+
+        if !zone.is_empty() && config.zone != get_fqdn(zone, zone) {
+            return Err(format!("the dns config is for `{}`, not `{}`", config.zone, zone));
+        }
+
+        Ok(config)
+    }
+
+    /// Get the records we want, grouped by their name and type.
+    /// Returns an error if a record is not valid, or if a name has
+    /// a CNAME as well as other records.
+    pub fn desired_records(&self) -> Result<BTreeMap<(String, &'static str), Vec<DnsRecord>>, String> {
+        let mut desired: BTreeMap<(String, &'static str), Vec<DnsRecord>> = Default::default();
+        for r in &self.records {
+            let name = r.fqdn(&self.zone);
+            let record = DnsRecord::new(&r.record_type, &r.value).map_err(|e| format!("record `{}`: {}", name, e))?;
+
+            let records = desired.entry((name, record.record_type())).or_default();
+            if !records.contains(&record) {
+                records.push(record);
+            }
+        }
+
+        // A CNAME can't live next to any other record.
+        for (name, record_type) in desired.keys() {
+            if *record_type == "CNAME" {
+                if desired[&(name.to_string(), "CNAME")].len() > 1 {
+                    return Err(format!("record `{}` has more than one CNAME", name));
+                }
+                if desired.keys().any(|(n, t)| n == name && *t != "CNAME") {
+                    return Err(format!("record `{}` has a CNAME as well as other records", name));
+                }
+            }
+        }
+
+        Ok(desired)
+    }
+}
+
+/// Get the zone config for a zone from `/dns/{zone}.toml` in the configs repo.
+pub async fn get_dns_zone_config_from_repo(github: &Github, zone: &str) -> Result<DnsZoneConfig, String> {
+    let repo = github.repo(github_org(), "configs");
+    let r = repo.get().await.map_err(|e| format!("getting the configs repo failed: {}", e))?;
+
+    let path = format!("/dns/{}.toml", zone);
+    let file = repo
+        .content()
+        .file(&path, &r.default_branch)
+        .await
+        .map_err(|e| format!("getting `{}` from the configs repo failed: {}", path, e))?;
+    let contents = from_utf8(&file.content).map_err(|e| format!("`{}` is not valid utf8: {}", path, e))?;
+
+    DnsZoneConfig::parse(contents, zone)
+}
+
+/// A change we need to make to a zone to match its config.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DnsRecordChange {
+    Create(String, DnsRecord),
+    Delete(String, DnsRecord),
+}
+
+impl DnsRecordChange {
+    /// Get the record the change is for.
+    pub fn record(&self) -> &DnsRecord {
+        match self {
+            DnsRecordChange::Create(_, record) | DnsRecordChange::Delete(_, record) => record,
+        }
+    }
+
+    /// Get the name and type of the record the change is for.
+    pub fn key(&self) -> (String, &'static str) {
+        match self {
+            DnsRecordChange::Create(name, record) | DnsRecordChange::Delete(name, record) => (name.to_string(), record.record_type()),
+        }
+    }
+}
+
+impl fmt::Display for DnsRecordChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DnsRecordChange::Create(name, record) => write!(f, "+ {} {}", name, record),
+            DnsRecordChange::Delete(name, record) => write!(f, "- {} {}", name, record),
+        }
+    }
+}
+
+/// Get the changes we need to make to the existing records to match the desired records.
+/// Names and types that are not in the desired records are left alone, unless `prune` is set,
+/// in which case they are deleted.
+pub fn diff_zone(existing: &[(String, DnsRecord)], desired: &BTreeMap<(String, &'static str), Vec<DnsRecord>>, prune: bool) -> Vec<DnsRecordChange> {
+    let mut current: BTreeMap<(String, &'static str), Vec<DnsRecord>> = Default::default();
+    for (name, record) in existing {
+        current.entry((name.to_string(), record.record_type())).or_default().push(record.clone());
+    }
+
+    let mut changes: BTreeSet<DnsRecordChange> = Default::default();
+    for ((name, record_type), records) in desired {
+        let have = current.get(&(name.to_string(), *record_type)).cloned().unwrap_or_default();
+
+        for r in records.iter().filter(|r| !have.contains(r)) {
+            changes.insert(DnsRecordChange::Create(name.to_string(), r.clone()));
+        }
+        for r in have.iter().filter(|r| !records.contains(r)) {
+            changes.insert(DnsRecordChange::Delete(name.to_string(), r.clone()));
+        }
+    }
+
+    if prune {
+        for ((name, record_type), records) in &current {
+            if desired.contains_key(&(name.to_string(), *record_type)) {
+                continue;
+            }
+
+            for r in records {
+                changes.insert(DnsRecordChange::Delete(name.to_string(), r.clone()));
+            }
+        }
+    }
+
+    changes.into_iter().collect()
+}
+
+/// Get the changes we need to make to a zone to match its config.
+pub async fn plan_zone(dns_provider: &dyn DnsProvider, config: &DnsZoneConfig, prune: bool) -> Result<Vec<DnsRecordChange>, String> {
+    let desired = config.desired_records()?;
+    let existing = dns_provider.list_records(&config.zone).await?;
+
+    Ok(diff_zone(&existing, &desired, prune))
+}
+
+/// Make a zone match its config, and return the changes we made.
+pub async fn apply_zone(dns_provider: &dyn DnsProvider, config: &DnsZoneConfig, prune: bool) -> Result<Vec<DnsRecordChange>, String> {
+    let desired = config.desired_records()?;
+    let changes = plan_zone(dns_provider, config, prune).await?;
+
+    // Update each name and type that changed in one go.
+    let keys: BTreeSet<(String, &'static str)> = changes.iter().map(|c| c.key()).collect();
+    for key in keys {
+        match desired.get(&key) {
+            Some(records) => dns_provider.ensure_records(&key.0, records).await?,
+            None => {
+                // We are pruning this name and type, all the changes for it are deletes.
+                if let Some(change) = changes.iter().find(|c| c.key() == key) {
+                    dns_provider.delete_record(&key.0, change.record()).await?;
+                }
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns_providers::{DnsProvider, DnsRecord, MockDnsProvider};
+    use crate::dns_zones::{apply_zone, get_fqdn, plan_zone, DnsRecordChange, DnsZoneConfig};
+
+    fn a(ip: &str) -> DnsRecord {
+        DnsRecord::A(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn test_get_fqdn() {
+        assert_eq!("oxide.computer", get_fqdn("@", "oxide.computer"));
+        assert_eq!("oxide.computer", get_fqdn("oxide.computer.", "oxide.computer"));
+        assert_eq!("rfd.oxide.computer", get_fqdn("RFD", "oxide.computer"));
+        assert_eq!("rfd.oxide.computer", get_fqdn("rfd.oxide.computer", "oxide.computer"));
+    }
+
+    #[test]
+    fn test_dns_zone_config_parse() {
+        let config = DnsZoneConfig::parse(
+            r#"
+[[records]]
+name = "rfd"
+type = "A"
+value = "1.2.3.4"
+
+[[records]]
+name = "www"
+type = "CNAME"
+value = "oxide.computer"
+"#,
+            "oxide.computer",
+        )
+        .unwrap();
+        assert_eq!("oxide.computer", config.zone);
+        assert_eq!(2, config.desired_records().unwrap().len());
+
+        assert!(DnsZoneConfig::parse("zone = \"example.com\"", "oxide.computer").is_err());
+
+        let cname_clash = DnsZoneConfig::parse(
+            r#"
+[[records]]
+name = "www"
+type = "CNAME"
+value = "oxide.computer"
+
+[[records]]
+name = "www"
+type = "A"
+value = "1.2.3.4"
+"#,
+            "oxide.computer",
+        )
+        .unwrap();
+        assert!(cname_clash.desired_records().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_zone() {
+        let provider = MockDnsProvider::default();
+        provider.ensure_records("rfd.oxide.computer", &[a("1.1.1.1"), a("2.2.2.2")]).await.unwrap();
+        provider.ensure_record("old.oxide.computer", &a("3.3.3.3")).await.unwrap();
+        provider.ensure_record("other.example.com", &a("4.4.4.4")).await.unwrap();
+
+        let config = DnsZoneConfig::parse(
+            r#"
+zone = "oxide.computer"
+
+[[records]]
+name = "rfd"
+type = "A"
+value = "2.2.2.2"
+
+[[records]]
+name = "rfd"
+type = "A"
+value = "5.5.5.5"
+"#,
+            "oxide.computer",
+        )
+        .unwrap();
+
+        // Without pruning, records that are not in the config are left alone.
+        let changes = plan_zone(&provider, &config, false).await.unwrap();
+        assert_eq!(
+            vec![
+                DnsRecordChange::Create("rfd.oxide.computer".to_string(), a("5.5.5.5")),
+                DnsRecordChange::Delete("rfd.oxide.computer".to_string(), a("1.1.1.1")),
+            ],
+            changes
+        );
+
+        let changes = apply_zone(&provider, &config, true).await.unwrap();
+        assert_eq!(3, changes.len());
+        assert!(changes.contains(&DnsRecordChange::Delete("old.oxide.computer".to_string(), a("3.3.3.3"))));
+
+        assert_eq!(vec![a("2.2.2.2"), a("5.5.5.5")], provider.get_records("rfd.oxide.computer", "A"));
+        assert!(provider.get_records("old.oxide.computer", "A").is_empty());
+        assert_eq!(vec![a("4.4.4.4")], provider.get_records("other.example.com", "A"));
+
+        // Once applied, there is nothing left to do.
+        assert!(plan_zone(&provider, &config, true).await.unwrap().is_empty());
+    }
+}
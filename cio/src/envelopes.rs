@@ -0,0 +1,551 @@
+#![allow(clippy::from_over_into)]
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use docusign::DocuSign;
+use google_drive::GoogleDrive;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::airtable::{AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_ENVELOPES_TABLE, AIRTABLE_ENVELOPE_EVENTS_TABLE};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::{envelope_events, envelopes};
+use crate::utils::get_or_create_automated_documents_folder;
+
+/// The folder in the "Automated Documents" shared drive we archive completed
+/// envelopes to.
+pub static ENVELOPE_ARCHIVE_FOLDER: &str = "signed_documents";
+
+fn default_envelope_reminder_days() -> i64 {
+    3
+}
+
+fn default_envelope_max_reminders() -> i32 {
+    3
+}
+
+/// How we remind the recipients of offer letters that are waiting on them,
+/// from the configs repo.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct EnvelopeRemindersConfig {
+    /// How many days an envelope can wait on a recipient before we remind them.
+    /// Set this to 0 to not send reminders.
+    #[serde(default = "default_envelope_reminder_days")]
+    pub days: i64,
+    /// How many reminders we send for an envelope before we give up and let a
+    /// human follow up.
+    #[serde(default = "default_envelope_max_reminders", alias = "max-reminders")]
+    pub max_reminders: i32,
+}
+
+impl Default for EnvelopeRemindersConfig {
+    fn default() -> Self {
+        EnvelopeRemindersConfig {
+            days: default_envelope_reminder_days(),
+            max_reminders: default_envelope_max_reminders(),
+        }
+    }
+}
+
+/// The status of a DocuSign envelope.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum EnvelopeStatus {
+    Created,
+    Sent,
+    Delivered,
+    Signed,
+    Completed,
+    Declined,
+    Voided,
+    /// DocuSign voids envelopes that expire, we keep them apart since they
+    /// likely need to be sent again rather than followed up on.
+    Expired,
+}
+
+impl EnvelopeStatus {
+    /// Returns the status of an envelope from DocuSign.
+    pub fn from_envelope(envelope: &docusign::Envelope) -> Result<Self, String> {
+        let status = EnvelopeStatus::from_str(&envelope.status)?;
+        if status == EnvelopeStatus::Voided && envelope.voided_reason.to_lowercase().contains("expired") {
+            return Ok(EnvelopeStatus::Expired);
+        }
+
+        Ok(status)
+    }
+
+    /// Returns if the envelope can no longer change.
+    pub fn is_final(&self) -> bool {
+        matches!(self, EnvelopeStatus::Completed | EnvelopeStatus::Declined | EnvelopeStatus::Voided | EnvelopeStatus::Expired)
+    }
+}
+
+impl FromStr for EnvelopeStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "created" => Ok(EnvelopeStatus::Created),
+            "sent" => Ok(EnvelopeStatus::Sent),
+            "delivered" => Ok(EnvelopeStatus::Delivered),
+            "signed" => Ok(EnvelopeStatus::Signed),
+            "completed" => Ok(EnvelopeStatus::Completed),
+            "declined" => Ok(EnvelopeStatus::Declined),
+            "voided" => Ok(EnvelopeStatus::Voided),
+            "expired" => Ok(EnvelopeStatus::Expired),
+            _ => Err(format!("invalid envelope status: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for EnvelopeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            EnvelopeStatus::Created => "created",
+            EnvelopeStatus::Sent => "sent",
+            EnvelopeStatus::Delivered => "delivered",
+            EnvelopeStatus::Signed => "signed",
+            EnvelopeStatus::Completed => "completed",
+            EnvelopeStatus::Declined => "declined",
+            EnvelopeStatus::Voided => "voided",
+            EnvelopeStatus::Expired => "expired",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The data type for a DocuSign envelope we sent.
+#[db {
+    new_struct_name = "Envelope",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_ENVELOPES_TABLE",
+    match_on = {
+        "envelope_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "envelopes"]
+pub struct NewEnvelope {
+    pub envelope_id: String,
+    /// The email of the applicant the envelope was sent to, if it was an offer.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email_subject: String,
+    /// One of the `EnvelopeStatus`es.
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivered_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declined_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub declined_reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voided_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub voided_reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_changed_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reminders_sent: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reminder_sent_time: Option<DateTime<Utc>>,
+    /// The links to the completed documents and the certificate of completion
+    /// in Google Drive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archived_documents: Vec<String>,
+}
+
+/// Implement updating the Airtable record for an Envelope.
+#[async_trait]
+impl UpdateAirtableRecord<Envelope> for Envelope {
+    async fn update_airtable_record(&mut self, _record: Envelope) {}
+}
+
+/// A status change of an envelope, or of one of its recipients.
+#[db {
+    new_struct_name = "EnvelopeEvent",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_ENVELOPE_EVENTS_TABLE",
+    match_on = {
+        "envelope_id" = "String",
+        "recipient_email" = "String",
+        "status" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "envelope_events"]
+pub struct NewEnvelopeEvent {
+    pub envelope_id: String,
+    /// The recipient whose status changed, this is empty for a change in the
+    /// status of the envelope itself.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub recipient_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub recipient_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub recipient_role: String,
+    pub status: String,
+    /// Why the envelope was declined, voided or expired.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    pub time: DateTime<Utc>,
+}
+
+/// Implement updating the Airtable record for an EnvelopeEvent.
+#[async_trait]
+impl UpdateAirtableRecord<EnvelopeEvent> for EnvelopeEvent {
+    async fn update_airtable_record(&mut self, _record: EnvelopeEvent) {}
+}
+
+impl NewEnvelope {
+    /// Parse the envelope out of the data we get from DocuSign.
+    pub fn from_docusign(envelope: &docusign::Envelope, applicant_email: &str) -> Self {
+        let status = EnvelopeStatus::from_envelope(envelope).map(|s| s.to_string()).unwrap_or_else(|_| envelope.status.to_lowercase());
+
+        NewEnvelope {
+            envelope_id: envelope.envelope_id.to_string(),
+            applicant_email: applicant_email.to_string(),
+            email_subject: envelope.email_subject.to_string(),
+            status,
+            created_time: envelope.created_date_time,
+            sent_time: envelope.sent_date_time,
+            delivered_time: envelope.delivered_date_time,
+            completed_time: envelope.completed_date_time,
+            declined_time: envelope.declined_date_time,
+            declined_reason: envelope
+                .recipients
+                .signers
+                .iter()
+                .find(|r| !r.declined_reason.is_empty())
+                .map(|r| r.declined_reason.to_string())
+                .unwrap_or_default(),
+            voided_time: envelope.voided_date_time,
+            voided_reason: envelope.voided_reason.to_string(),
+            status_changed_time: envelope.status_changed_date_time,
+            reminders_sent: 0,
+            last_reminder_sent_time: None,
+            archived_documents: Default::default(),
+        }
+    }
+
+    /// Returns every status change of the envelope and its recipients we
+    /// know the time of.
+    pub fn events(envelope: &docusign::Envelope) -> Vec<NewEnvelopeEvent> {
+        let mut events: Vec<NewEnvelopeEvent> = Default::default();
+
+        let mut push = |recipient: Option<&docusign::Recipient>, status: EnvelopeStatus, time: Option<DateTime<Utc>>, reason: &str| {
+            if let Some(time) = time {
+                events.push(NewEnvelopeEvent {
+                    envelope_id: envelope.envelope_id.to_string(),
+                    recipient_email: recipient.map(|r| r.email.to_string()).unwrap_or_default(),
+                    recipient_name: recipient.map(|r| r.name.to_string()).unwrap_or_default(),
+                    recipient_role: recipient.map(|r| r.role_name.to_string()).unwrap_or_default(),
+                    status: status.to_string(),
+                    reason: reason.to_string(),
+                    time,
+                });
+            }
+        };
+
+        push(None, EnvelopeStatus::Created, envelope.created_date_time, "");
+        push(None, EnvelopeStatus::Sent, envelope.sent_date_time, "");
+        push(None, EnvelopeStatus::Delivered, envelope.delivered_date_time, "");
+        push(None, EnvelopeStatus::Completed, envelope.completed_date_time, "");
+        push(None, EnvelopeStatus::Declined, envelope.declined_date_time, "");
+        if let Ok(status) = EnvelopeStatus::from_envelope(envelope) {
+            if status == EnvelopeStatus::Voided || status == EnvelopeStatus::Expired {
+                push(None, status, envelope.voided_date_time.or(envelope.status_changed_date_time), &envelope.voided_reason);
+            }
+        }
+
+        for recipient in envelope.recipients.signers.iter().chain(envelope.recipients.agents.iter()) {
+            push(Some(recipient), EnvelopeStatus::Sent, recipient.sent_date_time, "");
+            push(Some(recipient), EnvelopeStatus::Delivered, recipient.delivered_date_time, "");
+            push(Some(recipient), EnvelopeStatus::Signed, recipient.signed_date_time, "");
+            push(Some(recipient), EnvelopeStatus::Declined, recipient.declined_date_time, &recipient.declined_reason);
+        }
+
+        events
+    }
+
+    /// Returns if the envelope has been waiting on its recipients long enough
+    /// that we should remind them.
+    /// We only remind people about offer letters, other envelopes we are told
+    /// about by the webhook are someone else's to follow up on.
+    pub fn needs_reminder(&self, now: DateTime<Utc>, config: &EnvelopeRemindersConfig) -> bool {
+        if self.applicant_email.is_empty() || config.days <= 0 {
+            return false;
+        }
+
+        match EnvelopeStatus::from_str(&self.status) {
+            Ok(EnvelopeStatus::Sent) | Ok(EnvelopeStatus::Delivered) | Ok(EnvelopeStatus::Signed) => (),
+            _ => return false,
+        }
+
+        if self.reminders_sent >= config.max_reminders {
+            return false;
+        }
+
+        // Wait from whenever we last heard from the envelope, or last reminded
+        // the recipients about it.
+        let last_activity = vec![self.sent_time, self.delivered_time, self.status_changed_time, self.last_reminder_sent_time]
+            .into_iter()
+            .flatten()
+            .max();
+        match last_activity {
+            Some(time) => now - time >= Duration::days(config.days),
+            None => false,
+        }
+    }
+}
+
+impl Envelope {
+    /// Returns a message for the hiring channel about an envelope that will
+    /// never be completed.
+    pub fn as_slack_msg(&self, name: &str) -> Value {
+        let reason = if !self.declined_reason.is_empty() {
+            format!(": _{}_", self.declined_reason)
+        } else if !self.voided_reason.is_empty() {
+            format!(": _{}_", self.voided_reason)
+        } else {
+            "".to_string()
+        };
+
+        json!(FormattedMessage {
+            channel: Default::default(),
+            attachments: Default::default(),
+            blocks: vec![MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        ":warning: the DocuSign envelope *{}* for {} ({}) was {}{}",
+                        self.email_subject, name, self.applicant_email, self.status, reason
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            }],
+        })
+    }
+}
+
+/// Save the envelope and any status changes we have not seen yet to the database.
+pub async fn record_envelope(db: &Database, envelope: &docusign::Envelope, applicant_email: &str) -> Envelope {
+    let mut new_envelope = NewEnvelope::from_docusign(envelope, applicant_email);
+
+    // Keep what we know that does not come from DocuSign, and anything the
+    // webhook did not send us this time.
+    if let Some(existing) = Envelope::get_from_db(db, envelope.envelope_id.to_string()) {
+        if new_envelope.applicant_email.is_empty() {
+            new_envelope.applicant_email = existing.applicant_email.to_string();
+        }
+        if new_envelope.email_subject.is_empty() {
+            new_envelope.email_subject = existing.email_subject.to_string();
+        }
+        if new_envelope.declined_reason.is_empty() {
+            new_envelope.declined_reason = existing.declined_reason.to_string();
+        }
+        new_envelope.created_time = new_envelope.created_time.or(existing.created_time);
+        new_envelope.sent_time = new_envelope.sent_time.or(existing.sent_time);
+        new_envelope.delivered_time = new_envelope.delivered_time.or(existing.delivered_time);
+        new_envelope.completed_time = new_envelope.completed_time.or(existing.completed_time);
+        new_envelope.declined_time = new_envelope.declined_time.or(existing.declined_time);
+        new_envelope.voided_time = new_envelope.voided_time.or(existing.voided_time);
+        new_envelope.status_changed_time = new_envelope.status_changed_time.or(existing.status_changed_time);
+        new_envelope.reminders_sent = existing.reminders_sent;
+        new_envelope.last_reminder_sent_time = existing.last_reminder_sent_time;
+        new_envelope.archived_documents = existing.archived_documents;
+    }
+
+    for event in NewEnvelope::events(envelope) {
+        event.upsert(db).await;
+    }
+
+    new_envelope.upsert(db).await
+}
+
+/// Archive the documents of a completed envelope, and its certificate of
+/// completion, to the "Automated Documents" shared drive.
+/// Returns the links to the files in Google Drive.
+pub async fn archive_envelope_documents(drive_client: &GoogleDrive, ds: &DocuSign, envelope: &docusign::Envelope, name: &str) -> Result<Vec<String>, String> {
+    let (drive_id, parent_id) = get_or_create_automated_documents_folder(drive_client, ENVELOPE_ARCHIVE_FOLDER).await?;
+
+    let mut documents = envelope.documents.clone();
+    // The certificate of completion is only in the list of documents if
+    // DocuSign sent it to us, so make sure we get it.
+    if !documents.iter().any(|d| d.id == "certificate") {
+        documents.push(docusign::Document {
+            id: "certificate".to_string(),
+            name: "Summary".to_string(),
+            pdf_bytes: Default::default(),
+        });
+    }
+
+    let mut links: Vec<String> = Default::default();
+    for document in documents {
+        let mut bytes = base64::decode(&document.pdf_bytes).unwrap_or_default();
+        // Check if we already have bytes to the data.
+        if bytes.is_empty() {
+            // Get the document from docusign.
+            // In order to not "over excessively poll the API here, we need to sleep for 15
+            // seconds before getting each of the documents.
+            // https://developers.docusign.com/docs/esign-rest-api/esign101/rules-and-limits/
+            thread::sleep(std::time::Duration::from_secs(15));
+            bytes = ds
+                .get_document(&envelope.envelope_id, &document.id)
+                .await
+                .map_err(|e| format!("getting document {} failed: {}", document.id, e))?
+                .to_vec();
+        }
+
+        let filename = if document.id == "certificate" {
+            format!("{} - Certificate of Completion.pdf", name)
+        } else {
+            format!("{} - {}.pdf", name, document.name.trim_end_matches(".pdf"))
+        };
+
+        // Create or update the file in the google_drive.
+        let file = drive_client
+            .create_or_update_file(&drive_id, &parent_id, &filename, "application/pdf", &bytes)
+            .await
+            .map_err(|e| format!("uploading {} failed: {}", filename, e))?;
+        println!("[docusign] archived completed file {} to drive", filename);
+        links.push(format!("https://drive.google.com/open?id={}", file.id));
+    }
+
+    Ok(links)
+}
+
+/// Remind the recipients of any offer letters that have been waiting on them for
+/// longer than the configured number of days.
+pub async fn refresh_envelope_reminders(db: &Database, ds: &DocuSign, config: &EnvelopeRemindersConfig) {
+    let now = Utc::now();
+
+    for envelope in Envelopes::get_from_db(db) {
+        if !NewEnvelope::from(envelope.clone()).needs_reminder(now, config) {
+            continue;
+        }
+
+        if let Err(e) = ds.resend_envelope(&envelope.envelope_id).await {
+            println!("[docusign] resending envelope {} failed: {}", envelope.envelope_id, e);
+            continue;
+        }
+
+        let mut envelope = envelope;
+        envelope.reminders_sent += 1;
+        envelope.last_reminder_sent_time = Some(now);
+        println!(
+            "[docusign] sent reminder {} for envelope {} to {}",
+            envelope.reminders_sent, envelope.envelope_id, envelope.applicant_email
+        );
+        envelope.update(db).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::envelopes::{EnvelopeRemindersConfig, EnvelopeStatus, NewEnvelope};
+
+    fn docusign_envelope(status: &str) -> docusign::Envelope {
+        let mut envelope: docusign::Envelope = Default::default();
+        envelope.envelope_id = "envelope".to_string();
+        envelope.status = status.to_string();
+        envelope.created_date_time = Some(Utc.ymd(2021, 6, 1).and_hms(9, 0, 0));
+        envelope.sent_date_time = Some(Utc.ymd(2021, 6, 1).and_hms(9, 0, 1));
+        envelope
+    }
+
+    #[test]
+    fn test_envelope_status() {
+        assert_eq!(EnvelopeStatus::from_envelope(&docusign_envelope("completed")).unwrap(), EnvelopeStatus::Completed);
+
+        let mut voided = docusign_envelope("voided");
+        voided.voided_reason = "Offer rescinded".to_string();
+        assert_eq!(EnvelopeStatus::from_envelope(&voided).unwrap(), EnvelopeStatus::Voided);
+        voided.voided_reason = "Envelope has expired.".to_string();
+        assert_eq!(EnvelopeStatus::from_envelope(&voided).unwrap(), EnvelopeStatus::Expired);
+        assert!(EnvelopeStatus::Expired.is_final());
+        assert!(!EnvelopeStatus::Delivered.is_final());
+
+        assert!(EnvelopeStatus::from_envelope(&docusign_envelope("lost")).is_err());
+    }
+
+    #[test]
+    fn test_envelope_events() {
+        let mut envelope = docusign_envelope("declined");
+        let mut recipient: docusign::Recipient = Default::default();
+        recipient.email = "jane@example.com".to_string();
+        recipient.role_name = "Applicant".to_string();
+        recipient.sent_date_time = envelope.sent_date_time;
+        recipient.declined_date_time = Some(Utc.ymd(2021, 6, 2).and_hms(12, 0, 0));
+        recipient.declined_reason = "Accepted another offer".to_string();
+        envelope.declined_date_time = recipient.declined_date_time;
+        envelope.recipients.signers.push(recipient);
+
+        let events: Vec<(String, String, String)> = NewEnvelope::events(&envelope).into_iter().map(|e| (e.recipient_email, e.status, e.reason)).collect();
+        assert_eq!(
+            events,
+            vec![
+                ("".to_string(), "created".to_string(), "".to_string()),
+                ("".to_string(), "sent".to_string(), "".to_string()),
+                ("".to_string(), "declined".to_string(), "".to_string()),
+                ("jane@example.com".to_string(), "sent".to_string(), "".to_string()),
+                ("jane@example.com".to_string(), "declined".to_string(), "Accepted another offer".to_string()),
+            ]
+        );
+
+        let new_envelope = NewEnvelope::from_docusign(&envelope, "jane@example.com");
+        assert_eq!(new_envelope.status, "declined");
+        assert_eq!(new_envelope.declined_reason, "Accepted another offer");
+    }
+
+    #[test]
+    fn test_envelope_needs_reminder() {
+        let config: EnvelopeRemindersConfig = Default::default();
+        let mut envelope = NewEnvelope::from_docusign(&docusign_envelope("sent"), "jane@example.com");
+        let sent = envelope.sent_time.unwrap();
+
+        assert!(!envelope.needs_reminder(sent + Duration::days(1), &config));
+        assert!(envelope.needs_reminder(sent + Duration::days(3), &config));
+
+        envelope.last_reminder_sent_time = Some(sent + Duration::days(3));
+        assert!(!envelope.needs_reminder(sent + Duration::days(4), &config));
+        assert!(envelope.needs_reminder(sent + Duration::days(6), &config));
+
+        envelope.reminders_sent = config.max_reminders;
+        assert!(!envelope.needs_reminder(sent + Duration::days(30), &config));
+
+        let completed = NewEnvelope::from_docusign(&docusign_envelope("completed"), "jane@example.com");
+        assert!(!completed.needs_reminder(sent + Duration::days(30), &config));
+
+        // Envelopes that are not offer letters do not get reminders.
+        let other = NewEnvelope::from_docusign(&docusign_envelope("sent"), "");
+        assert!(!other.needs_reminder(sent + Duration::days(30), &config));
+
+        let weekly = EnvelopeRemindersConfig { days: 7, max_reminders: 1 };
+        let envelope = NewEnvelope::from_docusign(&docusign_envelope("sent"), "jane@example.com");
+        assert!(!envelope.needs_reminder(sent + Duration::days(6), &weekly));
+        assert!(envelope.needs_reminder(sent + Duration::days(7), &weekly));
+
+        let off = EnvelopeRemindersConfig { days: 0, max_reminders: 3 };
+        assert!(!envelope.needs_reminder(sent + Duration::days(30), &off));
+    }
+}
@@ -0,0 +1,1222 @@
+#![allow(clippy::from_over_into)]
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use google_drive::{GoogleDrive, Permission};
+use lopdf::{Bookmark, Document, Object, ObjectId};
+use macros::db;
+use pandoc::OutputKind;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::airtable::{AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_INTERVIEW_PACKETS_TABLE};
+use crate::applicant_retention::hash_applicant_email;
+use crate::applicant_scores::get_scores_for_applicant;
+use crate::applicant_status::Status;
+use crate::applicants::{get_drive_file_id, Applicant, Applicants};
+use crate::configs::{User, Users};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::interviews::{ApplicantInterview, ApplicantInterviews};
+use crate::materials::{MaterialsFormat, Sandbox};
+use crate::schema::interview_packets;
+use crate::utils::get_gsuite_token;
+
+/// The shared drive we keep the packets in. Only the account we run as should be a
+/// member, the interviewers get access to their own packets through the permissions
+/// we add to each file.
+pub static INTERVIEW_PACKETS_DRIVE: &str = "Interview Packets";
+
+/// How long interviewers keep access to a packet after their last interview with the
+/// applicant, so they can refer to it while they write up their notes.
+pub static INTERVIEW_PACKET_ACCESS_HOURS: i64 = 24;
+
+/// The shared drive and folder we keep the packets for applicants in, so the people
+/// who send them to the applicants can get to them.
+pub static APPLICANT_PACKETS_DRIVE: &str = "Automated Documents";
+static APPLICANT_PACKETS_FOLDER: &str = "interview_packets";
+
+/// Bump this when the layout of the packets changes, so every packet gets rebuilt.
+static INTERVIEW_PACKET_FORMAT: u32 = 1;
+
+/// A packet for one interviewer about one applicant: the applicant's materials, the
+/// scores from the reviewers, and when the interviews are.
+///
+/// The packet for the applicant, with the materials of everyone they are talking to,
+/// has no interviewer.
+#[db {
+    new_struct_name = "InterviewPacket",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_INTERVIEW_PACKETS_TABLE",
+    match_on = {
+        "interviewer" = "String",
+        "applicant_email" = "String",
+        "sheet_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "interview_packets"]
+pub struct NewInterviewPacket {
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        serialize_with = "airtable_api::user_format_as_string::serialize",
+        deserialize_with = "airtable_api::user_format_as_string::deserialize"
+    )]
+    pub interviewer: String,
+    pub applicant_email: String,
+    pub applicant_name: String,
+    pub role: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sheet_id: String,
+    /// The start of the interviewer's first interview with the applicant.
+    pub interview_start_time: DateTime<Utc>,
+    /// The end of the interviewer's last interview with the applicant.
+    pub interview_end_time: DateTime<Utc>,
+    /// The hash of everything in the packet, see `InterviewPacketContents::hash`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub content_hash: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub google_drive_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+    /// The ID of the permission that shares the packet with the interviewer.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub permission_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub built_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_time: Option<DateTime<Utc>>,
+}
+
+/// Implement updating the Airtable record for an InterviewPacket.
+#[async_trait]
+impl UpdateAirtableRecord<InterviewPacket> for InterviewPacket {
+    async fn update_airtable_record(&mut self, _record: InterviewPacket) {}
+}
+
+/// A score from a reviewer, as it is shown in a packet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PacketScore {
+    pub reviewer: String,
+    pub recommendation: String,
+    pub score: i32,
+    pub technical_depth: i32,
+    pub writing: i32,
+    pub concerns: Vec<String>,
+    pub notes: String,
+}
+
+/// One of the applicant's interviews, as it is shown in a packet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PacketInterview {
+    pub interviewers: Vec<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// If the interviewer the packet is for is in this interview.
+    pub yours: bool,
+}
+
+/// Everything that goes into a packet. We only rebuild a packet when the hash of
+/// this changes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterviewPacketContents {
+    pub format: u32,
+    pub interviewer_name: String,
+    pub applicant_name: String,
+    pub role: String,
+    /// The ID of the materials in Google Drive, and their checksum, so changes to the
+    /// file are picked up without downloading it.
+    pub materials_id: String,
+    pub materials_checksum: String,
+    /// If the materials are a PDF, they are added to the packet as they are.
+    /// Otherwise we add the text we extracted from them.
+    pub materials_pdf: bool,
+    pub materials_text: String,
+    pub scores: Vec<PacketScore>,
+    pub schedule: Vec<PacketInterview>,
+}
+
+impl InterviewPacketContents {
+    /// The hash of everything in the packet, as a hex string.
+    pub fn hash(&self) -> String {
+        hash_contents(self)
+    }
+
+    /// The first page of the packet, as HTML.
+    pub fn cover_html(&self) -> String {
+        let tz = chrono_tz::US::Pacific;
+
+        let mut schedule = "".to_string();
+        for interview in &self.schedule {
+            let mut interviewers = escape_html(&interview.interviewers.join(", "));
+            if interview.yours {
+                interviewers = format!("<b>{}</b>", interviewers);
+            }
+            schedule += &format!(
+                "<tr><td>{} - {}</td><td>{}</td></tr>\n",
+                interview.start_time.with_timezone(&tz).format("%A, %B %e from %l:%M%P"),
+                interview.end_time.with_timezone(&tz).format("%l:%M%P %Z"),
+                interviewers
+            );
+        }
+
+        let mut scores = "".to_string();
+        let mut notes = "".to_string();
+        for score in &self.scores {
+            scores += &format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&score.reviewer),
+                escape_html(&score.recommendation),
+                score.score,
+                score.technical_depth,
+                score.writing,
+                escape_html(&score.concerns.join(", "))
+            );
+            if !score.notes.trim().is_empty() {
+                notes += &format!("<p><b>{}:</b> {}</p>\n", escape_html(&score.reviewer), escape_html(score.notes.trim()));
+            }
+        }
+        if scores.is_empty() {
+            scores = "<tr><td>Nobody has scored the applicant yet.</td></tr>\n".to_string();
+        }
+
+        let materials = if self.materials_pdf {
+            "<p>The materials follow on the next pages.</p>".to_string()
+        } else if self.materials_text.trim().is_empty() {
+            "<p>We could not read the applicant's materials.</p>".to_string()
+        } else {
+            format!("<pre>{}</pre>", escape_html(self.materials_text.trim()))
+        };
+
+        format!(
+            r#"<html>
+<body>
+<h1>{}</h1>
+<p>{}, prepared for {}.</p>
+<p>
+This packet is for you alone, please do not share it. You will have access to
+it until a day after your last interview with the applicant.
+</p>
+<h2>Interviews</h2>
+<table>
+{}</table>
+<h2>Scores</h2>
+<table>
+<tr><th>Reviewer</th><th>Recommendation</th><th>Score</th><th>Technical depth</th><th>Writing</th><th>Concerns</th></tr>
+{}</table>
+{}<h2>Materials</h2>
+{}
+</body>
+</html>"#,
+            escape_html(&self.applicant_name),
+            escape_html(&self.role),
+            escape_html(&self.interviewer_name),
+            schedule,
+            scores,
+            notes,
+            materials
+        )
+    }
+}
+
+/// One of the people the applicant is talking to, as they are shown in the packet for
+/// the applicant.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApplicantPacketInterviewer {
+    pub name: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// The materials the interviewer wrote when they applied.
+    pub materials: PacketMaterials,
+}
+
+impl ApplicantPacketInterviewer {
+    /// The page before the interviewer's materials, as HTML.
+    pub fn header_html(&self) -> String {
+        let tz = chrono_tz::US::Pacific;
+
+        let materials = if self.materials.pdf {
+            "".to_string()
+        } else if self.materials.text.trim().is_empty() {
+            "<p>We could not find their materials.</p>\n".to_string()
+        } else {
+            format!("<pre>{}</pre>\n", escape_html(self.materials.text.trim()))
+        };
+
+        format!(
+            "<html><body><table><tr><td><h1>{}</h1></td></tr><tr><td><p>{} - {}</p></td></tr></table>\n{}</body></html>",
+            escape_html(&self.name),
+            self.start_time.with_timezone(&tz).format("%A, %B %e from %l:%M%P"),
+            self.end_time.with_timezone(&tz).format("%l:%M%P %Z"),
+            materials
+        )
+    }
+}
+
+/// Everything that goes into the packet for the applicant. We only rebuild it when the
+/// hash of this changes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApplicantPacketContents {
+    pub format: u32,
+    pub applicant_name: String,
+    /// Everyone the applicant is talking to, in the order they are talking to them.
+    pub interviewers: Vec<ApplicantPacketInterviewer>,
+}
+
+impl ApplicantPacketContents {
+    /// The hash of everything in the packet, as a hex string.
+    pub fn hash(&self) -> String {
+        hash_contents(self)
+    }
+
+    /// The letter at the start of the packet, as HTML.
+    pub fn cover_html(&self) -> String {
+        let tz = chrono_tz::US::Pacific;
+
+        let mut interviewers = "".to_string();
+        for interviewer in &self.interviewers {
+            interviewers += &format!(
+                "<tr><td>{}</td><td>{} - {}</td></tr>\n",
+                escape_html(&interviewer.name),
+                interviewer.start_time.with_timezone(&tz).format("%A, %B %e from %l:%M%P"),
+                interviewer.end_time.with_timezone(&tz).format("%l:%M%P %Z")
+            );
+        }
+
+        format!(
+            r#"<html>
+<body>
+<p>{},</p>
+<p>
+Thank you for your interest in Oxide!  We have enjoyed reading the materials
+you submitted to Oxide, and we are looking forward to having more conversations
+with you.
+<p>
+At Oxide, we believe that you should be choosing to work with us as much as
+we are choosing to work with you: teamwork is one of our values, and the
+inspiration that we draw from our colleagues forms an important part of our
+motivation.  Because every Oxide employee (including the founders!) has
+submitted written answers to the same questions, we are afforded a unique
+opportunity to inform our conversations with you: by sharing an employees' Oxide
+materials with you, <b>you can get to know Oxide employees</b> as much as we
+get to know you.
+<p>
+In this document, you will find the Oxide materials of the people with whom
+you will be talking:
+<p>
+<table>
+{}</table>
+<p>
+It should go without saying that you should treat these materials in confidence,
+but they are open within the walls of Oxide.
+(That is, we have all read one another's materials.)
+Feel free to print this packet out and refer to it during your conversations
+with Oxide.
+<p>
+Let us know if you have any questions, and thank you again for your interest
+in Oxide!
+<p>
+Sincerely,<br>
+The Oxide Team
+</body>
+</html>"#,
+            escape_html(&self.applicant_name),
+            interviewers
+        )
+    }
+}
+
+/// Materials in Google Drive, as they go into a packet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PacketMaterials {
+    /// The ID of the file in Google Drive, and its checksum, so changes to the file
+    /// are picked up without downloading it.
+    pub id: String,
+    pub checksum: String,
+    /// If the materials are a PDF, they are added to the packet as they are.
+    /// Otherwise we add the text we extracted from them.
+    pub pdf: bool,
+    pub text: String,
+}
+
+fn hash_contents<T: Serialize>(contents: &T) -> String {
+    let json = serde_json::to_vec(contents).unwrap();
+    openssl::sha::sha256(&json).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Group the interviews that still need a packet by interviewer and applicant email.
+/// An interview needs a packet until `INTERVIEW_PACKET_ACCESS_HOURS` after it ends.
+pub fn get_packet_interviews(interviews: &[ApplicantInterview], now: DateTime<Utc>) -> BTreeMap<(String, String), Vec<ApplicantInterview>> {
+    let mut packets: BTreeMap<(String, String), Vec<ApplicantInterview>> = Default::default();
+    for interview in interviews {
+        if interview.end_time + Duration::hours(INTERVIEW_PACKET_ACCESS_HOURS) < now || interview.email.is_empty() {
+            continue;
+        }
+
+        for interviewer in &interview.interviewers {
+            packets.entry((interviewer.to_string(), interview.email.to_string())).or_default().push(interview.clone());
+        }
+    }
+
+    for interviews in packets.values_mut() {
+        interviews.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    }
+
+    packets
+}
+
+/// Work out which permissions on a packet to add or remove, so that it is shared with
+/// the interviewer and nobody else, or with nobody if `share` is false.
+/// Returns if the interviewer needs to be added, and the IDs of the permissions to remove.
+/// Permissions that only come from the shared drive are left alone, they are for the
+/// account we run as.
+pub fn plan_packet_permissions(permissions: &[Permission], interviewer: &str, share: bool) -> (bool, Vec<String>) {
+    let mut found = false;
+    let mut remove: Vec<String> = Default::default();
+
+    for permission in permissions {
+        let inherited = permission
+            .permission_details
+            .as_ref()
+            .map(|details| !details.is_empty() && details.iter().all(|d| d.inherited.unwrap_or_default()))
+            .unwrap_or_default();
+        let role = permission.role.as_deref().unwrap_or_default();
+        if inherited || role == "owner" || role == "organizer" {
+            continue;
+        }
+
+        let is_interviewer = permission.type_.as_deref() == Some("user") && permission.email_address.as_deref().map(|e| e.eq_ignore_ascii_case(interviewer)).unwrap_or_default();
+        if share && is_interviewer && !found {
+            found = true;
+            continue;
+        }
+
+        if let Some(id) = &permission.id {
+            remove.push(id.to_string());
+        }
+    }
+
+    (share && !found, remove)
+}
+
+/// Get the name of the packet in Google Drive. The hash of the applicant's email keeps
+/// the names of applicants with the same name apart.
+fn packet_file_name(applicant: &Applicant, interviewer: &str) -> String {
+    let name = if interviewer.is_empty() {
+        format!("Interview Packet - {} ({}).pdf", applicant.name, &hash_applicant_email(&applicant.email)[..8])
+    } else {
+        let username = interviewer.split('@').next().unwrap_or_default();
+        format!("Interview Packet - {} ({}) - {}.pdf", applicant.name, &hash_applicant_email(&applicant.email)[..8], username)
+    };
+    // We find the file by name, and quotes would break the query.
+    name.replace('\'', "").replace('\\', "")
+}
+
+/// Find the application an interview is for. Interviews are linked to the applicant
+/// in Airtable, otherwise we go by email and prefer an application that is interviewing.
+fn find_applicant<'a>(applicants: &'a [Applicant], interview: &ApplicantInterview) -> Option<&'a Applicant> {
+    if let Some(a) = applicants.iter().find(|a| !a.airtable_record_id.is_empty() && interview.applicant.contains(&a.airtable_record_id)) {
+        return Some(a);
+    }
+
+    let mut matches: Vec<&Applicant> = applicants.iter().filter(|a| a.email == interview.email).collect();
+    matches.sort_by_key(|a| Status::from_str(&a.status) != Ok(Status::Interviewing));
+    matches.first().copied()
+}
+
+/// Get everything that goes into the packet for the interviewer about the applicant.
+async fn get_packet_contents(
+    db: &Database,
+    drive_client: &GoogleDrive,
+    applicant: &Applicant,
+    interviewer: &str,
+    interviews: &[ApplicantInterview],
+    users: &[User],
+) -> Result<InterviewPacketContents, String> {
+    let name = |email: &str| users.iter().find(|u| u.email() == email).map(|u| u.full_name()).unwrap_or_else(|| email.to_string());
+
+    let mut contents = InterviewPacketContents {
+        format: INTERVIEW_PACKET_FORMAT,
+        interviewer_name: name(interviewer),
+        applicant_name: applicant.name.to_string(),
+        role: applicant.role.to_string(),
+        materials_id: "".to_string(),
+        materials_checksum: "".to_string(),
+        materials_pdf: false,
+        materials_text: applicant.materials_contents.to_string(),
+        scores: get_scores_for_applicant(db, applicant)
+            .into_iter()
+            .map(|s| PacketScore {
+                reviewer: name(&s.reviewer),
+                recommendation: s.recommendation,
+                score: s.score,
+                technical_depth: s.technical_depth,
+                writing: s.writing,
+                concerns: s.concerns,
+                notes: s.notes,
+            })
+            .collect(),
+        schedule: interviews
+            .iter()
+            .map(|i| PacketInterview {
+                interviewers: i.interviewers.iter().map(|e| name(e)).collect(),
+                start_time: i.start_time,
+                end_time: i.end_time,
+                yours: i.interviewers.iter().any(|e| e == interviewer),
+            })
+            .collect(),
+    };
+
+    let materials = get_packet_materials(drive_client, &applicant.materials, &applicant.materials_contents).await?;
+    contents.materials_id = materials.id;
+    contents.materials_checksum = materials.checksum;
+    contents.materials_pdf = materials.pdf;
+    contents.materials_text = materials.text;
+
+    Ok(contents)
+}
+
+/// Get the materials at the link, with the text we extracted from them.
+async fn get_packet_materials(drive_client: &GoogleDrive, materials_link: &str, text: &str) -> Result<PacketMaterials, String> {
+    let mut materials = PacketMaterials {
+        text: text.to_string(),
+        ..Default::default()
+    };
+
+    if let Some(id) = get_drive_file_id(materials_link) {
+        let file = drive_client.get_file_by_id(&id).await.map_err(|e| format!("getting the materials {} failed: {}", id, e))?;
+        materials.pdf = MaterialsFormat::from_name(&file.name, &file.mime_type) == MaterialsFormat::Pdf;
+        materials.checksum = if file.md5_checksum.is_empty() {
+            format!("{}@{}", file.version, file.modified_time)
+        } else {
+            file.md5_checksum
+        };
+        materials.id = id;
+    }
+    if materials.pdf {
+        // The PDF goes in as it is.
+        materials.text = "".to_string();
+    }
+
+    Ok(materials)
+}
+
+/// Get everything that goes into the packet for the applicant. The materials of each
+/// interviewer are the ones they wrote when they applied, and are kept in `materials`
+/// by interviewer, so we only look them up once.
+async fn get_applicant_packet_contents(
+    drive_client: &GoogleDrive,
+    applicant: &Applicant,
+    schedule: &[ApplicantInterview],
+    users: &[User],
+    applicants: &[Applicant],
+    materials: &mut BTreeMap<String, PacketMaterials>,
+) -> Result<ApplicantPacketContents, String> {
+    let mut contents = ApplicantPacketContents {
+        format: INTERVIEW_PACKET_FORMAT,
+        applicant_name: applicant.name.to_string(),
+        interviewers: Default::default(),
+    };
+
+    for interview in schedule {
+        for interviewer in &interview.interviewers {
+            let user = users.iter().find(|u| u.email() == *interviewer);
+            if !materials.contains_key(interviewer) {
+                let application = user.and_then(|u| applicants.iter().find(|a| !u.recovery_email.is_empty() && a.email == u.recovery_email));
+                let found = match application {
+                    Some(a) => get_packet_materials(drive_client, &a.materials, &a.materials_contents).await?,
+                    None => {
+                        println!("[interview_packets] could not find the materials for {}", interviewer);
+                        Default::default()
+                    }
+                };
+                materials.insert(interviewer.to_string(), found);
+            }
+
+            contents.interviewers.push(ApplicantPacketInterviewer {
+                name: user.map(|u| u.full_name()).unwrap_or_else(|| interviewer.to_string()),
+                start_time: interview.start_time,
+                end_time: interview.end_time,
+                materials: materials[interviewer].clone(),
+            });
+        }
+    }
+
+    Ok(contents)
+}
+
+/// Convert the HTML to a PDF in the sandbox, and return the path to it.
+fn html_to_pdf(sandbox: &Sandbox, html: &str) -> Result<String, String> {
+    let html_path = sandbox.path("html");
+    fs::write(&html_path, html).map_err(|e| format!("writing {} failed: {}", html_path.display(), e))?;
+    let pdf = sandbox.path("pdf");
+    let mut pandoc = pandoc::new();
+    pandoc.add_input(&html_path);
+    pandoc.set_output(OutputKind::File(pdf.clone()));
+    pandoc.execute().map_err(|e| format!("converting {} to a PDF failed: {}", html_path.display(), e))?;
+
+    Ok(pdf.to_str().unwrap().to_string())
+}
+
+/// Download the PDF from Google Drive to the sandbox, and return the path to it.
+async fn download_pdf(drive_client: &GoogleDrive, sandbox: &Sandbox, id: &str) -> Result<String, String> {
+    let bytes = drive_client.download_file_by_id(id).await.map_err(|e| format!("downloading the materials {} failed: {}", id, e))?;
+    let pdf = sandbox.path("pdf");
+    fs::write(&pdf, &bytes).map_err(|e| format!("writing {} failed: {}", pdf.display(), e))?;
+
+    Ok(pdf.to_str().unwrap().to_string())
+}
+
+/// Build the PDF for a packet.
+async fn build_packet(drive_client: &GoogleDrive, contents: &InterviewPacketContents) -> Result<Vec<u8>, String> {
+    let sandbox = Sandbox::new()?;
+
+    let mut pdfs = vec![html_to_pdf(&sandbox, &contents.cover_html())?];
+    if contents.materials_pdf {
+        pdfs.push(download_pdf(drive_client, &sandbox, &contents.materials_id).await?);
+    }
+
+    combine_pdfs(pdfs)
+}
+
+/// Build the PDF for the packet for the applicant: the letter, then a page for each
+/// interviewer followed by their materials.
+async fn build_applicant_packet(drive_client: &GoogleDrive, contents: &ApplicantPacketContents) -> Result<Vec<u8>, String> {
+    let sandbox = Sandbox::new()?;
+
+    let mut pdfs = vec![html_to_pdf(&sandbox, &contents.cover_html())?];
+    for interviewer in &contents.interviewers {
+        pdfs.push(html_to_pdf(&sandbox, &interviewer.header_html())?);
+        if interviewer.materials.pdf {
+            pdfs.push(download_pdf(drive_client, &sandbox, &interviewer.materials.id).await?);
+        }
+    }
+
+    combine_pdfs(pdfs)
+}
+
+/// Make sure the packet is shared with the interviewer and nobody else, or with nobody
+/// if `share` is false. Returns the ID of the interviewer's permission, if we added it.
+async fn update_packet_permissions(drive_client: &GoogleDrive, file_id: &str, interviewer: &str, share: bool) -> Result<Option<String>, String> {
+    let permissions = drive_client
+        .list_permissions(file_id)
+        .await
+        .map_err(|e| format!("listing the permissions on {} failed: {}", file_id, e))?;
+
+    let (add, remove) = plan_packet_permissions(&permissions, interviewer, share);
+    for id in remove {
+        drive_client
+            .delete_permission(file_id, &id)
+            .await
+            .map_err(|e| format!("removing permission {} from {} failed: {}", id, file_id, e))?;
+    }
+
+    if !add {
+        return Ok(None);
+    }
+
+    // Let Google send the interviewer the link.
+    let permission = drive_client
+        .create_user_permission(file_id, interviewer, "reader", true)
+        .await
+        .map_err(|e| format!("sharing {} with {} failed: {}", file_id, interviewer, e))?;
+    Ok(permission.id)
+}
+
+/// Rebuild the packet for the interviewer about the applicant if anything in it changed,
+/// and make sure it is shared with them. The schedule is all of the applicant's interviews,
+/// sorted by start time.
+async fn refresh_interview_packet(
+    db: &Database,
+    drive_client: &GoogleDrive,
+    drive_id: &str,
+    applicant: &Applicant,
+    interviewer: &str,
+    schedule: &[ApplicantInterview],
+    users: &[User],
+) -> Result<(), String> {
+    let now = Utc::now();
+    let interviews: Vec<&ApplicantInterview> = schedule.iter().filter(|i| i.interviewers.iter().any(|e| e == interviewer)).collect();
+    if interviews.is_empty() {
+        return Err("the interviewer is not in any of the interviews".to_string());
+    }
+    let contents = get_packet_contents(db, drive_client, applicant, interviewer, schedule, users).await?;
+    let hash = contents.hash();
+
+    let existing = InterviewPacket::get_from_db(db, interviewer.to_string(), applicant.email.to_string(), applicant.sheet_id.to_string());
+    let mut packet = NewInterviewPacket {
+        interviewer: interviewer.to_string(),
+        applicant_email: applicant.email.to_string(),
+        applicant_name: applicant.name.to_string(),
+        role: applicant.role.to_string(),
+        sheet_id: applicant.sheet_id.to_string(),
+        interview_start_time: interviews.first().unwrap().start_time,
+        interview_end_time: interviews.iter().map(|i| i.end_time).max().unwrap(),
+        content_hash: Default::default(),
+        google_drive_id: Default::default(),
+        link: Default::default(),
+        permission_id: Default::default(),
+        built_time: None,
+        shared_time: None,
+        revoked_time: None,
+    };
+    if let Some(existing) = existing {
+        packet.content_hash = existing.content_hash;
+        packet.google_drive_id = existing.google_drive_id;
+        packet.link = existing.link;
+        packet.permission_id = existing.permission_id;
+        packet.built_time = existing.built_time;
+        packet.shared_time = existing.shared_time;
+    }
+
+    if packet.content_hash != hash || packet.google_drive_id.is_empty() {
+        let buffer = build_packet(drive_client, &contents).await?;
+        let file_name = packet_file_name(applicant, interviewer);
+        let drive_file = drive_client
+            .create_or_update_file(drive_id, "", &file_name, "application/pdf", &buffer)
+            .await
+            .map_err(|e| format!("uploading {} failed: {}", file_name, e))?;
+
+        println!("[interview_packets] built the packet for {} about {}", interviewer, applicant.email);
+        packet.content_hash = hash;
+        packet.google_drive_id = drive_file.id.to_string();
+        packet.link = format!("https://drive.google.com/open?id={}", drive_file.id);
+        packet.built_time = Some(now);
+    }
+
+    // Check the permissions every time, so anyone else who was given access loses it.
+    if let Some(permission_id) = update_packet_permissions(drive_client, &packet.google_drive_id, interviewer, true).await? {
+        println!("[interview_packets] shared the packet about {} with {}", applicant.email, interviewer);
+        packet.permission_id = permission_id;
+        packet.shared_time = Some(now);
+    }
+
+    packet.upsert(db).await;
+
+    Ok(())
+}
+
+/// Rebuild the packet for the applicant if anything in it changed, and link it from
+/// the applicant. The packet goes in the folder with `parent_id` in the shared drive
+/// with `drive_id`.
+async fn refresh_applicant_packet(
+    db: &Database,
+    drive_client: &GoogleDrive,
+    drive_id: &str,
+    parent_id: &str,
+    applicant: &Applicant,
+    schedule: &[ApplicantInterview],
+    contents: &ApplicantPacketContents,
+) -> Result<(), String> {
+    let hash = contents.hash();
+
+    let existing = InterviewPacket::get_from_db(db, "".to_string(), applicant.email.to_string(), applicant.sheet_id.to_string());
+    let mut packet = NewInterviewPacket {
+        interviewer: "".to_string(),
+        applicant_email: applicant.email.to_string(),
+        applicant_name: applicant.name.to_string(),
+        role: applicant.role.to_string(),
+        sheet_id: applicant.sheet_id.to_string(),
+        interview_start_time: schedule.first().unwrap().start_time,
+        interview_end_time: schedule.iter().map(|i| i.end_time).max().unwrap(),
+        content_hash: Default::default(),
+        google_drive_id: Default::default(),
+        link: Default::default(),
+        permission_id: Default::default(),
+        built_time: None,
+        shared_time: None,
+        revoked_time: None,
+    };
+    if let Some(existing) = existing {
+        packet.content_hash = existing.content_hash;
+        packet.google_drive_id = existing.google_drive_id;
+        packet.link = existing.link;
+        packet.built_time = existing.built_time;
+    }
+
+    if packet.content_hash != hash || packet.google_drive_id.is_empty() {
+        let buffer = build_applicant_packet(drive_client, contents).await?;
+        let file_name = packet_file_name(applicant, "");
+        let drive_file = drive_client
+            .create_or_update_file(drive_id, parent_id, &file_name, "application/pdf", &buffer)
+            .await
+            .map_err(|e| format!("uploading {} failed: {}", file_name, e))?;
+
+        println!("[interview_packets] built the packet for {}", applicant.email);
+        packet.content_hash = hash;
+        packet.google_drive_id = drive_file.id.to_string();
+        packet.link = format!("https://drive.google.com/open?id={}", drive_file.id);
+        packet.built_time = Some(Utc::now());
+    }
+
+    let packet = packet.upsert(db).await;
+    if applicant.interview_packet != packet.link {
+        let mut applicant = applicant.clone();
+        applicant.interview_packet = packet.link.to_string();
+        applicant.update(db).await;
+    }
+
+    Ok(())
+}
+
+/// Get the IDs of the shared drive and folder we keep the packets for applicants in.
+async fn get_applicant_packets_folder(drive_client: &GoogleDrive) -> Result<(String, String), String> {
+    let drive = drive_client
+        .get_drive_by_name(APPLICANT_PACKETS_DRIVE)
+        .await
+        .map_err(|e| format!("getting the shared drive `{}` failed: {}", APPLICANT_PACKETS_DRIVE, e))?;
+    let folders = drive_client
+        .get_file_by_name(&drive.id, APPLICANT_PACKETS_FOLDER)
+        .await
+        .map_err(|e| format!("getting the folder `{}` failed: {}", APPLICANT_PACKETS_FOLDER, e))?;
+    match folders.into_iter().next() {
+        Some(folder) => Ok((drive.id, folder.id)),
+        None => Err(format!("the folder `{}` was not found", APPLICANT_PACKETS_FOLDER)),
+    }
+}
+
+impl InterviewPacket {
+    /// Take away everyone's access to the packet.
+    pub async fn revoke(&mut self, db: &Database, drive_client: &GoogleDrive) -> Result<(), String> {
+        if !self.google_drive_id.is_empty() {
+            update_packet_permissions(drive_client, &self.google_drive_id, &self.interviewer, false).await?;
+        }
+
+        self.permission_id = "".to_string();
+        self.revoked_time = Some(Utc::now());
+        *self = self.update(db).await;
+
+        Ok(())
+    }
+}
+
+/// Build a packet for every interviewer about each applicant they are interviewing, and
+/// share it with them. Packets are only rebuilt when something in them changed, and the
+/// interviewers lose access once their interviews with the applicant are over.
+///
+/// Each applicant gets a packet too, with the materials of the people they are talking to.
+/// It is linked from the applicant, for us to send to them.
+pub async fn refresh_interview_packets(db: &Database) {
+    let now = Utc::now();
+
+    // Initialize the Google Drive client.
+    let drive_client = GoogleDrive::new(get_gsuite_token("").await);
+    let drive_id = match drive_client.get_drive_by_name(INTERVIEW_PACKETS_DRIVE).await {
+        Ok(drive) => drive.id,
+        Err(e) => {
+            println!("[interview_packets] getting the shared drive `{}` failed: {}", INTERVIEW_PACKETS_DRIVE, e);
+            return;
+        }
+    };
+
+    let users: Vec<User> = Users::get_from_db(db).into_iter().collect();
+    let applicants: Vec<Applicant> = Applicants::get_from_db(db).into_iter().collect();
+    let interviews: Vec<ApplicantInterview> = ApplicantInterviews::get_from_db(db).into_iter().collect();
+
+    // The packets show the rest of the applicant's interviews too.
+    let get_schedule = |email: &str| {
+        let mut schedule: Vec<ApplicantInterview> = interviews
+            .iter()
+            .filter(|i| i.email == email && i.end_time + Duration::hours(INTERVIEW_PACKET_ACCESS_HOURS) >= now)
+            .cloned()
+            .collect();
+        schedule.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        schedule
+    };
+
+    let packet_interviews = get_packet_interviews(&interviews, now);
+    let mut active: BTreeSet<(String, String, String)> = Default::default();
+    for ((interviewer, email), their_interviews) in packet_interviews.iter() {
+        let applicant = match find_applicant(&applicants, &their_interviews[0]) {
+            Some(applicant) => applicant,
+            None => {
+                println!("[interview_packets] could not find the applicant with email {}", email);
+                continue;
+            }
+        };
+        active.insert((interviewer.to_string(), applicant.email.to_string(), applicant.sheet_id.to_string()));
+
+        if let Err(e) = refresh_interview_packet(db, &drive_client, &drive_id, applicant, interviewer, &get_schedule(email.as_str()), &users).await {
+            println!("[interview_packets] refreshing the packet for {} about {} failed: {}", interviewer, email, e);
+        }
+    }
+
+    match get_applicant_packets_folder(&drive_client).await {
+        Ok((applicant_drive_id, parent_id)) => {
+            let mut materials: BTreeMap<String, PacketMaterials> = Default::default();
+            let emails: BTreeSet<&String> = packet_interviews.keys().map(|(_, email)| email).collect();
+            for email in emails {
+                let schedule = get_schedule(email.as_str());
+                let applicant = match find_applicant(&applicants, &schedule[0]) {
+                    Some(applicant) => applicant,
+                    None => continue,
+                };
+
+                let result = match get_applicant_packet_contents(&drive_client, applicant, &schedule, &users, &applicants, &mut materials).await {
+                    Ok(contents) => refresh_applicant_packet(db, &drive_client, &applicant_drive_id, &parent_id, applicant, &schedule, &contents).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    println!("[interview_packets] refreshing the packet for {} failed: {}", email, e);
+                }
+            }
+        }
+        Err(e) => println!("[interview_packets] not refreshing the packets for applicants: {}", e),
+    }
+
+    // Take away access to the packets for interviews that are over, or that the
+    // interviewer is no longer in. The packets for applicants are not shared.
+    for mut packet in InterviewPackets::get_from_db(db) {
+        if packet.interviewer.is_empty() || packet.revoked_time.is_some() || active.contains(&(packet.interviewer.to_string(), packet.applicant_email.to_string(), packet.sheet_id.to_string())) {
+            continue;
+        }
+
+        match packet.revoke(db, &drive_client).await {
+            Ok(_) => println!("[interview_packets] revoked access for {} to the packet about {}", packet.interviewer, packet.applicant_email),
+            Err(e) => println!(
+                "[interview_packets] revoking access for {} to the packet about {} failed: {}",
+                packet.interviewer, packet.applicant_email, e
+            ),
+        }
+    }
+}
+
+/// Combine multiple pdfs into one pdf and return the byte stream of it.
+pub fn combine_pdfs(pdfs: Vec<String>) -> Result<Vec<u8>, String> {
+    // Define a starting max_id (will be used as start index for object_ids)
+    let mut max_id = 1;
+    let mut pagenum = 1;
+    // Collect all Documents Objects grouped by a map
+    let mut documents_pages = BTreeMap::new();
+    let mut documents_objects = BTreeMap::new();
+    let mut document = Document::with_version("1.5");
+
+    for pdf in pdfs {
+        // Load the pdf as a file.
+        let mut doc = Document::load(&pdf).map_err(|e| format!("loading {} failed: {}", pdf, e))?;
+
+        let mut first = false;
+        doc.renumber_objects_with(max_id);
+
+        max_id = doc.max_id + 1;
+
+        documents_pages.extend(
+            doc.get_pages()
+                .into_iter()
+                .map(|(_, object_id)| {
+                    if !first {
+                        let bookmark = Bookmark::new(format!("Page_{}", pagenum), [0.0, 0.0, 1.0], 0, object_id);
+                        document.add_bookmark(bookmark, None);
+                        first = true;
+                        pagenum += 1;
+                    }
+
+                    (object_id, doc.get_object(object_id).unwrap().to_owned())
+                })
+                .collect::<BTreeMap<ObjectId, Object>>(),
+        );
+        documents_objects.extend(doc.objects);
+    }
+
+    // Catalog and Pages are mandatory
+    let mut catalog_object: Option<(ObjectId, Object)> = None;
+    let mut pages_object: Option<(ObjectId, Object)> = None;
+
+    // Process all objects except "Page" type
+    for (object_id, object) in documents_objects.iter() {
+        // We have to ignore "Page" (as are processed later), "Outlines" and "Outline" objects
+        // All other objects should be collected and inserted into the main Document
+        match object.type_name().unwrap_or("") {
+            "Catalog" => {
+                // Collect a first "Catalog" object and use it for the future "Pages"
+                catalog_object = Some((if let Some((id, _)) = catalog_object { id } else { *object_id }, object.clone()));
+            }
+            "Pages" => {
+                // Collect and update a first "Pages" object and use it for the future "Catalog"
+                // We have also to merge all dictionaries of the old and the new "Pages" object
+                if let Ok(dictionary) = object.as_dict() {
+                    let mut dictionary = dictionary.clone();
+                    if let Some((_, ref object)) = pages_object {
+                        if let Ok(old_dictionary) = object.as_dict() {
+                            dictionary.extend(old_dictionary);
+                        }
+                    }
+
+                    pages_object = Some((if let Some((id, _)) = pages_object { id } else { *object_id }, Object::Dictionary(dictionary)));
+                }
+            }
+            "Page" => {}     // Ignored, processed later and separately
+            "Outlines" => {} // Ignored, not supported yet
+            "Outline" => {}  // Ignored, not supported yet
+            _ => {
+                document.objects.insert(*object_id, object.clone());
+            }
+        }
+    }
+
+    // If no "Pages" found abort
+    if pages_object.is_none() {
+        return Err("[merge-pdfs] pages root not found.".to_string());
+    }
+
+    // Iter over all "Page" and collect with the parent "Pages" created before
+    for (object_id, object) in documents_pages.iter() {
+        if let Ok(dictionary) = object.as_dict() {
+            let mut dictionary = dictionary.clone();
+            dictionary.set("Parent", pages_object.as_ref().unwrap().0);
+
+            document.objects.insert(*object_id, Object::Dictionary(dictionary));
+        }
+    }
+
+    // If no "Catalog" found abort
+    if catalog_object.is_none() {
+        return Err("[merge-pdfs] catalog root not found.".to_string());
+    }
+
+    let catalog_object = catalog_object.unwrap();
+    let pages_object = pages_object.unwrap();
+
+    // Build a new "Pages" with updated fields
+    if let Ok(dictionary) = pages_object.1.as_dict() {
+        let mut dictionary = dictionary.clone();
+
+        // Set new pages count
+        dictionary.set("Count", documents_pages.len() as u32);
+
+        // Set new "Kids" list (collected from documents pages) for "Pages"
+        dictionary.set("Kids", documents_pages.into_iter().map(|(object_id, _)| Object::Reference(object_id)).collect::<Vec<_>>());
+
+        document.objects.insert(pages_object.0, Object::Dictionary(dictionary));
+    }
+
+    // Build a new "Catalog" with updated fields
+    if let Ok(dictionary) = catalog_object.1.as_dict() {
+        let mut dictionary = dictionary.clone();
+        dictionary.set("Pages", pages_object.0);
+        dictionary.remove(b"Outlines"); // Outlines not supported in merged PDFs
+
+        document.objects.insert(catalog_object.0, Object::Dictionary(dictionary));
+    }
+
+    document.trailer.set("Root", catalog_object.0);
+
+    // Update the max internal ID as wasn't updated before due to direct objects insertion
+    document.max_id = document.objects.len() as u32;
+
+    // Reorder all new Document objects
+    document.renumber_objects();
+
+    //Set any Bookmarks to the First child if they are not set to a page
+    document.adjust_zero_pages();
+
+    //Set all bookmarks to the PDF Object tree then set the Outlines to the Bookmark content map.
+    if let Some(n) = document.build_outline() {
+        if let Ok(Object::Dictionary(ref mut dict)) = document.get_object_mut(catalog_object.0) {
+            dict.set("Outlines", Object::Reference(n));
+        }
+    }
+
+    document.compress();
+
+    // Save the merged PDF
+    let mut buffer = Vec::new();
+    document.save_to(&mut buffer).map_err(|e| format!("saving the combined PDF failed: {}", e))?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use google_drive::{Permission, PermissionPermissionDetails};
+
+    use crate::interview_packets::{
+        get_packet_interviews, plan_packet_permissions, ApplicantPacketContents, ApplicantPacketInterviewer, InterviewPacketContents, PacketInterview, PacketMaterials, PacketScore,
+        INTERVIEW_PACKET_FORMAT,
+    };
+    use crate::interviews::ApplicantInterview;
+
+    fn interview(id: i32, email: &str, interviewers: &[&str], hours_from_now: i64) -> ApplicantInterview {
+        let start_time = Utc::now() + Duration::hours(hours_from_now);
+        ApplicantInterview {
+            id,
+            start_time,
+            end_time: start_time + Duration::hours(1),
+            name: "".to_string(),
+            email: email.to_string(),
+            interviewers: interviewers.iter().map(|i| i.to_string()).collect(),
+            google_event_id: format!("event-{}", id),
+            event_link: "".to_string(),
+            applicant: vec![],
+            airtable_record_id: "".to_string(),
+        }
+    }
+
+    fn permission(id: &str, email: &str, role: &str, inherited: bool) -> Permission {
+        Permission {
+            id: Some(id.to_string()),
+            type_: Some("user".to_string()),
+            email_address: Some(email.to_string()),
+            role: Some(role.to_string()),
+            permission_details: Some(vec![PermissionPermissionDetails {
+                inherited: Some(inherited),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }
+    }
+
+    fn contents() -> InterviewPacketContents {
+        InterviewPacketContents {
+            format: INTERVIEW_PACKET_FORMAT,
+            interviewer_name: "Alice Example".to_string(),
+            applicant_name: "Jane <Doe>".to_string(),
+            role: "Engineering".to_string(),
+            materials_id: "abc".to_string(),
+            materials_checksum: "123".to_string(),
+            materials_pdf: false,
+            materials_text: "I like <b>computers</b> & systems.".to_string(),
+            scores: vec![PacketScore {
+                reviewer: "Bob Example".to_string(),
+                recommendation: "Yes".to_string(),
+                score: 8,
+                technical_depth: 7,
+                writing: 9,
+                concerns: vec![],
+                notes: "Great writing.".to_string(),
+            }],
+            schedule: vec![PacketInterview {
+                interviewers: vec!["Alice Example".to_string()],
+                start_time: Utc.ymd(2021, 6, 30).and_hms(16, 0, 0),
+                end_time: Utc.ymd(2021, 6, 30).and_hms(17, 0, 0),
+                yours: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_get_packet_interviews() {
+        let interviews = vec![
+            interview(1, "jane@example.com", &["alice@oxide.computer", "bob@oxide.computer"], 48),
+            interview(2, "jane@example.com", &["alice@oxide.computer"], 24),
+            // Over, but within the time interviewers keep access.
+            interview(3, "john@example.com", &["carol@oxide.computer"], -4),
+            // Over for long enough that nobody needs the packet.
+            interview(4, "john@example.com", &["dave@oxide.computer"], -48),
+        ];
+
+        let packets = get_packet_interviews(&interviews, Utc::now());
+        let keys: Vec<(String, String)> = packets.keys().cloned().collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("alice@oxide.computer".to_string(), "jane@example.com".to_string()),
+                ("bob@oxide.computer".to_string(), "jane@example.com".to_string()),
+                ("carol@oxide.computer".to_string(), "john@example.com".to_string()),
+            ]
+        );
+
+        // The interviews are sorted by when they start.
+        let alice: Vec<i32> = packets[&("alice@oxide.computer".to_string(), "jane@example.com".to_string())].iter().map(|i| i.id).collect();
+        assert_eq!(alice, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_plan_packet_permissions() {
+        let permissions = vec![
+            permission("1", "robot@oxide.computer", "organizer", true),
+            permission("2", "alice@oxide.computer", "reader", false),
+            permission("3", "bob@oxide.computer", "reader", false),
+        ];
+
+        // Alice keeps her access, Bob loses his, and the drive member is left alone.
+        assert_eq!(plan_packet_permissions(&permissions, "alice@oxide.computer", true), (false, vec!["3".to_string()]));
+        // Carol needs to be added.
+        assert_eq!(plan_packet_permissions(&permissions, "carol@oxide.computer", true), (true, vec!["2".to_string(), "3".to_string()]));
+        // Once the interview is over, nobody keeps access.
+        assert_eq!(plan_packet_permissions(&permissions, "alice@oxide.computer", false), (false, vec!["2".to_string(), "3".to_string()]));
+
+        // Someone who was also given access to the file directly loses it.
+        let mut shared = permission("4", "dave@oxide.computer", "reader", true);
+        shared.permission_details.as_mut().unwrap().push(PermissionPermissionDetails {
+            inherited: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(plan_packet_permissions(&[shared], "alice@oxide.computer", true), (true, vec!["4".to_string()]));
+    }
+
+    #[test]
+    fn test_applicant_packet_html() {
+        let interviewer = |name: &str, pdf: bool, text: &str| ApplicantPacketInterviewer {
+            name: name.to_string(),
+            start_time: Utc.ymd(2021, 6, 30).and_hms(16, 0, 0),
+            end_time: Utc.ymd(2021, 6, 30).and_hms(17, 0, 0),
+            materials: PacketMaterials {
+                pdf,
+                text: text.to_string(),
+                ..Default::default()
+            },
+        };
+        let packet = ApplicantPacketContents {
+            format: INTERVIEW_PACKET_FORMAT,
+            applicant_name: "Jane <Doe>".to_string(),
+            interviewers: vec![interviewer("Alice Example", true, ""), interviewer("Bob Example", false, "I like <b>computers</b>.")],
+        };
+
+        let cover = packet.cover_html();
+        assert!(cover.contains("<p>Jane &lt;Doe&gt;,</p>"));
+        assert!(cover.contains("<tr><td>Alice Example</td><td>Wednesday, June 30 from  9:00am - 10:00am PDT</td></tr>"));
+        assert!(cover.contains("<tr><td>Bob Example</td>"));
+
+        assert!(packet.interviewers[0].header_html().contains("<h1>Alice Example</h1>"));
+        assert!(!packet.interviewers[0].header_html().contains("<pre>"));
+        assert!(packet.interviewers[1].header_html().contains("<pre>I like &lt;b&gt;computers&lt;/b&gt;.</pre>"));
+        assert!(interviewer("Carol Example", false, "").header_html().contains("We could not find their materials."));
+
+        // A new interviewer is a new packet.
+        let mut changed = packet.clone();
+        changed.interviewers.push(interviewer("Carol Example", false, ""));
+        assert_ne!(packet.hash(), changed.hash());
+    }
+
+    #[test]
+    fn test_interview_packet_contents_hash() {
+        let packet = contents();
+        assert_eq!(packet.hash(), contents().hash());
+        assert_eq!(packet.hash().len(), 64);
+
+        // A new version of the materials is a new packet.
+        let mut changed = contents();
+        changed.materials_checksum = "456".to_string();
+        assert_ne!(packet.hash(), changed.hash());
+
+        // So is a new score.
+        let mut changed = contents();
+        changed.scores[0].notes = "Great writing, and code.".to_string();
+        assert_ne!(packet.hash(), changed.hash());
+    }
+
+    #[test]
+    fn test_interview_packet_cover_html() {
+        let html = contents().cover_html();
+        assert!(html.contains("<h1>Jane &lt;Doe&gt;</h1>"));
+        assert!(html.contains("<pre>I like &lt;b&gt;computers&lt;/b&gt; &amp; systems.</pre>"));
+        assert!(html.contains("<td><b>Alice Example</b></td>"));
+        assert!(html.contains("<p><b>Bob Example:</b> Great writing.</p>"));
+
+        let mut packet = contents();
+        packet.materials_pdf = true;
+        packet.materials_text = "".to_string();
+        assert!(packet.cover_html().contains("The materials follow on the next pages."));
+    }
+}
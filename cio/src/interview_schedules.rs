@@ -0,0 +1,1149 @@
+#![allow(clippy::from_over_into)]
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use gsuite_api::{Attendee, CalendarEvent, ConferenceData, Date, GSuite};
+use hubcaps::Github;
+use macros::db;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use schemars::JsonSchema;
+use sendgrid_api::SendGrid;
+use serde::{Deserialize, Serialize};
+use zoom_api::{NewMeeting, NewMeetingSettings, Zoom};
+
+use crate::airtable::{AIRTABLE_BASE_ID_RECURITING_APPLICATIONS, AIRTABLE_INTERVIEW_SCHEDULES_TABLE};
+use crate::applicant_status::Status;
+use crate::applicants::{Applicant, Applicants};
+use crate::configs::{get_configs_from_repo, User, Users};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::interviews::{ApplicantInterview, ApplicantInterviews, NewApplicantInterview};
+use crate::schema::applicant_interview_schedules;
+use crate::utils::{authenticate_github_jwt, get_gsuite_token, DOMAIN, GSUITE_DOMAIN};
+
+/// The most times we offer an applicant.
+pub static MAX_PROPOSED_PANELS: usize = 10;
+
+/// The most times we offer an applicant on a single day, so they get a choice of days.
+pub static MAX_PROPOSED_PANELS_PER_DAY: usize = 2;
+
+/// How far apart the start times we try are.
+static PROPOSAL_STEP_MINUTES: i64 = 30;
+
+/// How many interviewers we try before giving up on a start time. Panels are
+/// small, this only stops us from trying every combination when there is none.
+static MAX_ASSIGNMENT_STEPS: usize = 10_000;
+
+fn default_panel_interviewers() -> usize {
+    4
+}
+
+fn default_interview_minutes() -> i64 {
+    60
+}
+
+fn default_panel_time_zone() -> String {
+    "America/Los_Angeles".to_string()
+}
+
+fn default_day_start_hour() -> u32 {
+    9
+}
+
+fn default_day_end_hour() -> u32 {
+    17
+}
+
+fn default_scheduling_days() -> i64 {
+    14
+}
+
+fn default_notice_hours() -> i64 {
+    48
+}
+
+/// How the applicant and the interviewer meet.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConferenceType {
+    GoogleMeet,
+    /// A Zoom meeting hosted by the interviewer.
+    Zoom,
+}
+
+impl Default for ConferenceType {
+    fn default() -> Self {
+        ConferenceType::GoogleMeet
+    }
+}
+
+impl FromStr for ConferenceType {
+    type Err = String;
+
+    fn from_str(conference: &str) -> Result<Self, Self::Err> {
+        match conference.to_lowercase().trim() {
+            "google_meet" => Ok(ConferenceType::GoogleMeet),
+            "zoom" => Ok(ConferenceType::Zoom),
+            s => Err(format!("`{}` is not a valid conference type", s)),
+        }
+    }
+}
+
+impl fmt::Display for ConferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ConferenceType::GoogleMeet => "google_meet",
+            ConferenceType::Zoom => "zoom",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// The panel of interviewers for a role, from the configs repo.
+/// The key in the configs is the name of the role, ie. "Engineering".
+/// Roles without a panel do not get a link to schedule their interviews.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewPanelConfig {
+    /// How many people the applicant talks to. Each of them gets their own
+    /// interview, one after the other on the same day.
+    #[serde(default = "default_panel_interviewers")]
+    pub interviewers: usize,
+    /// The usernames of the people who can be on the panel.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pool: Vec<String>,
+    /// The roles that are always on the panel, by name, ie. "founder". The values
+    /// are the usernames of the people who fill the role, they do not need to be
+    /// in the pool.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub required: BTreeMap<String, Vec<String>>,
+    #[serde(default = "default_interview_minutes")]
+    pub interview_minutes: i64,
+    /// How many minutes the applicant gets between interviews.
+    #[serde(default)]
+    pub break_minutes: i64,
+    #[serde(default)]
+    pub conference: ConferenceType,
+    /// The time zone of the hours we schedule interviews in.
+    #[serde(default = "default_panel_time_zone")]
+    pub time_zone: String,
+    #[serde(default = "default_day_start_hour")]
+    pub day_start_hour: u32,
+    #[serde(default = "default_day_end_hour")]
+    pub day_end_hour: u32,
+    /// How many days out we offer times. This is also how long the link to
+    /// schedule works for.
+    #[serde(default = "default_scheduling_days")]
+    pub scheduling_days: i64,
+    /// The least notice, in hours, the interviewers get before an interview.
+    #[serde(default = "default_notice_hours")]
+    pub notice_hours: i64,
+}
+
+impl Default for InterviewPanelConfig {
+    fn default() -> Self {
+        InterviewPanelConfig {
+            interviewers: default_panel_interviewers(),
+            pool: Default::default(),
+            required: Default::default(),
+            interview_minutes: default_interview_minutes(),
+            break_minutes: 0,
+            conference: Default::default(),
+            time_zone: default_panel_time_zone(),
+            day_start_hour: default_day_start_hour(),
+            day_end_hour: default_day_end_hour(),
+            scheduling_days: default_scheduling_days(),
+            notice_hours: default_notice_hours(),
+        }
+    }
+}
+
+impl InterviewPanelConfig {
+    /// Check the panel can be scheduled at all, and return its time zone.
+    pub fn validate(&self) -> Result<Tz, String> {
+        let mut errors: Vec<String> = Default::default();
+
+        let tz = match self.time_zone.parse::<Tz>() {
+            Ok(tz) => Some(tz),
+            Err(e) => {
+                errors.push(format!("invalid time zone: {}", e));
+                None
+            }
+        };
+        if self.interviewers == 0 {
+            errors.push("the panel needs at least one interviewer".to_string());
+        }
+        if self.interview_minutes <= 0 || self.break_minutes < 0 {
+            errors.push("interviews need to be longer than a minute and breaks cannot be negative".to_string());
+        }
+        if self.day_start_hour >= self.day_end_hour || self.day_end_hour > 24 {
+            errors.push(format!("the day cannot start at {}:00 and end at {}:00", self.day_start_hour, self.day_end_hour));
+        } else if self.panel_length() > Duration::hours((self.day_end_hour - self.day_start_hour) as i64) {
+            errors.push(format!("the interviews take {} minutes, which is longer than the day", self.panel_length().num_minutes()));
+        }
+        if self.required.len() > self.interviewers {
+            errors.push(format!("the panel has {} required roles but only {} interviewers", self.required.len(), self.interviewers));
+        }
+        if self.people().len() < self.interviewers {
+            errors.push(format!("the panel has {} interviewers but only {} people to choose from", self.interviewers, self.people().len()));
+        }
+
+        match tz {
+            Some(tz) if errors.is_empty() => Ok(tz),
+            _ => Err(errors.join("; ")),
+        }
+    }
+
+    /// The usernames of everyone who could be on the panel.
+    pub fn people(&self) -> BTreeSet<String> {
+        self.pool.iter().chain(self.required.values().flatten()).cloned().collect()
+    }
+
+    /// How long the applicant is interviewing for, from the start of the first
+    /// interview to the end of the last.
+    pub fn panel_length(&self) -> Duration {
+        let n = self.interviewers as i64;
+        Duration::minutes(n * self.interview_minutes + (n - 1).max(0) * self.break_minutes)
+    }
+
+    /// The start and end of each interview, for a panel that starts at `start`.
+    fn interview_times(&self, start: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        (0..self.interviewers as i64)
+            .map(|i| {
+                let start = start + Duration::minutes(i * (self.interview_minutes + self.break_minutes));
+                (start, start + Duration::minutes(self.interview_minutes))
+            })
+            .collect()
+    }
+}
+
+/// Someone who could interview an applicant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterviewerCandidate {
+    pub username: String,
+    pub email: String,
+    /// The number of interviews they have coming up.
+    pub upcoming_interviews: usize,
+    /// The start and end of the busy times on their calendar.
+    pub busy: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl InterviewerCandidate {
+    /// Returns if nothing on their calendar overlaps with the time between `start` and `end`.
+    pub fn is_free(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        !self.busy.iter().any(|(busy_start, busy_end)| *busy_start < end && *busy_end > start)
+    }
+}
+
+/// One interview on a panel.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PanelInterview {
+    pub interviewer: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// The link to join the interview, once it is scheduled.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub meeting_link: String,
+}
+
+/// A time we can offer an applicant, with the interviewers who are free then.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedPanel {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub interviews: Vec<PanelInterview>,
+}
+
+/// Choose the interviewers for a panel that starts at `start`, if there are enough
+/// people free. Every required role gets filled, and the people with the fewest
+/// upcoming interviews go first, so the load evens out over time.
+pub fn assign_panel(config: &InterviewPanelConfig, candidates: &[InterviewerCandidate], start: DateTime<Utc>) -> Option<Vec<PanelInterview>> {
+    let times = config.interview_times(start);
+    let people = config.people();
+
+    let mut ordered: Vec<&InterviewerCandidate> = candidates.iter().filter(|c| people.contains(&c.username)).collect();
+    ordered.sort_by(|a, b| a.upcoming_interviews.cmp(&b.upcoming_interviews).then(a.email.cmp(&b.email)));
+
+    let mut chosen: Vec<&InterviewerCandidate> = Default::default();
+    let mut steps = MAX_ASSIGNMENT_STEPS;
+    if !fill_panel(config, &times, &ordered, &mut chosen, &mut steps) {
+        return None;
+    }
+
+    Some(
+        chosen
+            .iter()
+            .zip(times)
+            .map(|(c, (start_time, end_time))| PanelInterview {
+                interviewer: c.email.to_string(),
+                start_time,
+                end_time,
+                meeting_link: "".to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// Choose someone for the next interview on the panel, and then the rest, going back to
+/// try someone else if we get stuck.
+fn fill_panel<'a>(
+    config: &InterviewPanelConfig,
+    times: &[(DateTime<Utc>, DateTime<Utc>)],
+    candidates: &[&'a InterviewerCandidate],
+    chosen: &mut Vec<&'a InterviewerCandidate>,
+    steps: &mut usize,
+) -> bool {
+    let unfilled: Vec<&Vec<String>> = config.required.values().filter(|usernames| !chosen.iter().any(|c| usernames.contains(&c.username))).collect();
+    if chosen.len() == times.len() {
+        return unfilled.is_empty();
+    }
+    if unfilled.len() > times.len() - chosen.len() {
+        return false;
+    }
+
+    let fills_role = |c: &InterviewerCandidate| unfilled.iter().any(|usernames| usernames.contains(&c.username));
+    let (start, end) = times[chosen.len()];
+    let mut options: Vec<&'a InterviewerCandidate> = candidates
+        .iter()
+        .filter(|c| !chosen.iter().any(|x| x.username == c.username))
+        .filter(|c| c.is_free(start, end))
+        // People outside of the pool are only on the panel to fill their role.
+        .filter(|c| config.pool.contains(&c.username) || fills_role(c))
+        .copied()
+        .collect();
+    // Fill the roles first, the sort is stable so the load order stays otherwise.
+    options.sort_by_key(|c| !fills_role(c));
+
+    for option in options {
+        if *steps == 0 {
+            return false;
+        }
+        *steps -= 1;
+
+        chosen.push(option);
+        if fill_panel(config, times, candidates, chosen, steps) {
+            return true;
+        }
+        chosen.pop();
+    }
+
+    false
+}
+
+/// Find the times we can offer an applicant, on weekdays during the hours in the
+/// config, from `notice_hours` out to `scheduling_days` out.
+pub fn propose_panels(config: &InterviewPanelConfig, tz: &Tz, candidates: &[InterviewerCandidate], now: DateTime<Utc>) -> Vec<ProposedPanel> {
+    let earliest = now + Duration::hours(config.notice_hours);
+    let latest = now + Duration::days(config.scheduling_days);
+    let length = config.panel_length();
+
+    let mut proposals: Vec<ProposedPanel> = Default::default();
+    let mut day = earliest.with_timezone(tz).date();
+    while proposals.len() < MAX_PROPOSED_PANELS {
+        // The start of the day does not exist when the clocks go forward over it.
+        let day_start = match tz.from_local_datetime(&day.naive_local().and_hms(config.day_start_hour, 0, 0)).earliest() {
+            Some(day_start) => day_start.with_timezone(&Utc),
+            None => {
+                day = day.succ();
+                continue;
+            }
+        };
+        if day_start >= latest {
+            break;
+        }
+        let day_end = day_start + Duration::hours((config.day_end_hour - config.day_start_hour) as i64);
+
+        let mut start = day_start;
+        let mut found = 0;
+        while day.weekday() != Weekday::Sat
+            && day.weekday() != Weekday::Sun
+            && start + length <= day_end
+            && start + length <= latest
+            && found < MAX_PROPOSED_PANELS_PER_DAY
+            && proposals.len() < MAX_PROPOSED_PANELS
+        {
+            if start >= earliest {
+                if let Some(interviews) = assign_panel(config, candidates, start) {
+                    proposals.push(ProposedPanel {
+                        start_time: start,
+                        end_time: start + length,
+                        interviews,
+                    });
+                    found += 1;
+                    // Do not offer overlapping times.
+                    start = start + length;
+                    continue;
+                }
+            }
+
+            start = start + Duration::minutes(PROPOSAL_STEP_MINUTES);
+        }
+
+        day = day.succ();
+    }
+
+    proposals
+}
+
+/// The states a link to schedule interviews can be in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+pub enum InterviewScheduleStatus {
+    /// We sent the applicant the link, they have not picked a time yet.
+    Proposed,
+    /// The applicant picked a time and we are putting the interviews on the calendar.
+    Scheduling,
+    /// The applicant picked a time and the interviews are on the calendar.
+    Scheduled,
+    /// The applicant did not pick a time before the link expired.
+    Expired,
+    /// The applicant moved on before they picked a time.
+    Cancelled,
+}
+
+impl FromStr for InterviewScheduleStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status.to_lowercase().trim() {
+            "proposed" => Ok(InterviewScheduleStatus::Proposed),
+            "scheduling" => Ok(InterviewScheduleStatus::Scheduling),
+            "scheduled" => Ok(InterviewScheduleStatus::Scheduled),
+            "expired" => Ok(InterviewScheduleStatus::Expired),
+            "cancelled" => Ok(InterviewScheduleStatus::Cancelled),
+            s => Err(format!("`{}` is not a valid interview schedule status", s)),
+        }
+    }
+}
+
+impl fmt::Display for InterviewScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            InterviewScheduleStatus::Proposed => "proposed",
+            InterviewScheduleStatus::Scheduling => "scheduling",
+            InterviewScheduleStatus::Scheduled => "scheduled",
+            InterviewScheduleStatus::Expired => "expired",
+            InterviewScheduleStatus::Cancelled => "cancelled",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// A link we sent an applicant to schedule their interviews with a panel.
+#[db {
+    new_struct_name = "ApplicantInterviewSchedule",
+    airtable_base_id = "AIRTABLE_BASE_ID_RECURITING_APPLICATIONS",
+    airtable_table = "AIRTABLE_INTERVIEW_SCHEDULES_TABLE",
+    match_on = {
+        "token" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "applicant_interview_schedules"]
+pub struct NewApplicantInterviewSchedule {
+    /// The secret in the link we sent the applicant.
+    pub token: String,
+    pub applicant_email: String,
+    pub applicant_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sheet_id: String,
+    pub role: String,
+    /// The name of the panel in the configs.
+    pub panel: String,
+    pub status: String,
+    pub conference: String,
+    pub created_time: DateTime<Utc>,
+    pub expires_time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_start_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_end_time: Option<DateTime<Utc>>,
+    /// The interviewers, in the order the applicant talks to them.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::user_format_as_array_of_strings::serialize",
+        deserialize_with = "airtable_api::user_format_as_array_of_strings::deserialize"
+    )]
+    pub interviewers: Vec<String>,
+    /// The events on the "Interviews" calendar, in the same order as the interviewers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub google_event_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub meeting_links: Vec<String>,
+}
+
+/// Implement updating the Airtable record for an ApplicantInterviewSchedule.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantInterviewSchedule> for ApplicantInterviewSchedule {
+    async fn update_airtable_record(&mut self, _record: ApplicantInterviewSchedule) {}
+}
+
+/// What the applicant sees when they open the link to schedule their interviews.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewScheduleOptions {
+    pub applicant_name: String,
+    pub status: String,
+    pub expires_time: DateTime<Utc>,
+    /// The time zone of the interviewers, times are shown in it.
+    pub time_zone: String,
+    pub interview_minutes: i64,
+    /// The times the applicant can pick from, by when the first interview starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub start_times: Vec<DateTime<Utc>>,
+    /// The interviews, once they are scheduled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interviews: Vec<PanelInterview>,
+}
+
+/// The time an applicant picked for their interviews.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewScheduleSelection {
+    pub start_time: DateTime<Utc>,
+}
+
+impl NewApplicantInterviewSchedule {
+    /// A new link to schedule interviews for the applicant with the panel.
+    pub fn new(applicant: &Applicant, panel_name: &str, panel: &InterviewPanelConfig, now: DateTime<Utc>) -> Self {
+        NewApplicantInterviewSchedule {
+            token: rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect(),
+            applicant_email: applicant.email.to_string(),
+            applicant_name: applicant.name.to_string(),
+            sheet_id: applicant.sheet_id.to_string(),
+            role: applicant.role.to_string(),
+            panel: panel_name.to_string(),
+            status: InterviewScheduleStatus::Proposed.to_string(),
+            conference: panel.conference.to_string(),
+            created_time: now,
+            expires_time: now + Duration::days(panel.scheduling_days),
+            scheduled_start_time: None,
+            scheduled_end_time: None,
+            interviewers: Default::default(),
+            google_event_ids: Default::default(),
+            meeting_links: Default::default(),
+        }
+    }
+}
+
+impl ApplicantInterviewSchedule {
+    /// Returns if the applicant can still pick a time.
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        self.status == InterviewScheduleStatus::Proposed.to_string() && self.expires_time > now
+    }
+
+    /// Get the link the applicant uses to schedule their interviews.
+    pub fn link(&self) -> Result<String, String> {
+        Ok(format!("{}/interviews/schedule/{}", get_server_url()?, self.token))
+    }
+
+    /// Get the times the applicant can pick from, or their interviews if they already picked one.
+    pub async fn get_options(&self, db: &Database) -> Result<InterviewScheduleOptions, String> {
+        let now = Utc::now();
+        let panel = get_interview_panel(&self.panel).await?;
+
+        let mut options = InterviewScheduleOptions {
+            applicant_name: self.applicant_name.to_string(),
+            status: self.status.to_string(),
+            expires_time: self.expires_time,
+            time_zone: panel.time_zone.to_string(),
+            interview_minutes: panel.interview_minutes,
+            start_times: Default::default(),
+            interviews: Default::default(),
+        };
+
+        if self.is_open(now) {
+            let tz = panel.validate()?;
+            let gsuite = get_gsuite().await;
+            let candidates = get_interviewer_candidates(db, &gsuite, &panel, &self.applicant_email, now, now + Duration::days(panel.scheduling_days)).await?;
+            options.start_times = propose_panels(&panel, &tz, &candidates, now).into_iter().map(|p| p.start_time).collect();
+        }
+
+        for (i, event_id) in self.google_event_ids.iter().enumerate() {
+            if let Some(interview) = ApplicantInterview::get_from_db(db, event_id.to_string()) {
+                options.interviews.push(PanelInterview {
+                    interviewer: interview.interviewers.get(0).cloned().unwrap_or_default(),
+                    start_time: interview.start_time,
+                    end_time: interview.end_time,
+                    meeting_link: self.meeting_links.get(i).cloned().unwrap_or_default(),
+                });
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Schedule the interviews at the time the applicant picked. The times are
+    /// proposed again from whoever is free now, in case calendars changed since the
+    /// applicant opened the link, and the time has to be one of them.
+    pub async fn schedule(&mut self, db: &Database, start_time: DateTime<Utc>) -> Result<(), String> {
+        let now = Utc::now();
+        if !self.is_open(now) {
+            return Err(format!(
+                "the interviews can no longer be scheduled, they are {}",
+                if self.status == InterviewScheduleStatus::Proposed.to_string() {
+                    "expired"
+                } else {
+                    self.status.as_str()
+                }
+            ));
+        }
+
+        let panel = get_interview_panel(&self.panel).await?;
+        let tz = panel.validate()?;
+        let gsuite = get_gsuite().await;
+        let candidates = get_interviewer_candidates(db, &gsuite, &panel, &self.applicant_email, now, now + Duration::days(panel.scheduling_days)).await?;
+        let interviews = match propose_panels(&panel, &tz, &candidates, now).into_iter().find(|p| p.start_time == start_time) {
+            Some(proposal) => proposal.interviews,
+            None => {
+                return Err(format!(
+                    "{} is not one of the times we can offer, please pick another time",
+                    start_time.with_timezone(&tz).format("%A, %B %-d at %-l:%M%P %Z")
+                ))
+            }
+        };
+
+        // Claim the link, so if the applicant picks a time twice at once only one of
+        // them puts interviews on the calendar.
+        self.set_status_if(db, InterviewScheduleStatus::Proposed, InterviewScheduleStatus::Scheduling)?;
+        let result = self.create_interviews(db, &gsuite, &panel, start_time, interviews).await;
+        if result.is_err() {
+            // Let the applicant try again.
+            if let Err(e) = self.set_status_if(db, InterviewScheduleStatus::Scheduling, InterviewScheduleStatus::Proposed) {
+                println!("[interview_schedules] releasing the link for {} failed: {}", self.applicant_email, e);
+            }
+        }
+
+        result
+    }
+
+    /// Change the status in the database, only if it is still `from`.
+    fn set_status_if(&mut self, db: &Database, from: InterviewScheduleStatus, to: InterviewScheduleStatus) -> Result<(), String> {
+        let updated = diesel::update(
+            applicant_interview_schedules::dsl::applicant_interview_schedules
+                .filter(applicant_interview_schedules::dsl::id.eq(self.id))
+                .filter(applicant_interview_schedules::dsl::status.eq(from.to_string())),
+        )
+        .set(applicant_interview_schedules::dsl::status.eq(to.to_string()))
+        .execute(&db.conn())
+        .map_err(|e| format!("updating the status failed: {}", e))?;
+        if updated == 0 {
+            return Err(format!("the interviews can no longer be scheduled, they are no longer {}", from));
+        }
+
+        self.status = to.to_string();
+        Ok(())
+    }
+
+    /// Put the interviews on the calendar, and take them all back down if any of them fail.
+    async fn create_interviews(&mut self, db: &Database, gsuite: &GSuite, panel: &InterviewPanelConfig, start_time: DateTime<Utc>, mut interviews: Vec<PanelInterview>) -> Result<(), String> {
+        let applicant = Applicants::get_from_db(db).into_iter().find(|a| a.email == self.applicant_email && a.sheet_id == self.sheet_id);
+        let calendar_id = get_interviews_calendar_id(gsuite).await?;
+        let conference = ConferenceType::from_str(&self.conference).unwrap_or_default();
+
+        // Create the events, and take them all back down if any of them fail, so
+        // the applicant can try again.
+        let mut events: Vec<CalendarEvent> = Default::default();
+        let mut zoom_meetings: Vec<i64> = Default::default();
+        let mut error = None;
+        for interview in interviews.iter_mut() {
+            match create_interview_event(gsuite, &calendar_id, self, panel, conference, interview).await {
+                Ok((event, zoom_meeting)) => {
+                    events.push(event);
+                    zoom_meetings.extend(zoom_meeting);
+                }
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+        if let Some(e) = error {
+            for event in &events {
+                if let Err(e) = gsuite.delete_calendar_event(&calendar_id, &event.id).await {
+                    println!("[interview_schedules] deleting event {} failed: {}", event.id, e);
+                }
+            }
+            if !zoom_meetings.is_empty() {
+                let zoom = Zoom::new_from_env();
+                for meeting in zoom_meetings {
+                    if let Err(e) = zoom.delete_meeting(meeting).await {
+                        println!("[interview_schedules] deleting zoom meeting {} failed: {}", meeting, e);
+                    }
+                }
+            }
+
+            return Err(format!("scheduling the interviews failed: {}", e));
+        }
+
+        for (interview, event) in interviews.iter().zip(&events) {
+            NewApplicantInterview {
+                start_time: interview.start_time,
+                end_time: interview.end_time,
+                name: event.summary.to_string(),
+                email: self.applicant_email.to_string(),
+                interviewers: vec![interview.interviewer.to_string()],
+                google_event_id: event.id.to_string(),
+                event_link: event.html_link.to_string(),
+                applicant: applicant.as_ref().map(|a| vec![a.airtable_record_id.to_string()]).unwrap_or_default(),
+            }
+            .upsert(db)
+            .await;
+        }
+
+        self.status = InterviewScheduleStatus::Scheduled.to_string();
+        self.scheduled_start_time = Some(start_time);
+        self.scheduled_end_time = interviews.last().map(|i| i.end_time);
+        self.interviewers = interviews.iter().map(|i| i.interviewer.to_string()).collect();
+        self.google_event_ids = events.iter().map(|e| e.id.to_string()).collect();
+        self.meeting_links = interviews.iter().map(|i| i.meeting_link.to_string()).collect();
+        *self = self.update(db).await;
+        println!("[interview_schedules] scheduled {} interviews for {} starting {}", interviews.len(), self.applicant_email, start_time);
+
+        Ok(())
+    }
+}
+
+fn interviewer_username(email: &str) -> &str {
+    email.trim_end_matches(GSUITE_DOMAIN).trim_end_matches(DOMAIN).trim_end_matches('@')
+}
+
+/// Get the panel from the configs repo.
+async fn get_interview_panel(name: &str) -> Result<InterviewPanelConfig, String> {
+    let github = authenticate_github_jwt();
+    match get_configs_from_repo(&github).await.interview_panels.remove(name) {
+        Some(panel) => Ok(panel),
+        None => Err(format!("the interview panel `{}` is no longer configured", name)),
+    }
+}
+
+/// Get the URL the applicant opens the links we send them on.
+fn get_server_url() -> Result<String, String> {
+    match env::var("CIO_SERVER_URL") {
+        Ok(url) if !url.trim().is_empty() => Ok(url.trim().trim_end_matches('/').to_string()),
+        _ => Err("CIO_SERVER_URL is not set".to_string()),
+    }
+}
+
+async fn get_gsuite() -> GSuite {
+    let gsuite_customer = env::var("GADMIN_ACCOUNT_ID").unwrap();
+    let token = get_gsuite_token("").await;
+    GSuite::new(&gsuite_customer, GSUITE_DOMAIN, token)
+}
+
+/// Get the ID of the "Interviews" calendar, which is where `crate::interviews::refresh_interviews`
+/// finds the interviews.
+async fn get_interviews_calendar_id(gsuite: &GSuite) -> Result<String, String> {
+    let calendars = gsuite.list_calendars().await.map_err(|e| format!("listing calendars failed: {}", e))?;
+    match calendars.into_iter().find(|c| c.summary == "Interviews") {
+        Some(calendar) => Ok(calendar.id),
+        None => Err("the Interviews calendar was not found".to_string()),
+    }
+}
+
+/// Get everyone who could be on the panel, with their load and when they are busy between
+/// `from` and `to`. Nobody interviews themselves, and anyone whose calendar we cannot read
+/// is left out.
+async fn get_interviewer_candidates(
+    db: &Database,
+    gsuite: &GSuite,
+    panel: &InterviewPanelConfig,
+    applicant_email: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<InterviewerCandidate>, String> {
+    let now = Utc::now();
+    let people = panel.people();
+    let applicant_email = applicant_email.trim().to_lowercase();
+    let users: Vec<User> = Users::get_from_db(db)
+        .into_iter()
+        .filter(|u| people.contains(&u.username))
+        .filter(|u| u.email().to_lowercase() != applicant_email && u.recovery_email.to_lowercase() != applicant_email)
+        .collect();
+
+    let emails: Vec<String> = users.iter().map(|u| u.email()).collect();
+    let calendars = gsuite.get_free_busy(from, to, &emails).await.map_err(|e| format!("getting the free/busy times failed: {}", e))?;
+    let interviews: Vec<ApplicantInterview> = ApplicantInterviews::get_from_db(db).into_iter().filter(|i| i.start_time > now).collect();
+
+    let mut candidates: Vec<InterviewerCandidate> = Default::default();
+    for user in users {
+        let email = user.email();
+        let calendar = match calendars.get(&email) {
+            Some(calendar) if calendar.errors.is_empty() => calendar,
+            Some(calendar) => {
+                println!("[interview_schedules] reading the calendar of {} failed: {:?}", email, calendar.errors);
+                continue;
+            }
+            None => {
+                println!("[interview_schedules] the calendar of {} was not found", email);
+                continue;
+            }
+        };
+
+        candidates.push(InterviewerCandidate {
+            username: user.username.to_string(),
+            email: email.to_string(),
+            upcoming_interviews: interviews.iter().filter(|i| i.interviewers.contains(&email)).count(),
+            busy: calendar.busy.iter().map(|b| (b.start, b.end)).collect(),
+        });
+    }
+
+    Ok(candidates)
+}
+
+/// Put an interview on the "Interviews" calendar and invite the applicant and the interviewer.
+/// This sets the meeting link on the interview, and returns the event along with the ID of the
+/// Zoom meeting if we created one.
+async fn create_interview_event(
+    gsuite: &GSuite,
+    calendar_id: &str,
+    schedule: &ApplicantInterviewSchedule,
+    panel: &InterviewPanelConfig,
+    conference: ConferenceType,
+    interview: &mut PanelInterview,
+) -> Result<(CalendarEvent, Option<i64>), String> {
+    let mut event = CalendarEvent {
+        // The same name as `crate::interviews::refresh_interviews` gives the interview.
+        summary: format!("{} ({})", schedule.applicant_name, interviewer_username(&interview.interviewer)),
+        start: Date {
+            time_zone: panel.time_zone.to_string(),
+            date: None,
+            date_time: Some(interview.start_time),
+        },
+        end: Date {
+            time_zone: panel.time_zone.to_string(),
+            date: None,
+            date_time: Some(interview.end_time),
+        },
+        attendees: vec![interview.interviewer.to_string(), schedule.applicant_email.to_string()]
+            .into_iter()
+            .map(|email| Attendee { email, ..Default::default() })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut zoom_meeting = None;
+    match conference {
+        ConferenceType::GoogleMeet => {
+            event.conference_data = Some(ConferenceData::new_google_meet(&format!("{}-{}", schedule.token, interview.start_time.timestamp())));
+        }
+        ConferenceType::Zoom => {
+            let zoom = Zoom::new_from_env();
+            let meeting = zoom
+                .create_meeting(
+                    &interview.interviewer,
+                    &NewMeeting {
+                        topic: format!("Oxide interview with {}", schedule.applicant_name),
+                        // A scheduled meeting.
+                        typev: 2,
+                        start_time: interview.start_time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                        duration: panel.interview_minutes,
+                        timezone: panel.time_zone.to_string(),
+                        agenda: "".to_string(),
+                        settings: Some(NewMeetingSettings {
+                            join_before_host: false,
+                            waiting_room: true,
+                        }),
+                    },
+                )
+                .await
+                .map_err(|e| format!("creating a zoom meeting for {} failed: {}", interview.interviewer, e))?;
+            interview.meeting_link = meeting.join_url.to_string();
+            event.location = meeting.join_url.to_string();
+            zoom_meeting = Some(meeting.id);
+        }
+    }
+    event.description = if interview.meeting_link.is_empty() {
+        "Join with the Google Meet link on this event.".to_string()
+    } else {
+        format!("Join the Zoom meeting: {}", interview.meeting_link)
+    };
+
+    match gsuite.create_calendar_event(calendar_id, &event, true).await {
+        Ok(event) => {
+            if interview.meeting_link.is_empty() {
+                interview.meeting_link = event.conference_data.as_ref().and_then(|c| c.video_uri()).unwrap_or_else(|| event.hangout_link.to_string());
+            }
+            Ok((event, zoom_meeting))
+        }
+        Err(e) => {
+            if let Some(meeting) = zoom_meeting {
+                if let Err(e) = Zoom::new_from_env().delete_meeting(meeting).await {
+                    println!("[interview_schedules] deleting zoom meeting {} failed: {}", meeting, e);
+                }
+            }
+            Err(format!("creating the event for {} failed: {}", interview.interviewer, e))
+        }
+    }
+}
+
+/// Send the applicant the link to schedule their interviews.
+async fn send_email_interview_schedule(schedule: &ApplicantInterviewSchedule, panel: &InterviewPanelConfig, link: &str) {
+    // Initialize the SendGrid client.
+    let sendgrid_client = SendGrid::new_from_env();
+
+    // Send the message.
+    sendgrid_client
+        .send_mail(
+            format!("Schedule your interviews with Oxide, {}", schedule.applicant_name),
+            format!(
+                "Dear {},
+
+We would love to talk with you more! You will meet with {} people at Oxide,
+for {} minutes each, one after the other.
+
+Please pick the time that works best for you here:
+{}
+
+The link works until {}. If none of the times work for you, reply to this
+email and we will find one that does.
+
+Sincerely,
+  The Oxide Team",
+                schedule.applicant_name,
+                panel.interviewers,
+                panel.interview_minutes,
+                link,
+                schedule.expires_time.format("%A, %B %-d"),
+            ),
+            vec![schedule.applicant_email.to_string()],
+            vec![format!("careers@{}", DOMAIN)],
+            vec![],
+            format!("careers@{}", DOMAIN),
+        )
+        .await;
+}
+
+/// Send a link to schedule their interviews to the applicants who are interviewing for a
+/// role with a panel, and do not have any interviews yet. Links that were not used are
+/// expired, or cancelled if the applicant moved on.
+pub async fn refresh_interview_schedules(db: &Database, github: &Github) {
+    let panels = get_configs_from_repo(github).await.interview_panels;
+    if panels.is_empty() {
+        println!("[interview_schedules] no roles have an interview panel configured, skipping");
+        return;
+    }
+
+    let now = Utc::now();
+    let applicants: Vec<Applicant> = Applicants::get_from_db(db).into_iter().collect();
+    let interviews: Vec<ApplicantInterview> = ApplicantInterviews::get_from_db(db).into_iter().collect();
+    let mut schedules: Vec<ApplicantInterviewSchedule> = ApplicantInterviewSchedules::get_from_db(db).into_iter().collect();
+
+    for schedule in schedules.iter_mut().filter(|s| s.status == InterviewScheduleStatus::Proposed.to_string()) {
+        let applicant = applicants.iter().find(|a| a.email == schedule.applicant_email && a.sheet_id == schedule.sheet_id);
+        let status = match applicant {
+            Some(a) if Status::from_str(&a.status) == Ok(Status::Interviewing) => {
+                if schedule.is_open(now) {
+                    continue;
+                }
+                InterviewScheduleStatus::Expired
+            }
+            _ => InterviewScheduleStatus::Cancelled,
+        };
+
+        println!("[interview_schedules] the link for {} is {}", schedule.applicant_email, status);
+        schedule.status = status.to_string();
+        *schedule = schedule.update(db).await;
+    }
+
+    // Do not make links we cannot send.
+    if let Err(e) = get_server_url() {
+        println!("[interview_schedules] not sending links to schedule interviews: {}", e);
+        return;
+    }
+
+    for applicant in applicants.iter() {
+        if Status::from_str(&applicant.status) != Ok(Status::Interviewing) {
+            continue;
+        }
+        let panel = match panels.get(&applicant.role) {
+            Some(panel) => panel,
+            None => continue,
+        };
+        if let Err(e) = panel.validate() {
+            println!("[interview_schedules] the interview panel for {} is invalid: {}", applicant.role, e);
+            continue;
+        }
+
+        // Anyone who already got a link, or whose interviews were scheduled by hand,
+        // does not get a new one.
+        if schedules.iter().any(|s| s.applicant_email == applicant.email && s.sheet_id == applicant.sheet_id) {
+            continue;
+        }
+        if interviews.iter().any(|i| i.email == applicant.email) {
+            continue;
+        }
+
+        let schedule = NewApplicantInterviewSchedule::new(applicant, &applicant.role, panel, now).upsert(db).await;
+        let link = match schedule.link() {
+            Ok(link) => link,
+            Err(e) => {
+                println!("[interview_schedules] getting the link for {} failed: {}", applicant.email, e);
+                continue;
+            }
+        };
+        send_email_interview_schedule(&schedule, panel, &link).await;
+        println!("[interview_schedules] sent {} a link to schedule their interviews ({})", applicant.email, applicant.role);
+
+        schedules.push(schedule);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::db::Database;
+    use crate::interview_schedules::{assign_panel, propose_panels, refresh_interview_schedules, InterviewPanelConfig, InterviewerCandidate};
+    use crate::utils::authenticate_github_jwt;
+
+    fn candidate(username: &str, upcoming_interviews: usize) -> InterviewerCandidate {
+        InterviewerCandidate {
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            upcoming_interviews,
+            busy: vec![],
+        }
+    }
+
+    fn panel(interviewers: usize, pool: &[&str]) -> InterviewPanelConfig {
+        InterviewPanelConfig {
+            interviewers,
+            pool: pool.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn interviewers(interviews: Option<Vec<crate::interview_schedules::PanelInterview>>) -> Vec<String> {
+        interviews.unwrap().into_iter().map(|i| i.interviewer).collect()
+    }
+
+    #[test]
+    fn test_interview_panel_validate() {
+        assert!(panel(2, &["alice", "bob"]).validate().is_ok());
+        assert!(panel(3, &["alice", "bob"]).validate().unwrap_err().contains("only 2 people"));
+
+        let mut config = panel(2, &["alice", "bob"]);
+        config.time_zone = "Mars/Olympus_Mons".to_string();
+        assert!(config.validate().unwrap_err().contains("invalid time zone"));
+
+        // Nine hours of interviews do not fit in an eight hour day.
+        let config = panel(9, &["a", "b", "c", "d", "e", "f", "g", "h", "i"]);
+        assert!(config.validate().unwrap_err().contains("longer than the day"));
+    }
+
+    #[test]
+    fn test_assign_panel_balances_load_and_fills_roles() {
+        let start = Utc.ymd(2021, 6, 30).and_hms(16, 0, 0);
+        let candidates = vec![candidate("alice", 3), candidate("bob", 0), candidate("carol", 1), candidate("dave", 2), candidate("founder", 5)];
+
+        assert_eq!(
+            interviewers(assign_panel(&panel(2, &["alice", "bob", "carol", "dave"]), &candidates, start)),
+            vec!["bob@example.com", "carol@example.com"]
+        );
+
+        // The founder is not in the pool, but is always on the panel.
+        let mut required: BTreeMap<String, Vec<String>> = Default::default();
+        required.insert("founder".to_string(), vec!["founder".to_string()]);
+        let config = InterviewPanelConfig {
+            required,
+            ..panel(2, &["alice", "bob", "carol", "dave"])
+        };
+        let interviews = assign_panel(&config, &candidates, start).unwrap();
+        assert_eq!(interviews[0].interviewer, "founder@example.com");
+        assert_eq!(interviews[0].start_time, start);
+        assert_eq!(interviews[1].interviewer, "bob@example.com");
+        assert_eq!(interviews[1].start_time, start + Duration::hours(1));
+    }
+
+    #[test]
+    fn test_assign_panel_busy() {
+        let start = Utc.ymd(2021, 6, 30).and_hms(16, 0, 0);
+        let mut bob = candidate("bob", 0);
+        // Bob is busy for the first interview, so Alice goes first.
+        bob.busy.push((start, start + Duration::minutes(30)));
+        let candidates = vec![candidate("alice", 2), bob];
+
+        assert_eq!(
+            interviewers(assign_panel(&panel(2, &["alice", "bob"]), &candidates, start)),
+            vec!["alice@example.com", "bob@example.com"]
+        );
+
+        // Nobody is free for three interviews.
+        let candidates = vec![candidate("alice", 0), candidate("bob", 0), candidate("carol", 0)];
+        let mut carol = candidates[2].clone();
+        carol.busy.push((start, start + Duration::hours(8)));
+        assert!(assign_panel(&panel(3, &["alice", "bob", "carol"]), &[candidates[0].clone(), candidates[1].clone(), carol], start).is_none());
+    }
+
+    #[test]
+    fn test_propose_panels() {
+        let config = InterviewPanelConfig {
+            scheduling_days: 7,
+            notice_hours: 24,
+            ..panel(2, &["alice", "bob"])
+        };
+        let tz = config.validate().unwrap();
+        // Thursday June 24th at noon in California.
+        let now = Utc.ymd(2021, 6, 24).and_hms(19, 0, 0);
+
+        let mut alice = candidate("alice", 0);
+        // Alice is busy on Friday until 3pm.
+        alice.busy.push((Utc.ymd(2021, 6, 25).and_hms(16, 0, 0), Utc.ymd(2021, 6, 25).and_hms(22, 0, 0)));
+        let proposals = propose_panels(&config, &tz, &[alice, candidate("bob", 0)], now);
+
+        let starts: Vec<String> = proposals.iter().map(|p| p.start_time.with_timezone(&tz).format("%a %-d %H:%M").to_string()).collect();
+        // Friday only has time after Alice is free, the weekend is skipped, we offer
+        // at most two times a day, and the last one ends before the link expires.
+        assert_eq!(
+            starts,
+            vec![
+                "Fri 25 15:00",
+                "Mon 28 09:00",
+                "Mon 28 11:00",
+                "Tue 29 09:00",
+                "Tue 29 11:00",
+                "Wed 30 09:00",
+                "Wed 30 11:00",
+                "Thu 1 09:00"
+            ]
+        );
+        assert_eq!(proposals[0].end_time - proposals[0].start_time, Duration::hours(2));
+    }
+
+    #[test]
+    fn test_propose_panels_skips_missing_day_start() {
+        let config = InterviewPanelConfig {
+            scheduling_days: 4,
+            notice_hours: 24,
+            day_start_hour: 2,
+            day_end_hour: 6,
+            ..panel(2, &["alice", "bob"])
+        };
+        let tz = config.validate().unwrap();
+        // Friday March 12th at noon in California, the clocks go forward at 2am on Sunday.
+        let now = Utc.ymd(2021, 3, 12).and_hms(20, 0, 0);
+
+        let proposals = propose_panels(&config, &tz, &[candidate("alice", 0), candidate("bob", 0)], now);
+
+        let starts: Vec<String> = proposals.iter().map(|p| p.start_time.with_timezone(&tz).format("%a %-d %H:%M").to_string()).collect();
+        assert_eq!(starts, vec!["Mon 15 02:00", "Mon 15 04:00", "Tue 16 02:00", "Tue 16 04:00"]);
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_applicants_interview_schedules() {
+        let github = authenticate_github_jwt();
+        let db = Database::new();
+
+        refresh_interview_schedules(&db, &github).await;
+    }
+}
@@ -3,26 +3,42 @@
 
 pub mod airtable;
 pub mod analytics;
+pub mod applicant_intake;
+pub mod applicant_offers;
+pub mod applicant_retention;
+pub mod applicant_reviews;
+pub mod applicant_scores;
 pub mod applicant_status;
 pub mod applicants;
 pub mod auth_logins;
+pub mod background_checks;
 pub mod certs;
 pub mod configs;
 pub mod core;
 pub mod db;
+pub mod dns_providers;
+pub mod dns_zones;
+pub mod envelopes;
 pub mod finance;
 pub mod gsuite;
 pub mod huddles;
+pub mod interview_packets;
+pub mod interview_schedules;
 pub mod interviews;
 pub mod journal_clubs;
+pub mod link_health;
 pub mod mailchimp;
 pub mod mailing_list;
+pub mod materials;
 pub mod models;
 pub mod png;
 pub mod rack_line;
 pub mod recorded_meetings;
+pub mod rfd_state;
+pub mod rfd_visibility;
 pub mod rfds;
 pub mod schema;
+pub mod secrets;
 pub mod shipments;
 pub mod shorturls;
 pub mod slack;
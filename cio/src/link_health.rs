@@ -0,0 +1,518 @@
+#![allow(clippy::from_over_into)]
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::TryStreamExt;
+use hubcaps::comments::CommentOptions;
+use hubcaps::issues::{Issue, IssueListOptions, IssueOptions, State};
+use hubcaps::Github;
+use macros::db;
+use reqwest::redirect::Policy;
+use reqwest::{header, Client, StatusCode, Url};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::airtable::{AIRTABLE_BASE_ID_DIRECTORY, AIRTABLE_LINK_CHECKS_TABLE};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::link_checks;
+use crate::shorturls::{get_all_shorturls, ShortUrlRedirect};
+use crate::utils::{authenticate_github_jwt, check_if_github_issue_exists, github_org};
+
+/// How long a link has to be broken before we open an issue for it.
+/// Sites go down for a bit, we only want to hear about the ones that stay down.
+pub static LINK_BROKEN_ISSUE_THRESHOLD_DAYS: i64 = 3;
+
+/// The label we put on the issues for broken links in the configs repo.
+pub static LINK_BROKEN_ISSUE_LABEL: &str = "broken-link";
+
+/// The most redirects we follow before calling a link broken.
+pub static LINK_CHECK_MAX_REDIRECTS: usize = 10;
+
+/// How long we wait for each request when checking a link.
+pub static LINK_CHECK_TIMEOUT_SECONDS: u64 = 15;
+
+/// The last time we checked where a short URL goes.
+#[db {
+    new_struct_name = "LinkCheck",
+    airtable_base_id = "AIRTABLE_BASE_ID_DIRECTORY",
+    airtable_table = "AIRTABLE_LINK_CHECKS_TABLE",
+    match_on = {
+        "short_link" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "link_checks"]
+pub struct NewLinkCheck {
+    /// short_link is the canonical short URL, ie. `https://42.rfd.oxide.computer/discussion`.
+    pub short_link: String,
+    pub subdomain: String,
+    pub name: String,
+    pub target: String,
+    /// status_code is the status of the last response, or 0 if we did not get one.
+    #[serde(default)]
+    pub status_code: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    /// redirect_chain is the URLs the target redirected through, ending at the final URL.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirect_chain: Vec<String>,
+    #[serde(default)]
+    pub healthy: bool,
+    pub last_checked: DateTime<Utc>,
+    pub last_success: Option<DateTime<Utc>>,
+    /// failing_since is when the link started failing, this is cleared once it works again.
+    pub failing_since: Option<DateTime<Utc>>,
+    /// github_issue is the issue in the configs repo for the broken link.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub github_issue: String,
+}
+
+/// Implement updating the Airtable record for a LinkCheck.
+#[async_trait]
+impl UpdateAirtableRecord<LinkCheck> for LinkCheck {
+    async fn update_airtable_record(&mut self, _record: LinkCheck) {}
+}
+
+/// The result of following a link.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LinkCheckResult {
+    pub status_code: i32,
+    pub error: String,
+    pub redirect_chain: Vec<String>,
+    pub healthy: bool,
+}
+
+/// Returns if a status means the link works.
+/// Links to things behind a login answer with 401 or 403 to us, but work for the people
+/// clicking on them.
+pub fn is_healthy_status(status: StatusCode) -> bool {
+    status.is_success() || status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+}
+
+/// Follow a link, and the redirects it returns, and record where it ends up.
+pub async fn check_link(client: &Client, url: &str) -> LinkCheckResult {
+    let mut result = LinkCheckResult::default();
+
+    let mut current = match Url::parse(url) {
+        Ok(u) => u,
+        Err(e) => {
+            result.error = format!("`{}` is not a valid URL: {}", url, e);
+            return result;
+        }
+    };
+
+    for _ in 0..=LINK_CHECK_MAX_REDIRECTS {
+        let resp = match client.get(current.clone()).send().await {
+            Ok(r) => r,
+            Err(e) => {
+                result.error = format!("requesting {} failed: {}", current, e);
+                return result;
+            }
+        };
+
+        let status = resp.status();
+        result.status_code = status.as_u16() as i32;
+        if !status.is_redirection() {
+            result.healthy = is_healthy_status(status);
+            if !result.healthy {
+                result.error = format!("{} returned {}", current, status);
+            }
+            return result;
+        }
+
+        // Follow the redirect, the location can be relative.
+        let location = resp.headers().get(header::LOCATION).and_then(|l| l.to_str().ok()).unwrap_or_default();
+        current = match current.join(location) {
+            Ok(u) if !location.is_empty() => u,
+            _ => {
+                result.error = format!("{} returned {} without a valid location", current, status);
+                return result;
+            }
+        };
+        result.redirect_chain.push(current.to_string());
+    }
+
+    result.error = format!("{} redirected more than {} times", url, LINK_CHECK_MAX_REDIRECTS);
+    result
+}
+
+/// Get the owner, repo and pull request number for a link to GitHub.
+/// Our repos are private, so these need to be checked with the GitHub API rather
+/// than by following the link.
+pub fn parse_github_link(url: &str) -> Option<(String, String, Option<u64>)> {
+    let url = Url::parse(url).ok()?;
+    if url.host_str()? != "github.com" {
+        return None;
+    }
+
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        [owner, repo, "pull", number, ..] => Some((owner.to_string(), repo.to_string(), number.parse().ok())),
+        [owner, repo, ..] => Some((owner.to_string(), repo.trim_end_matches(".git").to_string(), None)),
+        _ => None,
+    }
+}
+
+/// Check a link to a GitHub repo, or a pull request in one, with the GitHub API.
+/// A renamed repo still works, but the new location is recorded in the redirect chain.
+pub async fn check_github_link(github: &Github, owner: &str, repo: &str, pull: Option<u64>) -> LinkCheckResult {
+    let mut result = LinkCheckResult::default();
+
+    let r = match github.repo(owner, repo).get().await {
+        Ok(r) => r,
+        Err(e) => {
+            result.status_code = get_github_error_status(&e);
+            result.error = format!("getting GitHub repo {}/{} failed: {}", owner, repo, e);
+            return result;
+        }
+    };
+    if !r.full_name.eq_ignore_ascii_case(&format!("{}/{}", owner, repo)) {
+        result.redirect_chain.push(r.html_url.to_string());
+    }
+
+    if let Some(number) = pull {
+        if let Err(e) = github.repo(owner, repo).pulls().get(number).get().await {
+            result.status_code = get_github_error_status(&e);
+            result.error = format!("getting GitHub pull request {}/{}#{} failed: {}", owner, repo, number, e);
+            return result;
+        }
+    }
+
+    result.status_code = StatusCode::OK.as_u16() as i32;
+    result.healthy = true;
+    result
+}
+
+fn get_github_error_status(e: &hubcaps::Error) -> i32 {
+    match e {
+        hubcaps::Error::Fault { code, .. } => code.as_u16() as i32,
+        _ => 0,
+    }
+}
+
+impl NewLinkCheck {
+    /// Update the link with the result of checking it.
+    pub fn update_from_result(&mut self, result: LinkCheckResult, now: DateTime<Utc>) {
+        self.status_code = result.status_code;
+        self.error = result.error;
+        self.redirect_chain = result.redirect_chain;
+        self.healthy = result.healthy;
+        self.last_checked = now;
+
+        if self.healthy {
+            self.last_success = Some(now);
+            self.failing_since = None;
+        } else if self.failing_since.is_none() {
+            self.failing_since = Some(now);
+        }
+    }
+
+    /// Returns if the link has been broken for longer than the threshold.
+    pub fn is_broken_past_threshold(&self, now: DateTime<Utc>) -> bool {
+        match self.failing_since {
+            Some(since) => !self.healthy && now - since > Duration::days(LINK_BROKEN_ISSUE_THRESHOLD_DAYS),
+            None => false,
+        }
+    }
+
+    /// Get the title for the issue in the configs repo for this link.
+    /// This is also what we search for to find an existing issue.
+    fn github_issue_title(&self) -> String {
+        get_github_issue_title(&self.short_link)
+    }
+
+    /// Get where the link comes from, so whoever picks up the issue knows what to fix.
+    fn source(&self) -> &'static str {
+        match self.subdomain.as_str() {
+            "rfd" => "the discussion or link for the RFD in the rfd repo",
+            "git" => "the repository in the GitHub org",
+            _ => "the link in configs/links.toml",
+        }
+    }
+
+    /// Open an issue in the configs repo for the link if it has been broken for longer than
+    /// the threshold, or close the issue if it works again.
+    pub async fn update_github_issue(&mut self, github: &Github, open_issues: &[Issue], now: DateTime<Utc>) -> Result<(), String> {
+        let repo = github.repo(github_org(), "configs");
+        let issue = check_if_github_issue_exists(open_issues, &self.github_issue_title());
+
+        if self.healthy {
+            close_github_issue(github, open_issues, &self.short_link, "works again").await?;
+            self.github_issue = Default::default();
+            return Ok(());
+        }
+
+        if let Some(i) = issue {
+            // We already have an issue for this link.
+            self.github_issue = i.html_url;
+            return Ok(());
+        }
+
+        if !self.is_broken_past_threshold(now) {
+            return Ok(());
+        }
+
+        let mut body = format!(
+            "{} redirects to {}, which has been broken since {}.\n\nStatus: `{}`\nError: {}\n",
+            self.short_link,
+            self.target,
+            self.failing_since.unwrap().format("%A, %B %-d, %Y"),
+            self.status_code,
+            self.error
+        );
+        if !self.redirect_chain.is_empty() {
+            body += &format!("\nRedirect chain:\n- {}\n", self.redirect_chain.join("\n- "));
+        }
+        body += &format!("\nTo fix it, update {}, or remove the link if we do not need it anymore.", self.source());
+
+        let issue = repo
+            .issues()
+            .create(&IssueOptions {
+                title: format!("{}{})", self.github_issue_title(), self.target),
+                body: Some(body),
+                assignee: Default::default(),
+                labels: vec![LINK_BROKEN_ISSUE_LABEL.to_string()],
+                milestone: Default::default(),
+                state: Default::default(),
+            })
+            .await
+            .map_err(|e| format!("could not create issue for {}: {}", self.short_link, e))?;
+
+        println!("[links] opened issue {} for {}", issue.number, self.short_link);
+        self.github_issue = issue.html_url;
+
+        Ok(())
+    }
+}
+
+/// Check where all the short URLs go, and open issues for the ones that stay broken.
+fn get_github_issue_title(short_link: &str) -> String {
+    format!("Broken short URL: {} (", short_link)
+}
+
+/// Close the issue in the configs repo for the short link, if there is one, saying why.
+async fn close_github_issue(github: &Github, open_issues: &[Issue], short_link: &str, reason: &str) -> Result<(), String> {
+    let i = match check_if_github_issue_exists(open_issues, &get_github_issue_title(short_link)) {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+
+    let repo = github.repo(github_org(), "configs");
+    repo.issue(i.number)
+        .comments()
+        .create(&CommentOptions {
+            body: format!("Closing issue automatically since {} {}.", short_link, reason),
+        })
+        .await
+        .map_err(|e| format!("could not comment on issue {}: {}", i.number, e))?;
+    repo.issue(i.number).close().await.map_err(|e| format!("could not close issue {}: {}", i.number, e))?;
+
+    println!("[links] closed issue {} for {}, it {}", i.number, short_link, reason);
+    Ok(())
+}
+
+pub async fn refresh_link_checks(db: &Database) {
+    let github = authenticate_github_jwt();
+
+    let client = Client::builder()
+        .redirect(Policy::none())
+        .timeout(std::time::Duration::from_secs(LINK_CHECK_TIMEOUT_SECONDS))
+        .user_agent("cio-link-checker")
+        .build()
+        .unwrap();
+
+    // Get all the open issues for broken links in the configs repository, across every page,
+    // so we never open a second issue for the same link.
+    let open_issues = match github
+        .repo(github_org(), "configs")
+        .issues()
+        .iter(&IssueListOptions::builder().per_page(100).state(State::Open).labels(vec![LINK_BROKEN_ISSUE_LABEL]).build())
+        .try_collect::<Vec<Issue>>()
+        .await
+    {
+        Ok(i) => i,
+        Err(e) => {
+            // Without the open issues we would open duplicates, so try again next time.
+            println!("[links] listing the open issues for broken links failed: {}", e);
+            return;
+        }
+    };
+
+    // Get every link and discussion the short URLs redirect to, by their short link.
+    let mut redirects: BTreeMap<String, ShortUrlRedirect> = Default::default();
+    for link in get_all_shorturls(db) {
+        let mut targets = vec![(false, link.link.to_string())];
+        if !link.discussion.is_empty() {
+            targets.push((true, link.discussion.to_string()));
+        }
+
+        for (discussion, target) in targets {
+            let redirect = ShortUrlRedirect {
+                subdomain: link.subdomain.to_string(),
+                name: link.name.to_string(),
+                discussion,
+                target,
+            };
+            redirects.entry(redirect.short_link()).or_insert(redirect);
+        }
+    }
+
+    // Short links that share a target, like link aliases, are only requested once.
+    let mut results: BTreeMap<String, LinkCheckResult> = Default::default();
+
+    // Get the existing checks.
+    let mut existing: BTreeMap<String, LinkCheck> = Default::default();
+    for c in LinkChecks::get_from_db(db) {
+        existing.insert(c.short_link.to_string(), c);
+    }
+
+    for (short_link, redirect) in redirects {
+        let now = Utc::now();
+
+        let mut check = NewLinkCheck {
+            short_link: short_link.to_string(),
+            subdomain: redirect.subdomain.to_string(),
+            name: redirect.name.to_string(),
+            target: redirect.target.to_string(),
+            status_code: 0,
+            error: Default::default(),
+            redirect_chain: Default::default(),
+            healthy: false,
+            last_checked: now,
+            last_success: None,
+            failing_since: None,
+            github_issue: Default::default(),
+        };
+        if let Some(e) = existing.remove(&short_link) {
+            // Start over if the link points somewhere new.
+            if e.target == check.target {
+                check.last_success = e.last_success;
+                check.failing_since = e.failing_since;
+                check.github_issue = e.github_issue;
+            }
+        }
+
+        let result = match results.get(&check.target) {
+            Some(r) => r.clone(),
+            None => {
+                let r = match parse_github_link(&check.target) {
+                    Some((owner, repo, pull)) => check_github_link(&github, &owner, &repo, pull).await,
+                    None => check_link(&client, &check.target).await,
+                };
+                results.insert(check.target.to_string(), r.clone());
+                r
+            }
+        };
+        check.update_from_result(result, now);
+        if !check.healthy {
+            println!("[links] {} -> {} is broken: {}", check.short_link, check.target, check.error);
+        }
+
+        if let Err(e) = check.update_github_issue(&github, &open_issues, now).await {
+            // Keep going, we will try again on the next run.
+            println!("[links] updating the issue for {} failed: {}", check.short_link, e);
+        }
+
+        check.upsert_in_db(db);
+    }
+
+    // Remove the checks for the short URLs that no longer exist, and close their issues.
+    for (_, check) in existing {
+        if let Err(e) = close_github_issue(&github, &open_issues, &check.short_link, "no longer exists").await {
+            // Keep the check, so we try again on the next run.
+            println!("[links] closing the issue for {} failed: {}", check.short_link, e);
+            continue;
+        }
+
+        check.delete_from_db(db);
+    }
+
+    // Update the link checks in airtable.
+    LinkChecks::get_from_db(db).update_airtable().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use reqwest::StatusCode;
+
+    use crate::db::Database;
+    use crate::link_health::{is_healthy_status, parse_github_link, refresh_link_checks, LinkCheckResult, NewLinkCheck};
+
+    #[test]
+    fn test_parse_github_link() {
+        assert_eq!(
+            Some(("oxidecomputer".to_string(), "rfd".to_string(), Some(42))),
+            parse_github_link("https://github.com/oxidecomputer/rfd/pull/42")
+        );
+        assert_eq!(
+            Some(("oxidecomputer".to_string(), "cio".to_string(), None)),
+            parse_github_link("https://github.com/oxidecomputer/cio/tree/master/cio")
+        );
+        assert_eq!(None, parse_github_link("https://github.com/oxidecomputer"));
+        assert_eq!(None, parse_github_link("https://rfd.shared.oxide.computer/rfd/0042"));
+    }
+
+    #[test]
+    fn test_is_healthy_status() {
+        assert!(is_healthy_status(StatusCode::OK));
+        assert!(is_healthy_status(StatusCode::FORBIDDEN));
+        assert!(!is_healthy_status(StatusCode::NOT_FOUND));
+        assert!(!is_healthy_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_link_check_broken_past_threshold() {
+        let start = Utc::now();
+        let mut check = NewLinkCheck {
+            short_link: "https://cal.corp.oxide.computer".to_string(),
+            subdomain: "corp".to_string(),
+            name: "cal".to_string(),
+            target: "https://calendar.google.com".to_string(),
+            status_code: 0,
+            error: Default::default(),
+            redirect_chain: Default::default(),
+            healthy: false,
+            last_checked: start,
+            last_success: None,
+            failing_since: None,
+            github_issue: Default::default(),
+        };
+
+        let broken = LinkCheckResult {
+            status_code: 404,
+            error: "not found".to_string(),
+            redirect_chain: Default::default(),
+            healthy: false,
+        };
+        check.update_from_result(broken.clone(), start);
+        assert_eq!(Some(start), check.failing_since);
+        assert!(!check.is_broken_past_threshold(start + Duration::days(1)));
+
+        // It keeps failing since the first time it broke.
+        check.update_from_result(broken, start + Duration::days(4));
+        assert_eq!(Some(start), check.failing_since);
+        assert!(check.is_broken_past_threshold(start + Duration::days(4)));
+
+        let works = LinkCheckResult {
+            status_code: 200,
+            healthy: true,
+            ..Default::default()
+        };
+        check.update_from_result(works, start + Duration::days(5));
+        assert_eq!(None, check.failing_since);
+        assert_eq!(Some(start + Duration::days(5)), check.last_success);
+        assert!(!check.is_broken_past_threshold(start + Duration::days(5)));
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cron_link_checks() {
+        // Initialize our database.
+        let db = Database::new();
+
+        refresh_link_checks(&db).await;
+    }
+}
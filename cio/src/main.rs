@@ -1,15 +1,25 @@
 use std::fs::File;
 use std::sync::Arc;
 
-use dropshot::{endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseOk, HttpServerStarter, RequestContext};
-
+use chrono::{Duration, Utc};
+use dropshot::{endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseOk, HttpServerStarter, Path, Query, RequestContext, TypedBody};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cio_api::analytics::{get_top_links, TopLink, TOP_LINKS_LIMIT};
+use cio_api::applicant_retention::{export_applicant_data, ApplicantDataExport};
+use cio_api::applicant_scores::{get_applicant_scorecards, ApplicantScore, ApplicantScorecard, ApplicantScores};
 use cio_api::applicants::{Applicant, Applicants};
 use cio_api::auth_logins::{AuthUser, AuthUsers};
+use cio_api::certs::{Certificate, Certificates};
 use cio_api::configs::{Building, Buildings, ConferenceRoom, ConferenceRooms, Group, Groups, Link, Links, User, Users};
 use cio_api::db::Database;
+use cio_api::interview_schedules::{ApplicantInterviewSchedule, InterviewScheduleOptions, InterviewScheduleSelection};
 use cio_api::journal_clubs::{JournalClubMeeting, JournalClubMeetings};
 use cio_api::mailing_list::{MailingListSubscriber, MailingListSubscribers};
-use cio_api::models::{GithubRepo, GithubRepos, RFDs, RFD};
+use cio_api::models::{GithubRepo, GithubRepos, RFDReference, RFDReferences, RFDs, RFD};
+use cio_api::rfds::{search_rfds, RFDSearchResult};
+use cio_api::utils::is_shared_secret_valid;
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
@@ -37,16 +47,25 @@ async fn main() -> Result<(), String> {
      */
     let mut api = ApiDescription::new();
     api.register(api_get_applicants).unwrap();
+    api.register(api_get_applicant_export).unwrap();
+    api.register(api_get_applicant_scorecards).unwrap();
+    api.register(api_get_applicant_scores).unwrap();
     api.register(api_get_auth_users).unwrap();
     api.register(api_get_buildings).unwrap();
+    api.register(api_get_certificates).unwrap();
     api.register(api_get_conference_rooms).unwrap();
     api.register(api_get_github_repos).unwrap();
     api.register(api_get_groups).unwrap();
+    api.register(api_get_interview_schedule).unwrap();
     api.register(api_get_journal_club_meetings).unwrap();
     api.register(api_get_links).unwrap();
+    api.register(api_get_top_links).unwrap();
     api.register(api_get_mailing_list_subscribers).unwrap();
+    api.register(api_get_rfd).unwrap();
     api.register(api_get_rfds).unwrap();
     api.register(api_get_schema).unwrap();
+    api.register(api_schedule_interviews).unwrap();
+    api.register(api_search_rfds).unwrap();
     api.register(api_get_users).unwrap();
 
     // Print the OpenAPI Spec to stdout.
@@ -138,6 +157,75 @@ async fn api_get_applicants(rqctx: Arc<RequestContext<Context>>) -> Result<HttpR
     Ok(HttpResponseOk(Applicants::get_from_db(db).0))
 }
 
+/**
+ * Fetch the scorecards for all the applicants who have been scored.
+ */
+#[endpoint {
+    method = GET,
+    path = "/applicants/scorecards",
+}]
+async fn api_get_applicant_scorecards(rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Vec<ApplicantScorecard>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    Ok(HttpResponseOk(get_applicant_scorecards(db)))
+}
+
+/**
+ * Fetch all the scores reviewers have given applicants.
+ */
+#[endpoint {
+    method = GET,
+    path = "/applicants/scores",
+}]
+async fn api_get_applicant_scores(rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Vec<ApplicantScore>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    Ok(HttpResponseOk(ApplicantScores::get_from_db(db).0))
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+struct ApplicantPathParams {
+    email: String,
+}
+
+/**
+ * Export all the data we hold about an applicant, by their email.
+ * This needs the token in `CIO_API_EXPORT_TOKEN` as a bearer token in the `Authorization` header.
+ */
+#[endpoint {
+    method = GET,
+    path = "/applicants/export/{email}",
+}]
+async fn api_get_applicant_export(rqctx: Arc<RequestContext<Context>>, path_params: Path<ApplicantPathParams>) -> Result<HttpResponseOk<ApplicantDataExport>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+    let email = path_params.into_inner().email;
+
+    // The export has everything we know about the applicant, so only let through
+    // the people who have the token.
+    let token = {
+        let req = rqctx.request.lock().await;
+        req.headers()
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .trim_start_matches("Bearer ")
+            .to_string()
+    };
+    if !is_shared_secret_valid("CIO_API_EXPORT_TOKEN", &token) {
+        return Err(HttpError::for_client_error(None, reqwest::StatusCode::UNAUTHORIZED, "the token is missing or invalid".to_string()));
+    }
+
+    let export = export_applicant_data(db, &email);
+    if export.applications.is_empty() && export.deletions.is_empty() {
+        return Err(HttpError::for_not_found(None, format!("no applicant was found with email `{}`", email)));
+    }
+
+    Ok(HttpResponseOk(export))
+}
+
 /**
  * Fetch a list of office buildings.
  */
@@ -152,6 +240,20 @@ async fn api_get_buildings(rqctx: Arc<RequestContext<Context>>) -> Result<HttpRe
     Ok(HttpResponseOk(Buildings::get_from_db(db).0))
 }
 
+/**
+ * Fetch a list of our TLS certificates, the ones expiring soonest first.
+ */
+#[endpoint {
+    method = GET,
+    path = "/certificates",
+}]
+async fn api_get_certificates(rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Vec<Certificate>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    Ok(HttpResponseOk(Certificates::get_by_expiry_from_db(db).0))
+}
+
 /**
  * Fetch a list of conference rooms.
  */
@@ -195,6 +297,64 @@ async fn api_get_groups(rqctx: Arc<RequestContext<Context>>) -> Result<HttpRespo
     Ok(HttpResponseOk(Groups::get_from_db(db).0))
 }
 
+#[derive(Deserialize, Debug, JsonSchema)]
+struct InterviewSchedulePathParams {
+    token: String,
+}
+
+/**
+ * Fetch the times an applicant can pick for their interviews, or their interviews
+ * once they are scheduled.
+ */
+#[endpoint {
+    method = GET,
+    path = "/interviews/schedule/{token}",
+}]
+async fn api_get_interview_schedule(rqctx: Arc<RequestContext<Context>>, path_params: Path<InterviewSchedulePathParams>) -> Result<HttpResponseOk<InterviewScheduleOptions>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    let schedule = match ApplicantInterviewSchedule::get_from_db(db, path_params.into_inner().token) {
+        Some(schedule) => schedule,
+        None => return Err(HttpError::for_not_found(None, "this link to schedule interviews was not found".to_string())),
+    };
+
+    match schedule.get_options(db).await {
+        Ok(options) => Ok(HttpResponseOk(options)),
+        Err(e) => Err(HttpError::for_bad_request(None, e)),
+    }
+}
+
+/**
+ * Schedule an applicant's interviews at the time they picked.
+ */
+#[endpoint {
+    method = POST,
+    path = "/interviews/schedule/{token}",
+}]
+async fn api_schedule_interviews(
+    rqctx: Arc<RequestContext<Context>>,
+    path_params: Path<InterviewSchedulePathParams>,
+    body: TypedBody<InterviewScheduleSelection>,
+) -> Result<HttpResponseOk<InterviewScheduleOptions>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    let mut schedule = match ApplicantInterviewSchedule::get_from_db(db, path_params.into_inner().token) {
+        Some(schedule) => schedule,
+        None => return Err(HttpError::for_not_found(None, "this link to schedule interviews was not found".to_string())),
+    };
+
+    if let Err(e) = schedule.schedule(db, body.into_inner().start_time).await {
+        return Err(HttpError::for_bad_request(None, e));
+    }
+
+    match schedule.get_options(db).await {
+        Ok(options) => Ok(HttpResponseOk(options)),
+        Err(e) => Err(HttpError::for_bad_request(None, e)),
+    }
+}
+
 /**
  * Fetch a list of journal club meetings.
  */
@@ -223,6 +383,40 @@ async fn api_get_links(rqctx: Arc<RequestContext<Context>>) -> Result<HttpRespon
     Ok(HttpResponseOk(Links::get_from_db(db).0))
 }
 
+#[derive(Deserialize, Debug, JsonSchema)]
+struct TopLinksParams {
+    /// days is how far back to count clicks, this defaults to 30.
+    days: Option<i64>,
+    /// subdomain only counts the clicks for one kind of short URL, ie. `rfd`, `git` or `corp`.
+    subdomain: Option<String>,
+    limit: Option<i64>,
+}
+
+/**
+ * Fetch the most clicked short URLs.
+ */
+#[endpoint {
+    method = GET,
+    path = "/links/top",
+}]
+async fn api_get_top_links(rqctx: Arc<RequestContext<Context>>, query_args: Query<TopLinksParams>) -> Result<HttpResponseOk<Vec<TopLink>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+    let params = query_args.into_inner();
+
+    let days = params.days.unwrap_or(30);
+    if !(1..=3650).contains(&days) {
+        return Err(HttpError::for_bad_request(None, format!("days must be between 1 and 3650, not {}", days)));
+    }
+    let limit = params.limit.unwrap_or(TOP_LINKS_LIMIT);
+    if !(1..=1000).contains(&limit) {
+        return Err(HttpError::for_bad_request(None, format!("limit must be between 1 and 1000, not {}", limit)));
+    }
+    let since = Utc::now() - Duration::days(days);
+
+    Ok(HttpResponseOk(get_top_links(db, since, &params.subdomain.unwrap_or_default(), limit)))
+}
+
 /**
  * Fetch a list of mailing list subscribers.
  */
@@ -238,7 +432,7 @@ async fn api_get_mailing_list_subscribers(rqctx: Arc<RequestContext<Context>>) -
 }
 
 /**
- * Fetch all RFDs.
+ * Fetch all RFDs, except the restricted ones.
  */
 #[endpoint {
     method = GET,
@@ -248,7 +442,71 @@ async fn api_get_rfds(rqctx: Arc<RequestContext<Context>>) -> Result<HttpRespons
     let api_context = rqctx.context();
     let db = &api_context.db;
 
-    Ok(HttpResponseOk(RFDs::get_from_db(db).0))
+    Ok(HttpResponseOk(RFDs::get_visible_from_db(db).0))
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+struct RFDPathParams {
+    number: i32,
+}
+
+/// An RFD with the references it makes and the RFDs that reference it.
+#[derive(Serialize, Debug, JsonSchema)]
+struct RFDWithReferences {
+    rfd: RFD,
+    references: Vec<RFDReference>,
+    backlinks: Vec<RFDReference>,
+}
+
+/**
+ * Fetch an RFD by its number, along with its references and backlinks.
+ */
+#[endpoint {
+    method = GET,
+    path = "/rfds/{number}",
+}]
+async fn api_get_rfd(rqctx: Arc<RequestContext<Context>>, path_params: Path<RFDPathParams>) -> Result<HttpResponseOk<RFDWithReferences>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+    let number = path_params.into_inner().number;
+
+    // Restricted RFDs are treated as if they do not exist.
+    let rfd = match RFD::get_from_db(db, number) {
+        Some(rfd) if !rfd.is_restricted() => rfd,
+        _ => return Err(HttpError::for_not_found(None, format!("no RFD was found with number `{}`", number))),
+    };
+
+    // Leave out the backlinks from restricted RFDs.
+    let restricted: Vec<i32> = RFDs::get_from_db(db).into_iter().filter(|r| r.is_restricted()).map(|r| r.number).collect();
+    let backlinks = RFDReferences::get_backlinks(db, number).into_iter().filter(|r| !restricted.contains(&r.rfd_number)).collect();
+
+    Ok(HttpResponseOk(RFDWithReferences {
+        rfd,
+        references: RFDReferences::get_for_rfd(db, number).0,
+        backlinks,
+    }))
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+struct RFDSearchParams {
+    q: String,
+}
+
+/**
+ * Search RFDs by their title, authors, state and content.
+ */
+#[endpoint {
+    method = GET,
+    path = "/search/rfds",
+}]
+async fn api_search_rfds(rqctx: Arc<RequestContext<Context>>, query_args: Query<RFDSearchParams>) -> Result<HttpResponseOk<Vec<RFDSearchResult>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    match search_rfds(db, &query_args.into_inner().q) {
+        Ok(results) => Ok(HttpResponseOk(results)),
+        Err(e) => Err(HttpError::for_internal_error(e)),
+    }
 }
 
 /**
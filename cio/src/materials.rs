@@ -0,0 +1,809 @@
+use std::env;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use flate2::read::GzDecoder;
+use html2text::from_read;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tar::Archive;
+
+/// The most files we read out of an archive.
+pub static MATERIALS_MAX_ARCHIVE_ENTRIES: usize = 500;
+
+/// The most bytes we read out of a single file, uncompressed.
+pub static MATERIALS_MAX_FILE_BYTES: u64 = 25 * 1024 * 1024;
+
+/// The most bytes we read out of an archive in total, uncompressed.
+pub static MATERIALS_MAX_ARCHIVE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// The highest ratio of uncompressed to compressed size we accept for a file
+/// in a zip archive. Text compresses well, but not this well.
+pub static MATERIALS_MAX_COMPRESSION_RATIO: u64 = 100;
+
+/// Files smaller than this are not checked against `MATERIALS_MAX_COMPRESSION_RATIO`,
+/// a small file of whitespace can compress better than that.
+static COMPRESSION_RATIO_MIN_BYTES: u64 = 1024 * 1024;
+
+/// The most text we keep from an applicant's materials, across all the files.
+/// The files that look like the candidate materials come first, so they are
+/// what we keep.
+pub static MATERIALS_MAX_TEXT_BYTES: usize = 2 * 1024 * 1024;
+
+/// How long we let `pdftotext` or pandoc run on a single file before we kill it.
+pub static MATERIALS_CONVERT_TIMEOUT_SECONDS: u64 = 60;
+
+/// The extensions of the files we convert with pandoc, and the pandoc reader
+/// for each. Anything else is not read, pandoc has readers we do not want to
+/// point at an upload.
+static PANDOC_READERS: &[(&str, &str)] = &[
+    ("epub", "epub"),
+    ("ipynb", "ipynb"),
+    ("latex", "latex"),
+    ("org", "org"),
+    ("rst", "rst"),
+    ("rtf", "rtf"),
+    ("tex", "latex"),
+    ("textile", "textile"),
+];
+
+/// The types of files we extract text from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialsFormat {
+    Pdf,
+    Html,
+    Docx,
+    Odt,
+    Markdown,
+    Text,
+    Zip,
+    Tar,
+    TarGz,
+    /// Anything else. A single file is converted with pandoc, a file in an
+    /// archive is read if it is text.
+    Other,
+}
+
+impl MaterialsFormat {
+    /// Get the format of a file from its name, or its mime type if the name
+    /// does not tell us.
+    pub fn from_name(name: &str, mime_type: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            return MaterialsFormat::TarGz;
+        }
+
+        let extension = Path::new(&name).extension().and_then(|e| e.to_str()).unwrap_or_default();
+        match extension {
+            "pdf" => MaterialsFormat::Pdf,
+            "html" | "htm" => MaterialsFormat::Html,
+            "docx" => MaterialsFormat::Docx,
+            "odt" => MaterialsFormat::Odt,
+            "md" | "markdown" => MaterialsFormat::Markdown,
+            "txt" => MaterialsFormat::Text,
+            "zip" => MaterialsFormat::Zip,
+            "tar" => MaterialsFormat::Tar,
+            _ => match mime_type {
+                "application/pdf" => MaterialsFormat::Pdf,
+                "text/html" => MaterialsFormat::Html,
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => MaterialsFormat::Docx,
+                "application/vnd.oasis.opendocument.text" => MaterialsFormat::Odt,
+                "text/markdown" => MaterialsFormat::Markdown,
+                "text/plain" => MaterialsFormat::Text,
+                "application/zip" => MaterialsFormat::Zip,
+                "application/x-tar" => MaterialsFormat::Tar,
+                "application/gzip" => MaterialsFormat::TarGz,
+                _ => MaterialsFormat::Other,
+            },
+        }
+    }
+
+    pub fn is_archive(&self) -> bool {
+        matches!(self, MaterialsFormat::Zip | MaterialsFormat::Tar | MaterialsFormat::TarGz)
+    }
+}
+
+/// The text we extracted from one file of an applicant's materials.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ExtractedFile {
+    /// The name of the file. For a file in an archive this is the name of the
+    /// archive followed by the path in the archive, ie. `materials.zip/answers.md`.
+    pub path: String,
+    pub format: MaterialsFormat,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// Why we did not extract the text, this is empty if we did.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// The text we extracted from an applicant's materials, by file.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct MaterialsExtraction {
+    pub files: Vec<ExtractedFile>,
+}
+
+impl MaterialsExtraction {
+    /// All the text. If there is more than one file, each starts with a header
+    /// saying which file it came from.
+    pub fn text(&self) -> String {
+        if self.files.len() == 1 {
+            return self.files[0].text.to_string();
+        }
+
+        let mut text = String::new();
+        for file in self.files.iter().filter(|f| !f.text.is_empty()) {
+            text += &format!("====================== {} ======================\n\n", file.path);
+            text += &file.text;
+            text += "\n\n\n";
+        }
+
+        text.trim().to_string()
+    }
+
+    /// The files we could not extract the text from, and why.
+    pub fn errors(&self) -> Vec<String> {
+        self.files.iter().filter(|f| !f.error.is_empty()).map(|f| format!("{}: {}", f.path, f.error)).collect()
+    }
+}
+
+/// Returns if the file name looks like the candidate materials themselves.
+pub fn is_materials(file_name: &str) -> bool {
+    file_name.ends_with("responses.pdf")
+        || (file_name.starts_with("Oxide Candidate Materials") && file_name.ends_with(".pdf"))
+        || (file_name.starts_with("Oxide_Candidate_Materials") && file_name.ends_with(".pdf"))
+        || file_name.ends_with("Oxide Candidate Materials.pdf")
+        || file_name.ends_with("Oxide Candidate Materials.pdf.pdf")
+        || file_name.ends_with("OxideQuestions.pdf")
+        || file_name.ends_with("oxide-computer-candidate-materials.pdf")
+        || file_name.ends_with("Questionnaire.pdf")
+        || file_name.ends_with("Questionairre.pdf")
+        || file_name.ends_with("Operations Manager.pdf")
+}
+
+/// A directory in the temporary directory for a single extraction, it is
+/// removed when it is dropped. We only write files here under names we make
+/// up, never under the names from the upload.
+pub struct Sandbox {
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new() -> Result<Self, String> {
+        let mut dir = env::temp_dir();
+        dir.push(format!("materials-{}", random_name()));
+        fs::create_dir(&dir).map_err(|e| format!("creating the directory {} failed: {}", dir.display(), e))?;
+
+        Ok(Sandbox { dir })
+    }
+
+    /// A new path in the sandbox with the extension.
+    pub fn path(&self, extension: &str) -> PathBuf {
+        let mut path = self.dir.clone();
+        path.push(format!("{}.{}", random_name(), extension));
+        path
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            println!("[materials] removing the directory {} failed: {}", self.dir.display(), e);
+        }
+    }
+}
+
+fn random_name() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(16).map(char::from).collect()
+}
+
+/// A file read out of an archive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveEntry {
+    /// The path of the file in the archive, see `sanitize_archive_path`.
+    pub path: String,
+    pub bytes: Vec<u8>,
+    /// Why we skipped the file, this is empty if we read it.
+    pub error: String,
+}
+
+impl ArchiveEntry {
+    fn skipped(path: &str, error: String) -> Self {
+        ArchiveEntry {
+            path: path.to_string(),
+            bytes: Default::default(),
+            error,
+        }
+    }
+}
+
+/// Clean up the path of a file in an archive. Absolute paths, and paths that
+/// climb out of the archive with `..`, are rejected.
+pub fn sanitize_archive_path(name: &str) -> Result<String, String> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') || name.chars().nth(1) == Some(':') {
+        return Err("is an absolute path".to_string());
+    }
+
+    let mut parts: Vec<&str> = Default::default();
+    for part in name.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return Err("is outside of the archive".to_string()),
+            p => parts.push(p),
+        }
+    }
+    if parts.is_empty() {
+        return Err("has no name".to_string());
+    }
+
+    Ok(parts.join("/"))
+}
+
+/// Read at most `limit` bytes. We do not trust the sizes in archive headers,
+/// so this is what actually stops us from reading too much.
+fn read_limited<R: Read>(reader: R, limit: u64) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Default::default();
+    reader.take(limit + 1).read_to_end(&mut bytes).map_err(|e| format!("reading failed: {}", e))?;
+    if bytes.len() as u64 > limit {
+        return Err(format!("is larger than {} bytes", limit));
+    }
+
+    Ok(bytes)
+}
+
+/// Read the files out of a zip archive, in memory.
+fn read_zip(bytes: &[u8]) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("reading the zip archive failed: {}", e))?;
+    if archive.len() > MATERIALS_MAX_ARCHIVE_ENTRIES {
+        return Err(format!("the archive has {} files, we only read up to {}", archive.len(), MATERIALS_MAX_ARCHIVE_ENTRIES));
+    }
+
+    let mut entries: Vec<ArchiveEntry> = Default::default();
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let file = match archive.by_index(i) {
+            Ok(file) => file,
+            Err(e) => {
+                entries.push(ArchiveEntry::skipped(&format!("file {}", i), format!("reading failed: {}", e)));
+                continue;
+            }
+        };
+        if file.name().ends_with('/') {
+            continue;
+        }
+
+        let path = match sanitize_archive_path(file.name()) {
+            Ok(path) => path,
+            Err(e) => {
+                entries.push(ArchiveEntry::skipped(file.name(), e));
+                continue;
+            }
+        };
+        // Skip the resource forks macOS adds to archives.
+        if path.starts_with("__MACOSX/") {
+            continue;
+        }
+        if file.unix_mode().map(|m| m & 0o170000 == 0o120000).unwrap_or(false) {
+            entries.push(ArchiveEntry::skipped(&path, "is a symlink".to_string()));
+            continue;
+        }
+        if file.size() > COMPRESSION_RATIO_MIN_BYTES && file.size() / file.compressed_size().max(1) > MATERIALS_MAX_COMPRESSION_RATIO {
+            entries.push(ArchiveEntry::skipped(
+                &path,
+                format!("compresses more than {} times, it might be a zip bomb", MATERIALS_MAX_COMPRESSION_RATIO),
+            ));
+            continue;
+        }
+
+        let remaining = MATERIALS_MAX_ARCHIVE_BYTES - total;
+        match read_limited(file, MATERIALS_MAX_FILE_BYTES.min(remaining)) {
+            Ok(bytes) => {
+                total += bytes.len() as u64;
+                entries.push(ArchiveEntry { path, bytes, error: "".to_string() });
+            }
+            Err(e) if remaining < MATERIALS_MAX_FILE_BYTES => {
+                entries.push(ArchiveEntry::skipped(&path, format!("the archive is larger than {} bytes uncompressed", MATERIALS_MAX_ARCHIVE_BYTES)));
+                println!("[materials] stopped reading the zip archive at {}: {}", path, e);
+                break;
+            }
+            Err(e) => entries.push(ArchiveEntry::skipped(&path, e)),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Read the files out of a tar archive, in memory. Only regular files are
+/// read, links and devices are skipped.
+fn read_tar<R: Read>(reader: R) -> Result<Vec<ArchiveEntry>, String> {
+    // Limit how much we decompress, the headers are small compared to this.
+    let mut archive = Archive::new(reader.take(MATERIALS_MAX_ARCHIVE_BYTES + MATERIALS_MAX_FILE_BYTES));
+
+    let mut entries: Vec<ArchiveEntry> = Default::default();
+    let mut total: u64 = 0;
+    for (i, entry) in archive.entries().map_err(|e| format!("reading the tar archive failed: {}", e))?.enumerate() {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                // We cannot find the next entry after a bad one.
+                entries.push(ArchiveEntry::skipped(&format!("file {}", i), format!("reading the archive stopped: {}", e)));
+                break;
+            }
+        };
+        if i >= MATERIALS_MAX_ARCHIVE_ENTRIES {
+            entries.push(ArchiveEntry::skipped(
+                &format!("file {}", i),
+                format!("the archive has more than {} files", MATERIALS_MAX_ARCHIVE_ENTRIES),
+            ));
+            break;
+        }
+
+        let name = entry.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        if !entry.header().entry_type().is_file() {
+            if !entry.header().entry_type().is_dir() {
+                entries.push(ArchiveEntry::skipped(&name, "is not a regular file".to_string()));
+            }
+            continue;
+        }
+
+        let path = match sanitize_archive_path(&name) {
+            Ok(path) => path,
+            Err(e) => {
+                entries.push(ArchiveEntry::skipped(&name, e));
+                continue;
+            }
+        };
+        if path.starts_with("__MACOSX/") || path.rsplit('/').next().unwrap_or_default().starts_with("._") {
+            continue;
+        }
+
+        let remaining = MATERIALS_MAX_ARCHIVE_BYTES - total;
+        match read_limited(&mut entry, MATERIALS_MAX_FILE_BYTES.min(remaining)) {
+            Ok(bytes) => {
+                total += bytes.len() as u64;
+                entries.push(ArchiveEntry { path, bytes, error: "".to_string() });
+            }
+            Err(e) if remaining < MATERIALS_MAX_FILE_BYTES => {
+                entries.push(ArchiveEntry::skipped(&path, format!("the archive is larger than {} bytes uncompressed", MATERIALS_MAX_ARCHIVE_BYTES)));
+                println!("[materials] stopped reading the tar archive at {}: {}", path, e);
+                break;
+            }
+            Err(e) => entries.push(ArchiveEntry::skipped(&path, e)),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Read the files out of an archive, in memory and within the limits above.
+/// Files we skip are returned with the reason we skipped them.
+pub fn read_archive(format: MaterialsFormat, bytes: &[u8]) -> Result<Vec<ArchiveEntry>, String> {
+    match format {
+        MaterialsFormat::Zip => read_zip(bytes),
+        MaterialsFormat::Tar => read_tar(Cursor::new(bytes)),
+        MaterialsFormat::TarGz => read_tar(GzDecoder::new(Cursor::new(bytes))),
+        _ => Err(format!("{:?} is not an archive", format)),
+    }
+}
+
+/// Turn the XML of a Word or OpenDocument document into text, one paragraph per line.
+fn xml_to_text(xml: &str) -> String {
+    let breaks = Regex::new(r"</w:p>|<w:br(\s[^>]*)?/>|<w:cr\s*/>|</text:p>|</text:h>|<text:line-break\s*/>").unwrap();
+    let tabs = Regex::new(r"<w:tab\s*/>|<text:tab\s*/>").unwrap();
+    let spaces = Regex::new(r"<text:s(\s[^>]*)?/>").unwrap();
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    let entities = Regex::new(r"&#(x[0-9a-fA-F]+|[0-9]+);").unwrap();
+
+    let text = breaks.replace_all(xml, "\n");
+    let text = tabs.replace_all(&text, "\t");
+    let text = spaces.replace_all(&text, " ");
+    let text = tags.replace_all(&text, "");
+    let text = entities.replace_all(&text, |caps: &Captures| {
+        let code = &caps[1];
+        let n = match code.strip_prefix('x') {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => code.parse::<u32>().ok(),
+        };
+        n.and_then(std::char::from_u32).map(|c| c.to_string()).unwrap_or_default()
+    });
+
+    // Do `&amp;` last, so `&amp;lt;` becomes `&lt;` and not `<`.
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// Read the text out of a Word (docx) or OpenDocument (odt) document, which
+/// are zip archives with the text in an XML file.
+fn read_office_document(bytes: &[u8], document_xml: &str) -> Result<String, String> {
+    let entries = read_zip(bytes)?;
+    let entry = match entries.iter().find(|e| e.path == document_xml) {
+        Some(entry) => entry,
+        None => return Err(format!("{} is missing from the document", document_xml)),
+    };
+    if !entry.error.is_empty() {
+        return Err(format!("{} {}", document_xml, entry.error));
+    }
+
+    Ok(xml_to_text(&String::from_utf8_lossy(&entry.bytes)))
+}
+
+/// Run a converter, and kill it if it runs for longer than `timeout`. Its
+/// output goes to a file in the sandbox, and its errors to another one so a
+/// full pipe cannot stall it.
+fn run_with_timeout(sandbox: &Sandbox, command: &mut Command, timeout: Duration) -> Result<(), String> {
+    let name = command.get_program().to_string_lossy().to_string();
+    let stderr_path = sandbox.path("stderr");
+    let stderr = fs::File::create(&stderr_path).map_err(|e| format!("creating {} failed: {}", stderr_path.display(), e))?;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(stderr)
+        .spawn()
+        .map_err(|e| format!("running {} failed: {}", name, e))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                let mut stderr = String::new();
+                if let Ok(f) = fs::File::open(&stderr_path) {
+                    f.take(4096).read_to_string(&mut stderr).unwrap_or_default();
+                }
+                return Err(format!("{} failed with {}: {}", name, status, stderr.trim()));
+            }
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(100)),
+            result => {
+                // Kill it, and wait on it so it does not linger as a zombie.
+                child.kill().unwrap_or_default();
+                child.wait().map_err(|e| format!("waiting on {} failed: {}", name, e))?;
+                return match result {
+                    Err(e) => Err(format!("waiting on {} failed: {}", name, e)),
+                    _ => Err(format!("{} took longer than {} seconds", name, timeout.as_secs())),
+                };
+            }
+        }
+    }
+}
+
+/// Read the text out of a PDF with `pdftotext`.
+fn read_pdf(sandbox: &Sandbox, bytes: &[u8]) -> Result<String, String> {
+    let input = sandbox.path("pdf");
+    fs::write(&input, bytes).map_err(|e| format!("writing {} failed: {}", input.display(), e))?;
+    let output = sandbox.path("txt");
+
+    run_with_timeout(
+        sandbox,
+        Command::new("pdftotext").arg("-enc").arg("UTF-8").arg(&input).arg(&output),
+        Duration::from_secs(MATERIALS_CONVERT_TIMEOUT_SECONDS),
+    )?;
+
+    fs::read_to_string(&output).map_err(|e| format!("reading the pdftotext output failed: {}", e))
+}
+
+/// Get the pandoc reader for a file, from its extension. Only the formats in
+/// `PANDOC_READERS` are read.
+fn get_pandoc_reader(name: &str) -> Result<&'static str, String> {
+    let extension = Path::new(name).extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    if extension.is_empty() {
+        return Err("has no extension, so we do not know its type".to_string());
+    }
+
+    match PANDOC_READERS.iter().find(|(e, _)| *e == extension) {
+        Some((_, reader)) => Ok(reader),
+        None => Err("is not a type of file we read".to_string()),
+    }
+}
+
+/// Convert any other file we know the format of to text with pandoc. Pandoc
+/// runs with `--sandbox`, so the file cannot make it read other files or
+/// fetch anything, which needs pandoc 2.15 or newer.
+fn read_with_pandoc(sandbox: &Sandbox, name: &str, bytes: &[u8]) -> Result<String, String> {
+    let reader = get_pandoc_reader(name)?;
+
+    let input = sandbox.path("input");
+    fs::write(&input, bytes).map_err(|e| format!("writing {} failed: {}", input.display(), e))?;
+    let output = sandbox.path("txt");
+
+    run_with_timeout(
+        sandbox,
+        Command::new("pandoc")
+            .arg("--sandbox")
+            .arg("--from")
+            .arg(reader)
+            .arg("--to")
+            .arg("plain")
+            .arg("--output")
+            .arg(&output)
+            .arg(&input),
+        Duration::from_secs(MATERIALS_CONVERT_TIMEOUT_SECONDS),
+    )?;
+
+    fs::read_to_string(&output).map_err(|e| format!("reading the pandoc output failed: {}", e))
+}
+
+/// Keep the text of the files to `MATERIALS_MAX_TEXT_BYTES` in total, in order.
+/// The files past the limit are cut short, and say so in their error.
+fn limit_text(files: &mut [ExtractedFile]) {
+    let mut remaining = MATERIALS_MAX_TEXT_BYTES;
+    for file in files.iter_mut() {
+        if file.text.len() <= remaining {
+            remaining -= file.text.len();
+            continue;
+        }
+
+        let mut end = remaining;
+        while !file.text.is_char_boundary(end) {
+            end -= 1;
+        }
+        file.text.truncate(end);
+        remaining = 0;
+        file.error = format!("the text of the materials is longer than {} bytes, the rest was left out", MATERIALS_MAX_TEXT_BYTES);
+    }
+}
+
+/// Extract the text from a single file.
+fn extract_file(sandbox: &Sandbox, path: &str, format: MaterialsFormat, bytes: &[u8], in_archive: bool) -> ExtractedFile {
+    let result = match format {
+        MaterialsFormat::Pdf => read_pdf(sandbox, bytes),
+        // Wrap lines at 80 characters.
+        MaterialsFormat::Html => Ok(from_read(bytes, 80)),
+        MaterialsFormat::Docx => read_office_document(bytes, "word/document.xml"),
+        MaterialsFormat::Odt => read_office_document(bytes, "content.xml"),
+        MaterialsFormat::Markdown | MaterialsFormat::Text => Ok(String::from_utf8_lossy(bytes).to_string()),
+        MaterialsFormat::Zip | MaterialsFormat::Tar | MaterialsFormat::TarGz => Err("archives in archives are not read".to_string()),
+        // Archives are full of source code, read anything that is text.
+        MaterialsFormat::Other if in_archive => match String::from_utf8(bytes.to_vec()) {
+            Ok(text) if !text.contains('\0') => Ok(text),
+            _ => Err("is not a type of file we read".to_string()),
+        },
+        MaterialsFormat::Other => read_with_pandoc(sandbox, path, bytes),
+    };
+
+    let (text, error) = match result {
+        Ok(text) => (text.trim().to_string(), "".to_string()),
+        Err(e) => ("".to_string(), e),
+    };
+
+    ExtractedFile {
+        path: path.to_string(),
+        format,
+        text,
+        error,
+    }
+}
+
+/// Extract the text from an applicant's materials or resume.
+///
+/// Archives are read in memory and every file in them gets its own result, so
+/// we can tell which file the text came from. The files that look like the
+/// candidate materials come first, since we parse the answers to the questions
+/// out of the first match.
+pub fn extract_materials(name: &str, mime_type: &str, bytes: &[u8]) -> MaterialsExtraction {
+    let format = MaterialsFormat::from_name(name, mime_type);
+
+    let sandbox = match Sandbox::new() {
+        Ok(sandbox) => sandbox,
+        Err(e) => {
+            return MaterialsExtraction {
+                files: vec![ExtractedFile {
+                    path: name.to_string(),
+                    format,
+                    text: "".to_string(),
+                    error: e,
+                }],
+            }
+        }
+    };
+
+    if !format.is_archive() {
+        let mut files = vec![extract_file(&sandbox, name, format, bytes, false)];
+        limit_text(&mut files);
+        return MaterialsExtraction { files };
+    }
+
+    let entries = match read_archive(format, bytes) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return MaterialsExtraction {
+                files: vec![ExtractedFile {
+                    path: name.to_string(),
+                    format,
+                    text: "".to_string(),
+                    error: e,
+                }],
+            }
+        }
+    };
+
+    let mut files: Vec<ExtractedFile> = Default::default();
+    for entry in entries {
+        let path = format!("{}/{}", name, entry.path);
+        let entry_format = MaterialsFormat::from_name(&entry.path, "");
+        if !entry.error.is_empty() {
+            files.push(ExtractedFile {
+                path,
+                format: entry_format,
+                text: "".to_string(),
+                error: entry.error,
+            });
+            continue;
+        }
+
+        files.push(extract_file(&sandbox, &path, entry_format, &entry.bytes, true));
+    }
+    files.sort_by_key(|f| !is_materials(f.path.rsplit('/').next().unwrap_or_default()));
+    limit_text(&mut files);
+
+    MaterialsExtraction { files }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use std::process::Command;
+    use std::time::Duration;
+
+    use crate::materials::{
+        extract_materials, get_pandoc_reader, limit_text, read_archive, run_with_timeout, sanitize_archive_path, xml_to_text, ExtractedFile, MaterialsFormat, Sandbox, MATERIALS_MAX_TEXT_BYTES,
+    };
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_materials_format() {
+        assert_eq!(MaterialsFormat::from_name("Materials.PDF", ""), MaterialsFormat::Pdf);
+        assert_eq!(MaterialsFormat::from_name("materials.tar.gz", ""), MaterialsFormat::TarGz);
+        assert_eq!(MaterialsFormat::from_name("materials.tgz", ""), MaterialsFormat::TarGz);
+        assert_eq!(MaterialsFormat::from_name("answers.md", ""), MaterialsFormat::Markdown);
+        assert_eq!(MaterialsFormat::from_name("resume", "application/vnd.oasis.opendocument.text"), MaterialsFormat::Odt);
+        assert_eq!(MaterialsFormat::from_name("main.rs", ""), MaterialsFormat::Other);
+    }
+
+    #[test]
+    fn test_sanitize_archive_path() {
+        assert_eq!(sanitize_archive_path("materials/./answers.md"), Ok("materials/answers.md".to_string()));
+        assert_eq!(sanitize_archive_path("materials\\answers.md"), Ok("materials/answers.md".to_string()));
+        assert!(sanitize_archive_path("../../etc/passwd").is_err());
+        assert!(sanitize_archive_path("materials/../../answers.md").is_err());
+        assert!(sanitize_archive_path("/etc/passwd").is_err());
+        assert!(sanitize_archive_path("C:\\Windows\\win.ini").is_err());
+        assert!(sanitize_archive_path("./").is_err());
+    }
+
+    #[test]
+    fn test_xml_to_text() {
+        let docx = r#"<w:document><w:body><w:p><w:r><w:t>Why Oxide?</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">Rust &amp; </w:t></w:r><w:r><w:tab/><w:t>&lt;hardware&gt; &#8212; yes</w:t></w:r></w:p></w:body></w:document>"#;
+        assert_eq!(xml_to_text(docx), "Why Oxide?\nRust & \t<hardware> \u{2014} yes\n");
+
+        let odt = r#"<office:text><text:h>Work samples</text:h><text:p>one<text:s text:c="2"/>two<text:line-break/>three</text:p></office:text>"#;
+        assert_eq!(xml_to_text(odt), "Work samples\none two\nthree\n");
+    }
+
+    #[test]
+    fn test_read_zip_skips_unsafe_entries() {
+        let bomb = vec![0u8; 4 * 1024 * 1024];
+        let bytes = zip(&[("answers.md", b"# Why Oxide?"), ("../../evil.txt", b"evil"), ("bomb.txt", &bomb), ("__MACOSX/._answers.md", b"fork")]);
+
+        let entries = read_archive(MaterialsFormat::Zip, &bytes).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].path, "answers.md");
+        assert_eq!(entries[0].bytes, b"# Why Oxide?");
+        assert!(entries[0].error.is_empty());
+        assert_eq!(entries[1].path, "../../evil.txt");
+        assert_eq!(entries[1].error, "is outside of the archive");
+        assert_eq!(entries[2].path, "bomb.txt");
+        assert!(entries[2].error.contains("zip bomb"));
+        assert!(entries[2].bytes.is_empty());
+    }
+
+    #[test]
+    fn test_extract_materials_from_tar_gz() {
+        let bytes = tar_gz(&[
+            ("materials/notes.txt", b"some notes"),
+            ("materials/main.rs", b"fn main() {}"),
+            ("materials/logo.png", &[0x89, 0x50, 0x4e, 0x47, 0x00]),
+        ]);
+
+        let extraction = extract_materials("materials.tar.gz", "application/gzip", &bytes);
+        assert_eq!(extraction.files.len(), 3);
+        assert_eq!(extraction.files[0].path, "materials.tar.gz/materials/notes.txt");
+        assert_eq!(extraction.files[0].text, "some notes");
+        assert_eq!(extraction.files[1].format, MaterialsFormat::Other);
+        assert_eq!(extraction.files[1].text, "fn main() {}");
+        assert_eq!(extraction.errors(), vec!["materials.tar.gz/materials/logo.png: is not a type of file we read".to_string()]);
+        assert_eq!(
+            extraction.text(),
+            "====================== materials.tar.gz/materials/notes.txt ======================\n\nsome notes\n\n\n====================== materials.tar.gz/materials/main.rs ======================\n\nfn main() {}"
+        );
+    }
+
+    #[test]
+    fn test_get_pandoc_reader() {
+        assert_eq!(get_pandoc_reader("answers.RST"), Ok("rst"));
+        assert_eq!(get_pandoc_reader("materials.zip/paper.tex"), Ok("latex"));
+        assert!(get_pandoc_reader("answers").is_err());
+        assert!(get_pandoc_reader("notes.docbook").is_err());
+        assert!(get_pandoc_reader("page.mediawiki").is_err());
+    }
+
+    #[test]
+    fn test_limit_text() {
+        let file = |path: &str, text: String| ExtractedFile {
+            path: path.to_string(),
+            format: MaterialsFormat::Text,
+            text,
+            error: "".to_string(),
+        };
+        let mut files = vec![
+            file("materials.md", "a".repeat(MATERIALS_MAX_TEXT_BYTES - 1)),
+            file("notes.txt", "\u{e9}\u{e9}".to_string()),
+            file("main.rs", "fn main() {}".to_string()),
+        ];
+
+        limit_text(&mut files);
+        assert_eq!(files[0].text.len(), MATERIALS_MAX_TEXT_BYTES - 1);
+        assert!(files[0].error.is_empty());
+        // We do not cut a character in half.
+        assert_eq!(files[1].text, "");
+        assert!(files[1].error.contains("the rest was left out"));
+        assert_eq!(files[2].text, "");
+        assert!(files[2].error.contains("the rest was left out"));
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let sandbox = Sandbox::new().unwrap();
+        assert!(run_with_timeout(&sandbox, &mut Command::new("true"), Duration::from_secs(5)).is_ok());
+
+        let err = run_with_timeout(&sandbox, Command::new("sleep").arg("5"), Duration::from_millis(200)).unwrap_err();
+        assert!(err.contains("took longer than"));
+
+        let err = run_with_timeout(&sandbox, Command::new("sh").arg("-c").arg("echo broken >&2; exit 3"), Duration::from_secs(5)).unwrap_err();
+        assert!(err.contains("broken"));
+    }
+
+    #[test]
+    fn test_extract_materials_from_docx() {
+        let document = br#"<w:document><w:body><w:p><w:r><w:t>Questionnaire</w:t></w:r></w:p></w:body></w:document>"#;
+        let bytes = zip(&[("[Content_Types].xml", b"<Types/>"), ("word/document.xml", document)]);
+
+        let extraction = extract_materials("resume.docx", "", &bytes);
+        assert_eq!(extraction.files.len(), 1);
+        assert_eq!(extraction.files[0].format, MaterialsFormat::Docx);
+        assert_eq!(extraction.text(), "Questionnaire");
+        assert!(extraction.errors().is_empty());
+    }
+}
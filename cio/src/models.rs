@@ -1,11 +1,12 @@
 #![allow(clippy::from_over_into)]
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::io::{stderr, stdout, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::str::from_utf8;
+use std::str::{from_utf8, FromStr};
 
 use async_trait::async_trait;
 use chrono::offset::Utc;
@@ -22,10 +23,15 @@ use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::airtable::{AIRTABLE_BASE_ID_MISC, AIRTABLE_BASE_ID_RACK_ROADMAP, AIRTABLE_GITHUB_REPOS_TABLE, AIRTABLE_RFD_TABLE};
+use crate::airtable::{
+    AIRTABLE_BASE_ID_MISC, AIRTABLE_BASE_ID_RACK_ROADMAP, AIRTABLE_GITHUB_REPOS_TABLE, AIRTABLE_RFD_COMMITS_TABLE, AIRTABLE_RFD_REFERENCES_TABLE, AIRTABLE_RFD_STATE_HISTORY_TABLE,
+    AIRTABLE_RFD_SUBSCRIBERS_TABLE, AIRTABLE_RFD_TABLE,
+};
 use crate::core::UpdateAirtableRecord;
-use crate::rfds::{clean_rfd_html_links, get_images_in_branch, get_rfd_contents_from_repo, parse_markdown, update_discussion_link, update_state};
-use crate::schema::{github_repos, rfds as r_f_ds, rfds};
+use crate::rfd_state::RfdState;
+use crate::rfd_visibility::RfdVisibility;
+use crate::rfds::{clean_rfd_html_links, get_author_emails, get_images_in_branch, get_rfd_contents_from_repo, parse_markdown, update_discussion_link, update_state};
+use crate::schema::{github_repos, rfd_commits, rfd_references, rfd_state_history, rfd_subscribers, rfds as r_f_ds, rfds};
 use crate::utils::{create_or_update_file_in_github_repo, get_gsuite_token, github_org, write_file};
 
 /// The data type for a GitHub user.
@@ -407,6 +413,16 @@ pub struct NewRFD {
     pub pdf_link_github: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub pdf_link_google_drive: String,
+    /// visibility is who can see the RFD: public, internal or restricted
+    #[serde(default = "default_rfd_visibility")]
+    pub visibility: String,
+    /// visibility_groups are the groups that can see the RFD when it is restricted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub visibility_groups: Vec<String>,
+}
+
+fn default_rfd_visibility() -> String {
+    RfdVisibility::default().to_string()
 }
 
 impl NewRFD {
@@ -438,6 +454,9 @@ impl NewRFD {
         // Parse the discussion from the contents.
         let discussion = NewRFD::get_discussion(&content);
 
+        // Parse who can see the RFD from the contents.
+        let (visibility, visibility_groups) = RfdVisibility::parse_metadata(&NewRFD::get_visibility(&content));
+
         NewRFD {
             number,
             number_string,
@@ -460,6 +479,8 @@ impl NewRFD {
             relevant_components: Default::default(),
             pdf_link_github: Default::default(),
             pdf_link_google_drive: Default::default(),
+            visibility: visibility.to_string(),
+            visibility_groups,
         }
     }
 
@@ -503,6 +524,14 @@ impl NewRFD {
         }
     }
 
+    pub fn get_visibility(content: &str) -> String {
+        let re = Regex::new(r"(?m)^:?visibility:(.*)$").unwrap();
+        match re.captures(&content) {
+            Some(c) => c[1].trim().to_string(),
+            None => Default::default(),
+        }
+    }
+
     pub fn generate_number_string(number: i32) -> String {
         // Add leading zeros to the number for the number_string.
         let mut number_string = number.to_string();
@@ -639,39 +668,71 @@ impl RFD {
         msg
     }
 
-    /// Get a changelog for the RFD.
-    pub async fn get_weekly_changelog(&self, github: &Github, since: DateTime<Utc>) -> String {
-        let repo = github.repo(github_org(), "rfd");
-        let r = repo.get().await.unwrap();
-        let mut changelog = String::new();
+    /// Get the filename for the PDF of the RFD.
+    pub fn get_pdf_filename(&self) -> String {
+        format!("RFD {} {}.pdf", self.number_string, self.title.replace("/", "-").replace("'", "").replace(":", "").trim())
+    }
 
-        let mut branch = self.number_string.to_string();
-        if self.link.contains(&format!("/{}/", r.default_branch)) {
-            branch = r.default_branch.to_string();
+    /// Update an RFDs state.
+    pub fn update_state(&mut self, state: &str, is_markdown: bool) {
+        self.content = update_state(&self.content, state, is_markdown);
+        self.state = state.to_string();
+    }
+
+    /// Get who can see the RFD.
+    /// If the visibility is not one we know about, the RFD is treated as restricted.
+    pub fn rfd_visibility(&self) -> RfdVisibility {
+        self.visibility.parse().unwrap_or(RfdVisibility::Restricted)
+    }
+
+    /// Returns if the RFD is restricted to its authors and a set of groups.
+    pub fn is_restricted(&self) -> bool {
+        self.rfd_visibility().is_restricted()
+    }
+
+    /// Returns if someone with the given email, who is a member of the given groups, can see the RFD.
+    pub fn can_be_seen_by(&self, email: &str, groups: &[String]) -> bool {
+        if !self.is_restricted() {
+            return true;
         }
 
-        // Get the commits from the last seven days to the file.
-        let commits = repo.commits().list(&format!("/rfd/{}/", self.number_string), &branch, Some(since)).await.unwrap();
+        get_author_emails(&self.authors).contains(&email.trim().to_lowercase()) || self.visibility_groups.iter().any(|g| groups.contains(g))
+    }
+
+    /// Update the RFD in the database, and in Airtable if it is not restricted.
+    /// Restricted RFDs are removed from Airtable, since everyone with access to the base
+    /// can see them.
+    pub async fn update_with_visibility(&self, db: &crate::db::Database) -> Self {
+        if !self.is_restricted() {
+            return self.update(db).await;
+        }
 
-        for commit in commits {
-            let message: Vec<&str> = commit.commit.message.lines().collect();
-            if !message.is_empty() {
-                changelog += &format!("\t- \"{}\" by @{}\n\t\thttps://github.com/oxidecomputer/rfd/commit/{}\n", message[0], commit.author.login, commit.sha);
-            }
+        let mut rfd = self.clone();
+        if !rfd.airtable_record_id.is_empty() {
+            rfd.delete_from_airtable().await;
+            rfd.airtable_record_id = String::new();
         }
 
-        changelog
+        rfd.update_in_db(db)
     }
 
-    /// Get the filename for the PDF of the RFD.
-    pub fn get_pdf_filename(&self) -> String {
-        format!("RFD {} {}.pdf", self.number_string, self.title.replace("/", "-").replace("'", "").replace(":", "").trim())
+    /// Get the parsed state of the RFD.
+    /// If the state in the document is not one we know about, this returns the default state.
+    pub fn rfd_state(&self) -> RfdState {
+        self.state.parse().unwrap_or_default()
     }
 
-    /// Update an RFDs state.
-    pub fn update_state(&mut self, state: &str, is_markdown: bool) {
-        self.content = update_state(&self.content, state, is_markdown);
-        self.state = state.to_string();
+    /// Move an RFD to a new state, if the transition is allowed.
+    /// This returns the state the RFD was in before the transition.
+    pub fn transition_state(&mut self, to: RfdState, is_markdown: bool) -> Result<RfdState, String> {
+        let from = self.rfd_state();
+        if !from.can_transition_to(to) {
+            return Err(format!("RFD {} cannot move from state `{}` to `{}`", self.number_string, from, to));
+        }
+
+        self.update_state(&to.to_string(), is_markdown);
+
+        Ok(from)
     }
 
     /// Update an RFDs discussion link.
@@ -746,16 +807,27 @@ impl RFD {
         let shared_drive = drive_client.get_drive_by_name("Automated Documents").await.unwrap();
         let drive_id = shared_drive.id.to_string();
 
-        // Get the directory by the name.
-        let drive_rfd_dir = drive_client.get_file_by_name(&drive_id, "rfds").await.unwrap();
-        let parent_id = drive_rfd_dir.get(0).unwrap().id.to_string();
-
-        // Create or update the file in the google_drive.
-        let drive_file = drive_client
-            .create_or_update_file(&drive_id, &parent_id, &file_name, "application/pdf", &cmd_output.stdout)
-            .await
-            .unwrap();
-        self.pdf_link_google_drive = format!("https://drive.google.com/open?id={}", drive_file.id);
+        if self.is_restricted() {
+            // Restricted RFDs do not go in the shared drive, since everyone can see it.
+            // Remove any copy from before the RFD was restricted.
+            if !self.pdf_link_google_drive.is_empty() {
+                if let Err(e) = drive_client.delete_file_by_name(&drive_id, &file_name).await {
+                    println!("[rfdpdf] deleting `{}` from google drive failed: {}", file_name, e);
+                }
+                self.pdf_link_google_drive = String::new();
+            }
+        } else {
+            // Get the directory by the name.
+            let drive_rfd_dir = drive_client.get_file_by_name(&drive_id, "rfds").await.unwrap();
+            let parent_id = drive_rfd_dir.get(0).unwrap().id.to_string();
+
+            // Create or update the file in the google_drive.
+            let drive_file = drive_client
+                .create_or_update_file(&drive_id, &parent_id, &file_name, "application/pdf", &cmd_output.stdout)
+                .await
+                .unwrap();
+            self.pdf_link_google_drive = format!("https://drive.google.com/open?id={}", drive_file.id);
+        }
 
         // Delete our temporary file.
         if path.exists() && !path.is_dir() {
@@ -814,6 +886,11 @@ impl RFD {
 
         self.authors = NewRFD::get_authors(&self.content, is_markdown);
 
+        // Parse who can see the RFD.
+        let (visibility, visibility_groups) = RfdVisibility::parse_metadata(&NewRFD::get_visibility(&self.content));
+        self.visibility = visibility.to_string();
+        self.visibility_groups = visibility_groups;
+
         // Set the pdf link
         let file_name = self.get_pdf_filename();
         let rfd_path = format!("/pdfs/{}", file_name);
@@ -835,6 +912,261 @@ impl UpdateAirtableRecord<RFD> for RFD {
     }
 }
 
+impl RFDs {
+    /// Get the RFDs that everyone in the company can see, leaving out restricted RFDs.
+    pub fn get_visible_from_db(db: &crate::db::Database) -> Self {
+        RFDs(
+            rfds::dsl::rfds
+                .filter(rfds::dsl::visibility.ne(RfdVisibility::Restricted.to_string()))
+                .order_by(rfds::dsl::id.desc())
+                .load::<RFD>(&db.conn())
+                .unwrap(),
+        )
+    }
+}
+
+/// The data type for a change in state of an RFD.
+#[db {
+    new_struct_name = "RFDStateChange",
+    airtable_base_id = "AIRTABLE_BASE_ID_RACK_ROADMAP",
+    airtable_table = "AIRTABLE_RFD_STATE_HISTORY_TABLE",
+    table_name = "rfd_state_history",
+    match_on = {
+        "rfd_number" = "i32",
+        "changed_at" = "DateTime<Utc>",
+    }
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "rfd_state_history"]
+pub struct NewRFDStateChange {
+    pub rfd_number: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub rfd_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from_state: String,
+    pub to_state: String,
+    /// valid_transition is false if the state was changed by hand in a way the
+    /// lifecycle does not allow.
+    #[serde(default)]
+    pub valid_transition: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub branch: String,
+    /// sha is the SHA of the commit that changed the state
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sha: String,
+    /// changed_by is the GitHub login of whoever changed the state
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub changed_by: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Implement updating the Airtable record for an RFDStateChange.
+#[async_trait]
+impl UpdateAirtableRecord<RFDStateChange> for RFDStateChange {
+    async fn update_airtable_record(&mut self, _record: RFDStateChange) {}
+}
+
+/// The data type for a reference from an RFD to another RFD or to GitHub.
+#[db {
+    new_struct_name = "RFDReference",
+    airtable_base_id = "AIRTABLE_BASE_ID_RACK_ROADMAP",
+    airtable_table = "AIRTABLE_RFD_REFERENCES_TABLE",
+    table_name = "rfd_references",
+    match_on = {
+        "rfd_number" = "i32",
+        "link" = "String",
+    }
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "rfd_references"]
+pub struct NewRFDReference {
+    /// rfd_number is the number of the RFD the reference was found in
+    pub rfd_number: i32,
+    /// reference_type is one of `rfd`, `issue`, `pull` or `repo`
+    pub reference_type: String,
+    /// target_rfd_number is the number of the RFD being referenced, if this is a reference to an RFD
+    #[serde(default)]
+    pub target_rfd_number: i32,
+    /// target is the human readable thing being referenced, ie. `RFD 43` or `oxidecomputer/cio#12`
+    pub target: String,
+    /// link is the canonical link to the thing being referenced
+    pub link: String,
+}
+
+/// Implement updating the Airtable record for an RFDReference.
+#[async_trait]
+impl UpdateAirtableRecord<RFDReference> for RFDReference {
+    async fn update_airtable_record(&mut self, _record: RFDReference) {}
+}
+
+impl RFDStateChanges {
+    /// Get the state changes for all RFDs since a point in time.
+    pub fn get_since(db: &crate::db::Database, since: DateTime<Utc>) -> Self {
+        RFDStateChanges(
+            rfd_state_history::dsl::rfd_state_history
+                .filter(rfd_state_history::dsl::changed_at.ge(since))
+                .order_by(rfd_state_history::dsl::changed_at.asc())
+                .load::<RFDStateChange>(&db.conn())
+                .unwrap(),
+        )
+    }
+}
+
+impl RFDReferences {
+    /// Get the references an RFD makes.
+    pub fn get_for_rfd(db: &crate::db::Database, number: i32) -> Self {
+        RFDReferences(
+            rfd_references::dsl::rfd_references
+                .filter(rfd_references::dsl::rfd_number.eq(number))
+                .order_by(rfd_references::dsl::id.asc())
+                .load::<RFDReference>(&db.conn())
+                .unwrap(),
+        )
+    }
+
+    /// Get the references other RFDs make to an RFD.
+    pub fn get_backlinks(db: &crate::db::Database, number: i32) -> Self {
+        RFDReferences(
+            rfd_references::dsl::rfd_references
+                .filter(rfd_references::dsl::reference_type.eq("rfd"))
+                .filter(rfd_references::dsl::target_rfd_number.eq(number))
+                .order_by(rfd_references::dsl::rfd_number.asc())
+                .load::<RFDReference>(&db.conn())
+                .unwrap(),
+        )
+    }
+}
+
+/// The data type for a commit that changed an RFD.
+/// These are recorded as we get pushes to the rfd repo so we don't have to go back to
+/// GitHub for every RFD when building the weekly digest.
+#[db {
+    new_struct_name = "RFDCommit",
+    airtable_base_id = "AIRTABLE_BASE_ID_RACK_ROADMAP",
+    airtable_table = "AIRTABLE_RFD_COMMITS_TABLE",
+    table_name = "rfd_commits",
+    match_on = {
+        "rfd_number" = "i32",
+        "sha" = "String",
+    }
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "rfd_commits"]
+pub struct NewRFDCommit {
+    pub rfd_number: i32,
+    pub sha: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub branch: String,
+    /// message is the first line of the commit message
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    pub committed_at: DateTime<Utc>,
+    /// diff is a short unified diff of the RFD content before and after the commit
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub diff: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+}
+
+/// Implement updating the Airtable record for an RFDCommit.
+#[async_trait]
+impl UpdateAirtableRecord<RFDCommit> for RFDCommit {
+    async fn update_airtable_record(&mut self, _record: RFDCommit) {}
+}
+
+impl RFDCommits {
+    /// Get the commits for all RFDs since a point in time.
+    pub fn get_since(db: &crate::db::Database, since: DateTime<Utc>) -> Self {
+        RFDCommits(
+            rfd_commits::dsl::rfd_commits
+                .filter(rfd_commits::dsl::committed_at.ge(since))
+                .order_by((rfd_commits::dsl::rfd_number.asc(), rfd_commits::dsl::committed_at.asc()))
+                .load::<RFDCommit>(&db.conn())
+                .unwrap(),
+        )
+    }
+}
+
+/// What someone wants to hear about in the weekly RFD digest.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RFDSubscription {
+    /// Everything that happened to any RFD.
+    All,
+
+    /// Everything that happened to RFDs they are an author of.
+    Mine,
+
+    /// Only new RFDs and RFDs that changed state.
+    StateChanges,
+}
+
+impl Default for RFDSubscription {
+    fn default() -> Self {
+        RFDSubscription::All
+    }
+}
+
+impl FromStr for RFDSubscription {
+    type Err = String;
+
+    fn from_str(subscription: &str) -> Result<Self, Self::Err> {
+        match subscription.to_lowercase().trim() {
+            "all" => Ok(RFDSubscription::All),
+            "mine" => Ok(RFDSubscription::Mine),
+            "state_changes" => Ok(RFDSubscription::StateChanges),
+            s => Err(format!("`{}` is not a valid RFD subscription", s)),
+        }
+    }
+}
+
+impl fmt::Display for RFDSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RFDSubscription::All => "all",
+            RFDSubscription::Mine => "mine",
+            RFDSubscription::StateChanges => "state_changes",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// The data type for someone who gets the weekly RFD digest.
+#[db {
+    new_struct_name = "RFDSubscriber",
+    airtable_base_id = "AIRTABLE_BASE_ID_RACK_ROADMAP",
+    airtable_table = "AIRTABLE_RFD_SUBSCRIBERS_TABLE",
+    table_name = "rfd_subscribers",
+    match_on = {
+        "email" = "String",
+    }
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "rfd_subscribers"]
+pub struct NewRFDSubscriber {
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// subscription is one of `all`, `mine` or `state_changes`
+    pub subscription: String,
+}
+
+/// Implement updating the Airtable record for an RFDSubscriber.
+#[async_trait]
+impl UpdateAirtableRecord<RFDSubscriber> for RFDSubscriber {
+    async fn update_airtable_record(&mut self, _record: RFDSubscriber) {}
+}
+
+impl RFDSubscriber {
+    /// Get the parsed subscription.
+    /// If the subscription is not one we know about, they get everything.
+    pub fn get_subscription(&self) -> RFDSubscription {
+        self.subscription.parse().unwrap_or_default()
+    }
+}
+
 pub fn truncate(s: &str, max_chars: usize) -> String {
     match s.char_indices().nth(max_chars) {
         None => s.to_string(),
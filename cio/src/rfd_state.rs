@@ -0,0 +1,149 @@
+use std::fmt;
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The various different states that an RFD can be in.
+/// See RFD 1 for what each of these mean.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RfdState {
+    /// The RFD is being written, but is not ready for discussion yet.
+    Prediscussion,
+
+    /// The RFD is a placeholder for an idea that still needs to be fleshed out.
+    Ideation,
+
+    /// The RFD has an open pull request and is being discussed.
+    Discussion,
+
+    /// The RFD has been merged into the default branch.
+    Published,
+
+    /// The RFD has been published and the work it describes has been done.
+    Committed,
+
+    /// The RFD is no longer being pursued.
+    Abandoned,
+}
+
+impl Default for RfdState {
+    fn default() -> Self {
+        RfdState::Prediscussion
+    }
+}
+
+impl RfdState {
+    /// Returns all the states an RFD can be in.
+    pub fn all() -> Vec<RfdState> {
+        vec![
+            RfdState::Prediscussion,
+            RfdState::Ideation,
+            RfdState::Discussion,
+            RfdState::Published,
+            RfdState::Committed,
+            RfdState::Abandoned,
+        ]
+    }
+
+    /// Returns the states this state is allowed to move to.
+    pub fn next_states(&self) -> Vec<RfdState> {
+        match self {
+            // An RFD can be merged into the default branch without ever having a discussion
+            // pull request, so these can move straight to `published`.
+            RfdState::Prediscussion => vec![RfdState::Ideation, RfdState::Discussion, RfdState::Published, RfdState::Abandoned],
+            RfdState::Ideation => vec![RfdState::Prediscussion, RfdState::Discussion, RfdState::Published, RfdState::Abandoned],
+            RfdState::Discussion => vec![RfdState::Prediscussion, RfdState::Ideation, RfdState::Published, RfdState::Committed, RfdState::Abandoned],
+            RfdState::Published => vec![RfdState::Committed, RfdState::Abandoned],
+            RfdState::Committed => vec![RfdState::Abandoned],
+            RfdState::Abandoned => vec![RfdState::Prediscussion, RfdState::Ideation],
+        }
+    }
+
+    /// Returns if an RFD in this state can move to the given state.
+    /// Staying in the same state is always allowed.
+    pub fn can_transition_to(&self, to: RfdState) -> bool {
+        *self == to || self.next_states().contains(&to)
+    }
+
+    /// Returns if an RFD in this state can have an open pull request without
+    /// us moving it to `discussion`.
+    ///  - published: an already published RFD is being updated in a pull request.
+    ///  - discussion: it is in discussion.
+    ///  - ideation: it is in ideation.
+    pub fn allows_open_pull_request(&self) -> bool {
+        matches!(self, RfdState::Discussion | RfdState::Published | RfdState::Ideation)
+    }
+
+    /// Returns the GitHub label we put on pull requests for RFDs in this state, if any.
+    pub fn github_label(&self) -> Option<&'static str> {
+        match self {
+            RfdState::Discussion => Some(":thought_balloon: discussion"),
+            RfdState::Ideation => Some(":hatching_chick: ideation"),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for RfdState {
+    type Err = String;
+
+    fn from_str(state: &str) -> Result<Self, Self::Err> {
+        match state.to_lowercase().trim() {
+            "prediscussion" => Ok(RfdState::Prediscussion),
+            "ideation" => Ok(RfdState::Ideation),
+            "discussion" => Ok(RfdState::Discussion),
+            "published" => Ok(RfdState::Published),
+            "committed" => Ok(RfdState::Committed),
+            "abandoned" => Ok(RfdState::Abandoned),
+            s => Err(format!("`{}` is not a valid RFD state", s)),
+        }
+    }
+}
+
+impl fmt::Display for RfdState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RfdState::Prediscussion => "prediscussion",
+            RfdState::Ideation => "ideation",
+            RfdState::Discussion => "discussion",
+            RfdState::Published => "published",
+            RfdState::Committed => "committed",
+            RfdState::Abandoned => "abandoned",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::rfd_state::RfdState;
+
+    #[test]
+    fn test_rfd_state_round_trip() {
+        for state in RfdState::all() {
+            assert_eq!(state, RfdState::from_str(&state.to_string()).unwrap());
+        }
+
+        assert_eq!(RfdState::Discussion, RfdState::from_str(" Discussion ").unwrap());
+        assert!(RfdState::from_str("thing").is_err());
+        assert!(RfdState::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_rfd_state_transitions() {
+        assert!(RfdState::Prediscussion.can_transition_to(RfdState::Discussion));
+        assert!(RfdState::Discussion.can_transition_to(RfdState::Published));
+        assert!(RfdState::Published.can_transition_to(RfdState::Committed));
+        assert!(RfdState::Published.can_transition_to(RfdState::Published));
+        assert!(RfdState::Abandoned.can_transition_to(RfdState::Ideation));
+
+        assert!(!RfdState::Prediscussion.can_transition_to(RfdState::Committed));
+        assert!(!RfdState::Published.can_transition_to(RfdState::Prediscussion));
+        assert!(!RfdState::Committed.can_transition_to(RfdState::Discussion));
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::GSUITE_DOMAIN;
+
+/// Who is allowed to see an RFD.
+/// This is set in the RFD metadata with a `visibility:` line, for example:
+///  - `visibility: public`
+///  - `visibility: internal`
+///  - `visibility: restricted: exec, finance`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RfdVisibility {
+    /// The RFD can be shared outside of the company.
+    Public,
+
+    /// The RFD can be seen by everyone in the company.
+    Internal,
+
+    /// The RFD can only be seen by its authors and the members of the groups named in
+    /// its metadata. It is kept out of Airtable, Google Drive, the short URLs, the cio API
+    /// and the weekly digest for everyone else.
+    Restricted,
+}
+
+impl Default for RfdVisibility {
+    fn default() -> Self {
+        RfdVisibility::Internal
+    }
+}
+
+impl RfdVisibility {
+    /// Returns if the RFD is restricted to a set of groups.
+    pub fn is_restricted(&self) -> bool {
+        *self == RfdVisibility::Restricted
+    }
+
+    /// Parse the value of the `visibility:` line in the RFD metadata into the visibility
+    /// and the groups the RFD is restricted to.
+    /// Anything we do not understand is treated as restricted with no groups, so that only the
+    /// authors can see it, rather than leaking it.
+    pub fn parse_metadata(value: &str) -> (RfdVisibility, Vec<String>) {
+        let value = value.trim();
+        if value.is_empty() {
+            return (RfdVisibility::default(), Default::default());
+        }
+
+        let (visibility, groups) = match value.split_once(':') {
+            Some((v, g)) => (v, g),
+            None => (value, ""),
+        };
+
+        match visibility.parse::<RfdVisibility>() {
+            Ok(RfdVisibility::Restricted) => {
+                let mut groups: Vec<String> = groups
+                    .split(',')
+                    .map(|g| g.trim().trim_end_matches(&format!("@{}", GSUITE_DOMAIN)).to_lowercase())
+                    .filter(|g| !g.is_empty())
+                    .collect();
+                groups.sort();
+                groups.dedup();
+
+                (RfdVisibility::Restricted, groups)
+            }
+            Ok(v) => (v, Default::default()),
+            Err(e) => {
+                println!("[rfds] {}, treating the RFD as restricted", e);
+                (RfdVisibility::Restricted, Default::default())
+            }
+        }
+    }
+}
+
+impl FromStr for RfdVisibility {
+    type Err = String;
+
+    fn from_str(visibility: &str) -> Result<Self, Self::Err> {
+        match visibility.to_lowercase().trim() {
+            "public" => Ok(RfdVisibility::Public),
+            "internal" => Ok(RfdVisibility::Internal),
+            "restricted" => Ok(RfdVisibility::Restricted),
+            s => Err(format!("`{}` is not a valid RFD visibility", s)),
+        }
+    }
+}
+
+impl fmt::Display for RfdVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RfdVisibility::Public => "public",
+            RfdVisibility::Internal => "internal",
+            RfdVisibility::Restricted => "restricted",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rfd_visibility::RfdVisibility;
+
+    #[test]
+    fn test_rfd_visibility_parse_metadata() {
+        assert_eq!((RfdVisibility::Internal, vec![]), RfdVisibility::parse_metadata(""));
+        assert_eq!((RfdVisibility::Public, vec![]), RfdVisibility::parse_metadata(" Public "));
+        assert_eq!((RfdVisibility::Internal, vec![]), RfdVisibility::parse_metadata("internal: exec"));
+        assert_eq!(
+            (RfdVisibility::Restricted, vec!["exec".to_string(), "finance".to_string()]),
+            RfdVisibility::parse_metadata("restricted: Finance, exec@oxidecomputer.com, ,exec")
+        );
+        assert_eq!((RfdVisibility::Restricted, vec![]), RfdVisibility::parse_metadata("restricted"));
+        assert_eq!((RfdVisibility::Restricted, vec![]), RfdVisibility::parse_metadata("secret"));
+    }
+}
@@ -1,18 +1,29 @@
 use std::collections::BTreeMap;
 use std::str::from_utf8;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use comrak::{markdown_to_html, ComrakOptions};
 use csv::ReaderBuilder;
+use diesel::sql_types::{BigInt, Float4, Integer, Text, Varchar};
+use diesel::RunQueryDsl;
 use futures_util::TryStreamExt;
+use handlebars::Handlebars;
+use hubcaps::issues::{IssueListOptions, State};
 use hubcaps::repositories::Repository;
 use hubcaps::Github;
 use regex::Regex;
-use sendgrid_api::SendGrid;
+use schemars::JsonSchema;
+use sendgrid_api::{Content, Email, Message, Personalization, SendGrid};
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
 
+use crate::configs::Users;
 use crate::db::Database;
-use crate::models::{NewRFD, RFDs};
-use crate::utils::{authenticate_github_jwt, create_or_update_file_in_github_repo, github_org, DOMAIN};
+use crate::models::{NewRFD, NewRFDReference, NewRFDStateChange, RFDCommit, RFDCommits, RFDReference, RFDReferences, RFDStateChange, RFDStateChanges, RFDSubscribers, RFDSubscription, RFDs, RFD};
+use crate::rfd_state::RfdState;
+use crate::slack::{get_rfds_channel_post_url, post_to_channel};
+use crate::templates::TEMPLATE_RFD_DIGEST;
+use crate::utils::{authenticate_github_jwt, create_or_update_file_in_github_repo, github_org, DOMAIN, GSUITE_DOMAIN};
 
 /// Get the RFDs from the rfd GitHub repo.
 pub async fn get_rfds_from_repo(github: &Github) -> BTreeMap<i32, NewRFD> {
@@ -197,13 +208,322 @@ pub fn update_state(content: &str, state: &str, is_markdown: bool) -> String {
     content.replacen(&replacement, &format!("{}state: {}", pre, state.trim()), 1)
 }
 
+/// Get all the references an RFD makes to other RFDs and to GitHub issues, pull requests
+/// and repositories from its content.
+pub fn get_rfd_references(content: &str, number: i32) -> Vec<NewRFDReference> {
+    let mut references: BTreeMap<String, NewRFDReference> = Default::default();
+
+    // References to RFDs can be in the short form https://{number}.rfd.oxide.computer,
+    // the rendered form https://rfd.shared.oxide.computer/rfd/{number_string} or just written out
+    // as "RFD {number}".
+    let re = Regex::new(r"(?:https?://(?P<short>[0-9]+)\.rfd\.oxide\.computer)|(?:rfd\.shared\.oxide\.computer/rfd/(?P<rendered>[0-9]+))|(?:\bRFD[ -]?(?P<text>[0-9]{1,4})\b)").unwrap();
+    for c in re.captures_iter(content) {
+        let n = c.name("short").or_else(|| c.name("rendered")).or_else(|| c.name("text")).unwrap().as_str();
+        let target_rfd_number = n.trim_start_matches('0').parse::<i32>().unwrap_or_default();
+        // Ignore the RFD referencing itself, this is usually the title.
+        if target_rfd_number == 0 || target_rfd_number == number {
+            continue;
+        }
+
+        let link = NewRFD::generate_rendered_link(&NewRFD::generate_number_string(target_rfd_number));
+        references.insert(
+            link.to_string(),
+            NewRFDReference {
+                rfd_number: number,
+                reference_type: "rfd".to_string(),
+                target_rfd_number,
+                target: format!("RFD {}", target_rfd_number),
+                link,
+            },
+        );
+    }
+
+    let re = Regex::new(r"https://github\.com/(?P<owner>[A-Za-z0-9_.-]+)/(?P<repo>[A-Za-z0-9_.-]+)(?:/(?P<kind>issues|pull)/(?P<num>[0-9]+))?").unwrap();
+    for c in re.captures_iter(content) {
+        let owner = &c["owner"];
+        let repo = c["repo"].trim_end_matches('.').trim_end_matches(".git");
+        // Links into the rfd repo itself are discussion links or links to files, not
+        // references.
+        if repo.is_empty() || repo == "rfd" {
+            continue;
+        }
+
+        let (reference_type, target, link) = match c.name("kind") {
+            Some(kind) => (
+                if kind.as_str() == "pull" { "pull" } else { "issue" },
+                format!("{}/{}#{}", owner, repo, &c["num"]),
+                format!("https://github.com/{}/{}/{}/{}", owner, repo, kind.as_str(), &c["num"]),
+            ),
+            None => ("repo", format!("{}/{}", owner, repo), format!("https://github.com/{}/{}", owner, repo)),
+        };
+
+        references.insert(
+            link.to_string(),
+            NewRFDReference {
+                rfd_number: number,
+                reference_type: reference_type.to_string(),
+                target_rfd_number: 0,
+                target,
+                link,
+            },
+        );
+    }
+
+    references.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Update the references an RFD makes in the database.
+/// Any references that are no longer in the content of the RFD are removed.
+pub async fn refresh_rfd_references(db: &Database, rfd: &RFD) {
+    let mut existing: BTreeMap<String, RFDReference> = Default::default();
+    for reference in RFDReferences::get_for_rfd(db, rfd.number) {
+        existing.insert(reference.link.to_string(), reference);
+    }
+
+    for reference in get_rfd_references(&rfd.content, rfd.number) {
+        existing.remove(&reference.link);
+
+        if !rfd.is_restricted() {
+            reference.upsert(db).await;
+            continue;
+        }
+
+        // Keep the references made by restricted RFDs out of Airtable.
+        let mut r = reference.upsert_in_db(db);
+        if !r.airtable_record_id.is_empty() {
+            r.delete_from_airtable().await;
+            r.airtable_record_id = String::new();
+            r.update_in_db(db);
+        }
+    }
+
+    // Remove the references that no longer exist.
+    for (_, reference) in existing {
+        reference.delete(db).await;
+    }
+}
+
+/// Get the references to RFDs that are abandoned or do not exist.
+/// References made by RFDs that are abandoned or restricted themselves are ignored, and so
+/// are references to restricted RFDs, since these go out to everyone in the weekly digest.
+pub fn get_broken_rfd_references(db: &Database) -> Vec<String> {
+    let mut rfds: BTreeMap<i32, RFD> = Default::default();
+    for rfd in RFDs::get_from_db(db) {
+        rfds.insert(rfd.number, rfd);
+    }
+
+    let mut broken: Vec<String> = Default::default();
+    for reference in RFDReferences::get_from_db(db) {
+        if reference.reference_type != "rfd" {
+            continue;
+        }
+
+        let from = match rfds.get(&reference.rfd_number) {
+            Some(r) => r,
+            None => continue,
+        };
+        if from.rfd_state() == RfdState::Abandoned || from.is_restricted() {
+            continue;
+        }
+
+        match rfds.get(&reference.target_rfd_number) {
+            Some(target) => {
+                if target.rfd_state() == RfdState::Abandoned && !target.is_restricted() {
+                    broken.push(format!("{} references {}, which is abandoned", from.name, target.name));
+                }
+            }
+            None => broken.push(format!("{} references {}, which does not exist", from.name, reference.target)),
+        }
+    }
+
+    broken
+}
+
+/// Get the email addresses out of the authors line of an RFD.
+/// Authors are in the form `Name <email>, Name <email>`, authors without an email are skipped.
+pub fn get_author_emails(authors: &str) -> Vec<String> {
+    let re = Regex::new(r"<(?P<email>[^<>@\s]+@[^<>\s]+)>").unwrap();
+
+    re.captures_iter(authors).map(|c| c["email"].trim().to_lowercase()).collect()
+}
+
+/// Record that an RFD moved from the state `from` to the state it is in now and let
+/// everyone who cares know about it.
+/// If the state did not change, this does nothing and returns `None`.
+pub async fn record_rfd_state_change(db: &Database, rfd: &RFD, from: &str, branch: &str, changed_by: &str) -> Option<RFDStateChange> {
+    let from = from.trim().to_lowercase();
+    let to = rfd.state.trim().to_lowercase();
+    if from == to {
+        return None;
+    }
+
+    // If we have never seen this RFD before, any state it starts in is fine.
+    let valid_transition = match from.parse::<RfdState>() {
+        Ok(f) => f.can_transition_to(rfd.rfd_state()),
+        Err(_) => from.is_empty(),
+    };
+    if !valid_transition {
+        println!("[rfd] RFD {} moved from state `{}` to `{}`, which the RFD lifecycle does not allow", rfd.number_string, from, to);
+    }
+
+    let change = NewRFDStateChange {
+        rfd_number: rfd.number,
+        rfd_name: rfd.name.to_string(),
+        from_state: from,
+        to_state: to,
+        valid_transition,
+        branch: branch.to_string(),
+        sha: rfd.sha.to_string(),
+        changed_by: changed_by.to_string(),
+        changed_at: Utc::now(),
+    };
+    // Keep the history of restricted RFDs out of Airtable.
+    let change = if rfd.is_restricted() { change.create_in_db(db) } else { change.create(db).await };
+
+    notify_rfd_state_change(db, rfd, &change).await;
+
+    Some(change)
+}
+
+/// Get the groups every user is in, by username, so we know who can see restricted RFDs.
+pub fn get_user_groups(db: &Database) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = Default::default();
+    for user in Users::get_from_db(db) {
+        groups.insert(user.username.to_string(), user.groups.clone());
+    }
+
+    groups
+}
+
+/// Get the groups of the user with the given email, from the map returned by `get_user_groups`.
+/// Anyone outside of our domains is not in any groups.
+pub fn get_groups_for_email(groups: &BTreeMap<String, Vec<String>>, email: &str) -> Vec<String> {
+    if let Some((username, domain)) = email.trim().to_lowercase().split_once('@') {
+        if domain == DOMAIN || domain == GSUITE_DOMAIN {
+            return groups.get(username).cloned().unwrap_or_default();
+        }
+    }
+
+    Default::default()
+}
+
+/// Get the subscribers who should hear about an RFD changing state, other than its
+/// authors: the ones who get state changes for it and are allowed to see it.
+pub fn get_rfd_state_change_subscribers(db: &Database, rfd: &RFD) -> Vec<String> {
+    let authors = get_author_emails(&rfd.authors);
+    let mut recipients: Vec<String> = Default::default();
+
+    let groups = get_user_groups(db);
+    for subscriber in RFDSubscribers::get_from_db(db) {
+        let email = subscriber.email.trim().to_lowercase();
+        if email.is_empty() || authors.contains(&email) || recipients.contains(&email) {
+            continue;
+        }
+
+        let wants_it = match subscriber.get_subscription() {
+            RFDSubscription::All | RFDSubscription::StateChanges => true,
+            // The authors were skipped above, they are notified separately.
+            RFDSubscription::Mine => false,
+        };
+        if wants_it && rfd.can_be_seen_by(&email, &get_groups_for_email(&groups, &email)) {
+            recipients.push(email);
+        }
+    }
+
+    recipients
+}
+
+/// Let the #rfds channel, the authors of an RFD and its subscribers know that it changed state.
+/// Only the authors and the subscribers who can see it are told about restricted RFDs.
+pub async fn notify_rfd_state_change(db: &Database, rfd: &RFD, change: &RFDStateChange) {
+    let mut context = format!("moved from *{}* to *{}*", change.from_state, change.to_state);
+    if change.from_state.is_empty() {
+        context = format!("created in *{}*", change.to_state);
+    }
+    if !change.changed_by.is_empty() {
+        context += &format!(" by @{}", change.changed_by);
+    }
+    if !change.valid_transition {
+        context += " (this is not a transition the RFD lifecycle allows)";
+    }
+
+    // Post to Slack.
+    if !rfd.is_restricted() {
+        post_to_channel(
+            get_rfds_channel_post_url(),
+            json!(FormattedMessage {
+                channel: Default::default(),
+                attachments: Default::default(),
+                blocks: vec![
+                    MessageBlock {
+                        block_type: MessageBlockType::Section,
+                        text: Some(MessageBlockText {
+                            text_type: MessageType::Markdown,
+                            text: rfd.as_slack_msg(),
+                        }),
+                        elements: Default::default(),
+                        accessory: Default::default(),
+                        block_id: Default::default(),
+                        fields: Default::default(),
+                    },
+                    MessageBlock {
+                        block_type: MessageBlockType::Context,
+                        elements: vec![MessageBlockText {
+                            text_type: MessageType::Markdown,
+                            text: context,
+                        }],
+                        text: Default::default(),
+                        accessory: Default::default(),
+                        block_id: Default::default(),
+                        fields: Default::default(),
+                    }
+                ],
+            }),
+        )
+        .await;
+    }
+
+    // Email the authors and the subscribers.
+    // The subscribers are bcc'd so they don't see each other's addresses.
+    let from = format!("rfds@{}", DOMAIN);
+    let mut authors = get_author_emails(&rfd.authors);
+    let subscribers = get_rfd_state_change_subscribers(db, rfd);
+    if authors.is_empty() && subscribers.is_empty() {
+        return;
+    }
+    if authors.is_empty() {
+        authors = vec![from.to_string()];
+    }
+
+    let mut message = format!("{} has moved from `{}` to `{}`.", rfd.name, change.from_state, change.to_state);
+    if change.from_state.is_empty() {
+        message = format!("{} was created in the state `{}`.", rfd.name, change.to_state);
+    }
+    message += &format!("\n\ngithub: {}\nrendered: {}", rfd.short_link, rfd.rendered_link);
+    if !rfd.discussion.is_empty() {
+        message += &format!("\ndiscussion: {}", rfd.discussion);
+    }
+
+    let sendgrid_client = SendGrid::new_from_env();
+    sendgrid_client
+        .send_mail(format!("{} is now in {}", rfd.name, change.to_state), message, authors, vec![], subscribers, from)
+        .await;
+}
+
 // Sync the rfds with our database.
 pub async fn refresh_db_rfds(db: &Database, github: &Github) {
     let rfds = get_rfds_from_repo(github).await;
 
     // Sync rfds.
     for (_, rfd) in rfds {
-        let mut new_rfd = rfd.upsert(db).await;
+        // Get the old state before we update the RFD, so we can tell if it changed.
+        let old_state = match RFD::get_from_db(db, rfd.number) {
+            Some(o) => o.state,
+            None => String::new(),
+        };
+
+        // We don't know who can see the RFD until we expand it, so only update the database for now.
+        let mut new_rfd = rfd.upsert_in_db(db);
 
         // Expand the fields in the RFD.
         new_rfd.expand(github).await;
@@ -213,50 +533,416 @@ pub async fn refresh_db_rfds(db: &Database, github: &Github) {
 
         // Update the RFD again.
         // We do this so the expand functions are only one place.
-        new_rfd.update(db).await;
+        new_rfd = new_rfd.update_with_visibility(db).await;
+
+        // Catch any state changes we missed from webhooks.
+        record_rfd_state_change(db, &new_rfd, &old_state, "", "").await;
+
+        // Update the references to other RFDs and GitHub.
+        refresh_rfd_references(db, &new_rfd).await;
+    }
+}
+
+/// The maximum number of results we return when searching RFDs.
+pub static RFD_SEARCH_LIMIT: i64 = 25;
+
+/// A ranked result from searching the RFDs.
+#[derive(Debug, Clone, QueryableByName, JsonSchema, Deserialize, Serialize)]
+pub struct RFDSearchResult {
+    #[sql_type = "Integer"]
+    pub number: i32,
+    #[sql_type = "Varchar"]
+    pub number_string: String,
+    #[sql_type = "Varchar"]
+    pub title: String,
+    #[sql_type = "Varchar"]
+    pub name: String,
+    #[sql_type = "Varchar"]
+    pub state: String,
+    #[sql_type = "Varchar"]
+    pub authors: String,
+    #[sql_type = "Varchar"]
+    pub short_link: String,
+    #[sql_type = "Varchar"]
+    pub rendered_link: String,
+    #[sql_type = "Varchar"]
+    pub discussion: String,
+    /// rank is how well the RFD matched the query, higher is better
+    #[sql_type = "Float4"]
+    pub rank: f32,
+    /// snippet is the parts of the content that matched, with the matches wrapped in `<mark>`
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+/// What ts_headline wraps the matches in the snippet with. These are characters from the
+/// private use area, so they can't clash with the content, and we can escape the snippet
+/// before we swap them for `<mark>` tags.
+static RFD_SEARCH_MATCH_START: &str = "\u{e000}";
+static RFD_SEARCH_MATCH_END: &str = "\u{e001}";
+
+/// Get the RFD number out of a search query like `123`, `0123` or `RFD 123`, if that is all it is.
+pub fn parse_rfd_search_number(query: &str) -> Option<i32> {
+    let query = query.trim().to_lowercase();
+    let number = query.trim_start_matches("rfd").trim().trim_start_matches('#');
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    number.parse().ok()
+}
+
+/// Turn the snippet from ts_headline into HTML: escape the content, mark the matches with
+/// `<mark>` and collapse the whitespace, so the markdown and asciidoc line breaks don't show.
+pub fn format_rfd_search_snippet(snippet: &str) -> String {
+    let escaped = snippet.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+    let marked = escaped.replace(RFD_SEARCH_MATCH_START, "<mark>").replace(RFD_SEARCH_MATCH_END, "</mark>");
+
+    marked.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Rank the results of a search: an RFD whose number is the query comes first, then the
+/// best matches, then the lowest numbers. Only the first `RFD_SEARCH_LIMIT` results are kept.
+pub fn rank_rfd_search_results(query: &str, mut results: Vec<RFDSearchResult>) -> Vec<RFDSearchResult> {
+    let number = parse_rfd_search_number(query);
+
+    results.sort_by(|a, b| {
+        let a_is_number = Some(a.number) == number;
+        let b_is_number = Some(b.number) == number;
+        b_is_number
+            .cmp(&a_is_number)
+            .then(b.rank.partial_cmp(&a.rank).unwrap_or(std::cmp::Ordering::Equal))
+            .then(a.number.cmp(&b.number))
+    });
+    results.truncate(RFD_SEARCH_LIMIT as usize);
+
+    results
+}
+
+/// Search the RFDs in the database by their title, authors, state and content.
+/// The query supports the same syntax as web search engines, ie. `"quoted phrases"`, `or` and `-excluded`.
+/// A query that is just a number, ie. `123` or `RFD 123`, also finds the RFD with that number.
+///
+/// The search index is an expression index on the `rfds` table (see the `rfds_search` migration),
+/// so it is kept up to date every time an RFD is upserted by `refresh_db_rfds` or on a push to the
+/// rfd repo.
+pub fn search_rfds(db: &Database, query: &str) -> Result<Vec<RFDSearchResult>, String> {
+    if query.trim().is_empty() {
+        return Ok(Default::default());
+    }
+
+    let results = diesel::sql_query(format!(
+        r#"SELECT number, number_string, title, name, state, authors, short_link, rendered_link, discussion,
+            ts_rank(rfd_search_vector(title, authors, state, content), query) AS rank,
+            ts_headline('english', content, query, 'StartSel={}, StopSel={}, MaxFragments=3, FragmentDelimiter=" ... "') AS snippet
+        FROM rfds, websearch_to_tsquery('english', $1) query
+        WHERE (rfd_search_vector(title, authors, state, content) @@ query OR number = $2) AND visibility <> 'restricted'
+        ORDER BY number = $2 DESC, rank DESC, number ASC
+        LIMIT $3"#,
+        RFD_SEARCH_MATCH_START, RFD_SEARCH_MATCH_END
+    ))
+    .bind::<Text, _>(query.trim())
+    .bind::<Integer, _>(parse_rfd_search_number(query).unwrap_or(-1))
+    .bind::<BigInt, _>(RFD_SEARCH_LIMIT)
+    .load::<RFDSearchResult>(&db.conn())
+    .map_err(|e| format!("searching rfds for `{}` failed: {}", query, e))?;
+
+    Ok(rank_rfd_search_results(
+        query,
+        results
+            .into_iter()
+            .map(|mut r| {
+                r.snippet = format_rfd_search_snippet(&r.snippet);
+                r
+            })
+            .collect(),
+    ))
+}
+
+/// The maximum number of lines of a diff we keep for a single commit.
+pub static RFD_DIFF_MAX_LINES: usize = 40;
+
+/// The maximum number of discussions we list as the most commented in the weekly digest.
+pub static RFD_DIGEST_MOST_COMMENTED_LIMIT: usize = 5;
+
+/// Get a short unified diff between the old and the new content of an RFD.
+/// The diff is cut off after `RFD_DIFF_MAX_LINES` lines.
+pub fn get_short_rfd_diff(old: &str, new: &str) -> String {
+    let patch = diffy::create_patch(old, new).to_string();
+
+    // Skip the `---` and `+++` header lines, we know what file this is.
+    let lines: Vec<&str> = patch.lines().skip(2).collect();
+    if lines.len() <= RFD_DIFF_MAX_LINES {
+        return lines.join("\n");
+    }
+
+    format!("{}\n... ({} more lines)", lines[..RFD_DIFF_MAX_LINES].join("\n"), lines.len() - RFD_DIFF_MAX_LINES)
+}
+
+/// The parts of an RFD we need for the weekly digest.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RFDDigestRFD {
+    pub number: i32,
+    pub name: String,
+    pub state: String,
+    pub short_link: String,
+    pub discussion: String,
+    #[serde(skip)]
+    pub author_emails: Vec<String>,
+    #[serde(skip)]
+    pub is_restricted: bool,
+    #[serde(skip)]
+    pub visibility_groups: Vec<String>,
+}
+
+impl RFDDigestRFD {
+    /// Returns if someone with the given email, who is a member of the given groups, can see the RFD.
+    pub fn can_be_seen_by(&self, email: &str, groups: &[String]) -> bool {
+        !self.is_restricted || self.author_emails.contains(&email.trim().to_lowercase()) || self.visibility_groups.iter().any(|g| groups.contains(g))
+    }
+}
+
+impl From<&RFD> for RFDDigestRFD {
+    fn from(rfd: &RFD) -> Self {
+        RFDDigestRFD {
+            number: rfd.number,
+            name: rfd.name.to_string(),
+            state: rfd.state.to_string(),
+            short_link: rfd.short_link.to_string(),
+            discussion: rfd.discussion.to_string(),
+            author_emails: get_author_emails(&rfd.authors),
+            is_restricted: rfd.is_restricted(),
+            visibility_groups: rfd.visibility_groups.clone(),
+        }
+    }
+}
+
+/// A change in state for the weekly digest.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RFDDigestStateChange {
+    pub rfd: RFDDigestRFD,
+    pub from_state: String,
+    pub to_state: String,
+    pub changed_by: String,
+}
+
+/// A discussion pull request and how many comments it has, for the weekly digest.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RFDDigestDiscussion {
+    pub rfd: RFDDigestRFD,
+    pub comments: u64,
+}
+
+/// The commits to an RFD for the weekly digest.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RFDDigestChanges {
+    pub rfd: RFDDigestRFD,
+    pub commits: Vec<RFDCommit>,
+}
+
+/// Everything that happened to the RFDs in a week.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RFDDigest {
+    pub week: String,
+    pub new_rfds: Vec<RFDDigestRFD>,
+    pub state_changes: Vec<RFDDigestStateChange>,
+    pub new_discussions: Vec<RFDDigestRFD>,
+    pub most_commented: Vec<RFDDigestDiscussion>,
+    pub changes: Vec<RFDDigestChanges>,
+    pub broken_references: Vec<String>,
+}
+
+impl RFDDigest {
+    /// Returns if nothing happened in the digest.
+    pub fn is_empty(&self) -> bool {
+        self.new_rfds.is_empty() && self.state_changes.is_empty() && self.new_discussions.is_empty() && self.most_commented.is_empty() && self.changes.is_empty() && self.broken_references.is_empty()
+    }
+
+    /// Get the parts of the digest a subscriber with the email `email`, who is a member of
+    /// `groups`, cares about and is allowed to see.
+    pub fn for_subscription(&self, subscription: RFDSubscription, email: &str, groups: &[String]) -> RFDDigest {
+        let mut digest = self.clone();
+
+        // Leave out the restricted RFDs the subscriber can't see.
+        let can_see = |rfd: &RFDDigestRFD| rfd.can_be_seen_by(email, groups);
+        digest.new_rfds.retain(|r| can_see(r));
+        digest.state_changes.retain(|c| can_see(&c.rfd));
+        digest.new_discussions.retain(|r| can_see(r));
+        digest.most_commented.retain(|d| can_see(&d.rfd));
+        digest.changes.retain(|c| can_see(&c.rfd));
+
+        match subscription {
+            RFDSubscription::All => (),
+            RFDSubscription::Mine => {
+                let email = email.trim().to_lowercase();
+                let is_mine = |rfd: &RFDDigestRFD| rfd.author_emails.contains(&email);
+
+                digest.new_rfds.retain(|r| is_mine(r));
+                digest.state_changes.retain(|c| is_mine(&c.rfd));
+                digest.new_discussions.retain(|r| is_mine(r));
+                digest.most_commented.retain(|d| is_mine(&d.rfd));
+                digest.changes.retain(|c| is_mine(&c.rfd));
+                // We can't tell whose these are, so leave them out.
+                digest.broken_references = Default::default();
+            }
+            RFDSubscription::StateChanges => {
+                digest.most_commented = Default::default();
+                digest.changes = Default::default();
+                digest.broken_references = Default::default();
+            }
+        }
+
+        digest
+    }
+
+    /// Render the digest as HTML.
+    pub fn as_html(&self) -> String {
+        let handlebars = Handlebars::new();
+        handlebars.render_template(TEMPLATE_RFD_DIGEST, self).unwrap()
     }
 }
 
-/// Create a changelog email for the RFDs.
+/// Get everything that happened to the RFDs since a point in time.
+pub async fn get_rfd_digest(db: &Database, github: &Github, since: DateTime<Utc>) -> RFDDigest {
+    let mut rfds: BTreeMap<i32, RFD> = Default::default();
+    for rfd in RFDs::get_from_db(db) {
+        rfds.insert(rfd.number, rfd);
+    }
+
+    let mut digest = RFDDigest {
+        week: format!("from {} to {}", since.format("%m-%d-%Y"), Utc::now().format("%m-%d-%Y")),
+        ..Default::default()
+    };
+
+    // Get the new RFDs and the state changes.
+    for change in RFDStateChanges::get_since(db, since) {
+        let rfd = match rfds.get(&change.rfd_number) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        if change.from_state.is_empty() {
+            digest.new_rfds.push(rfd.into());
+            continue;
+        }
+
+        if change.to_state == RfdState::Discussion.to_string() {
+            digest.new_discussions.push(rfd.into());
+        }
+
+        digest.state_changes.push(RFDDigestStateChange {
+            rfd: rfd.into(),
+            from_state: change.from_state,
+            to_state: change.to_state,
+            changed_by: change.changed_by,
+        });
+    }
+
+    // Get all the commits for the week at once and group them by RFD.
+    let mut commits: BTreeMap<i32, Vec<RFDCommit>> = Default::default();
+    for commit in RFDCommits::get_since(db, since) {
+        commits.entry(commit.rfd_number).or_insert_with(Vec::new).push(commit);
+    }
+    for (number, commits) in commits {
+        if let Some(rfd) = rfds.get(&number) {
+            digest.changes.push(RFDDigestChanges { rfd: rfd.into(), commits });
+        }
+    }
+
+    // Get the discussions that were active this week, sorted by how many comments they have.
+    let issues = github
+        .repo(github_org(), "rfd")
+        .issues()
+        .list(
+            &IssueListOptions::builder()
+                .per_page(100)
+                .state(State::Open)
+                .labels(vec![RfdState::Discussion.github_label().unwrap()])
+                .build(),
+        )
+        .await
+        .unwrap();
+    for issue in issues {
+        let updated_at = DateTime::parse_from_rfc3339(&issue.updated_at).unwrap().with_timezone(&Utc);
+        if updated_at < since || issue.comments == 0 {
+            continue;
+        }
+
+        if let Some(rfd) = rfds.values().find(|r| r.discussion == issue.html_url) {
+            digest.most_commented.push(RFDDigestDiscussion {
+                rfd: rfd.into(),
+                comments: issue.comments,
+            });
+        }
+    }
+    digest.most_commented.sort_by(|a, b| b.comments.cmp(&a.comments));
+    digest.most_commented.truncate(RFD_DIGEST_MOST_COMMENTED_LIMIT);
+
+    // Flag any references to RFDs that are abandoned or do not exist.
+    digest.broken_references = get_broken_rfd_references(db);
+
+    digest
+}
+
+/// Send the weekly digest of changes to the RFDs.
+/// Everyone in the `rfd_subscribers` table gets the parts they are subscribed to, if nobody
+/// is subscribed we send the whole thing to all@.
 pub async fn send_rfd_changelog() {
     // Initialize our database.
     let db = Database::new();
     let github = authenticate_github_jwt();
     let seven_days_ago = Utc::now() - Duration::days(7);
-    let week_format = format!("from {} to {}", seven_days_ago.format("%m-%d-%Y"), Utc::now().format("%m-%d-%Y"));
 
-    let mut changelog = format!("Changes to RFDs for the week {}:\n", week_format);
+    let digest = get_rfd_digest(&db, &github, seven_days_ago).await;
 
-    // Iterate over the RFDs.
-    let rfds = RFDs::get_from_db(&db);
-    for rfd in rfds {
-        let changes = rfd.get_weekly_changelog(&github, seven_days_ago).await;
-        if !changes.is_empty() {
-            changelog += &format!("\n{} {}\n{}", rfd.name, rfd.short_link, changes);
-        }
+    // Get the groups everyone is in, so we know who can see restricted RFDs.
+    let groups = get_user_groups(&db);
+
+    let mut recipients: Vec<(String, RFDDigest)> = Default::default();
+    let subscribers = RFDSubscribers::get_from_db(&db);
+    if subscribers.0.is_empty() {
+        let all = format!("all@{}", DOMAIN);
+        let d = digest.for_subscription(RFDSubscription::All, &all, &[]);
+        recipients.push((all, d));
+    }
+    for subscriber in subscribers {
+        let subscriber_groups = get_groups_for_email(&groups, &subscriber.email);
+        recipients.push((
+            subscriber.email.to_string(),
+            digest.for_subscription(subscriber.get_subscription(), &subscriber.email, &subscriber_groups),
+        ));
     }
 
-    // Initialize the SendGrid clVient.
+    // Initialize the SendGrid client.
     let sendgrid_client = SendGrid::new_from_env();
+    let from = format!("rfds@{}", DOMAIN);
 
-    // Send the message.
-    sendgrid_client
-        .send_mail(
-            format!("RFD changelog for the week from {}", week_format),
-            changelog,
-            vec![format!("all@{}", DOMAIN)],
-            vec![],
-            vec![],
-            format!("rfds@{}", DOMAIN),
-        )
-        .await;
+    for (email, d) in recipients {
+        if d.is_empty() {
+            continue;
+        }
+
+        // Send the message.
+        sendgrid_client
+            .send_raw_mail(
+                Message::new()
+                    .set_from(Email::new().set_email(&from).set_name(&from))
+                    .set_subject(&format!("RFD digest for the week {}", d.week))
+                    .add_content(Content::new().set_content_type("text/html").set_value(&d.as_html()))
+                    .add_personalization(Personalization::new().add_to(Email::new().set_email(&email).set_name(&email))),
+            )
+            .await;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::db::Database;
     use crate::models::{NewRFD, RFDs};
-    use crate::rfds::{clean_rfd_html_links, refresh_db_rfds, send_rfd_changelog, update_discussion_link, update_state};
+    use crate::rfds::{
+        clean_rfd_html_links, format_rfd_search_snippet, get_author_emails, get_rfd_references, get_short_rfd_diff, parse_rfd_search_number, rank_rfd_search_results, refresh_db_rfds, search_rfds,
+        send_rfd_changelog, update_discussion_link, update_state, RFDSearchResult, RFD_DIFF_MAX_LINES, RFD_SEARCH_LIMIT,
+    };
     use crate::utils::authenticate_github_jwt;
 
     #[ignore]
@@ -268,8 +954,70 @@ mod tests {
         let github = authenticate_github_jwt();
         refresh_db_rfds(&db, &github).await;
 
-        // Update rfds in airtable.
-        RFDs::get_from_db(&db).update_airtable().await;
+        // Update rfds in airtable, this removes any restricted RFDs.
+        RFDs::get_visible_from_db(&db).update_airtable().await;
+    }
+
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rfds_search() {
+        // Initialize our database.
+        let db = Database::new();
+
+        let results = search_rfds(&db, "rfd process").unwrap();
+        assert!(!results.is_empty());
+        assert!(search_rfds(&db, " ").unwrap().is_empty());
+    }
+
+    fn search_result(number: i32, rank: f32) -> RFDSearchResult {
+        RFDSearchResult {
+            number,
+            number_string: format!("{:04}", number),
+            title: Default::default(),
+            name: Default::default(),
+            state: Default::default(),
+            authors: Default::default(),
+            short_link: Default::default(),
+            rendered_link: Default::default(),
+            discussion: Default::default(),
+            rank,
+            snippet: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rfd_search_number() {
+        assert_eq!(parse_rfd_search_number("123"), Some(123));
+        assert_eq!(parse_rfd_search_number(" 0042 "), Some(42));
+        assert_eq!(parse_rfd_search_number("RFD 1"), Some(1));
+        assert_eq!(parse_rfd_search_number("rfd #7"), Some(7));
+        assert_eq!(parse_rfd_search_number("rfd process"), None);
+        assert_eq!(parse_rfd_search_number("12 things"), None);
+        assert_eq!(parse_rfd_search_number("rfd"), None);
+    }
+
+    #[test]
+    fn test_rank_rfd_search_results() {
+        let results = vec![search_result(3, 0.1), search_result(12, 0.9), search_result(2, 0.1), search_result(40, 0.0)];
+
+        let ranked: Vec<i32> = rank_rfd_search_results("networking", results.clone()).iter().map(|r| r.number).collect();
+        assert_eq!(ranked, vec![12, 2, 3, 40]);
+
+        // The RFD with the number in the query comes first, even if it matched the worst.
+        let ranked: Vec<i32> = rank_rfd_search_results("RFD 40", results).iter().map(|r| r.number).collect();
+        assert_eq!(ranked, vec![40, 12, 2, 3]);
+
+        let many = (0..RFD_SEARCH_LIMIT as i32 + 5).map(|n| search_result(n, 0.5)).collect();
+        assert_eq!(rank_rfd_search_results("networking", many).len(), RFD_SEARCH_LIMIT as usize);
+    }
+
+    #[test]
+    fn test_format_rfd_search_snippet() {
+        let snippet = "the <b>rack</b> \u{e000}networking\u{e001}\n\n  stack ... use \u{e000}networking\u{e001} & more";
+        assert_eq!(
+            format_rfd_search_snippet(snippet),
+            "the &lt;b&gt;rack&lt;/b&gt; <mark>networking</mark> stack ... use <mark>networking</mark> &amp; more"
+        );
     }
 
     #[ignore]
@@ -349,6 +1097,60 @@ sdf"#;
         assert_eq!(expected, authors);
     }
 
+    #[test]
+    fn test_get_rfd_references() {
+        let content = r#"= RFD 32 Things
+https://3.rfd.oxide.computer
+https://rfd.shared.oxide.computer/rfd/0041#-some-link
+As we said in RFD 543, and again in RFD 3.
+https://github.com/oxidecomputer/cio/issues/12
+https://github.com/oxidecomputer/omicron/pull/7.
+https://github.com/oxidecomputer/propolis
+https://github.com/oxidecomputer/rfd/pull/32"#;
+
+        let references = get_rfd_references(&content, 32);
+        let mut targets: Vec<String> = references.iter().map(|r| format!("{} {}", r.reference_type, r.target)).collect();
+        targets.sort();
+
+        assert_eq!(
+            vec![
+                "issue oxidecomputer/cio#12".to_string(),
+                "pull oxidecomputer/omicron#7".to_string(),
+                "repo oxidecomputer/propolis".to_string(),
+                "rfd RFD 3".to_string(),
+                "rfd RFD 41".to_string(),
+                "rfd RFD 543".to_string(),
+            ],
+            targets
+        );
+
+        let rfd3 = references.iter().find(|r| r.target_rfd_number == 3).unwrap();
+        assert_eq!("https://rfd.shared.oxide.computer/rfd/0003", rfd3.link);
+    }
+
+    #[test]
+    fn test_get_short_rfd_diff() {
+        let old = "one\ntwo\nthree\n";
+        let new = "one\n2\nthree\n";
+        assert_eq!("@@ -1,3 +1,3 @@\n one\n-two\n+2\n three", get_short_rfd_diff(old, new));
+
+        assert_eq!("", get_short_rfd_diff(old, old));
+
+        let long: String = (0..100).map(|i| format!("{}\n", i)).collect();
+        let diff = get_short_rfd_diff("", &long);
+        assert_eq!(RFD_DIFF_MAX_LINES + 1, diff.lines().count());
+        assert!(diff.ends_with("... (61 more lines)"));
+    }
+
+    #[test]
+    fn test_get_author_emails() {
+        let mut emails = get_author_emails("things <Things@email.com>, joe <joe@email.com>");
+        assert_eq!(vec!["things@email.com".to_string(), "joe@email.com".to_string()], emails);
+
+        emails = get_author_emails("things, joe");
+        assert!(emails.is_empty());
+    }
+
     #[test]
     fn test_get_state() {
         let mut content = r#"sdfsdf
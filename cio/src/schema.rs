@@ -1,3 +1,43 @@
+table! {
+    applicant_deletions (id) {
+        id -> Int4,
+        applicant_email_hash -> Varchar,
+        sheet_id -> Varchar,
+        role -> Varchar,
+        applicant_status -> Varchar,
+        submitted_time -> Nullable<Timestamptz>,
+        action -> Varchar,
+        reason -> Varchar,
+        requested_by -> Varchar,
+        removed -> Array<Text>,
+        errors -> Array<Text>,
+        completed_time -> Timestamptz,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    applicant_interview_schedules (id) {
+        id -> Int4,
+        token -> Varchar,
+        applicant_email -> Varchar,
+        applicant_name -> Varchar,
+        sheet_id -> Varchar,
+        role -> Varchar,
+        panel -> Varchar,
+        status -> Varchar,
+        conference -> Varchar,
+        created_time -> Timestamptz,
+        expires_time -> Timestamptz,
+        scheduled_start_time -> Nullable<Timestamptz>,
+        scheduled_end_time -> Nullable<Timestamptz>,
+        interviewers -> Array<Text>,
+        google_event_ids -> Array<Text>,
+        meeting_links -> Array<Text>,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     applicant_interviews (id) {
         id -> Int4,
@@ -13,6 +53,24 @@ table! {
     }
 }
 
+table! {
+    applicant_review_assignments (id) {
+        id -> Int4,
+        reviewer -> Varchar,
+        applicant_email -> Varchar,
+        applicant_name -> Varchar,
+        role -> Varchar,
+        sheet_id -> Varchar,
+        status -> Varchar,
+        assigned_time -> Timestamptz,
+        due_time -> Timestamptz,
+        completed_time -> Nullable<Timestamptz>,
+        reminders_sent -> Int4,
+        last_reminder_time -> Nullable<Timestamptz>,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     applicant_reviewers (id) {
         id -> Int4,
@@ -24,6 +82,36 @@ table! {
         pass -> Int4,
         no -> Int4,
         not_applicable -> Int4,
+        open_assignments -> Int4,
+        overdue_assignments -> Int4,
+        out_of_office_until -> Nullable<Timestamptz>,
+        legacy_evaluations -> Int4,
+        legacy_emphatic_yes -> Int4,
+        legacy_yes -> Int4,
+        legacy_pass -> Int4,
+        legacy_no -> Int4,
+        legacy_not_applicable -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    applicant_scores (id) {
+        id -> Int4,
+        reviewer -> Varchar,
+        applicant_email -> Varchar,
+        applicant_name -> Varchar,
+        role -> Varchar,
+        sheet_id -> Varchar,
+        recommendation -> Varchar,
+        score -> Int4,
+        technical_depth -> Int4,
+        writing -> Int4,
+        values_reflected -> Array<Text>,
+        values_violated -> Array<Text>,
+        concerns -> Array<Text>,
+        notes -> Text,
+        submitted_time -> Nullable<Timestamptz>,
         airtable_record_id -> Varchar,
     }
 }
@@ -72,9 +160,6 @@ table! {
         interviews -> Array<Text>,
         scorers -> Array<Text>,
         scorers_completed -> Array<Text>,
-        scoring_form_id -> Varchar,
-        scoring_form_url -> Varchar,
-        scoring_form_responses_url -> Varchar,
         scoring_evaluations_count -> Int4,
         scoring_enthusiastic_yes_count -> Int4,
         scoring_yes_count -> Int4,
@@ -85,14 +170,21 @@ table! {
         scoring_inapplicable_experience_count -> Int4,
         scoring_job_function_yet_needed_count -> Int4,
         scoring_underwhelming_materials_count -> Int4,
+        scoring_mean -> Float4,
+        scoring_variance -> Float4,
+        scoring_disagreement -> Bool,
         request_background_check -> Bool,
         criminal_background_check_status -> Varchar,
         motor_vehicle_background_check_status -> Varchar,
         start_date -> Nullable<Date>,
+        offer_employment_type -> Varchar,
+        offer_salary -> Varchar,
         interested_in -> Array<Text>,
         geocode_cache -> Varchar,
         docusign_envelope_id -> Varchar,
         docusign_envelope_status -> Varchar,
+        source -> Varchar,
+        intake_token_hash -> Varchar,
         airtable_record_id -> Varchar,
     }
 }
@@ -153,6 +245,29 @@ table! {
     }
 }
 
+table! {
+    background_checks (id) {
+        id -> Int4,
+        report_id -> Varchar,
+        candidate_id -> Varchar,
+        applicant_email -> Varchar,
+        package -> Varchar,
+        status -> Varchar,
+        result -> Varchar,
+        adjudication -> Varchar,
+        screenings -> Array<Text>,
+        created_time -> Nullable<Timestamptz>,
+        estimated_completion_time -> Nullable<Timestamptz>,
+        completed_time -> Nullable<Timestamptz>,
+        adverse_action_requested -> Bool,
+        adverse_action_reasons -> Text,
+        adverse_action_status -> Varchar,
+        pre_adverse_notice_sent_time -> Nullable<Timestamptz>,
+        post_adverse_notice_sent_time -> Nullable<Timestamptz>,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     barcode_scans (id) {
         id -> Int4,
@@ -190,9 +305,13 @@ table! {
         id -> Int4,
         domain -> Varchar,
         certificate -> Text,
-        private_key -> Text,
+        encrypted_private_key -> Text,
+        fingerprint -> Varchar,
         valid_days_left -> Int4,
         expiration_date -> Date,
+        sans -> Array<Text>,
+        staging -> Bool,
+        renewal_window_days -> Int4,
         airtable_record_id -> Varchar,
     }
 }
@@ -212,6 +331,43 @@ table! {
     }
 }
 
+table! {
+    envelope_events (id) {
+        id -> Int4,
+        envelope_id -> Varchar,
+        recipient_email -> Varchar,
+        recipient_name -> Varchar,
+        recipient_role -> Varchar,
+        status -> Varchar,
+        reason -> Varchar,
+        time -> Timestamptz,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    envelopes (id) {
+        id -> Int4,
+        envelope_id -> Varchar,
+        applicant_email -> Varchar,
+        email_subject -> Varchar,
+        status -> Varchar,
+        created_time -> Nullable<Timestamptz>,
+        sent_time -> Nullable<Timestamptz>,
+        delivered_time -> Nullable<Timestamptz>,
+        completed_time -> Nullable<Timestamptz>,
+        declined_time -> Nullable<Timestamptz>,
+        declined_reason -> Varchar,
+        voided_time -> Nullable<Timestamptz>,
+        voided_reason -> Varchar,
+        status_changed_time -> Nullable<Timestamptz>,
+        reminders_sent -> Int4,
+        last_reminder_sent_time -> Nullable<Timestamptz>,
+        archived_documents -> Array<Text>,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     github_repos (id) {
         id -> Int4,
@@ -325,6 +481,27 @@ table! {
     }
 }
 
+table! {
+    interview_packets (id) {
+        id -> Int4,
+        interviewer -> Varchar,
+        applicant_email -> Varchar,
+        applicant_name -> Varchar,
+        role -> Varchar,
+        sheet_id -> Varchar,
+        interview_start_time -> Timestamptz,
+        interview_end_time -> Timestamptz,
+        content_hash -> Varchar,
+        google_drive_id -> Varchar,
+        link -> Varchar,
+        permission_id -> Varchar,
+        built_time -> Nullable<Timestamptz>,
+        shared_time -> Nullable<Timestamptz>,
+        revoked_time -> Nullable<Timestamptz>,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     journal_club_meetings (id) {
         id -> Int4,
@@ -351,6 +528,39 @@ table! {
     }
 }
 
+table! {
+    link_checks (id) {
+        id -> Int4,
+        short_link -> Varchar,
+        subdomain -> Varchar,
+        name -> Varchar,
+        target -> Varchar,
+        status_code -> Int4,
+        error -> Varchar,
+        redirect_chain -> Array<Text>,
+        healthy -> Bool,
+        last_checked -> Timestamptz,
+        last_success -> Nullable<Timestamptz>,
+        failing_since -> Nullable<Timestamptz>,
+        github_issue -> Varchar,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    link_clicks (id) {
+        id -> Int4,
+        time -> Timestamptz,
+        subdomain -> Varchar,
+        name -> Varchar,
+        short_link -> Varchar,
+        target -> Varchar,
+        referrer -> Varchar,
+        user_email -> Varchar,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     links (id) {
         id -> Int4,
@@ -475,6 +685,59 @@ table! {
     }
 }
 
+table! {
+    rfd_commits (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        sha -> Varchar,
+        branch -> Varchar,
+        message -> Varchar,
+        author -> Varchar,
+        committed_at -> Timestamptz,
+        diff -> Text,
+        link -> Varchar,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    rfd_references (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        reference_type -> Varchar,
+        target_rfd_number -> Int4,
+        target -> Varchar,
+        link -> Varchar,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    rfd_state_history (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        rfd_name -> Varchar,
+        from_state -> Varchar,
+        to_state -> Varchar,
+        valid_transition -> Bool,
+        branch -> Varchar,
+        sha -> Varchar,
+        changed_by -> Varchar,
+        changed_at -> Timestamptz,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    rfd_subscribers (id) {
+        id -> Int4,
+        email -> Varchar,
+        name -> Varchar,
+        subscription -> Varchar,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     rfds (id) {
         id -> Int4,
@@ -496,6 +759,8 @@ table! {
         relevant_components -> Array<Text>,
         pdf_link_github -> Varchar,
         pdf_link_google_drive -> Varchar,
+        visibility -> Varchar,
+        visibility_groups -> Array<Text>,
         airtable_record_id -> Varchar,
     }
 }
@@ -555,6 +820,19 @@ table! {
     }
 }
 
+table! {
+    tailscale_device_reports (id) {
+        id -> Int4,
+        device_id -> Varchar,
+        device_name -> Varchar,
+        device_user -> Varchar,
+        policy -> Varchar,
+        reason -> Varchar,
+        reported_at -> Timestamptz,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     users (id) {
         id -> Int4,
@@ -604,29 +882,44 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    applicant_deletions,
+    applicant_interview_schedules,
     applicant_interviews,
+    applicant_review_assignments,
     applicant_reviewers,
+    applicant_scores,
     applicants,
     auth_user_logins,
     auth_users,
+    background_checks,
     barcode_scans,
     buildings,
     certificates,
     conference_rooms,
+    envelope_events,
+    envelopes,
     github_repos,
     groups,
     inbound_shipments,
+    interview_packets,
     journal_club_meetings,
     journal_club_papers,
+    link_checks,
+    link_clicks,
     links,
     mailing_list_subscribers,
     outbound_shipments,
     page_views,
     rack_line_subscribers,
     recorded_meetings,
+    rfd_commits,
+    rfd_references,
+    rfd_state_history,
+    rfd_subscribers,
     rfds,
     software_vendors,
     swag_inventory_items,
     swag_items,
+    tailscale_device_reports,
     users,
 );
@@ -1,15 +1,18 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
 use hubcaps::repositories::Repository;
 use serde::Serialize;
 use tailscale_api::Tailscale;
 
-use crate::configs::Links;
+use crate::configs::{LinkConfig, Links};
 use crate::db::Database;
 use crate::models::{GithubRepos, RFDs};
 use crate::templates::{generate_nginx_and_terraform_files_for_shorturls, generate_terraform_files_for_shorturls};
 use crate::utils::{authenticate_github_jwt, github_org, DOMAIN, GSUITE_DOMAIN};
 
-/// Generate the files for the GitHub repository short URLs.
-pub async fn generate_shorturls_for_repos(db: &Database, repo: &Repository) {
+/// Get the short URLs for the GitHub repositories.
+pub fn get_shorturls_for_repos(db: &Database) -> Vec<ShortUrl> {
     let subdomain = "git";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
@@ -27,24 +30,37 @@ pub async fn generate_shorturls_for_repos(db: &Database, repo: &Repository) {
             subdomain: subdomain.to_string(),
             aliases: Default::default(),
             discussion: Default::default(),
+            origin: format!("GitHub repository `{}/{}`", repo.owner, repo.name),
         };
 
         // Add the link.
         links.push(link.clone());
     }
 
+    links
+}
+
+/// Generate the files for the GitHub repository short URLs.
+/// Nothing is generated if any of the short URLs collide.
+pub async fn generate_shorturls_for_repos(db: &Database, repo: &Repository) -> Result<(), String> {
+    let links = get_shorturls_for_repos(db);
+    check_all_shorturls(db).await.into_result()?;
+
     // Generate the files for the links.
-    generate_nginx_and_terraform_files_for_shorturls(repo, links.clone()).await;
+    generate_nginx_and_terraform_files_for_shorturls(repo, links).await;
+
+    Ok(())
 }
 
-/// Generate the files for the RFD short URLs.
-pub async fn generate_shorturls_for_rfds(db: &Database, repo: &Repository) {
+/// Get the short URLs for the RFDs.
+pub fn get_shorturls_for_rfds(db: &Database) -> Vec<ShortUrl> {
     let subdomain = "rfd";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
 
     // Get the rfds from the database.
-    let rfds = RFDs::get_from_db(db);
+    // Restricted RFDs do not get short URLs, since the short URLs are public.
+    let rfds = RFDs::get_visible_from_db(db);
     for rfd in rfds {
         let mut link = ShortUrl {
             name: rfd.number.to_string(),
@@ -54,6 +70,7 @@ pub async fn generate_shorturls_for_rfds(db: &Database, repo: &Repository) {
             subdomain: subdomain.to_string(),
             aliases: Default::default(),
             discussion: rfd.discussion,
+            origin: format!("RFD {}", rfd.number_string),
         };
 
         // Add the link.
@@ -64,13 +81,51 @@ pub async fn generate_shorturls_for_rfds(db: &Database, repo: &Repository) {
         links.push(link.clone());
     }
 
+    links
+}
+
+/// Generate the files for the RFD short URLs.
+/// Nothing is generated if any of the short URLs collide.
+pub async fn generate_shorturls_for_rfds(db: &Database, repo: &Repository) -> Result<(), String> {
+    let links = get_shorturls_for_rfds(db);
+    check_all_shorturls(db).await.into_result()?;
+
     // Generate the files for the links.
-    generate_nginx_and_terraform_files_for_shorturls(repo, links.clone()).await;
+    generate_nginx_and_terraform_files_for_shorturls(repo, links).await;
+
+    Ok(())
 }
 
-/// Generate the files for the configs links.
-pub async fn generate_shorturls_for_configs_links(db: &Database, repo: &Repository) {
-    let subdomain = "corp";
+/// Get the short URLs for a configs link, one for its name and one for each of its aliases.
+fn get_shorturls_for_configs_link(name: &str, description: &str, link: &str, aliases: &[String]) -> Vec<ShortUrl> {
+    let mut l = ShortUrl {
+        name: name.to_string(),
+        description: description.to_string(),
+        link: link.to_string(),
+        ip: "var.maverick_ip".to_string(),
+        subdomain: "corp".to_string(),
+        aliases: Default::default(),
+        discussion: Default::default(),
+        origin: format!("configs link `{}`", name),
+    };
+
+    // Add the link.
+    let mut links = vec![l.clone()];
+
+    // Add any aliases.
+    for alias in aliases {
+        // Set the name.
+        l.name = alias.to_string();
+
+        // Add the link.
+        links.push(l.clone());
+    }
+
+    links
+}
+
+/// Get the short URLs for the configs links, including their aliases.
+pub fn get_shorturls_for_configs_links(db: &Database) -> Vec<ShortUrl> {
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
 
@@ -79,35 +134,59 @@ pub async fn generate_shorturls_for_configs_links(db: &Database, repo: &Reposito
 
     // Create the array of links.
     for link in configs_links {
-        let mut l = ShortUrl {
-            name: link.name.to_string(),
-            description: link.description,
-            link: link.link,
-            ip: "var.maverick_ip".to_string(),
-            subdomain: subdomain.to_string(),
-            aliases: Default::default(),
-            discussion: Default::default(),
-        };
-
-        // Add the link.
-        links.push(l.clone());
+        links.append(&mut get_shorturls_for_configs_link(&link.name, &link.description, &link.link, &link.aliases));
+    }
 
-        // Add any aliases.
-        for alias in link.aliases {
-            // Set the name.
-            l.name = alias;
+    links
+}
 
-            // Add the link.
-            links.push(l.clone());
-        }
+/// Get the short URLs for the links in a config, for example from a pull request
+/// that has not been synced to the database yet.
+pub fn get_shorturls_for_link_configs(configs_links: &BTreeMap<String, LinkConfig>) -> Vec<ShortUrl> {
+    let mut links: Vec<ShortUrl> = Default::default();
+    for (name, link) in configs_links {
+        links.append(&mut get_shorturls_for_configs_link(name, &link.description, &link.link, &link.aliases));
     }
 
+    links
+}
+
+/// Generate the files for the configs links.
+/// Nothing is generated if any of the short URLs collide.
+pub async fn generate_shorturls_for_configs_links(db: &Database, repo: &Repository) -> Result<(), String> {
+    let links = get_shorturls_for_configs_links(db);
+    check_all_shorturls(db).await.into_result()?;
+
     // Generate the files for the links.
     generate_nginx_and_terraform_files_for_shorturls(repo, links).await;
+
+    Ok(())
 }
 
-/// Generate the cloudflare terraform files for the tailscale devices.
-pub async fn generate_dns_for_tailscale_devices(repo: &Repository) {
+/// Get all the short URLs that redirect somewhere: the repos, RFDs and configs links.
+pub fn get_all_shorturls(db: &Database) -> Vec<ShortUrl> {
+    let mut links = get_shorturls_for_repos(db);
+    links.append(&mut get_shorturls_for_rfds(db));
+    links.append(&mut get_shorturls_for_configs_links(db));
+
+    links
+}
+
+/// Check all the short URLs for collisions and reserved names: the ones that redirect
+/// somewhere along with the tailscale devices.
+pub async fn check_all_shorturls(db: &Database) -> ShortUrlReport {
+    let mut links = get_all_shorturls(db);
+    match get_shorturls_for_tailscale_devices().await {
+        Ok(mut devices) => links.append(&mut devices),
+        // We can still check everything else.
+        Err(e) => println!("[shorturls] skipping the tailscale devices in the check: {}", e),
+    }
+
+    check_shorturls(&links)
+}
+
+/// Get the DNS records for the tailscale devices.
+pub async fn get_shorturls_for_tailscale_devices() -> Result<Vec<ShortUrl>, String> {
     let subdomain = "internal";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
@@ -115,7 +194,7 @@ pub async fn generate_dns_for_tailscale_devices(repo: &Repository) {
     // Initialize the Tailscale API.
     let tailscale = Tailscale::new_from_env();
     // Get the devices.
-    let devices = tailscale.list_devices().await.unwrap();
+    let devices = tailscale.list_devices().await.map_err(|e| format!("listing the tailscale devices failed: {}", e))?;
 
     // Create the array of links.
     for device in devices {
@@ -142,6 +221,7 @@ pub async fn generate_dns_for_tailscale_devices(repo: &Repository) {
             subdomain: subdomain.to_string(),
             aliases: Default::default(),
             discussion: Default::default(),
+            origin: format!("Tailscale device `{}`", hostname),
         };
 
         // Add the link.
@@ -157,6 +237,7 @@ pub async fn generate_dns_for_tailscale_devices(repo: &Repository) {
                 subdomain: subdomain.to_string(),
                 aliases: Default::default(),
                 discussion: Default::default(),
+                origin: l.origin.to_string(),
             };
 
             // Add the link.
@@ -164,21 +245,72 @@ pub async fn generate_dns_for_tailscale_devices(repo: &Repository) {
         }
     }
 
+    Ok(links)
+}
+
+/// Generate the cloudflare terraform files for the tailscale devices.
+/// Nothing is generated if any of the short URLs collide.
+pub async fn generate_dns_for_tailscale_devices(db: &Database, repo: &Repository) -> Result<(), String> {
+    let links = get_shorturls_for_tailscale_devices().await?;
+
+    let mut all = get_all_shorturls(db);
+    all.append(&mut links.clone());
+    check_shorturls(&all).into_result()?;
+
     // Generate the files for the links.
     generate_terraform_files_for_shorturls(repo, links).await;
+
+    Ok(())
+}
+
+/// Check the short URLs a configs pull request would give us: the links in its configs
+/// along with the repos and RFDs in the database and the tailscale devices.
+pub async fn check_shorturls_for_configs_links(db: &Database, configs_links: &BTreeMap<String, LinkConfig>) -> ShortUrlReport {
+    let mut links = get_shorturls_for_repos(db);
+    links.append(&mut get_shorturls_for_rfds(db));
+    links.append(&mut get_shorturls_for_link_configs(configs_links));
+
+    match get_shorturls_for_tailscale_devices().await {
+        Ok(mut devices) => links.append(&mut devices),
+        // We can still check everything else.
+        Err(e) => println!("[shorturls] skipping the tailscale devices in the check: {}", e),
+    }
+
+    check_shorturls(&links)
 }
 
 /// Update all the short URLs and DNS.
-pub async fn refresh_shorturls() {
+/// All the short URLs are checked before we generate any files, so if any of them collide
+/// nothing is changed and the report is returned as the error.
+pub async fn refresh_shorturls() -> Result<(), String> {
     let github = authenticate_github_jwt();
     let repo = github.repo(github_org(), "configs");
 
     let db = Database::new();
 
-    generate_shorturls_for_repos(&db, &repo).await;
-    generate_shorturls_for_rfds(&db, &repo).await;
-    generate_shorturls_for_configs_links(&db, &repo).await;
-    generate_dns_for_tailscale_devices(&repo).await;
+    let repos = get_shorturls_for_repos(&db);
+    let rfds = get_shorturls_for_rfds(&db);
+    let configs_links = get_shorturls_for_configs_links(&db);
+    // Keep updating the rest if we can't get the devices, their files are left as they are.
+    let tailscale_devices = match get_shorturls_for_tailscale_devices().await {
+        Ok(devices) => Some(devices),
+        Err(e) => {
+            println!("[shorturls] skipping the tailscale devices: {}", e);
+            None
+        }
+    };
+
+    let all: Vec<ShortUrl> = repos.iter().chain(rfds.iter()).chain(configs_links.iter()).chain(tailscale_devices.iter().flatten()).cloned().collect();
+    check_shorturls(&all).into_result()?;
+
+    generate_nginx_and_terraform_files_for_shorturls(&repo, repos).await;
+    generate_nginx_and_terraform_files_for_shorturls(&repo, rfds).await;
+    generate_nginx_and_terraform_files_for_shorturls(&repo, configs_links).await;
+    if let Some(tailscale_devices) = tailscale_devices {
+        generate_terraform_files_for_shorturls(&repo, tailscale_devices).await;
+    }
+
+    Ok(())
 }
 
 /// The data type for a short URL that will be used in a template.
@@ -195,15 +327,417 @@ pub struct ShortUrl {
     pub subdomain: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub discussion: String,
+    /// origin is what the short URL was generated from, for example "RFD 0042".
+    /// The name of a link and its aliases share the same origin.
+    #[serde(skip)]
+    pub origin: String,
+}
+
+impl ShortUrl {
+    /// Get the host for the short URL, without our domain.
+    pub fn host(&self) -> String {
+        format!("{}.{}", self.name, self.subdomain).to_lowercase()
+    }
+
+    /// Get where the short URL points to, the link it redirects to or the IP of its record.
+    pub fn target(&self) -> &str {
+        if self.link.is_empty() {
+            &self.ip
+        } else {
+            &self.link
+        }
+    }
+}
+
+/// A short URL name that is reserved, so that nothing else can take it over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReservedShortUrl {
+    /// subdomain is the subdomain the name is reserved on, `*` for all of them.
+    pub subdomain: &'static str,
+    pub name: &'static str,
+    /// owner is the origin that is allowed to use the name, if any.
+    pub owner: Option<&'static str>,
+    pub reason: &'static str,
+}
+
+/// The short URL names that are reserved.
+pub static SHORTURL_RESERVED_NAMES: &[ReservedShortUrl] = &[
+    ReservedShortUrl {
+        subdomain: "internal",
+        name: "api",
+        owner: Some("Tailscale device `cio-api`"),
+        reason: "it is the alias for the CIO API",
+    },
+    ReservedShortUrl {
+        subdomain: "corp",
+        name: "www",
+        owner: None,
+        reason: "it would be mistaken for our website",
+    },
+    ReservedShortUrl {
+        subdomain: "internal",
+        name: "www",
+        owner: None,
+        reason: "it would be mistaken for our website",
+    },
+    ReservedShortUrl {
+        subdomain: "corp",
+        name: "mail",
+        owner: None,
+        reason: "it would be mistaken for our mail server",
+    },
+    ReservedShortUrl {
+        subdomain: "internal",
+        name: "mail",
+        owner: None,
+        reason: "it would be mistaken for our mail server",
+    },
+];
+
+/// Get the reservation for a short URL, if its name is reserved.
+pub fn get_shorturl_reservation(subdomain: &str, name: &str) -> Option<&'static ReservedShortUrl> {
+    let subdomain = subdomain.to_lowercase();
+    let name = name.to_lowercase();
+    SHORTURL_RESERVED_NAMES.iter().find(|r| r.name == name && (r.subdomain == "*" || r.subdomain == subdomain))
+}
+
+/// A problem with the short URLs that stops us from generating them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShortUrlProblem {
+    /// More than one thing wants the same short URL.
+    /// claims are the origins and targets of the short URLs that collide.
+    Collision { short_link: String, claims: Vec<String> },
+    /// Something other than its owner wants a reserved short URL.
+    Reserved { short_link: String, origin: String, reason: String },
+}
+
+impl fmt::Display for ShortUrlProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShortUrlProblem::Collision { short_link, claims } => write!(f, "`{}` is wanted by more than one thing: {}", short_link, claims.join(", ")),
+            ShortUrlProblem::Reserved { short_link, origin, reason } => write!(f, "`{}` is reserved since {}, but {} wants it", short_link, reason, origin),
+        }
+    }
+}
+
+/// The problems found when checking the short URLs from all our sources.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShortUrlReport {
+    /// checked is the number of short URLs that were checked.
+    pub checked: usize,
+    pub problems: Vec<ShortUrlProblem>,
+}
+
+impl ShortUrlReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Get the report as an error if there are any problems.
+    pub fn into_result(self) -> Result<(), String> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(self.to_string())
+        }
+    }
+
+    /// Get the report as markdown, for a comment on a pull request.
+    pub fn as_markdown(&self) -> String {
+        if self.is_ok() {
+            return format!("All {} short URLs are unique and none of them use a reserved name. :white_check_mark:", self.checked);
+        }
+
+        let mut markdown = format!(
+            "Found {} problems with the short URLs, none of the nginx or Terraform files will be generated until they are fixed:\n\n",
+            self.problems.len()
+        );
+        for problem in &self.problems {
+            markdown += &format!("- {}\n", problem);
+        }
+
+        markdown
+    }
+}
+
+impl fmt::Display for ShortUrlReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "all {} short URLs are ok", self.checked);
+        }
+
+        write!(f, "found {} problems with the short URLs:", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the short URLs from all our sources for collisions and reserved names.
+/// Short URLs with the same origin and target are the same link, so they do not collide.
+pub fn check_shorturls(links: &[ShortUrl]) -> ShortUrlReport {
+    let mut hosts: BTreeMap<String, Vec<&ShortUrl>> = Default::default();
+    for link in links {
+        let claims = hosts.entry(link.host()).or_default();
+        if !claims.iter().any(|c| c.origin == link.origin && c.target() == link.target()) {
+            claims.push(link);
+        }
+    }
+
+    let mut report = ShortUrlReport {
+        checked: links.len(),
+        problems: Default::default(),
+    };
+    for (host, claims) in hosts {
+        let short_link = format!("{}.{}", host, DOMAIN);
+
+        if claims.len() > 1 {
+            report.problems.push(ShortUrlProblem::Collision {
+                short_link: short_link.to_string(),
+                claims: claims.iter().map(|c| format!("{} ({})", c.origin, c.target())).collect(),
+            });
+        }
+
+        for claim in claims {
+            if let Some(reservation) = get_shorturl_reservation(&claim.subdomain, &claim.name) {
+                if reservation.owner != Some(claim.origin.as_str()) {
+                    report.problems.push(ShortUrlProblem::Reserved {
+                        short_link: short_link.to_string(),
+                        origin: claim.origin.to_string(),
+                        reason: reservation.reason.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Where `/` on a paths short URL domain, like `rfd.oxide.computer`, redirects to.
+/// This matches the generated nginx config.
+pub static SHORTURL_ROOT_REDIRECT: &str = "https://119.rfd.oxide.computer";
+
+/// Where a request for a short URL redirects to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortUrlRedirect {
+    pub subdomain: String,
+    /// name is the name of the short URL, this is empty for the root of a paths domain.
+    pub name: String,
+    /// discussion is set if this is the `/discussion` link for the short URL.
+    pub discussion: bool,
+    pub target: String,
+}
+
+impl ShortUrlRedirect {
+    /// Get the canonical short link for the redirect, so the clicks for the
+    /// `{name}.{subdomain}` and `{subdomain}/{name}` forms are counted together.
+    pub fn short_link(&self) -> String {
+        if self.name.is_empty() {
+            return format!("https://{}.{}", self.subdomain, DOMAIN);
+        }
+
+        let mut link = format!("https://{}.{}.{}", self.name, self.subdomain, DOMAIN);
+        if self.discussion {
+            link += "/discussion";
+        }
+
+        link
+    }
+}
+
+/// The short URLs by their subdomain and name, so we can serve the same redirects as
+/// the generated nginx config.
+#[derive(Debug, Default, Clone)]
+pub struct ShortUrlRoutes {
+    routes: BTreeMap<(String, String), ShortUrl>,
+}
+
+impl ShortUrlRoutes {
+    pub fn new(links: Vec<ShortUrl>) -> Self {
+        let mut routes: BTreeMap<(String, String), ShortUrl> = Default::default();
+        for link in links {
+            if link.link.is_empty() {
+                // This is only a DNS record, there is nowhere to redirect to.
+                continue;
+            }
+
+            for name in link.aliases.iter().chain(std::iter::once(&link.name)) {
+                routes.insert((link.subdomain.to_lowercase(), name.to_lowercase()), link.clone());
+            }
+        }
+
+        ShortUrlRoutes { routes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Get where a request for `path` on `host` redirects to.
+    /// The host is either `{name}.{subdomain}` or `{subdomain}`, with or without our domain.
+    pub fn resolve(&self, host: &str, path: &str) -> Option<ShortUrlRedirect> {
+        let host = host.split(':').next().unwrap_or_default().trim_end_matches('.').to_lowercase();
+        let host = host.strip_suffix(&format!(".{}", DOMAIN)).unwrap_or(&host);
+        let path = path.split(|c| c == '?' || c == '#').next().unwrap_or_default().trim_matches('/').to_lowercase();
+
+        let parts: Vec<&str> = host.split('.').collect();
+        let (subdomain, name, discussion) = match parts.as_slice() {
+            // {name}.{subdomain}, any path redirects to the link, except for /discussion.
+            [name, subdomain] => (*subdomain, name.to_string(), path == "discussion" || path.starts_with("discussion/")),
+            // {subdomain}/{name} or {subdomain}/{name}/discussion.
+            [subdomain] => {
+                if path.is_empty() {
+                    if !self.routes.keys().any(|(s, _)| s == subdomain) {
+                        return None;
+                    }
+
+                    return Some(ShortUrlRedirect {
+                        subdomain: subdomain.to_string(),
+                        name: Default::default(),
+                        discussion: false,
+                        target: SHORTURL_ROOT_REDIRECT.to_string(),
+                    });
+                }
+
+                match path.strip_suffix("/discussion") {
+                    Some(name) => (*subdomain, name.to_string(), true),
+                    None => (*subdomain, path.to_string(), false),
+                }
+            }
+            _ => return None,
+        };
+
+        let link = self.routes.get(&(subdomain.to_string(), name.to_string()))?;
+        let target = if discussion && !link.discussion.is_empty() {
+            link.discussion.to_string()
+        } else if discussion && parts.len() == 1 {
+            // There is no discussion for this link.
+            return None;
+        } else {
+            link.link.to_string()
+        };
+
+        Some(ShortUrlRedirect {
+            subdomain: subdomain.to_string(),
+            name,
+            discussion: discussion && !link.discussion.is_empty(),
+            target,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::shorturls::refresh_shorturls;
+    use crate::shorturls::{check_shorturls, refresh_shorturls, ShortUrl, ShortUrlProblem, ShortUrlRoutes, SHORTURL_ROOT_REDIRECT};
+
+    fn link(subdomain: &str, name: &str, link: &str, discussion: &str) -> ShortUrl {
+        ShortUrl {
+            name: name.to_string(),
+            description: Default::default(),
+            link: link.to_string(),
+            ip: "var.maverick_ip".to_string(),
+            aliases: Default::default(),
+            subdomain: subdomain.to_string(),
+            discussion: discussion.to_string(),
+            origin: format!("{} {}", subdomain, name),
+        }
+    }
+
+    fn device(name: &str, ip: &str) -> ShortUrl {
+        let mut l = link("internal", name, "", "");
+        l.ip = ip.to_string();
+        l.origin = format!("Tailscale device `{}`", name);
+        l
+    }
+
+    #[test]
+    fn test_shorturl_routes_resolve() {
+        let routes = ShortUrlRoutes::new(vec![
+            link("rfd", "42", "https://rfd.shared.oxide.computer/rfd/0042", "https://github.com/oxidecomputer/rfd/pull/1"),
+            link("corp", "cal", "https://calendar.google.com", ""),
+            link("internal", "cio-api", "", ""),
+        ]);
+        assert_eq!(2, routes.len());
+
+        let r = routes.resolve("42.rfd.oxide.computer:443", "/anything?x=1").unwrap();
+        assert_eq!("https://rfd.shared.oxide.computer/rfd/0042", r.target);
+        assert_eq!("https://42.rfd.oxide.computer", r.short_link());
+
+        let r = routes.resolve("rfd.oxide.computer", "/42/discussion").unwrap();
+        assert_eq!("https://github.com/oxidecomputer/rfd/pull/1", r.target);
+        assert_eq!("https://42.rfd.oxide.computer/discussion", r.short_link());
+        assert_eq!(r, routes.resolve("42.rfd", "/discussion").unwrap());
+
+        // Links without a discussion redirect to the link on their own domain, like nginx.
+        assert_eq!("https://calendar.google.com", routes.resolve("cal.corp.oxide.computer", "/discussion").unwrap().target);
+        assert!(routes.resolve("corp.oxide.computer", "/cal/discussion").is_none());
+
+        assert_eq!(SHORTURL_ROOT_REDIRECT, routes.resolve("corp.oxide.computer", "/").unwrap().target);
+        assert!(routes.resolve("nope.oxide.computer", "/").is_none());
+        assert!(routes.resolve("cio-api.internal.oxide.computer", "/").is_none());
+        assert!(routes.resolve("a.b.c.oxide.computer", "/").is_none());
+    }
+
+    #[test]
+    fn test_check_shorturls() {
+        let mut rfd = link("rfd", "42", "https://rfd.shared.oxide.computer/rfd/0042", "");
+        rfd.origin = "RFD 0042".to_string();
+        let mut cal = link("corp", "cal", "https://calendar.google.com", "");
+        cal.origin = "configs link `calendar`".to_string();
+        let mut api = device("api", "100.1.1.1");
+        api.origin = "Tailscale device `cio-api`".to_string();
+
+        // The same link twice is fine, and the CIO API owns its reserved alias.
+        let report = check_shorturls(&[rfd.clone(), rfd, cal.clone(), cal, device("cio-api", "100.1.1.1"), api]);
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(6, report.checked);
+
+        let mut other_cal = link("corp", "Cal", "https://cal.com", "");
+        other_cal.origin = "configs link `cal`".to_string();
+        let report = check_shorturls(&[
+            link("corp", "cal", "https://calendar.google.com", ""),
+            other_cal,
+            device("api", "100.2.2.2"),
+            device("laptop", "100.3.3.3"),
+            device("laptop", "100.4.4.4"),
+        ]);
+        assert!(!report.is_ok());
+        assert_eq!(
+            vec![
+                ShortUrlProblem::Reserved {
+                    short_link: "api.internal.oxide.computer".to_string(),
+                    origin: "Tailscale device `api`".to_string(),
+                    reason: "it is the alias for the CIO API".to_string(),
+                },
+                ShortUrlProblem::Collision {
+                    short_link: "cal.corp.oxide.computer".to_string(),
+                    claims: vec!["corp cal (https://calendar.google.com)".to_string(), "configs link `cal` (https://cal.com)".to_string()],
+                },
+                ShortUrlProblem::Collision {
+                    short_link: "laptop.internal.oxide.computer".to_string(),
+                    claims: vec!["Tailscale device `laptop` (100.3.3.3)".to_string(), "Tailscale device `laptop` (100.4.4.4)".to_string()],
+                },
+            ],
+            report.problems
+        );
+        assert!(report.as_markdown().contains("- `cal.corp.oxide.computer` is wanted by more than one thing"));
+        assert!(report.into_result().is_err());
+
+        // A repo can be called www, but a configs link can't.
+        assert!(check_shorturls(&[link("git", "www", "https://github.com/oxidecomputer/www", "")]).is_ok());
+        assert_eq!(1, check_shorturls(&[link("corp", "www", "https://oxide.computer", "")]).problems.len());
+    }
 
     #[ignore]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_cron_shorturls() {
-        refresh_shorturls().await;
+        refresh_shorturls().await.unwrap();
     }
 }
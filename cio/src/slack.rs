@@ -13,11 +13,21 @@ pub fn get_hiring_channel_post_url() -> String {
     env::var("SLACK_HIRING_CHANNEL_POST_URL").unwrap()
 }
 
+/// The Slack app webhook URL for our app to post to the #infra channel.
+pub fn get_infra_channel_post_url() -> String {
+    env::var("SLACK_INFRA_CHANNEL_POST_URL").unwrap()
+}
+
 /// The Slack app webhook URL for our app to post to the #public-relations channel.
 pub fn get_public_relations_channel_post_url() -> String {
     env::var("SLACK_PUBLIC_RELATIONS_CHANNEL_POST_URL").unwrap()
 }
 
+/// The Slack app webhook URL for our app to post to the #rfds channel.
+pub fn get_rfds_channel_post_url() -> String {
+    env::var("SLACK_RFDS_CHANNEL_POST_URL").unwrap()
+}
+
 /// Post text to a channel.
 pub async fn post_to_channel(url: String, v: Value) {
     let client = Client::new();
@@ -1,39 +1,713 @@
-use chrono::{Duration, Utc};
-use tailscale_api::Tailscale;
+#![allow(clippy::from_over_into)]
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use hubcaps::Github;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tailscale_api::{Acl, Device, Tailscale};
+
+use crate::airtable::{AIRTABLE_BASE_ID_DIRECTORY, AIRTABLE_TAILSCALE_DEVICE_REPORTS_TABLE};
+use crate::configs::{get_configs_from_repo, Group, Groups, User, Users};
+use crate::core::UpdateAirtableRecord;
+use crate::db::Database;
+use crate::schema::tailscale_device_reports;
+use crate::slack::{get_infra_channel_post_url, post_to_channel, FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use crate::utils::{create_or_update_file_in_github_repo, get_file_content_from_repo, github_org, DOMAIN, GSUITE_DOMAIN};
+
+/// The file in the configs repo with the Tailscale ACL we generate. Like the terraform
+/// files, the changes to it are reviewed before they are applied.
+pub static TAILSCALE_ACL_FILE: &str = "tailscale/generated.acl.json";
+
+/// The most devices we list in each section of the Slack report.
+pub static TAILSCALE_REPORT_MAX_DEVICES: usize = 25;
+
+/// The name we give the findings for devices owned by folks who are no longer employees.
+pub static TAILSCALE_DEPARTED_EMPLOYEES_POLICY: &str = "departed-employees";
+
+/// A policy for the lifecycle of our Tailscale devices, from `[tailscale_device_policies]`
+/// in the configs repo. For example:
+///
+/// ```toml
+/// [tailscale_device_policies.console-git]
+/// description = "The VMs we create for every branch of the console repo"
+/// hostname_prefixes = ["console-git-"]
+/// max_idle_hours = 24
+/// ```
+///
+/// A device matches the policy if its hostname starts with any of the prefixes or it
+/// has any of the tags. A policy needs at least one of them, so it can never match
+/// every device by accident.
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct TailscaleDevicePolicy {
+    /// name is the key of the policy in the config.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hostname_prefixes: Vec<String>,
+    /// tags are the Tailscale ACL tags, like `tag:ci`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// max_idle_hours is how long a device can go without being seen before we
+    /// delete it, 0 to never delete devices.
+    #[serde(default)]
+    pub max_idle_hours: i64,
+    /// owners are the users allowed to own the devices, as emails or `@domain`.
+    /// Devices owned by anyone else are flagged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// key_expiry_warning_days is how many days before its key expires we flag a
+    /// device, 0 to not warn.
+    #[serde(default)]
+    pub key_expiry_warning_days: i64,
+    /// dry_run reports the devices the policy would delete, without deleting them.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl TailscaleDevicePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems: Vec<String> = Default::default();
+
+        if self.hostname_prefixes.iter().all(|p| p.trim().is_empty()) && self.tags.iter().all(|t| t.trim().is_empty()) {
+            problems.push("at least one of hostname_prefixes or tags is required".to_string());
+        }
+        if self.max_idle_hours < 0 {
+            problems.push("max_idle_hours must not be negative".to_string());
+        }
+        if self.key_expiry_warning_days < 0 {
+            problems.push("key_expiry_warning_days must not be negative".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+
+    /// Return if the policy applies to a device.
+    pub fn matches(&self, device: &Device) -> bool {
+        let hostname = device.hostname.to_lowercase();
+        self.hostname_prefixes.iter().any(|p| !p.trim().is_empty() && hostname.starts_with(&p.to_lowercase())) || self.tags.iter().any(|t| device.tags.contains(t))
+    }
+
+    /// Return if a user is one of the owners allowed by the policy.
+    pub fn is_allowed_owner(&self, user: &str) -> bool {
+        if self.owners.is_empty() {
+            return true;
+        }
+
+        let user = user.to_lowercase();
+        self.owners.iter().any(|o| {
+            let o = o.to_lowercase();
+            if o.starts_with('@') {
+                user.ends_with(&o)
+            } else {
+                user == o
+            }
+        })
+    }
+
+    /// Check a device against the policy. Everything the policy flags a device for is
+    /// in a single finding, so each device shows up once per policy in the report.
+    pub fn check(&self, device: &Device, now: DateTime<Utc>) -> Vec<TailscaleDeviceFinding> {
+        let mut findings: Vec<TailscaleDeviceFinding> = Default::default();
+        if !self.matches(device) {
+            return findings;
+        }
+
+        let idle = now - device.last_seen;
+        if self.max_idle_hours > 0 && idle > Duration::hours(self.max_idle_hours) {
+            findings.push(TailscaleDeviceFinding::new(
+                device,
+                &self.name,
+                TailscaleDeviceAction::Delete,
+                &format!("last seen {} ago, more than the {} hours allowed", format_duration(idle), self.max_idle_hours),
+                self.dry_run,
+            ));
+        }
+
+        let mut flags: Vec<String> = Default::default();
+        if !self.is_allowed_owner(&device.user) {
+            flags.push(format!("owned by `{}`, who is not one of {}", device.user, self.owners.join(", ")));
+        }
+
+        // Devices that never expire have a zero expiry.
+        if self.key_expiry_warning_days > 0 && !device.never_expires && device.expires > device.created {
+            let left = device.expires - now;
+            if left < Duration::zero() {
+                flags.push(format!("key expired {} ago", format_duration(-left)));
+            } else if left < Duration::days(self.key_expiry_warning_days) {
+                flags.push(format!("key expires in {}", format_duration(left)));
+            }
+        }
+
+        if !flags.is_empty() {
+            findings.push(TailscaleDeviceFinding::new(device, &self.name, TailscaleDeviceAction::Flag, &flags.join(", "), self.dry_run));
+        }
+
+        findings
+    }
+}
+
+/// The policies we use when the configs repo does not have any.
+pub fn get_default_tailscale_device_policies() -> BTreeMap<String, TailscaleDevicePolicy> {
+    // When we generate VMs for the console repo on every branch we get lingering
+    // Tailscale devices that need to cleaned up when they are no longer active.
+    let mut policies: BTreeMap<String, TailscaleDevicePolicy> = Default::default();
+    policies.insert(
+        "console-git".to_string(),
+        TailscaleDevicePolicy {
+            name: "console-git".to_string(),
+            description: "The VMs we create for every branch of the console repo".to_string(),
+            hostname_prefixes: vec!["console-git-".to_string()],
+            max_idle_hours: 24,
+            ..Default::default()
+        },
+    );
+
+    policies
+}
+
+/// What we do with a device that breaks a policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TailscaleDeviceAction {
+    Delete,
+    Flag,
+}
+
+impl fmt::Display for TailscaleDeviceAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TailscaleDeviceAction::Delete => write!(f, "delete"),
+            TailscaleDeviceAction::Flag => write!(f, "flag"),
+        }
+    }
+}
+
+/// A device that breaks a policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailscaleDeviceFinding {
+    pub device_id: String,
+    pub device_name: String,
+    pub user: String,
+    pub policy: String,
+    pub action: TailscaleDeviceAction,
+    pub reason: String,
+    /// dry_run is set if the policy is in dry run mode, so we only report the device.
+    pub dry_run: bool,
+    /// reported_at is when we first reported we are going to delete the device.
+    /// We only delete devices that were reported on an earlier run.
+    pub reported_at: Option<DateTime<Utc>>,
+}
+
+impl TailscaleDeviceFinding {
+    fn new(device: &Device, policy: &str, action: TailscaleDeviceAction, reason: &str, dry_run: bool) -> Self {
+        TailscaleDeviceFinding {
+            device_id: device.id.to_string(),
+            device_name: device.name.to_string(),
+            user: device.user.to_string(),
+            policy: policy.to_string(),
+            action,
+            reason: reason.to_string(),
+            dry_run,
+            reported_at: None,
+        }
+    }
+}
+
+impl fmt::Display for TailscaleDeviceFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` ({}) {} [{}]", self.device_name, self.user, self.reason, self.policy)?;
+        if self.dry_run {
+            write!(f, " (dry run)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a duration in the largest unit that makes sense, like `3 days`.
+fn format_duration(d: Duration) -> String {
+    if d.num_days() >= 2 {
+        format!("{} days", d.num_days())
+    } else if d.num_hours() >= 2 {
+        format!("{} hours", d.num_hours())
+    } else {
+        format!("{} minutes", d.num_minutes())
+    }
+}
+
+/// Return if a device is owned by one of our own accounts that no longer belongs to
+/// an employee. Devices owned by anyone outside our domains are not checked.
+pub fn is_owned_by_departed_employee(device: &Device, employees: &BTreeSet<String>) -> bool {
+    let user = device.user.to_lowercase();
+    let mut parts = user.splitn(2, '@');
+    let username = parts.next().unwrap_or_default();
+    let domain = parts.next().unwrap_or_default();
+
+    if device.is_external || (domain != GSUITE_DOMAIN && domain != DOMAIN) {
+        return false;
+    }
+
+    !employees.contains(username)
+}
+
+/// Check all the devices against the policies, and flag the devices owned by folks
+/// who are no longer employees. `employees` are the usernames and aliases of our users.
+/// A device is only deleted once, by the first policy by name that deletes it.
+pub fn check_tailscale_devices(devices: &[Device], policies: &BTreeMap<String, TailscaleDevicePolicy>, employees: &BTreeSet<String>, now: DateTime<Utc>) -> Vec<TailscaleDeviceFinding> {
+    let mut findings: Vec<TailscaleDeviceFinding> = Default::default();
+    for device in devices {
+        for policy in policies.values() {
+            findings.append(&mut policy.check(device, now));
+        }
+
+        if is_owned_by_departed_employee(device, employees) {
+            findings.push(TailscaleDeviceFinding::new(
+                device,
+                TAILSCALE_DEPARTED_EMPLOYEES_POLICY,
+                TailscaleDeviceAction::Flag,
+                &format!("owned by `{}`, who is no longer an employee", device.user),
+                false,
+            ));
+        }
+    }
+
+    findings.sort_by(|a, b| (a.action, &a.device_name, &a.policy).cmp(&(b.action, &b.device_name, &b.policy)));
+    let mut deleted: BTreeSet<String> = Default::default();
+    findings.retain(|f| f.action != TailscaleDeviceAction::Delete || deleted.insert(f.device_id.to_string()));
+    findings
+}
+
+/// Get the Slack message for the devices that break our policies.
+pub fn tailscale_devices_slack_msg(findings: &[TailscaleDeviceFinding], dry_run: bool) -> Value {
+    let section = |text: String| MessageBlock {
+        block_type: MessageBlockType::Section,
+        text: Some(MessageBlockText {
+            text_type: MessageType::Markdown,
+            text,
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    };
+
+    let mut sections: Vec<(&str, Vec<&TailscaleDeviceFinding>)> = vec![
+        ("are being deleted", vec![]),
+        ("will be deleted on the next run if they are still idle", vec![]),
+        ("would be deleted (dry run)", vec![]),
+        ("need someone to look at them", vec![]),
+    ];
+    for finding in findings {
+        let i = match finding.action {
+            TailscaleDeviceAction::Delete if dry_run || finding.dry_run => 2,
+            TailscaleDeviceAction::Delete if finding.reported_at.is_some() => 0,
+            TailscaleDeviceAction::Delete => 1,
+            TailscaleDeviceAction::Flag => 3,
+        };
+        sections[i].1.push(finding);
+    }
+
+    let mut blocks: Vec<MessageBlock> = Default::default();
+    for (title, matching) in sections {
+        if matching.is_empty() {
+            continue;
+        }
+
+        let mut text = format!("*{} Tailscale devices {}:*", matching.len(), title);
+        for finding in matching.iter().take(TAILSCALE_REPORT_MAX_DEVICES) {
+            text += &format!("\n• {}", finding);
+        }
+        if matching.len() > TAILSCALE_REPORT_MAX_DEVICES {
+            text += &format!("\n… and {} more", matching.len() - TAILSCALE_REPORT_MAX_DEVICES);
+        }
+
+        blocks.push(section(text));
+    }
+
+    json!(FormattedMessage {
+        channel: Default::default(),
+        attachments: Default::default(),
+        blocks,
+    })
+}
+
+/// A Tailscale device we reported we are going to delete. Deleting waits for the
+/// next run, so folks have a chance to see the report and use the device again.
+#[db {
+    new_struct_name = "TailscaleDeviceReport",
+    airtable_base_id = "AIRTABLE_BASE_ID_DIRECTORY",
+    airtable_table = "AIRTABLE_TAILSCALE_DEVICE_REPORTS_TABLE",
+    match_on = {
+        "device_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[table_name = "tailscale_device_reports"]
+pub struct NewTailscaleDeviceReport {
+    pub device_id: String,
+    pub device_name: String,
+    pub device_user: String,
+    /// policy is the name of the policy that deletes the device.
+    pub policy: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    pub reported_at: DateTime<Utc>,
+}
+
+/// Implement updating the Airtable record for a TailscaleDeviceReport.
+#[async_trait]
+impl UpdateAirtableRecord<TailscaleDeviceReport> for TailscaleDeviceReport {
+    async fn update_airtable_record(&mut self, _record: TailscaleDeviceReport) {}
+}
+
+impl From<&TailscaleDeviceFinding> for NewTailscaleDeviceReport {
+    fn from(finding: &TailscaleDeviceFinding) -> Self {
+        NewTailscaleDeviceReport {
+            device_id: finding.device_id.to_string(),
+            device_name: finding.device_name.to_string(),
+            device_user: finding.user.to_string(),
+            policy: finding.policy.to_string(),
+            reason: finding.reason.to_string(),
+            reported_at: finding.reported_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Enforce the Tailscale device policies from the configs repo.
+/// The report goes to Slack before we delete anything, and a device is only deleted
+/// once it was reported on an earlier run. With `dry_run` nothing is deleted or
+/// recorded as reported, no matter the policies.
+pub async fn enforce_tailscale_device_policies(db: &Database, github: &Github, dry_run: bool) -> Result<Vec<TailscaleDeviceFinding>, String> {
+    let configs = get_configs_from_repo(github).await;
+    let mut policies = if configs.tailscale_device_policies.is_empty() {
+        get_default_tailscale_device_policies()
+    } else {
+        configs.tailscale_device_policies
+    };
+    policies.retain(|name, policy| match policy.validate() {
+        Ok(()) => true,
+        Err(e) => {
+            println!("[tailscale] skipping device policy {}: {}", name, e);
+            false
+        }
+    });
+    for (name, policy) in policies.iter_mut() {
+        policy.name = name.to_string();
+    }
+
+    let mut employees: BTreeSet<String> = Default::default();
+    for user in Users::get_from_db(db) {
+        employees.insert(user.username.to_lowercase());
+        employees.extend(user.aliases.iter().map(|a| a.to_lowercase()));
+    }
 
-/// When we generate VMs for the console repo on every branch we get lingering
-/// Tailscale devices that need to cleaned up when they are no longer active.
-/// This function does that.
-pub async fn cleanup_old_tailscale_devices() {
     // Initialize the Tailscale API.
     let tailscale = Tailscale::new_from_env();
     // Get the devices.
-    let devices = tailscale.list_devices().await.unwrap();
+    let devices = tailscale.list_devices().await.map_err(|e| format!("listing the tailscale devices failed: {}", e))?;
 
-    // Create the array of links.
-    for device in devices {
-        if !device.hostname.starts_with("console-git-") {
-            // Continue early.
-            // We only care about the hostnames we have from the console generated
-            // VMs.
+    let mut reports: BTreeMap<String, TailscaleDeviceReport> = TailscaleDeviceReports::get_from_db(db).into_iter().map(|r| (r.device_id.to_string(), r)).collect();
+
+    let mut findings = check_tailscale_devices(&devices, &policies, &employees, Utc::now());
+    for finding in findings.iter_mut() {
+        if finding.action == TailscaleDeviceAction::Delete {
+            finding.reported_at = reports.get(&finding.device_id).map(|r| r.reported_at);
+        }
+    }
+
+    if findings.is_empty() {
+        println!("[tailscale] all {} devices follow the policies", devices.len());
+    } else {
+        // Let folks know what we are about to do.
+        post_to_channel(get_infra_channel_post_url(), tailscale_devices_slack_msg(&findings, dry_run)).await;
+    }
+
+    for finding in &findings {
+        if finding.action != TailscaleDeviceAction::Delete || finding.dry_run || dry_run {
+            println!("[tailscale] {} {}", finding.action, finding);
             continue;
         }
 
-        let last_seen_duration = Utc::now() - device.last_seen;
-        if last_seen_duration > Duration::days(1) {
-            println!("Deleting tailscale device {}", device.name);
-            tailscale.delete_device(&device.id).await.unwrap();
+        let report = match reports.remove(&finding.device_id) {
+            Some(r) => r,
+            None => {
+                // Delete it on the next run, now that folks know about it.
+                println!("[tailscale] reporting {}", finding);
+                NewTailscaleDeviceReport::from(finding).upsert_in_db(db);
+                continue;
+            }
+        };
+
+        println!("[tailscale] deleting {}", finding);
+        tailscale
+            .delete_device(&finding.device_id)
+            .await
+            .map_err(|e| format!("deleting tailscale device {} failed: {}", finding.device_name, e))?;
+        report.delete_from_db(db);
+    }
+
+    if !dry_run {
+        // The devices left were used again, changed or deleted by hand, so they start
+        // over if they break a policy again.
+        for report in reports.values() {
+            report.delete_from_db(db);
         }
     }
+
+    Ok(findings)
+}
+
+/// Get the Tailscale ACL groups for our groups, as `group:{name}` with the emails of
+/// the members. Groups without members are left out, since Tailscale has nothing to match.
+pub fn get_tailscale_acl_groups(users: &[User], groups: &[Group]) -> BTreeMap<String, Vec<String>> {
+    let mut acl_groups: BTreeMap<String, Vec<String>> = Default::default();
+    for group in groups {
+        let mut members: Vec<String> = users.iter().filter(|u| u.groups.contains(&group.name)).map(|u| u.email()).collect();
+        if members.is_empty() {
+            continue;
+        }
+
+        members.sort();
+        acl_groups.insert(format!("group:{}", group.name), members);
+    }
+
+    acl_groups
+}
+
+/// Replace the ACL groups the configs repo owns, `group:{name}` for each of our groups,
+/// with the generated ones. Any other groups were made by hand and are kept, since the
+/// rules and tag owners can refer to them.
+pub fn merge_tailscale_acl_groups(existing: &BTreeMap<String, Vec<String>>, groups: &[Group], generated: BTreeMap<String, Vec<String>>) -> BTreeMap<String, Vec<String>> {
+    let owned: BTreeSet<String> = groups.iter().map(|g| format!("group:{}", g.name)).collect();
+
+    let mut merged: BTreeMap<String, Vec<String>> = existing
+        .iter()
+        .filter(|(name, _)| !owned.contains(*name))
+        .map(|(name, members)| (name.to_string(), members.clone()))
+        .collect();
+    merged.extend(generated);
+
+    merged
+}
+
+/**
+ * Generate the Tailscale ACL file in the configs repo with the groups from the members of
+ * our groups, so the ACL rules can use `group:{name}`. The rest of the ACL, and any groups
+ * made by hand, are left as they are. Like the terraform files, the changes are reviewed
+ * and then applied, this does not update Tailscale.
+ *
+ * This function uses the users.toml and the groups.toml file in the configs repo for information.
+ */
+pub async fn generate_tailscale_acl(github: &Github, db: &Database) -> Result<(), String> {
+    let users: Vec<User> = Users::get_from_db(db).into_iter().collect();
+    let groups: Vec<Group> = Groups::get_from_db(db).into_iter().collect();
+
+    let repo = github.repo(github_org(), "configs");
+    let r = repo.get().await.map_err(|e| format!("getting the configs repo failed: {}", e))?;
+
+    // Start from the ACL in the configs repo, or the one in Tailscale if we do not have
+    // the file yet.
+    let (content, _) = get_file_content_from_repo(&repo, &r.default_branch, TAILSCALE_ACL_FILE).await;
+    let mut acl: Acl = if content.is_empty() {
+        let tailscale = Tailscale::new_from_env();
+        tailscale.get_acl().await.map_err(|e| format!("getting the tailscale acl failed: {}", e))?.0
+    } else {
+        serde_json::from_slice(&content).map_err(|e| format!("parsing {} failed: {}", TAILSCALE_ACL_FILE, e))?
+    };
+
+    acl.groups = merge_tailscale_acl_groups(&acl.groups, &groups, get_tailscale_acl_groups(&users, &groups));
+
+    let rendered = serde_json::to_string_pretty(&acl).unwrap();
+    create_or_update_file_in_github_repo(&repo, &r.default_branch, TAILSCALE_ACL_FILE, rendered.as_bytes().to_vec()).await;
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tailscale::cleanup_old_tailscale_devices;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use chrono::{DateTime, Duration, Utc};
+    use tailscale_api::{Acl, Device};
+
+    use crate::configs::{Group, User};
+    use crate::db::Database;
+    use crate::tailscale::{
+        check_tailscale_devices, enforce_tailscale_device_policies, get_default_tailscale_device_policies, get_tailscale_acl_groups, merge_tailscale_acl_groups, TailscaleDeviceAction,
+        TailscaleDevicePolicy, TAILSCALE_DEPARTED_EMPLOYEES_POLICY,
+    };
+    use crate::utils::authenticate_github_jwt;
+
+    fn device(hostname: &str, user: &str, tags: &[&str], last_seen: DateTime<Utc>, expires: DateTime<Utc>) -> Device {
+        serde_json::from_value(json!({
+            "id": format!("id-{}", hostname),
+            "name": format!("{}.example.com", hostname),
+            "hostname": hostname,
+            "user": user,
+            "tags": tags,
+            "created": last_seen - Duration::days(30),
+            "lastSeen": last_seen,
+            "expires": expires,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_tailscale_devices() {
+        let now = Utc::now();
+        let devices = vec![
+            device("console-git-old", "ci@oxidecomputer.com", &[], now - Duration::days(2), now + Duration::days(90)),
+            device("console-git-new", "ci@oxidecomputer.com", &[], now - Duration::hours(1), now + Duration::days(90)),
+            device("runner", "jess@oxidecomputer.com", &["tag:ci"], now, now + Duration::days(3)),
+            device("laptop", "departed@oxide.computer", &[], now, now + Duration::days(90)),
+            device("friend", "friend@example.com", &[], now, now + Duration::days(90)),
+        ];
+
+        let mut policies = get_default_tailscale_device_policies();
+        policies.insert(
+            "ci".to_string(),
+            TailscaleDevicePolicy {
+                name: "ci".to_string(),
+                tags: vec!["tag:ci".to_string()],
+                owners: vec!["ci@oxidecomputer.com".to_string()],
+                key_expiry_warning_days: 7,
+                ..Default::default()
+            },
+        );
+
+        let employees: BTreeSet<String> = vec!["ci".to_string(), "jess".to_string()].into_iter().collect();
+
+        let findings = check_tailscale_devices(&devices, &policies, &employees, now);
+        let summary: Vec<(TailscaleDeviceAction, &str, &str)> = findings.iter().map(|f| (f.action, f.device_name.as_str(), f.policy.as_str())).collect();
+        assert_eq!(
+            vec![
+                (TailscaleDeviceAction::Delete, "console-git-old.example.com", "console-git"),
+                (TailscaleDeviceAction::Flag, "laptop.example.com", TAILSCALE_DEPARTED_EMPLOYEES_POLICY),
+                (TailscaleDeviceAction::Flag, "runner.example.com", "ci"),
+            ],
+            summary
+        );
+        assert!(findings[2].reason.starts_with("owned by `jess@oxidecomputer.com`"));
+        assert!(findings[2].reason.contains("key expires in"));
+
+        // A device is only deleted once, even if more than one policy deletes it.
+        let mut overlapping = get_default_tailscale_device_policies();
+        let mut console = overlapping.get("console-git").unwrap().clone();
+        console.name = "console".to_string();
+        console.hostname_prefixes = vec!["console-".to_string()];
+        overlapping.insert("console".to_string(), console);
+        let findings = check_tailscale_devices(&devices, &overlapping, &employees, now);
+        assert_eq!(1, findings.iter().filter(|f| f.action == TailscaleDeviceAction::Delete).count());
+        assert_eq!("console", findings[0].policy);
+
+        // Dry run policies still report the devices.
+        let mut dry_run = get_default_tailscale_device_policies();
+        dry_run.get_mut("console-git").unwrap().dry_run = true;
+        let findings = check_tailscale_devices(&devices, &dry_run, &employees, now);
+        assert!(findings[0].dry_run);
+
+        // No policies match nothing, but departed employees are still flagged.
+        assert_eq!(1, check_tailscale_devices(&devices, &BTreeMap::new(), &employees, now).len());
+    }
+
+    #[test]
+    fn test_tailscale_device_policy_validate() {
+        for policy in get_default_tailscale_device_policies().values() {
+            assert_eq!(Ok(()), policy.validate());
+        }
+
+        // A policy without a hostname prefix or a tag would match every device.
+        let policy = TailscaleDevicePolicy {
+            name: "everything".to_string(),
+            hostname_prefixes: vec!["".to_string()],
+            max_idle_hours: 24,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+        assert!(!policy.matches(&device("laptop", "jess@oxidecomputer.com", &[], Utc::now(), Utc::now())));
+
+        let policy = TailscaleDevicePolicy {
+            name: "ci".to_string(),
+            tags: vec!["tag:ci".to_string()],
+            max_idle_hours: -1,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn test_get_tailscale_acl_groups() {
+        let user = |username: &str, groups: &[&str]| -> User {
+            serde_json::from_value(json!({
+                "first_name": username,
+                "last_name": "",
+                "username": username,
+                "groups": groups,
+            }))
+            .unwrap()
+        };
+        let group = |name: &str| -> Group { serde_json::from_value(json!({ "name": name })).unwrap() };
+
+        let users = vec![user("jess", &["eng", "all"]), user("bob", &["all"]), user("alice", &["eng"])];
+        let groups = vec![group("eng"), group("all"), group("empty")];
+
+        let acl_groups = get_tailscale_acl_groups(&users, &groups);
+        let mut expected: BTreeMap<String, Vec<String>> = Default::default();
+        expected.insert("group:all".to_string(), vec![users[1].email(), users[0].email()]);
+        expected.insert("group:eng".to_string(), vec![users[2].email(), users[0].email()]);
+        assert_eq!(expected, acl_groups);
+
+        // Groups made by hand are kept, ours are replaced, and ours without members are dropped.
+        let mut existing: BTreeMap<String, Vec<String>> = Default::default();
+        existing.insert("group:eng".to_string(), vec!["old@oxidecomputer.com".to_string()]);
+        existing.insert("group:empty".to_string(), vec!["old@oxidecomputer.com".to_string()]);
+        existing.insert("group:contractors".to_string(), vec!["someone@example.com".to_string()]);
+        let merged = merge_tailscale_acl_groups(&existing, &groups, acl_groups);
+        expected.insert("group:contractors".to_string(), vec!["someone@example.com".to_string()]);
+        assert_eq!(expected, merged);
+    }
+
+    #[test]
+    fn test_tailscale_acl_round_trip() {
+        let original = json!({
+            "acls": [
+                {"action": "accept", "src": ["group:eng"], "dst": ["tag:ci:*"], "proto": "tcp"},
+                {"Action": "accept", "Users": ["group:all"], "Ports": ["*:443"]},
+            ],
+            "groups": {"group:eng": ["jess@oxidecomputer.com"]},
+            "tagOwners": {"tag:ci": ["group:eng"]},
+            "tests": [{"src": "jess@oxidecomputer.com", "accept": ["tag:ci:22"]}],
+        });
+
+        let acl: Acl = serde_json::from_value(original).unwrap();
+        assert_eq!(json!(["tag:ci:*"]), acl.acls[0].other["dst"]);
+        assert_eq!(json!("tcp"), acl.acls[0].other["proto"]);
+
+        // Nothing we do not know about is dropped when we write the ACL back.
+        let rendered = serde_json::to_value(&acl).unwrap();
+        assert_eq!(json!(["group:eng"]), rendered["ACLs"][0]["src"]);
+        assert_eq!(json!("tcp"), rendered["ACLs"][0]["proto"]);
+        assert_eq!(json!(["*:443"]), rendered["ACLs"][1]["Ports"]);
+        assert_eq!(json!("jess@oxidecomputer.com"), rendered["tests"][0]["src"]);
+        assert_eq!(acl, serde_json::from_value::<Acl>(rendered).unwrap());
+    }
 
     #[ignore]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_cron_tailscale() {
-        cleanup_old_tailscale_devices().await;
+        let db = Database::new();
+        let github = authenticate_github_jwt();
+        // Set dry-run on a policy in the configs repo to only report what it would delete.
+        enforce_tailscale_device_policies(&db, &github, false).await.unwrap();
     }
 }
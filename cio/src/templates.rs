@@ -3,7 +3,7 @@ use hubcaps::repositories::Repository;
 use hubcaps::Github;
 use serde::{Deserialize, Serialize};
 
-use crate::configs::{Groups, User, Users};
+use crate::configs::{Buildings, ConferenceRooms, Group, Groups, User, Users};
 use crate::db::Database;
 use crate::shorturls::ShortUrl;
 use crate::utils::{create_or_update_file_in_github_repo, github_org};
@@ -41,6 +41,28 @@ struct GitHubTeamMembers {
     pub members: Vec<User>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct GroupMembers {
+    pub group: Group,
+    pub members: Vec<User>,
+}
+
+/// Get each of the groups along with the users that are members of it.
+fn get_group_members(groups: &[Group], users: &[User]) -> Vec<GroupMembers> {
+    groups
+        .iter()
+        .map(|group| GroupMembers {
+            group: group.clone(),
+            members: users.iter().filter(|u| u.groups.contains(&group.name)).cloned().collect(),
+        })
+        .collect()
+}
+
+/// Returns if a user has a Slack account we can look up by their email.
+fn has_slack_account(user: &User) -> bool {
+    !user.is_system_account() && !user.is_consultant()
+}
+
 /**
  * Generate Okta terraform configs that configure members of the
  * organization, groups, and group membership. We use terraform instead of calling out the
@@ -80,6 +102,93 @@ pub async fn generate_terraform_files_for_okta(github: &Github, db: &Database) {
     create_or_update_file_in_github_repo(&repo, &r.default_branch, &groups_file, groups_rendered.as_bytes().to_vec()).await;
 }
 
+/**
+ * Generate Google Workspace terraform configs for the groups, their settings and
+ * membership, as well as the buildings and conference rooms, so the diffs of the files
+ * after changes can be reviewed.
+ *
+ * `sync_groups` and `sync_conference_rooms` still apply these through the api, so the
+ * files only have locals. Terraform resources for the same groups would fight the sync
+ * over who owns them.
+ *
+ * This function uses the users.toml, groups.toml, buildings.toml and resources.toml files
+ * in the configs repo for information.
+ */
+pub async fn generate_terraform_files_for_gsuite(github: &Github, db: &Database) {
+    let users: Vec<User> = Users::get_from_db(db).into_iter().collect();
+    let groups: Vec<Group> = Groups::get_from_db(db).into_iter().collect();
+    let buildings = Buildings::get_from_db(db);
+    let conference_rooms = ConferenceRooms::get_from_db(db);
+
+    let repo = github.repo(github_org(), "configs");
+    let r = repo.get().await.unwrap();
+
+    // Set the paths for the files.
+    let gsuite_path = "terraform/gsuite";
+
+    // Initialize handlebars.
+    let mut handlebars = Handlebars::new();
+    handlebars.register_helper("terraformize", Box::new(terraform_username_helper));
+
+    // Generate the groups file.
+    let groups_rendered = handlebars.render_template(&TEMPLATE_TERRAFORM_GSUITE_GROUP, &get_group_members(&groups, &users)).unwrap();
+
+    // Join it with the directory to save the files in.
+    let groups_file = format!("{}/generated.groups.tf", gsuite_path);
+
+    create_or_update_file_in_github_repo(&repo, &r.default_branch, &groups_file, groups_rendered.as_bytes().to_vec()).await;
+
+    // Generate the buildings and conference rooms file.
+    let resources_rendered = handlebars
+        .render_template(&TEMPLATE_TERRAFORM_GSUITE_RESOURCES, &json!({"buildings": buildings, "conference_rooms": conference_rooms}))
+        .unwrap();
+
+    // Join it with the directory to save the files in.
+    let resources_file = format!("{}/generated.resources.tf", gsuite_path);
+
+    create_or_update_file_in_github_repo(&repo, &r.default_branch, &resources_file, resources_rendered.as_bytes().to_vec()).await;
+}
+
+/**
+ * Generate Slack terraform configs that configure a user group for each of our groups.
+ * We use terraform instead of calling out the api ourselves because the diffs of the
+ * files after changes are more readable than not having that functionality at all.
+ *
+ * System accounts and consultants do not have Slack accounts, so they are left out.
+ *
+ * This function uses the users.toml and the groups.toml file in the configs repo for information.
+ */
+pub async fn generate_terraform_files_for_slack(github: &Github, db: &Database) {
+    let users: Vec<User> = Users::get_from_db(db).into_iter().filter(has_slack_account).collect();
+    let groups: Vec<Group> = Groups::get_from_db(db).into_iter().collect();
+
+    let repo = github.repo(github_org(), "configs");
+    let r = repo.get().await.unwrap();
+
+    // Set the paths for the files.
+    let slack_path = "terraform/slack";
+
+    // Initialize handlebars.
+    let mut handlebars = Handlebars::new();
+    handlebars.register_helper("terraformize", Box::new(terraform_username_helper));
+
+    // Generate the users file, so the user groups can look up the Slack IDs.
+    let users_rendered = handlebars.render_template(&TEMPLATE_TERRAFORM_SLACK_USER, &users).unwrap();
+
+    // Join it with the directory to save the files in.
+    let users_file = format!("{}/generated.users.tf", slack_path);
+
+    create_or_update_file_in_github_repo(&repo, &r.default_branch, &users_file, users_rendered.as_bytes().to_vec()).await;
+
+    // Generate the user groups file.
+    let usergroups_rendered = handlebars.render_template(&TEMPLATE_TERRAFORM_SLACK_USERGROUP, &get_group_members(&groups, &users)).unwrap();
+
+    // Join it with the directory to save the files in.
+    let usergroups_file = format!("{}/generated.usergroups.tf", slack_path);
+
+    create_or_update_file_in_github_repo(&repo, &r.default_branch, &usergroups_file, usergroups_rendered.as_bytes().to_vec()).await;
+}
+
 /**
  * Generate GitHub and AWS terraform configs that configure members of the
  * organization and team membership. We use terraform instead of calling out the
@@ -453,3 +562,210 @@ resource "okta_group" "{{terraformize this.name}}" {
 }
 {{/if}}{{/each}}
 "#;
+
+/// Template for the Google Workspace groups, their settings and membership.
+pub static TEMPLATE_TERRAFORM_GSUITE_GROUP: &str = r#"# THIS IS A GENERATED FILE, DO NOT EDIT THIS FILE DIRECTLY.
+# The groups are applied by the CIO bot, so these are not terraform resources that
+# would fight it over the same groups. They are here so the changes can be reviewed.
+locals {
+  groups = {
+{{#each this}}    "{{this.group.name}}" = {
+      email       = "{{this.group.name}}@oxidecomputer.com"
+      description = "{{this.group.description}}"
+      aliases     = [{{#each this.group.aliases}}"{{this}}@oxidecomputer.com"{{#if @last}}{{else}}, {{/if}}{{/each}}]
+      settings = {
+        allow_external_members     = {{this.group.allow_external_members}}
+        allow_web_posting          = {{this.group.allow_web_posting}}
+        is_archived                = {{this.group.is_archived}}
+        enable_collaborative_inbox = {{this.group.enable_collaborative_inbox}}{{#if this.group.who_can_discover_group}}
+        who_can_discover_group     = "{{this.group.who_can_discover_group}}"{{/if}}{{#if this.group.who_can_join}}
+        who_can_join               = "{{this.group.who_can_join}}"{{/if}}{{#if this.group.who_can_moderate_members}}
+        who_can_moderate_members   = "{{this.group.who_can_moderate_members}}"{{/if}}{{#if this.group.who_can_post_message}}
+        who_can_post_message       = "{{this.group.who_can_post_message}}"{{/if}}{{#if this.group.who_can_view_group}}
+        who_can_view_group         = "{{this.group.who_can_view_group}}"{{/if}}{{#if this.group.who_can_view_membership}}
+        who_can_view_membership    = "{{this.group.who_can_view_membership}}"{{/if}}
+      }
+      members = {
+{{#each this.members}}        "{{this.username}}@oxidecomputer.com" = "{{#if this.is_group_admin}}OWNER{{else}}MEMBER{{/if}}"
+{{/each}}      }
+    }
+{{/each}}  }
+}
+"#;
+
+/// Template for the Google Workspace buildings and conference rooms.
+pub static TEMPLATE_TERRAFORM_GSUITE_RESOURCES: &str = r#"# THIS IS A GENERATED FILE, DO NOT EDIT THIS FILE DIRECTLY.
+# The Google Workspace provider does not manage calendar resources, these are
+# applied by the CIO bot. They are here so the changes can be reviewed.
+locals {
+  buildings = {
+{{#each this.buildings}}    "{{this.name}}" = {
+      description = "{{this.description}}"
+      address     = "{{this.street_address}}, {{this.city}}, {{this.state}} {{this.zipcode}}, {{this.country}}"
+      floors      = [{{#each this.floors}}"{{this}}"{{#if @last}}{{else}}, {{/if}}{{/each}}]
+    }
+{{/each}}  }
+
+  conference_rooms = {
+{{#each this.conference_rooms}}    "{{this.name}}" = {
+      description = "{{this.description}}"
+      type        = "{{this.type}}"
+      building    = "{{this.building}}"
+      floor       = "{{this.floor}}"
+      section     = "{{this.section}}"
+      capacity    = {{this.capacity}}
+    }
+{{/each}}  }
+}
+"#;
+
+/// Template for looking up the Slack users in terraform.
+pub static TEMPLATE_TERRAFORM_SLACK_USER: &str = r#"# THIS IS A GENERATED FILE, DO NOT EDIT THIS FILE DIRECTLY.
+# Look up the Slack users for the members of the organization.
+{{#each this}}
+data "slack_user" "{{terraformize this.username}}" {
+  email = "{{this.username}}@oxidecomputer.com"
+}
+{{/each}}
+"#;
+
+/// Template for terraform Slack user groups.
+pub static TEMPLATE_TERRAFORM_SLACK_USERGROUP: &str = r#"# THIS IS A GENERATED FILE, DO NOT EDIT THIS FILE DIRECTLY.
+# Define the user groups in Slack.
+{{#each this}}{{#if (eq this.group.name "everyone")}}{{else}}{{#if this.members}}
+# Add @{{this.group.name}} as a user group in Slack.
+resource "slack_usergroup" "{{terraformize this.group.name}}" {
+  name        = "{{this.group.name}}"
+  handle      = "{{this.group.name}}"
+  description = "{{this.group.description}}"
+  users = [{{#each this.members}}
+    data.slack_user.{{terraformize this.username}}.id,{{/each}}
+  ]
+}
+{{/if}}{{/if}}{{/each}}
+"#;
+
+/// Template for the weekly RFD digest email.
+pub static TEMPLATE_RFD_DIGEST: &str = r#"<html>
+<body style="font-family: sans-serif;">
+<h2>RFD digest for the week {{week}}</h2>
+{{#if new_rfds}}
+<h3>New RFDs</h3>
+<ul>
+{{#each new_rfds}}  <li><a href="{{this.short_link}}">RFD {{this.number}} {{this.name}}</a> ({{this.state}})</li>
+{{/each}}</ul>
+{{/if}}{{#if state_changes}}
+<h3>State changes</h3>
+<ul>
+{{#each state_changes}}  <li><a href="{{this.rfd.short_link}}">RFD {{this.rfd.number}} {{this.rfd.name}}</a> moved from <b>{{this.from_state}}</b> to <b>{{this.to_state}}</b>{{#if this.changed_by}} by {{this.changed_by}}{{/if}}</li>
+{{/each}}</ul>
+{{/if}}{{#if new_discussions}}
+<h3>Newly opened for discussion</h3>
+<ul>
+{{#each new_discussions}}  <li><a href="{{this.short_link}}">RFD {{this.number}} {{this.name}}</a>: <a href="{{this.discussion}}">join the discussion</a></li>
+{{/each}}</ul>
+{{/if}}{{#if most_commented}}
+<h3>Most active discussions</h3>
+<ul>
+{{#each most_commented}}  <li><a href="{{this.rfd.discussion}}">RFD {{this.rfd.number}} {{this.rfd.name}}</a>: {{this.comments}} comments</li>
+{{/each}}</ul>
+{{/if}}{{#if changes}}
+<h3>Changes</h3>
+{{#each changes}}
+<h4><a href="{{this.rfd.short_link}}">RFD {{this.rfd.number}} {{this.rfd.name}}</a></h4>
+{{#each this.commits}}
+<p><a href="{{this.link}}">{{this.message}}</a> by {{this.author}} on {{this.branch}}</p>
+{{#if this.diff}}<pre style="background: #f6f8fa; padding: 8px; font-size: 12px;">{{this.diff}}</pre>{{/if}}
+{{/each}}{{/each}}
+{{/if}}{{#if broken_references}}
+<h3>Broken references</h3>
+<ul>
+{{#each broken_references}}  <li>{{this}}</li>
+{{/each}}</ul>
+{{/if}}
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use crate::configs::{Group, User};
+    use crate::templates::{get_group_members, has_slack_account, terraform_username_helper, TEMPLATE_TERRAFORM_GSUITE_GROUP, TEMPLATE_TERRAFORM_SLACK_USER, TEMPLATE_TERRAFORM_SLACK_USERGROUP};
+
+    fn user(username: &str, typev: &str, groups: &[&str], is_group_admin: bool) -> User {
+        serde_json::from_value(json!({
+            "first_name": username,
+            "last_name": "",
+            "username": username,
+            "type": typev,
+            "groups": groups,
+            "is_group_admin": is_group_admin,
+        }))
+        .unwrap()
+    }
+
+    fn group(name: &str) -> Group {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": format!("The {} group", name),
+            "aliases": [format!("{}-team", name)],
+            "who_can_join": "INVITED_CAN_JOIN",
+        }))
+        .unwrap()
+    }
+
+    fn handlebars() -> Handlebars<'static> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("terraformize", Box::new(terraform_username_helper));
+        handlebars
+    }
+
+    #[test]
+    fn test_render_terraform_gsuite_groups() {
+        let users = vec![user("jess", "full-time", &["eng"], true), user("bob.smith", "full-time", &["eng"], false)];
+        let groups = vec![group("eng")];
+
+        let rendered = handlebars().render_template(TEMPLATE_TERRAFORM_GSUITE_GROUP, &get_group_members(&groups, &users)).unwrap();
+
+        // The groups are applied by sync_groups, so terraform must not manage them.
+        assert!(!rendered.contains("resource "));
+        assert!(rendered.contains(r#"    "eng" = {"#));
+        assert!(rendered.contains(r#"aliases     = ["eng-team@oxidecomputer.com"]"#));
+        assert!(rendered.contains(r#"who_can_join               = "INVITED_CAN_JOIN""#));
+        assert!(!rendered.contains("who_can_post_message"));
+        assert!(rendered.contains(r#""jess@oxidecomputer.com" = "OWNER""#));
+        assert!(rendered.contains(r#""bob.smith@oxidecomputer.com" = "MEMBER""#));
+    }
+
+    #[test]
+    fn test_render_terraform_slack() {
+        let users: Vec<User> = vec![
+            user("jess", "full-time", &["eng", "everyone"], false),
+            user("bob.smith", "full-time", &["eng"], false),
+            user("builder", "system account", &["eng"], false),
+            user("contractor", "consultant", &["eng"], false),
+        ]
+        .into_iter()
+        .filter(has_slack_account)
+        .collect();
+        let groups = vec![group("eng"), group("everyone"), group("empty")];
+
+        let rendered = handlebars().render_template(TEMPLATE_TERRAFORM_SLACK_USER, &users).unwrap();
+        assert!(rendered.contains(r#"data "slack_user" "jess" {"#));
+        assert!(rendered.contains(r#"data "slack_user" "bobsmith" {"#));
+        assert!(rendered.contains(r#"email = "bob.smith@oxidecomputer.com""#));
+        assert!(!rendered.contains("builder"));
+        assert!(!rendered.contains("contractor"));
+
+        let rendered = handlebars().render_template(TEMPLATE_TERRAFORM_SLACK_USERGROUP, &get_group_members(&groups, &users)).unwrap();
+        assert!(rendered.contains(r#"resource "slack_usergroup" "eng" {"#));
+        assert!(rendered.contains("data.slack_user.jess.id,"));
+        assert!(rendered.contains("data.slack_user.bobsmith.id,"));
+        assert!(!rendered.contains("builder"));
+        // Slack already has everyone, and groups without members are left out.
+        assert!(!rendered.contains(r#""everyone""#));
+        assert!(!rendered.contains(r#""empty""#));
+    }
+}
@@ -9,6 +9,7 @@ use std::thread;
 use std::time;
 
 use futures_util::stream::TryStreamExt;
+use google_drive::GoogleDrive;
 use hubcaps::http_cache::FileBasedCache;
 use hubcaps::issues::Issue;
 use hubcaps::repositories::{OrgRepoType, OrganizationRepoListOptions, Repository};
@@ -358,6 +359,26 @@ pub async fn create_or_update_file_in_github_repo(repo: &Repository, branch: &st
     println!("[github content] Created file at {}", file_path);
 }
 
+/// Get the ID of the "Automated Documents" shared drive and the ID of a folder at the
+/// root of it, creating the folder if it does not exist yet.
+pub async fn get_or_create_automated_documents_folder(drive_client: &GoogleDrive, name: &str) -> Result<(String, String), String> {
+    let shared_drive = drive_client
+        .get_drive_by_name("Automated Documents")
+        .await
+        .map_err(|e| format!("getting the shared drive failed: {}", e))?;
+    let drive_id = shared_drive.id.to_string();
+
+    let folder_id = match drive_client.get_file_by_name(&drive_id, name).await {
+        Ok(folders) if !folders.is_empty() => folders[0].id.to_string(),
+        _ => drive_client
+            .create_folder(&drive_id, "", name)
+            .await
+            .map_err(|e| format!("creating the {} folder failed: {}", name, e))?,
+    };
+
+    Ok((drive_id, folder_id))
+}
+
 trait SliceExt {
     fn trim(&self) -> Self;
 }
@@ -384,15 +405,70 @@ impl SliceExt for Vec<u8> {
     }
 }
 
+/// Returns if the secret sent with a request matches the one in the environment
+/// variable. If the environment variable is not set, nothing matches.
+pub fn is_shared_secret_valid(env_var: &str, given: &str) -> bool {
+    let secret = env::var(env_var).unwrap_or_default();
+    !secret.is_empty() && secret.len() == given.len() && openssl::memcmp::eq(secret.as_bytes(), given.as_bytes())
+}
+
+/// Returns the config entry that ranks highest, ie. the most specific one that
+/// matches. If more than one ranks highest, the first one wins, so with entries
+/// from a `BTreeMap` the first by name wins.
+pub fn get_most_specific<T, K: Ord>(entries: impl IntoIterator<Item = T>, rank: impl Fn(&T) -> K) -> Option<T> {
+    let mut best: Option<(K, T)> = None;
+    for entry in entries {
+        let r = rank(&entry);
+        if best.as_ref().map(|(b, _)| r > *b).unwrap_or(true) {
+            best = Some((r, entry));
+        }
+    }
+
+    best.map(|(_, entry)| entry)
+}
+
 pub fn default_date() -> chrono::naive::NaiveDate {
     chrono::naive::NaiveDate::parse_from_str("1970-01-01", "%Y-%m-%d").unwrap()
 }
 
+/// Return the spreadsheet column letters for a zero-based column index,
+/// so 0 is "A", 25 is "Z", 26 is "AA" and so on.
+pub fn get_column_letters(index: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = index + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push((b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::db::Database;
     use crate::models::GithubRepos;
-    use crate::utils::{authenticate_github_jwt, refresh_db_github_repos};
+    use crate::utils::{authenticate_github_jwt, get_column_letters, get_most_specific, refresh_db_github_repos};
+
+    #[test]
+    fn test_get_column_letters() {
+        assert_eq!(get_column_letters(0), "A");
+        assert_eq!(get_column_letters(25), "Z");
+        assert_eq!(get_column_letters(26), "AA");
+        assert_eq!(get_column_letters(27), "AB");
+        assert_eq!(get_column_letters(51), "AZ");
+        assert_eq!(get_column_letters(52), "BA");
+        assert_eq!(get_column_letters(701), "ZZ");
+        assert_eq!(get_column_letters(702), "AAA");
+    }
+
+    #[test]
+    fn test_get_most_specific() {
+        let entries = vec![("a", 1), ("b", 2), ("c", 2), ("d", 0)];
+        assert_eq!(get_most_specific(entries.clone(), |(_, r)| *r), Some(("b", 2)));
+        assert_eq!(get_most_specific(entries.into_iter().filter(|(n, _)| *n != "b"), |(_, r)| *r), Some(("c", 2)));
+        assert_eq!(get_most_specific(Vec::<(&str, i32)>::new(), |(_, r)| *r), None);
+    }
 
     #[ignore]
     #[tokio::test(flavor = "multi_thread")]
@@ -170,7 +170,7 @@ impl DocuSign {
             Method::GET,
             &format!("accounts/{}/envelopes/{}", self.jwt_config.account_id, envelope_id),
             (),
-            Some(&[("include", "documents")]),
+            Some(&[("include", "documents,recipients")]),
         );
 
         let resp = self.client.execute(request).await.unwrap();
@@ -221,6 +221,7 @@ impl DocuSign {
             "Signed".to_string(),
             "Voided".to_string(),
         ];
+        connect.recipient_events = vec!["Sent".to_string(), "Delivered".to_string(), "Completed".to_string(), "Declined".to_string()];
         connect.all_users = "true".to_string();
         connect.include_document_fields = "true".to_string();
         connect.name = "CIO Webhook".to_string();
@@ -231,7 +232,7 @@ impl DocuSign {
         connect.use_soap_interface = "false".to_string();
         connect.event_data = WebhookEventData {
             format: "json".to_string(),
-            include_data: vec!["documents".to_string(), "attachments".to_string(), "custom_fields".to_string()],
+            include_data: vec!["documents".to_string(), "attachments".to_string(), "custom_fields".to_string(), "recipients".to_string()],
             version: "restv2.1".to_string(),
         };
 
@@ -281,6 +282,32 @@ impl DocuSign {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Resend an envelope, this sends the email notification again to the
+    /// recipients who have not yet signed.
+    pub async fn resend_envelope(&self, envelope_id: &str) -> Result<(), APIError> {
+        // Build the request.
+        let body: BTreeMap<String, String> = Default::default();
+        let request = self.request(
+            Method::PUT,
+            &format!("accounts/{}/envelopes/{}", self.jwt_config.account_id, envelope_id),
+            body,
+            Some(&[("resend_envelope", "true")]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
     /// Get envelope form fields.
     pub async fn get_envelope_form_data(&self, envelope_id: &str) -> Result<Vec<FormDatum>, APIError> {
         // Build the request.
@@ -412,6 +439,18 @@ pub struct Envelope {
     pub declined_date_time: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "deliveredDateTime")]
     pub delivered_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "sentDateTime")]
+    pub sent_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "statusChangedDateTime")]
+    pub status_changed_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "voidedDateTime")]
+    pub voided_date_time: Option<DateTime<Utc>>,
+    /// The reason the envelope was voided. Envelopes that expire are voided
+    /// by DocuSign with a reason saying so.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "voidedReason")]
+    pub voided_reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "expireDateTime")]
+    pub expire_date_time: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "transactionId")]
     pub transaction_id: String,
     /// Indicates the envelope status. Valid values are:
@@ -534,6 +573,22 @@ pub struct Recipient {
     /// Unique for the recipient. It is used by the tab element to indicate which recipient is to sign the Document.
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "recipientId")]
     pub recipient_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "routingOrder")]
+    pub routing_order: String,
+    /// The status of the recipient: `created`, `sent`, `delivered`, `signed`, `declined`,
+    /// `completed`, `faxpending` or `autoresponded`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "sentDateTime")]
+    pub sent_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "deliveredDateTime")]
+    pub delivered_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "signedDateTime")]
+    pub signed_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "declinedDateTime")]
+    pub declined_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "declinedReason")]
+    pub declined_reason: String,
 }
 
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
@@ -563,6 +618,27 @@ pub struct TemplateRole {
     pub routing_order: String,
     #[serde(default, rename = "emailNotification")]
     pub email_notification: EmailNotification,
+    /// The values to fill in for the tabs that belong to the role in the template.
+    #[serde(default)]
+    pub tabs: Tabs,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct Tabs {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "textTabs")]
+    pub text_tabs: Vec<Tab>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct Tab {
+    /// The label of the tab in the template, this is how the value is matched to the tab.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabLabel")]
+    pub tab_label: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+    /// When set to `true`, the signer cannot change the value of the tab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locked: String,
 }
 
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
@@ -197,7 +197,18 @@ impl GoogleDrive {
     /// Get a file by it's ID.
     pub async fn get_file_by_id(&self, id: &str) -> Result<File, APIError> {
         // Build the request.
-        let request = self.request(Method::GET, format!("files/{}", id), (), Some(vec![("supportsAllDrives", "true".to_string())]), &[], "");
+        let request = self.request(
+            Method::GET,
+            format!("files/{}", id),
+            (),
+            Some(vec![
+                ("supportsAllDrives", "true".to_string()),
+                // Include the fields that tell us if the contents changed, along with the defaults.
+                ("fields", "kind,id,name,mimeType,md5Checksum,modifiedTime,version".to_string()),
+            ]),
+            &[],
+            "",
+        );
 
         let resp = self.client.execute(request).await.unwrap();
         match resp.status() {
@@ -416,13 +427,113 @@ impl GoogleDrive {
         }
 
         let file = files.get(0).unwrap().clone();
+        self.delete_file_by_id(&file.id).await
+    }
+
+    /// Delete a file by its ID.
+    pub async fn delete_file_by_id(&self, id: &str) -> Result<(), APIError> {
         // Make the request.
-        let request = self.request(Method::DELETE, format!("files/{}", file.id), (), Some(vec![("includeItemsFromAllDrives", "true".to_string())]), &[], "");
+        let request = self.request(Method::DELETE, format!("files/{}", id), (), Some(vec![("supportsAllDrives", "true".to_string())]), &[], "");
 
         let resp = self.client.execute(request).await.unwrap();
         match resp.status() {
             StatusCode::OK => (),
             StatusCode::CREATED => (),
+            StatusCode::NO_CONTENT => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                });
+            }
+        };
+
+        Ok(())
+    }
+
+    /// List the permissions on a file.
+    pub async fn list_permissions(&self, file_id: &str) -> Result<Vec<Permission>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            format!("files/{}/permissions", file_id),
+            (),
+            Some(vec![
+                ("supportsAllDrives", "true".to_string()),
+                ("pageSize", "100".to_string()),
+                ("fields", "permissions(id,type,role,emailAddress,permissionDetails)".to_string()),
+            ]),
+            &[],
+            "",
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                });
+            }
+        };
+
+        // Try to deserialize the response.
+        let permissions_response: PermissionsResponse = resp.json().await.unwrap();
+
+        Ok(permissions_response.permissions)
+    }
+
+    /// Share a file with a user.
+    /// The role is one of "reader", "commenter" or "writer". If `notify` is true, Google
+    /// emails the user a link to the file.
+    pub async fn create_user_permission(&self, file_id: &str, email: &str, role: &str, notify: bool) -> Result<Permission, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            format!("files/{}/permissions", file_id),
+            NewPermission {
+                type_: "user".to_string(),
+                role: role.to_string(),
+                email_address: email.to_string(),
+            },
+            Some(vec![("supportsAllDrives", "true".to_string()), ("sendNotificationEmail", notify.to_string())]),
+            &[],
+            "",
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                });
+            }
+        };
+
+        // Try to deserialize the response.
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Delete a permission from a file.
+    pub async fn delete_permission(&self, file_id: &str, permission_id: &str) -> Result<(), APIError> {
+        // Make the request.
+        let request = self.request(
+            Method::DELETE,
+            format!("files/{}/permissions/{}", file_id, permission_id),
+            (),
+            Some(vec![("supportsAllDrives", "true".to_string())]),
+            &[],
+            "",
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::NO_CONTENT => (),
             s => {
                 return Err(APIError {
                     status_code: s,
@@ -501,6 +612,24 @@ struct FilesResponse {
     pub files: Vec<File>,
 }
 
+/// From: https://developers.google.com/drive/api/v3/reference/permissions/list
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+struct PermissionsResponse {
+    /// The list of permissions. We only ask for the first page, files have far fewer permissions than that.
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// From: https://developers.google.com/drive/api/v3/reference/permissions/create
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+struct NewPermission {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub role: String,
+    #[serde(rename = "emailAddress")]
+    pub email_address: String,
+}
+
 /// From: https://developers.google.com/drive/api/v3/reference/drives/list
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 struct DrivesResponse {
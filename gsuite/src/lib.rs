@@ -18,7 +18,9 @@
  *     // Get the GSuite credentials file.
  *     let gsuite_credential_file = env::var("GADMIN_CREDENTIAL_FILE").unwrap();
  *     let gsuite_subject = env::var("GADMIN_SUBJECT").unwrap();
- *     let gsuite_secret = read_service_account_key(gsuite_credential_file).await.expect("failed to read gsuite credential file");
+ *     let gsuite_secret = read_service_account_key(gsuite_credential_file)
+ *         .await
+ *         .expect("failed to read gsuite credential file");
  *     let auth = ServiceAccountAuthenticator::builder(gsuite_secret)
  *         .subject(gsuite_subject.to_string())
  *         .build()
@@ -819,6 +821,42 @@ impl GSuite {
         Ok(value.items)
     }
 
+    /// List the out of office events on a calendar that overlap with the time range.
+    pub async fn list_out_of_office_events(&self, calendar_id: &str, time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> Result<Vec<CalendarEvent>, APIError> {
+        // Build the request.
+        let request = self.request(
+            CALENDAR_ENDPOINT,
+            Method::GET,
+            &format!("calendars/{}/events", calendar_id),
+            (),
+            Some(&[
+                ("singleEvents", "true"),
+                ("maxResults", "2500"),
+                ("eventTypes", "outOfOffice"),
+                ("timeMin", &time_min.to_rfc3339()),
+                ("timeMax", &time_max.to_rfc3339()),
+            ]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                });
+            }
+        };
+
+        // Try to deserialize the response.
+        let value: CalendarEvents = resp.json().await.unwrap();
+
+        // Filter out anything that is not out of office, in case the event types
+        // filter is not honored.
+        Ok(value.items.into_iter().filter(|e| e.event_type == "outOfOffice").collect())
+    }
+
     /// List past events on a calendar.
     pub async fn list_past_calendar_events(&self, calendar_id: &str) -> Result<Vec<CalendarEvent>, APIError> {
         // Build the request.
@@ -847,10 +885,18 @@ impl GSuite {
         Ok(value.items)
     }
 
-    /// Create a calendar event.
-    pub async fn create_calendar_event(&self, calendar_id: &str, event: &CalendarEvent) -> Result<CalendarEvent, APIError> {
+    /// Create a calendar event. If `send_updates` is true, the attendees get an
+    /// invitation. A conference is created for the event if it has a create
+    /// request in its conference data.
+    pub async fn create_calendar_event(&self, calendar_id: &str, event: &CalendarEvent, send_updates: bool) -> Result<CalendarEvent, APIError> {
         // Build the request.
-        let request = self.request(CALENDAR_ENDPOINT, Method::POST, &format!("calendars/{}/events", calendar_id), event, None);
+        let request = self.request(
+            CALENDAR_ENDPOINT,
+            Method::POST,
+            &format!("calendars/{}/events", calendar_id),
+            event,
+            Some(&[("conferenceDataVersion", "1"), ("sendUpdates", if send_updates { "all" } else { "none" })]),
+        );
 
         let resp = self.client.execute(request).await.unwrap();
         match resp.status() {
@@ -929,6 +975,42 @@ impl GSuite {
 
         Ok(())
     }
+
+    /// Get the busy times on calendars between `time_min` and `time_max`, by calendar ID.
+    /// The calendar ID of a user's primary calendar is their email address.
+    /// FROM: https://developers.google.com/calendar/v3/reference/freebusy/query
+    pub async fn get_free_busy(&self, time_min: DateTime<Utc>, time_max: DateTime<Utc>, calendar_ids: &[String]) -> Result<HashMap<String, FreeBusyCalendar>, APIError> {
+        let mut calendars: HashMap<String, FreeBusyCalendar> = HashMap::new();
+
+        // We can only ask for 50 calendars at a time.
+        for ids in calendar_ids.chunks(50) {
+            let body = FreeBusyRequest {
+                time_min,
+                time_max,
+                items: ids.iter().map(|id| FreeBusyRequestItem { id: id.to_string() }).collect(),
+            };
+
+            // Build the request.
+            let request = self.request(CALENDAR_ENDPOINT, Method::POST, "freeBusy", body, None);
+
+            let resp = self.client.execute(request).await.unwrap();
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    return Err(APIError {
+                        status_code: s,
+                        body: resp.text().await.unwrap(),
+                    });
+                }
+            };
+
+            // Try to deserialize the response.
+            let value: FreeBusyResponse = resp.json().await.unwrap();
+            calendars.extend(value.calendars);
+        }
+
+        Ok(calendars)
+    }
 }
 
 /// Error type returned by our library.
@@ -1081,6 +1163,120 @@ pub struct CalendarEvent {
     pub attachments: Vec<Attachment>,
     #[serde(default)]
     pub organizer: EventOrganizer,
+    /// The type of the event, for example "default" or "outOfOffice".
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "eventType")]
+    pub event_type: String,
+    /// The link to the Google Meet for the event, if it has one.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "hangoutLink")]
+    pub hangout_link: String,
+    /// The conference for the event, ie. a Google Meet.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "conferenceData")]
+    pub conference_data: Option<ConferenceData>,
+}
+
+/// The conference for a calendar event.
+/// FROM: https://developers.google.com/calendar/v3/reference/events#conferenceData
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ConferenceData {
+    /// Set this to have a new conference created for the event.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "createRequest")]
+    pub create_request: Option<CreateConferenceRequest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "entryPoints")]
+    pub entry_points: Vec<ConferenceEntryPoint>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "conferenceId")]
+    pub conference_id: String,
+}
+
+impl ConferenceData {
+    /// Conference data that asks for a new Google Meet to be created for the event.
+    /// The request ID must be unique for each event.
+    pub fn new_google_meet(request_id: &str) -> Self {
+        ConferenceData {
+            create_request: Some(CreateConferenceRequest {
+                request_id: request_id.to_string(),
+                conference_solution_key: ConferenceSolutionKey { type_: "hangoutsMeet".to_string() },
+            }),
+            entry_points: Default::default(),
+            conference_id: Default::default(),
+        }
+    }
+
+    /// Get the link to join the conference by video.
+    pub fn video_uri(&self) -> Option<String> {
+        self.entry_points.iter().find(|e| e.entry_point_type == "video").map(|e| e.uri.to_string())
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct CreateConferenceRequest {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "requestId")]
+    pub request_id: String,
+    #[serde(default, rename = "conferenceSolutionKey")]
+    pub conference_solution_key: ConferenceSolutionKey,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ConferenceSolutionKey {
+    /// The type of conference, ie. "hangoutsMeet".
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ConferenceEntryPoint {
+    /// The type of entry point, ie. "video" or "phone".
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "entryPointType")]
+    pub entry_point_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+}
+
+/// A request for the busy times on calendars.
+/// FROM: https://developers.google.com/calendar/v3/reference/freebusy/query
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct FreeBusyRequest {
+    #[serde(rename = "timeMin")]
+    time_min: DateTime<Utc>,
+    #[serde(rename = "timeMax")]
+    time_max: DateTime<Utc>,
+    items: Vec<FreeBusyRequestItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct FreeBusyRequestItem {
+    id: String,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+struct FreeBusyResponse {
+    #[serde(default)]
+    calendars: HashMap<String, FreeBusyCalendar>,
+}
+
+/// The busy times on a calendar.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct FreeBusyCalendar {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub busy: Vec<TimePeriod>,
+    /// Why we could not get the busy times for the calendar, ie. it was not found.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FreeBusyError>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimePeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct FreeBusyError {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub domain: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::fmt;
 
 extern crate proc_macro;
 
 use inflector::Inflector;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
+use serde::de::{Deserializer, MapAccess, Visitor};
 use serde::Deserialize;
 use serde_tokenstream::from_tokenstream;
 use syn::{Field, ItemStruct, Type};
@@ -27,7 +28,44 @@ struct Params {
     #[serde(default)]
     custom_partial_eq: bool,
     /// The struct item and type that we will filter on to find unique database entries.
-    match_on: HashMap<String, String>,
+    /// These are kept in the order they are given, since that is the order of the
+    /// arguments to the generated `get_from_db`.
+    #[serde(deserialize_with = "deserialize_match_on")]
+    match_on: Vec<(String, String)>,
+    /// The name of the database table, if it is not the snake_case plural of
+    /// `new_struct_name`.
+    #[serde(default)]
+    table_name: String,
+}
+
+/// Deserialize a map into a vector of its entries, in the order they were given.
+fn deserialize_match_on<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct MatchOnVisitor;
+
+    impl<'de> Visitor<'de> for MatchOnVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map of field names to types")
+        }
+
+        fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+        where
+            M: MapAccess<'de>,
+        {
+            let mut entries = Vec::new();
+            while let Some(entry) = access.next_entry()? {
+                entries.push(entry);
+            }
+
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(MatchOnVisitor)
 }
 
 #[proc_macro_attribute]
@@ -53,6 +91,13 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
     if new_struct_name == params.new_struct_name.to_uppercase() {
         db_schema = format_ident!("{}s", params.new_struct_name.to_lowercase());
     }
+    // If we were given an explicit table name, that always wins.
+    let mut table_name_text = quote!();
+    if !params.table_name.is_empty() {
+        db_schema = format_ident!("{}", params.table_name);
+        let table_name = params.table_name.to_string();
+        table_name_text = quote!(#[table_name = #table_name]);
+    }
 
     // Let's create the database filter.
     let mut filter = quote!();
@@ -440,6 +485,7 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
             Deserialize,
             Serialize,
         )]
+        #table_name_text
         pub struct #new_struct_name {
             // This has to be the first field.
             #[serde(default)]
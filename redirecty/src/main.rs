@@ -0,0 +1,211 @@
+use std::env;
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use cio_api::analytics::NewLinkClick;
+use cio_api::db::Database;
+use cio_api::shorturls::{get_all_shorturls, ShortUrlRoutes};
+use dropshot::{endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpServerStarter, Path, RequestContext};
+use http::{header, HeaderMap, Response, StatusCode};
+use hyper::Body;
+use schemars::JsonSchema;
+use sentry::IntoDsn;
+use serde::Deserialize;
+use tokio::time::{sleep, Duration};
+
+/// How often we reload the short URLs from the database.
+static ROUTES_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// The environment variable with the name of the header our authenticating proxy sets to
+/// the email of the signed in user, ie. `cf-access-authenticated-user-email`.
+/// The proxy must overwrite the header on every request, so clients can't set it
+/// themselves. If this is not set, we do not record who clicked.
+static USER_EMAIL_HEADER_VAR: &str = "REDIRECTY_USER_EMAIL_HEADER";
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    // Try to get the current git hash.
+    let git_hash = if let Ok(gh) = env::var("GIT_HASH") {
+        gh
+    } else {
+        // Try to shell out.
+        let output = Command::new("git").arg("rev-parse").arg("HEAD").output().expect("failed to execute process");
+        let o = std::str::from_utf8(&output.stdout).unwrap();
+        o[0..8].to_string()
+    };
+    println!("git hash: {}", git_hash);
+
+    // Initialize sentry.
+    let sentry_dsn = env::var("REDIRECTY_SENTRY_DSN").unwrap_or_default();
+    let _guard = sentry::init(sentry::ClientOptions {
+        dsn: sentry_dsn.into_dsn().unwrap(),
+
+        release: Some(git_hash.into()),
+        environment: Some(env::var("SENTRY_ENV").unwrap_or_else(|_| "development".to_string()).into()),
+        ..Default::default()
+    });
+
+    let service_address = "0.0.0.0:8080";
+
+    let config_dropshot = ConfigDropshot {
+        bind_address: service_address.parse().unwrap(),
+        request_body_max_bytes: 1024,
+    };
+
+    let config_logging = ConfigLogging::StderrTerminal { level: ConfigLoggingLevel::Info };
+    let log = config_logging.to_logger("redirecty-server").map_err(|error| format!("failed to create logger: {}", error)).unwrap();
+
+    // Describe the API.
+    let mut api = ApiDescription::new();
+    api.register(redirect_root).unwrap();
+    api.register(redirect_name).unwrap();
+    api.register(redirect_discussion).unwrap();
+
+    /*
+     * The functions that implement our API endpoints will share this context.
+     */
+    let api_context = Context::new();
+
+    // Keep the short URLs up to date with the database.
+    let routes = api_context.routes.clone();
+    tokio::spawn(async move {
+        let db = Database::new();
+        loop {
+            sleep(ROUTES_REFRESH_INTERVAL).await;
+
+            let updated = ShortUrlRoutes::new(get_all_shorturls(&db));
+            println!("reloaded {} short URLs", updated.len());
+            *routes.write().unwrap() = updated;
+        }
+    });
+
+    /*
+     * Set up the server.
+     */
+    let server = HttpServerStarter::new(&config_dropshot, api, api_context, &log)
+        .map_err(|error| format!("failed to start server: {}", error))
+        .unwrap()
+        .start();
+    server.await
+}
+
+/**
+ * Application-specific context (state shared by handler functions)
+ */
+struct Context {
+    db: Arc<Database>,
+    routes: Arc<RwLock<ShortUrlRoutes>>,
+    user_email_header: String,
+}
+
+impl Context {
+    /**
+     * Return a new Context.
+     */
+    pub fn new() -> Context {
+        let db = Database::new();
+
+        let routes = ShortUrlRoutes::new(get_all_shorturls(&db));
+        println!("loaded {} short URLs", routes.len());
+
+        Context {
+            db: Arc::new(db),
+            routes: Arc::new(RwLock::new(routes)),
+            user_email_header: env::var(USER_EMAIL_HEADER_VAR).unwrap_or_default().trim().to_lowercase(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+struct NamePathParams {
+    name: String,
+}
+
+/** Redirect `{name}.{subdomain}` or the root of `{subdomain}`. */
+#[endpoint {
+    method = GET,
+    path = "/",
+}]
+async fn redirect_root(rqctx: Arc<RequestContext<Context>>) -> Result<Response<Body>, HttpError> {
+    redirect(rqctx, "/").await
+}
+
+/** Redirect `{name}.{subdomain}` with any path, or `{subdomain}/{name}`. */
+#[endpoint {
+    method = GET,
+    path = "/{name}",
+}]
+async fn redirect_name(rqctx: Arc<RequestContext<Context>>, path_params: Path<NamePathParams>) -> Result<Response<Body>, HttpError> {
+    let name = path_params.into_inner().name;
+    redirect(rqctx, &format!("/{}", name)).await
+}
+
+/** Redirect `{subdomain}/{name}/discussion`. */
+#[endpoint {
+    method = GET,
+    path = "/{name}/discussion",
+}]
+async fn redirect_discussion(rqctx: Arc<RequestContext<Context>>, path_params: Path<NamePathParams>) -> Result<Response<Body>, HttpError> {
+    let name = path_params.into_inner().name;
+    redirect(rqctx, &format!("/{}/discussion", name)).await
+}
+
+/// Look up where a request redirects to, record the click and redirect.
+async fn redirect(rqctx: Arc<RequestContext<Context>>, path: &str) -> Result<Response<Body>, HttpError> {
+    let api_context = rqctx.context();
+
+    let (host, referrer, user_email) = {
+        let req = rqctx.request.lock().await;
+        let headers = req.headers();
+
+        // Behind a proxy the original host is in X-Forwarded-Host.
+        let host = get_header(headers, "x-forwarded-host");
+        let host = if host.is_empty() { get_header(headers, header::HOST.as_str()) } else { host };
+        // Only trust the header our proxy sets.
+        let user_email = if api_context.user_email_header.is_empty() {
+            String::new()
+        } else {
+            get_header(headers, &api_context.user_email_header)
+        };
+
+        (host, get_header(headers, header::REFERER.as_str()), user_email)
+    };
+
+    let redirect = api_context.routes.read().unwrap().resolve(&host, path);
+    let redirect = match redirect {
+        Some(r) => r,
+        None => return Err(HttpError::for_not_found(None, format!("no short URL was found for `{}{}`", host, path))),
+    };
+
+    // Record the click off the request path, a problem with the database should never
+    // break a redirect.
+    let click = NewLinkClick {
+        time: Utc::now(),
+        subdomain: redirect.subdomain.to_string(),
+        name: redirect.name.to_string(),
+        short_link: redirect.short_link(),
+        target: redirect.target.to_string(),
+        referrer,
+        user_email,
+    };
+    let db = api_context.db.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = click.record(&db) {
+            println!("[redirecty] {}", e);
+        }
+    });
+
+    // Use a temporary redirect, unlike nginx, so browsers do not cache it and we
+    // see every click.
+    Ok(Response::builder()
+        .status(StatusCode::FOUND)
+        .header(header::LOCATION, redirect.target)
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::empty())
+        .unwrap())
+}
+
+fn get_header(headers: &HeaderMap, name: &str) -> String {
+    headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().trim().to_string()
+}
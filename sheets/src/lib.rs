@@ -169,13 +169,19 @@ impl Sheets {
 
     /// Update values.
     pub async fn update_values(&self, sheet_id: &str, range: &str, value: String) -> Result<UpdateValuesResponse, APIError> {
+        self.update_row_values(sheet_id, range, vec![value]).await
+    }
+
+    /// Update the values in a row, in one request.
+    /// The `range` is something like `A2:F2` and the values fill it in from the left.
+    pub async fn update_row_values(&self, sheet_id: &str, range: &str, values: Vec<String>) -> Result<UpdateValuesResponse, APIError> {
         // Build the request.
         let request = self.request(
             Method::PUT,
             format!("spreadsheets/{}/values/{}", sheet_id.to_string(), range.to_string()),
             ValueRange {
                 range: Some(range.to_string()),
-                values: Some(vec![vec![value]]),
+                values: Some(vec![values]),
                 major_dimension: None,
             },
             Some(vec![
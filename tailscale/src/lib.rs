@@ -22,6 +22,7 @@
  * ```
  */
 #![allow(clippy::field_reassign_with_default)]
+use std::collections::BTreeMap;
 use std::env;
 use std::error;
 use std::fmt;
@@ -31,6 +32,7 @@ use chrono::offset::Utc;
 use chrono::DateTime;
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Endpoint for the Tailscale API.
 const ENDPOINT: &str = "https://api.tailscale.com/api/v2/";
@@ -126,7 +128,25 @@ impl Tailscale {
         Ok(r.devices)
     }
 
-    /// List devices.
+    /// Get a device.
+    pub async fn get_device(&self, device_id: &str) -> Result<Device, APIError> {
+        let request = self.request(Method::GET, &format!("device/{}", device_id), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Delete a device.
     pub async fn delete_device(&self, device_id: &str) -> Result<(), APIError> {
         let request = self.request(Method::DELETE, &format!("device/{}", device_id), (), None);
 
@@ -143,6 +163,206 @@ impl Tailscale {
 
         Ok(())
     }
+
+    /// Authorize a device, for tailnets that require devices to be authorized
+    /// before they can join.
+    pub async fn authorize_device(&self, device_id: &str) -> Result<(), APIError> {
+        let request = self.request(Method::POST, &format!("device/{}/authorized", device_id), DeviceAuthorized { authorized: true }, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Set the tags for a device, replacing any tags it had before.
+    /// The tags must be owned by the API key's user in the ACL `TagOwners`.
+    pub async fn set_device_tags(&self, device_id: &str, tags: &[String]) -> Result<(), APIError> {
+        let request = self.request(Method::POST, &format!("device/{}/tags", device_id), DeviceTags { tags: tags.to_vec() }, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get the ACL policy file for the tailnet, along with its ETag.
+    /// Pass the ETag to `update_acl` so we don't overwrite changes made in the meantime.
+    pub async fn get_acl(&self) -> Result<(Acl, String), APIError> {
+        let mut request = self.request(Method::GET, &format!("tailnet/{}/acl", self.domain), (), None);
+        // Otherwise we get back HuJSON.
+        request.headers_mut().insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let etag = resp.headers().get(header::ETAG).and_then(|e| e.to_str().ok()).unwrap_or_default().to_string();
+        let acl: Acl = resp.json().await.unwrap();
+
+        Ok((acl, etag))
+    }
+
+    /// Update the ACL policy file for the tailnet.
+    /// If `etag` is not empty, the update fails if the ACL changed since we got it.
+    pub async fn update_acl(&self, acl: &Acl, etag: &str) -> Result<Acl, APIError> {
+        let mut request = self.request(Method::POST, &format!("tailnet/{}/acl", self.domain), acl, None);
+        request.headers_mut().insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+        if !etag.is_empty() {
+            request.headers_mut().insert(header::IF_MATCH, header::HeaderValue::from_str(etag).unwrap());
+        }
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// List the auth keys for the tailnet.
+    /// Only the IDs are returned, use `get_key` for the details.
+    pub async fn list_keys(&self) -> Result<Vec<Key>, APIError> {
+        let request = self.request(Method::GET, &format!("tailnet/{}/keys", self.domain), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: KeysResponse = resp.json().await.unwrap();
+
+        Ok(r.keys)
+    }
+
+    /// Create an auth key for adding devices to the tailnet.
+    /// The secret is only returned in the `key` field of the response, so save it.
+    pub async fn create_key(&self, capabilities: KeyCapabilities, expiry_seconds: i64) -> Result<Key, APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("tailnet/{}/keys", self.domain),
+            CreateKeyRequest {
+                capabilities,
+                expiry_seconds,
+            },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Get an auth key.
+    pub async fn get_key(&self, key_id: &str) -> Result<Key, APIError> {
+        let request = self.request(Method::GET, &format!("tailnet/{}/keys/{}", self.domain, key_id), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Revoke an auth key.
+    pub async fn delete_key(&self, key_id: &str) -> Result<(), APIError> {
+        let request = self.request(Method::DELETE, &format!("tailnet/{}/keys/{}", self.domain, key_id), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get the DNS settings for the tailnet: the nameservers, whether MagicDNS is
+    /// enabled and the search paths.
+    pub async fn get_dns(&self) -> Result<DnsSettings, APIError> {
+        let mut settings = DnsSettings::default();
+
+        for path in &["nameservers", "preferences", "searchpaths"] {
+            let request = self.request(Method::GET, &format!("tailnet/{}/dns/{}", self.domain, path), (), None);
+
+            let resp = self.client.execute(request).await.unwrap();
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    return Err(APIError {
+                        status_code: s,
+                        body: resp.text().await.unwrap(),
+                    })
+                }
+            };
+
+            // Each endpoint returns some of the fields.
+            let r: DnsSettings = resp.json().await.unwrap();
+            match *path {
+                "nameservers" => settings.nameservers = r.nameservers,
+                "preferences" => settings.magic_dns = r.magic_dns,
+                _ => settings.search_paths = r.search_paths,
+            }
+        }
+
+        Ok(settings)
+    }
 }
 
 /// Error type returned by our library.
@@ -227,4 +447,122 @@ pub struct Device {
     pub route_all: bool,
     #[serde(default, rename = "hasSubnet")]
     pub has_subnet: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct DeviceAuthorized {
+    authorized: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct DeviceTags {
+    tags: Vec<String>,
+}
+
+/// The data type for the ACL policy file of a tailnet.
+/// FROM: https://tailscale.com/kb/1018/acls
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Acl {
+    #[serde(default, rename = "ACLs", alias = "acls", skip_serializing_if = "Vec::is_empty")]
+    pub acls: Vec<AclRule>,
+    /// groups are the groups of users, the names start with `group:`.
+    #[serde(default, rename = "Groups", alias = "groups", skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    #[serde(default, rename = "Hosts", alias = "hosts", skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, String>,
+    /// tag_owners are the users and groups that can apply each tag.
+    #[serde(default, rename = "TagOwners", alias = "tagOwners", skip_serializing_if = "BTreeMap::is_empty")]
+    pub tag_owners: BTreeMap<String, Vec<String>>,
+    /// Any other sections, like `Tests`, so they are kept when we update the ACL.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// The data type for a rule in the ACL policy file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AclRule {
+    #[serde(rename = "Action", alias = "action")]
+    pub action: String,
+    #[serde(default, rename = "Users", alias = "users", skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+    #[serde(default, rename = "Ports", alias = "ports", skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<String>,
+    /// Any other fields, like `src`, `dst` and `proto`, so they are kept when we
+    /// update the ACL.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct KeysResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keys: Vec<Key>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct CreateKeyRequest {
+    capabilities: KeyCapabilities,
+    #[serde(rename = "expirySeconds", skip_serializing_if = "is_zero")]
+    expiry_seconds: i64,
+}
+
+fn is_zero(i: &i64) -> bool {
+    *i == 0
+}
+
+/// The data type for an auth key.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Key {
+    pub id: String,
+    /// key is the secret, it is only set when the key is created.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub key: String,
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub capabilities: KeyCapabilities,
+}
+
+/// The data type for what an auth key can do.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KeyCapabilities {
+    #[serde(default)]
+    pub devices: KeyDeviceCapabilities,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KeyDeviceCapabilities {
+    #[serde(default)]
+    pub create: KeyDeviceCreateCapabilities,
+}
+
+/// The data type for the devices an auth key can add.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KeyDeviceCreateCapabilities {
+    /// reusable keys can add more than one device.
+    #[serde(default)]
+    pub reusable: bool,
+    /// ephemeral devices are removed soon after they go offline.
+    #[serde(default)]
+    pub ephemeral: bool,
+    /// preauthorized devices do not need to be authorized.
+    #[serde(default)]
+    pub preauthorized: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The data type for the DNS settings of a tailnet.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsSettings {
+    #[serde(default, rename = "dns", skip_serializing_if = "Vec::is_empty")]
+    pub nameservers: Vec<String>,
+    #[serde(default, rename = "magicDNS")]
+    pub magic_dns: bool,
+    #[serde(default, rename = "searchPaths", skip_serializing_if = "Vec::is_empty")]
+    pub search_paths: Vec<String>,
 }
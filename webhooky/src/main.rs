@@ -23,6 +23,7 @@ use dropshot::{
     endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted, HttpResponseOk, HttpServerStarter, Path, Query, RequestContext, TypedBody,
     UntypedBody,
 };
+use futures_util::TryStreamExt;
 use google_drive::GoogleDrive;
 use hubcaps::issues::{IssueListOptions, State};
 use hubcaps::Github;
@@ -33,22 +34,31 @@ use serde_qs::Config as QSConfig;
 use sheets::Sheets;
 
 use cio_api::analytics::NewPageView;
-use cio_api::applicants::{get_role_from_sheet_id, Applicant, NewApplicant};
-use cio_api::configs::{get_configs_from_repo, sync_buildings, sync_certificates, sync_conference_rooms, sync_github_outside_collaborators, sync_groups, sync_links, sync_users, User};
+use cio_api::applicant_intake::{check_application_limits, get_application_roles, submit_application, ApplicationReceipt, ApplicationRole, ApplicationSubmission};
+use cio_api::applicant_retention::{erase_applicant, get_applicant_erasure_request};
+use cio_api::applicant_scores::{submit_applicant_score, ApplicantScore, NewApplicantScore};
+use cio_api::applicants::{get_role_from_sheet_id, Applicant, NewApplicant, APPLICANT_SOURCE_API};
+use cio_api::background_checks::{get_applicant_for_candidate, ingest_background_check_report, update_background_check_from_airtable, BackgroundCheck};
+use cio_api::configs::{
+    get_configs_from_repo, get_configs_from_repo_at_ref, sync_buildings, sync_certificates, sync_conference_rooms, sync_github_outside_collaborators, sync_groups, sync_links, sync_users, User,
+};
 use cio_api::db::Database;
+use cio_api::envelopes::record_envelope;
 use cio_api::mailchimp::MailchimpWebhook;
 use cio_api::mailing_list::MailingListSubscriber;
-use cio_api::models::{GitHubUser, NewRFD, NewRepo, RFD};
+use cio_api::models::{GitHubUser, NewRFD, NewRFDCommit, NewRepo, RFD};
 use cio_api::rack_line::RackLineSubscriber;
-use cio_api::rfds::is_image;
+use cio_api::rfd_state::RfdState;
+use cio_api::rfds::{get_short_rfd_diff, is_image, record_rfd_state_change, refresh_rfd_references};
 use cio_api::schema::applicants;
 use cio_api::shipments::{get_shipments_spreadsheets, InboundShipment, NewInboundShipment, NewOutboundShipment, OutboundShipment};
-use cio_api::shorturls::{generate_shorturls_for_configs_links, generate_shorturls_for_repos, generate_shorturls_for_rfds};
+use cio_api::shorturls::{check_shorturls_for_configs_links, generate_shorturls_for_configs_links, generate_shorturls_for_repos, generate_shorturls_for_rfds};
 use cio_api::slack::{get_hiring_channel_post_url, get_public_relations_channel_post_url, post_to_channel};
 use cio_api::swag_inventory::SwagInventoryItem;
 use cio_api::swag_store::Order;
-use cio_api::templates::generate_terraform_files_for_okta;
-use cio_api::utils::{authenticate_github_jwt, create_or_update_file_in_github_repo, get_file_content_from_repo, get_gsuite_token, github_org};
+use cio_api::tailscale::generate_tailscale_acl;
+use cio_api::templates::{generate_terraform_files_for_gsuite, generate_terraform_files_for_okta, generate_terraform_files_for_slack};
+use cio_api::utils::{authenticate_github_jwt, create_or_update_file_in_github_repo, get_column_letters, get_file_content_from_repo, get_gsuite_token, github_org, is_shared_secret_valid};
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
@@ -89,8 +99,13 @@ async fn main() -> Result<(), String> {
      * allowing this metadata to live right alongside the handler function.
      */
     api.register(ping).unwrap();
+    api.register(listen_applicant_submissions).unwrap();
+    api.register(get_applicant_roles).unwrap();
     api.register(github_rate_limit).unwrap();
     api.register(listen_airtable_applicants_edit_webhooks).unwrap();
+    api.register(listen_airtable_applicants_erase_webhooks).unwrap();
+    api.register(listen_airtable_applicants_scores_create_webhooks).unwrap();
+    api.register(listen_airtable_background_checks_edit_webhooks).unwrap();
     api.register(listen_airtable_employees_edit_webhooks).unwrap();
     api.register(listen_airtable_shipments_inbound_create_webhooks).unwrap();
     api.register(listen_airtable_shipments_outbound_create_webhooks).unwrap();
@@ -208,6 +223,50 @@ async fn ping(_rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Str
     Ok(HttpResponseOk("pong".to_string()))
 }
 
+/** Listen for applications submitted through the intake API. */
+#[endpoint {
+    method = POST,
+    path = "/applicants",
+}]
+async fn listen_applicant_submissions(rqctx: Arc<RequestContext<Context>>, body_param: TypedBody<ApplicationSubmission>) -> Result<HttpResponseAccepted<ApplicationReceipt>, HttpError> {
+    sentry::start_session();
+    // Get gsuite token.
+    // We re-get the token here since otherwise it will expire.
+    let token = get_gsuite_token("").await;
+    // Initialize the Google Drive client.
+    let drive = GoogleDrive::new(token);
+
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    let submission = body_param.into_inner();
+
+    if let Err(e) = check_application_limits(db, &submission) {
+        sentry::end_session();
+        println!("application from `{}` for `{}` was rate limited: {}", submission.email, submission.role, e);
+        return Err(HttpError::for_client_error(None, http::StatusCode::TOO_MANY_REQUESTS, e));
+    }
+
+    let result = submit_application(db, &drive, &submission).await;
+    sentry::end_session();
+    match result {
+        Ok(receipt) => Ok(HttpResponseAccepted(receipt)),
+        Err(e) => {
+            println!("application from `{}` for `{}` was rejected: {}", submission.email, submission.role, e);
+            Err(HttpError::for_bad_request(None, e))
+        }
+    }
+}
+
+/** Return the roles we are hiring for and the questions we ask for each. */
+#[endpoint {
+    method = GET,
+    path = "/applicants/roles",
+}]
+async fn get_applicant_roles(_rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Vec<ApplicationRole>>, HttpError> {
+    Ok(HttpResponseOk(get_application_roles()))
+}
+
 /** Listen for GitHub webhooks. */
 #[endpoint {
     method = POST,
@@ -330,13 +389,19 @@ async fn listen_github_webhooks(rqctx: Arc<RequestContext<Context>>, body_param:
                 }
                 _ => (),
             },
-            Repo::Configs => {
-                if let EventType::Push = event_type {
+            Repo::Configs => match event_type {
+                EventType::Push => {
                     let resp = handle_configs_push(api_context, event).await;
                     sentry::end_session();
                     return resp;
                 }
-            }
+                EventType::PullRequest => {
+                    let resp = handle_configs_pull_request(api_context, event).await;
+                    sentry::end_session();
+                    return resp;
+                }
+                _ => (),
+            },
             _ => {
                 // We can throw this out, log it and return early.
                 println!("`{}` event was to the {} repo, no automations are set up for this repo yet", event_type, repo_name);
@@ -383,7 +448,7 @@ async fn trigger_rfd_update_by_number(rqctx: Arc<RequestContext<Context>>, path_
     println!("updated pdf `{}` for RFD {}", rfd.get_pdf_filename(), rfd.number_string);
 
     // Save the rfd back to our database.
-    rfd.update(db).await;
+    rfd.update_with_visibility(db).await;
 
     sentry::end_session();
     Ok(HttpResponseAccepted("ok".to_string()))
@@ -468,9 +533,9 @@ async fn listen_google_sheets_edit_webhooks(rqctx: Arc<RequestContext<Context>>,
 
     // Now let's get the header for the column of the cell that changed.
     // This is always in row 1.
-    // These should be zero indexed.
-    let column_letters = "0ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-    cell_name = format!("{}1", column_letters.chars().nth(event.event.range.column_start.try_into().unwrap()).unwrap().to_string());
+    // The column start is one indexed.
+    let column_index: usize = event.event.range.column_start.try_into().unwrap();
+    cell_name = format!("{}1", get_column_letters(column_index - 1));
     let column_header = sheets.get_value(&event.spreadsheet.id, cell_name).await.unwrap().to_lowercase();
 
     // Now let's get the applicant from the database so we can update it.
@@ -681,6 +746,15 @@ async fn listen_google_sheets_row_create_webhooks(rqctx: Arc<RequestContext<Cont
         return Ok(HttpResponseAccepted("ok".to_string()));
     }
 
+    // The intake API is the source of truth for applications submitted through it.
+    if let Some(existing) = Applicant::get_from_db(db, applicant.email.to_string(), applicant.sheet_id.to_string()) {
+        if existing.source == APPLICANT_SOURCE_API {
+            println!("applicant {} was submitted through the intake api, ignoring the sheet row", existing.email);
+            sentry::end_session();
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    }
+
     // We do not need to add one to the end of the columns to get the column where the email sent verification is
     // because google sheets index's at 0, so adding one would put us over, we are just right here.
     let sent_email_received_column_index = event.event.range.column_end;
@@ -832,6 +906,157 @@ async fn listen_airtable_applicants_edit_webhooks(rqctx: Arc<RequestContext<Cont
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+/**
+ * Listen for requests from applicants to delete their data, sent from our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves, which sends
+ * the secret in `AIRTABLE_WEBHOOK_SECRET` in the `X-Webhook-Secret` header.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/applicants/erase",
+}]
+async fn listen_airtable_applicants_erase_webhooks(rqctx: Arc<RequestContext<Context>>, body_param: TypedBody<AirtableApplicantErasureEvent>) -> Result<HttpResponseAccepted<String>, HttpError> {
+    sentry::start_session();
+    let api_context = rqctx.context();
+
+    // Make sure the request came from our Airtable script, since it deletes data.
+    let secret = {
+        let req = rqctx.request.lock().await;
+        req.headers().get("X-Webhook-Secret").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string()
+    };
+    if !is_shared_secret_valid("AIRTABLE_WEBHOOK_SECRET", &secret) {
+        sentry::end_session();
+        return Err(HttpError::for_client_error(
+            None,
+            http::StatusCode::UNAUTHORIZED,
+            "the webhook secret is missing or invalid".to_string(),
+        ));
+    }
+
+    let event = body_param.into_inner();
+    println!("{:?}", event);
+
+    if event.record_id.is_empty() {
+        sentry::capture_message("Record id is empty", sentry::Level::Fatal);
+        sentry::end_session();
+        return Ok(HttpResponseAccepted("ok".to_string()));
+    }
+
+    // Read the request back from the row in Airtable, and get the applicant for it from the database.
+    let request = match get_applicant_erasure_request(&event.record_id).await {
+        Ok(request) => request,
+        Err(e) => {
+            sentry::capture_message(&format!("reading the erasure request for Airtable record {} failed: {}", event.record_id, e), sentry::Level::Info);
+            sentry::end_session();
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    };
+    let applicant = match Applicant::get_from_db(&api_context.db, request.email.to_string(), request.sheet_id.to_string()) {
+        Some(applicant) => applicant,
+        None => {
+            sentry::capture_message(&format!("could not find the applicant for Airtable record {}", event.record_id), sentry::Level::Info);
+            sentry::end_session();
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    };
+
+    match erase_applicant(&api_context.db, &applicant, &request.erasure_requested_by).await {
+        Ok(deletion) => {
+            println!(
+                "deleted applicant {} for {}, removed: {}, errors: {}",
+                deletion.applicant_email_hash,
+                deletion.role,
+                deletion.removed.join(", "),
+                deletion.errors.join("; ")
+            );
+        }
+        Err(e) => {
+            sentry::capture_message(&format!("deleting the applicant for Airtable record {} failed: {}", event.record_id, e), sentry::Level::Info);
+        }
+    }
+
+    sentry::end_session();
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for scores added to our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/applicants/scores/create",
+}]
+async fn listen_airtable_applicants_scores_create_webhooks(rqctx: Arc<RequestContext<Context>>, body_param: TypedBody<AirtableRowEvent>) -> Result<HttpResponseAccepted<String>, HttpError> {
+    sentry::start_session();
+    let api_context = rqctx.context();
+
+    let event = body_param.into_inner();
+    println!("{:?}", event);
+
+    if event.record_id.is_empty() {
+        sentry::capture_message("Record id is empty", sentry::Level::Fatal);
+        sentry::end_session();
+        return Ok(HttpResponseAccepted("ok".to_string()));
+    }
+
+    // Get the row from airtable.
+    let score = ApplicantScore::get_from_airtable(&event.record_id).await;
+    match submit_applicant_score(&api_context.db, &NewApplicantScore::from(score), &event.record_id).await {
+        Ok(scorecard) => {
+            println!(
+                "saved score for applicant {}, they now have {} evaluations with a mean of {:.1}",
+                scorecard.applicant_email, scorecard.evaluations, scorecard.score.mean
+            );
+        }
+        Err(e) => {
+            sentry::capture_message(&format!("saving the score from Airtable record {} failed: {}", event.record_id, e), sentry::Level::Info);
+        }
+    }
+
+    sentry::end_session();
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for background checks edited in our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/background_checks/edit",
+}]
+async fn listen_airtable_background_checks_edit_webhooks(rqctx: Arc<RequestContext<Context>>, body_param: TypedBody<AirtableRowEvent>) -> Result<HttpResponseAccepted<String>, HttpError> {
+    sentry::start_session();
+    let api_context = rqctx.context();
+
+    let event = body_param.into_inner();
+    println!("{:?}", event);
+
+    if event.record_id.is_empty() {
+        sentry::capture_message("Record id is empty", sentry::Level::Fatal);
+        sentry::end_session();
+        return Ok(HttpResponseAccepted("ok".to_string()));
+    }
+
+    // Get the row from airtable.
+    let background_check = BackgroundCheck::get_from_airtable(&event.record_id).await;
+    match update_background_check_from_airtable(&api_context.db, background_check).await {
+        Ok(background_check) => {
+            println!(
+                "updated background check {} for {}, adverse action status: `{}`",
+                background_check.report_id, background_check.applicant_email, background_check.adverse_action_status
+            );
+        }
+        Err(e) => {
+            sentry::capture_message(&format!("updating the background check from Airtable record {} failed: {}", event.record_id, e), sentry::Level::Info);
+        }
+    }
+
+    sentry::end_session();
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
 /**
  * Listen for rows created in our Airtable workspace.
  * These are set up with an Airtable script on the workspaces themselves.
@@ -886,6 +1111,15 @@ pub struct AirtableRowEvent {
     pub record_id: String,
 }
 
+/// An applicant asked us to delete their data, sent by the Airtable script
+/// on their record in the applicants table. We read who asked for it from the
+/// record, not from here.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AirtableApplicantErasureEvent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub record_id: String,
+}
+
 /**
  * Listen for rows edited in our Airtable workspace.
  * These are set up with an Airtable script on the workspaces themselves.
@@ -1131,28 +1365,22 @@ async fn listen_checkr_background_update_webhooks(rqctx: Arc<RequestContext<Cont
         return Ok(HttpResponseAccepted("ok".to_string()));
     }
 
+    // We only keep track of reports, not invitations.
+    if event.data.object.object != "report" {
+        return Ok(HttpResponseAccepted("ok".to_string()));
+    }
+
     let candidate = api_context.checkr.get_candidate(&event.data.object.candidate_id).await.unwrap();
-    let result = applicants::dsl::applicants
-        .filter(
-            applicants::dsl::email
-                .eq(candidate.email.to_string())
-                // TODO: matching on name might be a bad idea here.
-                .or(applicants::dsl::name.eq(format!("{} {}", candidate.first_name, candidate.last_name))),
-        )
-        .filter(applicants::dsl::status.eq(cio_api::applicant_status::Status::Onboarding.to_string()))
-        .first::<Applicant>(&api_context.db.conn());
-    if result.is_ok() {
-        let mut applicant = result.unwrap();
-        // Set the status for the report.
-        if event.data.object.package.contains("premium_criminal") {
-            applicant.criminal_background_check_status = event.data.object.status.to_string();
+    let mut applicant = get_applicant_for_candidate(&api_context.db, &candidate);
+
+    // Get the whole report, the event does not have everything we save.
+    match api_context.checkr.get_report(&event.data.object.id).await {
+        Ok(report) => {
+            ingest_background_check_report(&api_context.db, &report, applicant.as_mut()).await;
         }
-        if event.data.object.package.contains("motor_vehicle") {
-            applicant.motor_vehicle_background_check_status = event.data.object.status.to_string();
+        Err(e) => {
+            sentry::capture_message(&format!("getting checkr report {} failed: {}", event.data.object.id, e), sentry::Level::Info);
         }
-
-        // Update the applicant.
-        applicant.update(&api_context.db).await;
     }
 
     Ok(HttpResponseAccepted("ok".to_string()))
@@ -1197,6 +1425,9 @@ async fn listen_docusign_envelope_update_webhooks(rqctx: Arc<RequestContext<Cont
             applicant.update_applicant_from_docusign_envelope(db, &ds, event).await;
         }
         Err(e) => {
+            // Still keep track of the envelope, even if it is not an offer.
+            record_envelope(db, &event, "").await;
+
             sentry::capture_message(
                 &format!("database could not find applicant with docusign envelope id {}: {}", event.envelope_id, e),
                 sentry::Level::Fatal,
@@ -1873,6 +2104,88 @@ pub mod deserialize_null_string {
     }
 }
 
+/// Combine the commits in a push into one, with the files each of them added, modified
+/// or removed. A file ends up in the list for the last thing that happened to it, and the
+/// rest of the fields come from the last commit.
+fn combine_commits(commits: &[GitHubCommit]) -> Option<GitHubCommit> {
+    let mut combined = commits.last()?.clone();
+    combined.added = Default::default();
+    combined.modified = Default::default();
+    combined.removed = Default::default();
+
+    for commit in commits {
+        for file in &commit.added {
+            combined.removed.retain(|f| f != file);
+            if !combined.added.contains(file) {
+                combined.added.push(file.to_string());
+            }
+        }
+        for file in &commit.modified {
+            if !combined.added.contains(file) && !combined.modified.contains(file) {
+                combined.modified.push(file.to_string());
+            }
+        }
+        for file in &commit.removed {
+            combined.added.retain(|f| f != file);
+            combined.modified.retain(|f| f != file);
+            if !combined.removed.contains(file) {
+                combined.removed.push(file.to_string());
+            }
+        }
+    }
+
+    Some(combined)
+}
+
+/// Record every commit in a push that changed the file for an RFD, with a short diff, for
+/// the weekly digest. Each commit is diffed against the commit before it on the pushed
+/// branch, not against what we have in the database, which could be from another branch.
+async fn record_rfd_push_commits(db: &Database, github_repo: &hubcaps::repositories::Repository, event: &GitHubWebhook, rfd: &RFD, file: &str) {
+    let branch = event.refv.trim_start_matches("refs/heads/");
+
+    // A push that creates a branch has no commit before it, so compare against the
+    // default branch it was made from.
+    let mut before = event.before.to_string();
+    if before.is_empty() || before.chars().all(|c| c == '0') {
+        before = event.repository.default_branch.to_string();
+    }
+    let (content, _) = get_file_content_from_repo(github_repo, &before, file).await;
+    let mut old_content = from_utf8(&content).unwrap_or_default().to_string();
+
+    for commit in &event.commits {
+        if !commit.file_changed(file) {
+            continue;
+        }
+
+        let (content, _) = get_file_content_from_repo(github_repo, &commit.id, file).await;
+        let new_content = from_utf8(&content).unwrap_or_default().to_string();
+
+        let mut author = commit.author.username.to_string();
+        if author.is_empty() {
+            author = commit.author.name.to_string();
+        }
+        let new_commit = NewRFDCommit {
+            rfd_number: rfd.number,
+            sha: commit.id.to_string(),
+            branch: branch.to_string(),
+            message: commit.message.lines().next().unwrap_or_default().to_string(),
+            author,
+            committed_at: commit.timestamp.unwrap_or_else(Utc::now),
+            diff: get_short_rfd_diff(&old_content, &new_content),
+            link: commit.url.to_string(),
+        };
+        // Keep the commits to restricted RFDs out of Airtable.
+        if rfd.is_restricted() {
+            new_commit.upsert_in_db(db);
+        } else {
+            new_commit.upsert(db).await;
+        }
+        println!("recorded commit `{}` for RFD {}", commit.id, rfd.number_string);
+
+        old_content = new_content;
+    }
+}
+
 fn filter(files: &[String], dir: &str) -> Vec<String> {
     let mut in_dir: Vec<String> = Default::default();
     for file in files {
@@ -1939,10 +2252,8 @@ async fn handle_rfd_pull_request(api_context: &Context, event: GitHubWebhook) ->
 
     // Update the labels for the pull request.
     let mut labels: Vec<&str> = Default::default();
-    if rfd.state == "discussion" {
-        labels.push(":thought_balloon: discussion");
-    } else if rfd.state == "ideation" {
-        labels.push(":hatching_chick: ideation");
+    if let Some(label) = rfd.rfd_state().github_label() {
+        labels.push(label);
     }
     github_repo.pulls().get(event.pull_request.number.try_into().unwrap()).labels().add(labels).await.unwrap();
 
@@ -1993,18 +2304,21 @@ async fn handle_rfd_pull_request(api_context: &Context, event: GitHubWebhook) ->
     let discussion_link = event.pull_request.html_url;
     rfd.update_discussion(&discussion_link, path.ends_with(".md"));
 
-    // A pull request can be open for an RFD if it is in the following states:
-    //  - published: a already published RFD is being updated in a pull request.
-    //  - discussion: it is in discussion
-    //  - ideation: it is in ideation
+    // A pull request can be open for an RFD if it is in a state that allows it.
     // We can update the state if it is not currently in an acceptable state.
-    if rfd.state != "discussion" && rfd.state != "published" && rfd.state != "ideation" {
+    let old_state = rfd.state.to_string();
+    if !rfd.rfd_state().allows_open_pull_request() {
         //  Update the state of the RFD in GitHub to show it as `discussion`.
-        rfd.update_state("discussion", path.ends_with(".md"));
+        if let Err(e) = rfd.transition_state(RfdState::Discussion, path.ends_with(".md")) {
+            sentry::capture_message(&format!("not moving RFD to discussion for pr#{}: {}", event.pull_request.number, e), sentry::Level::Warning);
+        }
     }
 
     // Update the RFD to show the new state and link in the database.
-    rfd.update(db).await;
+    let rfd = rfd.update_with_visibility(db).await;
+
+    // Record the state change, if there was one.
+    record_rfd_state_change(db, &rfd, &old_state, &branch, &event.sender.login).await;
 
     // Update the file in GitHub.
     // Keep in mind: this push will kick off another webhook.
@@ -2027,8 +2341,14 @@ async fn handle_rfd_push(api_context: &Context, event: GitHubWebhook) -> Result<
     // Get the repo.
     let github_repo = api_context.github.repo(api_context.github_org.to_string(), event.repository.name.to_string());
 
-    // Get the commit.
-    let mut commit = event.commits.get(0).unwrap().clone();
+    // Get the files changed by all the commits in the push.
+    let mut commit = match combine_commits(&event.commits) {
+        Some(c) => c,
+        None => {
+            println!("`push` event has no commits");
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    };
 
     // Ignore any changes that are not to the `rfd/` directory.
     let dir = "rfd/";
@@ -2131,21 +2451,36 @@ async fn handle_rfd_push(api_context: &Context, event: GitHubWebhook) -> Result<
             }
 
             // Update the RFD in the database.
-            let mut rfd = new_rfd.upsert(db).await;
+            // We only update Airtable once we have expanded the RFD and know who can see it.
+            let mut rfd = new_rfd.upsert_in_db(db);
             // Update all the fields for the RFD.
             rfd.expand(&api_context.github).await;
-            rfd.update(db).await;
+            rfd = rfd.update_with_visibility(db).await;
             println!("updated RFD {} in the database", new_rfd.number_string);
             println!("updated airtable for RFD {}", new_rfd.number_string);
 
+            // Record the state change, if the push changed the state.
+            record_rfd_state_change(db, &rfd, &old_rfd_state, branch, &event.sender.login).await;
+
+            // Update the references the RFD makes to other RFDs and GitHub.
+            refresh_rfd_references(db, &rfd).await;
+            println!("updated references for RFD {}", new_rfd.number_string);
+
+            // Record the commits, with a short diff, for the weekly digest.
+            record_rfd_push_commits(db, &github_repo, &event, &rfd, &file).await;
+
             // Create all the shorturls for the RFD if we need to,
             // this would be on added files, only.
-            generate_shorturls_for_rfds(&db, &api_context.github.repo(&api_context.github_org, "configs")).await;
-            println!("generated shorturls for the rfds");
+            match generate_shorturls_for_rfds(&db, &api_context.github.repo(&api_context.github_org, "configs")).await {
+                Ok(_) => println!("generated shorturls for the rfds"),
+                Err(e) => {
+                    sentry::capture_message(&format!("generating the shorturls for the rfds failed: {}", e), sentry::Level::Fatal);
+                }
+            }
 
             // Update the PDFs for the RFD.
             rfd.convert_and_upload_pdf(&api_context.github).await;
-            rfd.update(db).await;
+            rfd.update_with_visibility(db).await;
             println!("updated pdf `{}` for RFD {}", new_rfd.number_string, rfd.get_pdf_filename());
 
             // Check if the RFD state changed from what is currently in the
@@ -2156,7 +2491,7 @@ async fn handle_rfd_push(api_context: &Context, event: GitHubWebhook) -> Result<
             // a PR. Instead, below, the state of the RFD would be moved to `published`.
             // TODO: see if we drop events, if we do, we might want to remove the check with
             // the old state and just do it everytime an RFD is in discussion.
-            if old_rfd_state != rfd.state && rfd.state == "discussion" && branch != event.repository.default_branch {
+            if old_rfd_state != rfd.state && rfd.rfd_state() == RfdState::Discussion && branch != event.repository.default_branch {
                 // First, we need to make sure we don't already have a pull request open.
                 let pulls = github_repo
                     .pulls()
@@ -2207,21 +2542,24 @@ async fn handle_rfd_push(api_context: &Context, event: GitHubWebhook) -> Result<
 
             // If the RFD was merged into the default branch, but the RFD state is not `published`,
             // update the state of the RFD in GitHub to show it as `published`.
-            if branch == event.repository.default_branch && rfd.state != "published" {
+            // RFDs that are already past `published`, like `committed` or `abandoned`, are left alone.
+            if branch == event.repository.default_branch && rfd.rfd_state() != RfdState::Published && rfd.rfd_state().can_transition_to(RfdState::Published) {
                 sentry::capture_message(
                     &format!(
                         "RFD {} is the branch {} but its state is {}, updating it to `published`",
-                        rfd.number_string, event.repository.default_branch, old_rfd_state,
+                        rfd.number_string, event.repository.default_branch, rfd.state,
                     ),
                     sentry::Level::Info,
                 );
 
                 //  Update the state of the RFD in GitHub to show it as `published`.
                 let mut rfd_mut = rfd.clone();
-                rfd_mut.update_state("published", file.ends_with(".md"));
+                let merged_state = rfd_mut.state.to_string();
+                rfd_mut.transition_state(RfdState::Published, file.ends_with(".md")).unwrap();
 
                 // Update the RFD to show the new state in the database.
-                rfd_mut.update(db).await;
+                let rfd_mut = rfd_mut.update_with_visibility(db).await;
+                record_rfd_state_change(db, &rfd_mut, &merged_state, branch, &event.sender.login).await;
 
                 // Update the file in GitHub.
                 // Keep in mind: this push will kick off another webhook.
@@ -2290,8 +2628,14 @@ async fn handle_configs_push(api_context: &Context, event: GitHubWebhook) -> Res
     // Get the repo.
     let github_repo = api_context.github.repo(api_context.github_org.to_string(), event.repository.name.to_string());
 
-    // Get the commit.
-    let mut commit = event.commits.get(0).unwrap().clone();
+    // Get the files changed by all the commits in the push.
+    let mut commit = match combine_commits(&event.commits) {
+        Some(c) => c,
+        None => {
+            println!("`push` event has no commits");
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    };
 
     // Ignore any changes that are not to the `configs/` directory.
     let dir = "configs/";
@@ -2327,8 +2671,12 @@ async fn handle_configs_push(api_context: &Context, event: GitHubWebhook) -> Res
         sync_links(&api_context.db, configs.links, configs.huddles).await;
 
         // We need to update the short URLs for the links.
-        generate_shorturls_for_configs_links(&api_context.db, &github_repo).await;
-        println!("generated shorturls for the configs links");
+        match generate_shorturls_for_configs_links(&api_context.db, &github_repo).await {
+            Ok(_) => println!("generated shorturls for the configs links"),
+            Err(e) => {
+                sentry::capture_message(&format!("generating the shorturls for the configs links failed: {}", e), sentry::Level::Fatal);
+            }
+        }
     }
 
     // Check if the groups.toml file changed.
@@ -2347,6 +2695,12 @@ async fn handle_configs_push(api_context: &Context, event: GitHubWebhook) -> Res
         // Sync okta users and group from the database.
         // Do this after we update the users and groups in the database.
         generate_terraform_files_for_okta(&api_context.github, &api_context.db).await;
+        generate_terraform_files_for_slack(&api_context.github, &api_context.db).await;
+
+        // Generate the Tailscale ACL with the groups, for review.
+        if let Err(e) = generate_tailscale_acl(&api_context.github, &api_context.db).await {
+            sentry::capture_message(&format!("generating the tailscale acl failed: {}", e), sentry::Level::Fatal);
+        }
     }
 
     // Check if the buildings.toml file changed.
@@ -2360,6 +2714,12 @@ async fn handle_configs_push(api_context: &Context, event: GitHubWebhook) -> Res
         sync_conference_rooms(&api_context.db, configs.resources).await;
     }
 
+    if commit.file_changed("configs/users.toml") || commit.file_changed("configs/groups.toml") || commit.file_changed("configs/buildings.toml") || commit.file_changed("configs/resources.toml") {
+        // Generate the GSuite terraform files after the groups, users, buildings and
+        // conference rooms are in the database.
+        generate_terraform_files_for_gsuite(&api_context.github, &api_context.db).await;
+    }
+
     // Check if the certificates.toml file changed.
     if commit.file_changed("configs/certificates.toml") {
         sync_certificates(&api_context.db, &api_context.github, configs.certificates).await;
@@ -2376,6 +2736,83 @@ async fn handle_configs_push(api_context: &Context, event: GitHubWebhook) -> Res
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+/// The start of our comment with the short URLs report on a configs pull request, so we
+/// can find it again to update it.
+static SHORTURLS_REPORT_MARKER: &str = "<!-- cio/shorturls -->";
+
+/// Handle a `pull_request` event for the configs repo.
+/// We check the short URLs the pull request would give us and report any collisions
+/// or reserved names as a status on the pull request, so they are caught before merging.
+async fn handle_configs_pull_request(api_context: &Context, event: GitHubWebhook) -> Result<HttpResponseAccepted<String>, HttpError> {
+    // We only care about new commits on the pull request.
+    if event.action != "opened" && event.action != "synchronize" && event.action != "reopened" {
+        println!("`pull_request` event action `{}` on the configs repo, we don't care", event.action);
+        return Ok(HttpResponseAccepted("ok".to_string()));
+    }
+
+    // Get the repo.
+    let github_repo = api_context.github.repo(api_context.github_org.to_string(), event.repository.name.to_string());
+
+    // Get the configs as they are in the pull request.
+    let sha = event.pull_request.head.sha.to_string();
+    let configs = get_configs_from_repo_at_ref(&api_context.github, &sha).await;
+
+    let report = check_shorturls_for_configs_links(&api_context.db, &configs.links).await;
+    println!("checked the shorturls for configs pr#{}: {}", event.pull_request.number, report);
+
+    let (state, description) = if report.is_ok() {
+        (hubcaps::statuses::State::Success, format!("All {} short URLs are ok", report.checked))
+    } else {
+        (hubcaps::statuses::State::Failure, format!("Found {} problems with the short URLs", report.problems.len()))
+    };
+    if let Err(e) = github_repo
+        .statuses()
+        .create(
+            &sha,
+            &hubcaps::statuses::StatusOptions::builder(state)
+                .context("cio/shorturls")
+                .description(description)
+                .target_url(event.pull_request.html_url.to_string())
+                .build(),
+        )
+        .await
+    {
+        sentry::capture_message(&format!("unable to set the shorturls status on configs pr#{}: {}", event.pull_request.number, e), sentry::Level::Error);
+    }
+
+    // Keep the full report in one comment, since the status description is short.
+    // We update the comment on every push, rather than adding a new one each time.
+    let comments = github_repo.issue(event.pull_request.number.try_into().unwrap()).comments();
+    let existing = match comments
+        .iter(&hubcaps::comments::CommentListOptions::builder().per_page(100).build())
+        .try_collect::<Vec<hubcaps::comments::Comment>>()
+        .await
+    {
+        Ok(c) => c.into_iter().find(|c| c.body.starts_with(SHORTURLS_REPORT_MARKER)),
+        Err(e) => {
+            sentry::capture_message(&format!("unable to list the comments on configs pr#{}: {}", event.pull_request.number, e), sentry::Level::Error);
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+    };
+
+    let body = format!("{}\n{}", SHORTURLS_REPORT_MARKER, report.as_markdown());
+    let result = match existing {
+        Some(c) if c.body == body => Ok(()),
+        Some(c) => comments.edit(c.id, &hubcaps::comments::CommentOptions { body }).await.map(|_| ()),
+        // Only start commenting once there are problems.
+        None if report.is_ok() => Ok(()),
+        None => comments.create(&hubcaps::comments::CommentOptions { body }).await.map(|_| ()),
+    };
+    if let Err(e) = result {
+        sentry::capture_message(
+            &format!("unable to comment the shorturls report on configs pr#{}: {}", event.pull_request.number, e),
+            sentry::Level::Error,
+        );
+    }
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
 /// Handle the `repository` event for all repos.
 async fn handle_repository_event(api_context: &Context, event: GitHubWebhook) -> Result<HttpResponseAccepted<String>, HttpError> {
     let repo = &api_context.github.repo(event.repository.owner.login, event.repository.name).get().await.unwrap();
@@ -2385,8 +2822,12 @@ async fn handle_repository_event(api_context: &Context, event: GitHubWebhook) ->
     // TODO: since we know only one repo changed we don't need to refresh them all,
     // make this a bit better.
     // Update the short urls for all the repos.
-    generate_shorturls_for_repos(&api_context.db, &api_context.github.repo(&api_context.github_org, "configs")).await;
-    println!("generated shorturls for all the GitHub repos");
+    match generate_shorturls_for_repos(&api_context.db, &api_context.github.repo(&api_context.github_org, "configs")).await {
+        Ok(_) => println!("generated shorturls for all the GitHub repos"),
+        Err(e) => {
+            sentry::capture_message(&format!("generating the shorturls for the GitHub repos failed: {}", e), sentry::Level::Fatal);
+        }
+    }
 
     Ok(HttpResponseAccepted("ok".to_string()))
 }
@@ -407,6 +407,48 @@ impl Zoom {
         Ok(())
     }
 
+    /// Schedule a meeting hosted by a user.
+    /// From: https://marketplace.zoom.us/docs/api-reference/zoom-api/meetings/meetingcreate
+    pub async fn create_meeting(&self, user_id: &str, meeting: &NewMeeting) -> Result<ScheduledMeeting, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, format!("users/{}/meetings", user_id), meeting, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        // Try to deserialize the response.
+        let scheduled: ScheduledMeeting = resp.json().await.unwrap();
+
+        Ok(scheduled)
+    }
+
+    /// Delete a scheduled meeting.
+    pub async fn delete_meeting(&self, meeting_id: i64) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(Method::DELETE, format!("meetings/{}", meeting_id), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::NO_CONTENT => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
     /// List cloud recordings available on an account.
     /// From: https://marketplace.zoom.us/docs/api-reference/zoom-api/cloud-recording/getaccountcloudrecording
     /// This assumes the caller is an admin.
@@ -779,6 +821,66 @@ pub struct Meeting {
     pub recording_files: Vec<Recording>,
 }
 
+/// A meeting to schedule.
+///
+/// From: https://marketplace.zoom.us/docs/api-reference/zoom-api/meetings/meetingcreate
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NewMeeting {
+    /// Meeting topic.
+    pub topic: String,
+    /// The type of meeting, 2 is a scheduled meeting.
+    #[serde(rename = "type")]
+    pub typev: i64,
+    /// The start time of the meeting in UTC, ie. `2021-06-30T17:00:00Z`.
+    pub start_time: String,
+    /// The duration of the meeting in minutes.
+    pub duration: i64,
+    /// The timezone to show the start time in, ie. `America/Los_Angeles`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub timezone: String,
+    /// Meeting description.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub agenda: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<NewMeetingSettings>,
+}
+
+/// The settings for a meeting to schedule.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NewMeetingSettings {
+    /// Allow participants to join the meeting before the host starts it.
+    pub join_before_host: bool,
+    /// Put participants in a waiting room until the host admits them.
+    pub waiting_room: bool,
+}
+
+/// A scheduled meeting.
+///
+/// From: https://marketplace.zoom.us/docs/api-reference/zoom-api/meetings/meetingcreate
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ScheduledMeeting {
+    /// Meeting ID - Unique Identifier for a meeting, also known as Meeting Number.
+    pub id: i64,
+    /// Meeting topic.
+    #[serde(default)]
+    pub topic: String,
+    /// The start time of the meeting in UTC.
+    #[serde(default)]
+    pub start_time: String,
+    /// The duration of the meeting in minutes.
+    #[serde(default)]
+    pub duration: i64,
+    /// The URL for participants to join the meeting.
+    #[serde(default)]
+    pub join_url: String,
+    /// The URL for the host to start the meeting.
+    #[serde(default)]
+    pub start_url: String,
+    /// The password to join the meeting.
+    #[serde(default)]
+    pub password: String,
+}
+
 /// A recording.
 ///
 /// From: https://marketplace.zoom.us/docs/api-reference/zoom-api/cloud-recording/getaccountcloudrecording